@@ -0,0 +1,51 @@
+/* C API of tinygrib2 (feature `capi`), see src/capi.rs */
+
+#ifndef TINYGRIB_H
+#define TINYGRIB_H
+
+#include <stddef.h>
+#include <stdint.h>
+#include <sys/types.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct TgFile TgFile;
+
+typedef struct TgFieldInfo {
+    uint8_t discipline;
+    /* 255 if unknown */
+    uint8_t parameter_category;
+    uint8_t parameter_number;
+    /* type of the first fixed surface, 255 if unknown */
+    uint8_t surface_type;
+    /* NAN if missing */
+    double surface_value;
+    /* -1 if not an ensemble member */
+    int perturbation_number;
+    /* seconds since 1970-01-01T00:00:00Z */
+    int64_t reference_time;
+    int64_t valid_time;
+    uint32_t ni;
+    uint32_t nj;
+    size_t number_of_values;
+} TgFieldInfo;
+
+/* NULL on error */
+TgFile *tg_open(const char *path);
+void tg_close(TgFile *file);
+size_t tg_field_count(const TgFile *file);
+/* 0 on success */
+int tg_field_info(const TgFile *file, size_t index, TgFieldInfo *info);
+/* length of the whole line, or -1 on error */
+ssize_t tg_field_inventory(const TgFile *file, size_t index, char *buf, size_t len);
+/* 0 on success; missing values are NAN */
+int tg_decode(const TgFile *file, size_t index, float *values, size_t len);
+const char *tg_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
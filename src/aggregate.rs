@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use crate::field::DecodedField;
+use crate::templates::{GridDefinitionTemplate, GridDefinitionTemplate3_0};
+use crate::{Error, Result};
+
+/// How the values of a block are combined into one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregation {
+    Max,
+    Min,
+    Mean,
+    /// Most frequent value, the smallest one on ties (for categorical fields)
+    Mode,
+}
+
+impl Aggregation {
+    /// Combine `values`, which have no missing values, NAN if there are none
+    fn apply(self, values: &mut [f32]) -> f32 {
+        if values.is_empty() {
+            return f32::NAN;
+        }
+        match self {
+            Self::Max => values.iter().copied().fold(f32::NEG_INFINITY, f32::max),
+            Self::Min => values.iter().copied().fold(f32::INFINITY, f32::min),
+            Self::Mean => {
+                (values.iter().map(|&v| v as f64).sum::<f64>() / values.len() as f64) as f32
+            }
+            Self::Mode => {
+                values.sort_by(f32::total_cmp);
+                values
+                    .chunk_by(|a, b| a == b)
+                    .fold((f32::NAN, 0), |(mode, count), run| {
+                        match run.len() > count {
+                            true => (run[0], run.len()),
+                            false => (mode, count),
+                        }
+                    })
+                    .0
+            }
+        }
+    }
+}
+
+/// Downsample `field` by combining blocks of `rows` x `columns` points
+///
+/// Missing points are left out of each block, and a block with no values is missing. The
+/// last blocks of a row or column may be partial. The grid of Template 3.0 is adjusted so
+/// that each point is at the centre of its (full) block; other grids are not supported.
+pub fn downsample(
+    mut field: DecodedField,
+    rows: usize,
+    columns: usize,
+    aggregation: Aggregation,
+) -> Result<DecodedField> {
+    if rows == 0 || columns == 0 {
+        return Err(Error::InvalidData(
+            "block size must not be zero".to_string(),
+        ));
+    }
+    let GridDefinitionTemplate::Template3_0(grid) = Arc::make_mut(&mut field.grid_definition)
+    else {
+        return Err(Error::UnsupportedData(
+            "downsampling grids other than template 3.0".to_string(),
+        ));
+    };
+    let (nj, ni) = field.shape;
+    if nj == 0 || ni == 0 || (grid.n_j as usize, grid.n_i as usize) != (nj, ni) {
+        return Err(Error::InvalidData(format!(
+            "field of shape {:?} does not fill its grid",
+            field.shape
+        )));
+    }
+    let (coarse_nj, coarse_ni) = (nj.div_ceil(rows), ni.div_ceil(columns));
+    scale_grid(grid, rows, columns, coarse_nj, coarse_ni)?;
+
+    let mut values = Vec::with_capacity(coarse_nj * coarse_ni);
+    let mut block = Vec::with_capacity(rows * columns);
+    for bj in 0..coarse_nj {
+        for bi in 0..coarse_ni {
+            block.clear();
+            for j in bj * rows..((bj + 1) * rows).min(nj) {
+                let row = &field.values[j * ni..(j + 1) * ni];
+                block.extend(
+                    row[bi * columns..((bi + 1) * columns).min(ni)]
+                        .iter()
+                        .filter(|v| !v.is_nan()),
+                );
+            }
+            values.push(aggregation.apply(&mut block));
+        }
+    }
+    field.shape = (coarse_nj, coarse_ni);
+    field.values = values;
+    Ok(field)
+}
+
+/// Adjust the number of points, increments and corners of `grid` to blocks of the given size
+fn scale_grid(
+    grid: &mut GridDefinitionTemplate3_0,
+    rows: usize,
+    columns: usize,
+    coarse_nj: usize,
+    coarse_ni: usize,
+) -> Result<()> {
+    let full_circle = (360.0 / grid.angle_unit()).round() as i64;
+    let mut lo2 = grid.lo2 as i64;
+    let positive_i = grid.scanning_mode & 0x80 == 0;
+    if positive_i && lo2 < grid.lo1 as i64 {
+        lo2 += full_circle;
+    } else if !positive_i && lo2 > grid.lo1 as i64 {
+        lo2 -= full_circle;
+    }
+    // corners of the coarse grid from the first point and the signed increment of the grid
+    let scale = |first: i64, last: i64, n: u32, block: usize, coarse_n: usize| {
+        let step = match n {
+            0 | 1 => 0.0,
+            n => (last - first) as f64 / (n - 1) as f64,
+        };
+        let first = first as f64 + step * (block - 1) as f64 / 2.0;
+        let last = first + step * (block * (coarse_n - 1)) as f64;
+        (first.round() as i64, last.round() as i64)
+    };
+    let (la1, la2) = scale(grid.la1 as i64, grid.la2 as i64, grid.n_j, rows, coarse_nj);
+    let (lo1, lo2) = scale(grid.lo1 as i64, lo2, grid.n_i, columns, coarse_ni);
+    let overflow = || Error::InvalidData("downsampled grid is out of range".to_string());
+    let wrap = |lo: i64| i32::try_from(lo.rem_euclid(full_circle)).map_err(|_| overflow());
+    grid.la1 = i32::try_from(la1).map_err(|_| overflow())?;
+    grid.la2 = i32::try_from(la2).map_err(|_| overflow())?;
+    grid.lo1 = wrap(lo1)?;
+    grid.lo2 = wrap(lo2)?;
+    grid.n_j = u32::try_from(coarse_nj).map_err(|_| overflow())?;
+    grid.n_i = u32::try_from(coarse_ni).map_err(|_| overflow())?;
+    grid.d_j = grid.d_j.saturating_mul(rows as u32);
+    grid.d_i = grid.d_i.saturating_mul(columns as u32);
+    Ok(())
+}
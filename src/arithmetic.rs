@@ -0,0 +1,62 @@
+//! Element-wise operations between fields on the same grid
+//!
+//! A point is missing (NAN) in the result if it is missing in any operand.
+
+use crate::field::DecodedField;
+use crate::{Error, Result};
+
+/// Apply `f` to the values of `a` and `b` at each point
+pub fn zip_with(
+    a: &DecodedField,
+    b: &DecodedField,
+    f: impl Fn(f32, f32) -> f32,
+) -> Result<Vec<f32>> {
+    ensure_same_grid(a, b)?;
+    Ok(a.values
+        .iter()
+        .zip(&b.values)
+        .map(|(&a, &b)| match a.is_nan() || b.is_nan() {
+            true => f32::NAN,
+            false => f(a, b),
+        })
+        .collect())
+}
+
+/// `a - b` at each point, e.g. a run-to-run change or an anomaly against a climatology
+pub fn difference(a: &DecodedField, b: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(a, b, |a, b| a - b)
+}
+
+/// `a / b` at each point, missing where `b` is zero
+pub fn ratio(a: &DecodedField, b: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(a, b, |a, b| match b {
+        0.0 => f32::NAN,
+        b => a / b,
+    })
+}
+
+/// `offset + Σ weight * field` at each point
+pub fn linear_combination(terms: &[(f32, &DecodedField)], offset: f32) -> Result<Vec<f32>> {
+    let Some((_, first)) = terms.first() else {
+        return Err(Error::InvalidData("no fields to combine".to_string()));
+    };
+    for (_, field) in &terms[1..] {
+        ensure_same_grid(first, field)?;
+    }
+    Ok((0..first.values.len())
+        .map(|p| {
+            terms.iter().fold(offset, |sum, (weight, field)| {
+                sum + weight * field.values[p]
+            })
+        })
+        .collect())
+}
+
+fn ensure_same_grid(a: &DecodedField, b: &DecodedField) -> Result<()> {
+    if !a.same_grid(b)? {
+        return Err(Error::InvalidData(
+            "fields are on different grids".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,265 @@
+//! Arrow and Parquet export of decoded fields and inventories (features `arrow` and `parquet`)
+//!
+//! Fields become one row per grid point with columns `lat`, `lon`, `value`, `parameter`,
+//! `valid_time` and `level`, named and described as in the wgrib2 inventory (see
+//! [`IdxRecord`]). Missing points are left out. Coordinates are those of
+//! [`crate::templates::GridDefinitionTemplate::lat_lon`], and null on other grids.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    Float32Builder, Float64Builder, StringBuilder, TimestampSecondBuilder, UInt32Builder,
+    UInt64Builder,
+};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+
+use crate::field::DecodedField;
+use crate::idx::{Idx, IdxRecord};
+use crate::{Error, Result};
+
+fn timestamp() -> DataType {
+    DataType::Timestamp(TimeUnit::Second, Some("UTC".into()))
+}
+
+/// Schema of the batches of [`fields_to_record_batch`]
+pub fn field_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("lat", DataType::Float64, true),
+        Field::new("lon", DataType::Float64, true),
+        Field::new("value", DataType::Float32, false),
+        Field::new("parameter", DataType::Utf8, false),
+        Field::new("valid_time", timestamp(), true),
+        Field::new("level", DataType::Utf8, false),
+    ]))
+}
+
+/// Rows of the grid points of `fields`, without missing values
+pub fn fields_to_record_batch<'a>(
+    fields: impl IntoIterator<Item = &'a DecodedField>,
+) -> Result<RecordBatch> {
+    let mut lat = Float64Builder::new();
+    let mut lon = Float64Builder::new();
+    let mut value = Float32Builder::new();
+    let mut parameter = StringBuilder::new();
+    let mut valid_time = TimestampSecondBuilder::new().with_timezone("UTC");
+    let mut level = StringBuilder::new();
+    for field in fields {
+        let record = IdxRecord::new(
+            0,
+            None,
+            0,
+            field.discipline,
+            field.centre,
+            field.reference_time,
+            &field.product_definition.template,
+        );
+        let time = field.valid_time.map(|t| t.end.unix_timestamp());
+        let ni = field.shape.1.max(1);
+        for (k, &v) in field.values.iter().enumerate() {
+            if v.is_nan() {
+                continue;
+            }
+            let lat_lon = field
+                .grid_definition
+                .lat_lon((k / ni) as f64, (k % ni) as f64);
+            lat.append_option(lat_lon.map(|(lat, _)| lat));
+            lon.append_option(lat_lon.map(|(_, lon)| lon));
+            value.append_value(v);
+            parameter.append_value(&record.variable);
+            valid_time.append_option(time);
+            level.append_value(&record.level);
+        }
+    }
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(lat.finish()),
+        Arc::new(lon.finish()),
+        Arc::new(value.finish()),
+        Arc::new(parameter.finish()),
+        Arc::new(valid_time.finish()),
+        Arc::new(level.finish()),
+    ];
+    RecordBatch::try_new(field_schema(), columns).map_err(arrow_error)
+}
+
+/// Schema of the batches of [`idx_to_record_batch`]
+pub fn idx_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("message_number", DataType::UInt32, false),
+        Field::new("field_number", DataType::UInt32, true),
+        Field::new("offset", DataType::UInt64, false),
+        Field::new("length", DataType::UInt64, true),
+        Field::new("reference_time", timestamp(), false),
+        Field::new("parameter", DataType::Utf8, false),
+        Field::new("level", DataType::Utf8, false),
+        Field::new("forecast", DataType::Utf8, false),
+    ]))
+}
+
+/// One row per record of an `.idx` inventory
+pub fn idx_to_record_batch(idx: &Idx) -> Result<RecordBatch> {
+    let mut message_number = UInt32Builder::new();
+    let mut field_number = UInt32Builder::new();
+    let mut offset = UInt64Builder::new();
+    let mut length = UInt64Builder::new();
+    let mut reference_time = TimestampSecondBuilder::new().with_timezone("UTC");
+    let mut parameter = StringBuilder::new();
+    let mut level = StringBuilder::new();
+    let mut forecast = StringBuilder::new();
+    for record in &idx.records {
+        message_number.append_value(record.message_number);
+        field_number.append_option(record.field_number);
+        offset.append_value(record.offset);
+        length.append_option(record.length);
+        reference_time.append_value(record.reference_time.unix_timestamp());
+        parameter.append_value(&record.variable);
+        level.append_value(&record.level);
+        forecast.append_value(&record.forecast);
+    }
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(message_number.finish()),
+        Arc::new(field_number.finish()),
+        Arc::new(offset.finish()),
+        Arc::new(length.finish()),
+        Arc::new(reference_time.finish()),
+        Arc::new(parameter.finish()),
+        Arc::new(level.finish()),
+        Arc::new(forecast.finish()),
+    ];
+    RecordBatch::try_new(idx_schema(), columns).map_err(arrow_error)
+}
+
+/// Write `batches` of the same schema as a Parquet file (feature `parquet`)
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W: std::io::Write + Send>(writer: W, batches: &[RecordBatch]) -> Result<()> {
+    let schema = batches
+        .first()
+        .map(|batch| batch.schema())
+        .ok_or_else(|| Error::InvalidData("no record batches to write".to_string()))?;
+    let mut writer =
+        parquet::arrow::ArrowWriter::try_new(writer, schema, None).map_err(arrow_error)?;
+    for batch in batches {
+        writer.write(batch).map_err(arrow_error)?;
+    }
+    writer.close().map_err(arrow_error)?;
+    Ok(())
+}
+
+fn arrow_error(e: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::IO(std::io::Error::other(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Float32Type, Float64Type, TimestampSecondType, UInt64Type};
+
+    use super::*;
+    use crate::field::DecodedFields;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Temperature at 850 hPa, 6 hours after 2024-01-02T00Z, on a 2 x 2 grid of 1 degree
+    /// from 35N 135E, with the last point missing
+    fn message() -> Vec<u8> {
+        let mut body = section(1, &[0, 7, 0, 0, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 35_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [36_000_000u32, 136_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x40);
+        body.extend(section(3, &grid));
+        body.extend(section(
+            4,
+            &[
+                0, 0, 0, 0, 0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 6, 100, 0, 0, 1, 76, 8, 255, 255,
+                255, 255, 255, 255,
+            ],
+        ));
+        let mut representation = vec![0, 0, 0, 3, 0, 0];
+        representation.extend(250f32.to_be_bytes());
+        representation.extend([0, 0, 0, 0, 8, 0]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[0, 0b1110_0000]));
+        body.extend(section(7, &[0, 1, 2]));
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn decoded_fields() -> Vec<DecodedField> {
+        DecodedFields::new(message().as_slice())
+            .collect::<Result<Vec<_>>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn fields_batch() {
+        let batch = fields_to_record_batch(&decoded_fields()).unwrap();
+        assert_eq!(batch.schema(), field_schema());
+        assert_eq!(batch.num_rows(), 3);
+        let column = |name| batch.column_by_name(name).unwrap();
+        let lat = column("lat").as_primitive::<Float64Type>();
+        let lon = column("lon").as_primitive::<Float64Type>();
+        assert_eq!(lat.values(), &[35.0, 35.0, 36.0]);
+        assert_eq!(lon.values(), &[135.0, 136.0, 135.0]);
+        let value = column("value").as_primitive::<Float32Type>();
+        assert_eq!(value.values(), &[250.0, 251.0, 252.0]);
+        assert_eq!(column("parameter").as_string::<i32>().value(0), "TMP");
+        assert_eq!(column("level").as_string::<i32>().value(2), "850 mb");
+        let valid_time = column("valid_time").as_primitive::<TimestampSecondType>();
+        assert_eq!(valid_time.value(0), 1_704_153_600 + 6 * 3600);
+    }
+
+    #[test]
+    fn idx_batch() {
+        let idx = Idx::build(message().as_slice()).unwrap();
+        let batch = idx_to_record_batch(&idx).unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        let column = |name| batch.column_by_name(name).unwrap();
+        assert_eq!(column("offset").as_primitive::<UInt64Type>().value(0), 0);
+        let length = column("length").as_primitive::<UInt64Type>().value(0);
+        assert_eq!(length, message().len() as u64);
+        assert_eq!(column("parameter").as_string::<i32>().value(0), "TMP");
+        assert_eq!(
+            column("forecast").as_string::<i32>().value(0),
+            "6 hour fcst"
+        );
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_round_trip() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let batch = fields_to_record_batch(&decoded_fields()).unwrap();
+        let path = std::env::temp_dir().join(format!("tinygrib2-{}.parquet", std::process::id()));
+        write_parquet(
+            std::fs::File::create(&path).unwrap(),
+            std::slice::from_ref(&batch),
+        )
+        .unwrap();
+        let read = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap()
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read, [batch]);
+        assert!(write_parquet(Vec::new(), &[]).is_err());
+    }
+}
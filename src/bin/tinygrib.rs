@@ -0,0 +1,235 @@
+//! Command line tool for inspecting and converting GRIB2 files
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use tinygrib2::filter::FieldFilter;
+use tinygrib2::idx::{Idx, IdxRecord};
+use tinygrib2::split::extract_fields;
+use tinygrib2::time::DateTime;
+use tinygrib2::{DecodedField, DecodedFields, Error, Result};
+
+const USAGE: &str = "\
+usage: tinygrib <command> <file> [options]
+
+commands:
+  inventory <file>                       print a wgrib2-style inventory
+  dump <file>                            print all sections and templates
+  extract <file> <output> [filters]      copy matching fields to <output>
+  convert <file> --format csv|geojson [filters]
+                                         print the values of matching fields
+
+filters:
+  --var <name>       variable abbreviation, e.g. TMP
+  --level <level>    level as in the inventory, e.g. \"850 mb\"
+  --where <expr>     e.g. 'var=TMP,UGRD level=\"850 mb\" fhour=0-24 member=1'
+";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Usage(message)) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            ExitCode::from(2)
+        }
+        Err(CliError::Grib(e)) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+enum CliError {
+    Usage(String),
+    Grib(Error),
+}
+
+impl From<Error> for CliError {
+    fn from(e: Error) -> Self {
+        Self::Grib(e)
+    }
+}
+
+impl From<std::io::Error> for CliError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Grib(e.into())
+    }
+}
+
+fn usage(message: impl Into<String>) -> CliError {
+    CliError::Usage(message.into())
+}
+
+fn run(args: &[String]) -> std::result::Result<(), CliError> {
+    let (command, rest) = args.split_first().ok_or_else(|| usage("no command"))?;
+    let (positional, options) = parse_options(rest)?;
+    let input = |i: usize| -> std::result::Result<BufReader<File>, CliError> {
+        let path = positional
+            .get(i)
+            .ok_or_else(|| usage(format!("{}: missing file", command)))?;
+        Ok(BufReader::new(File::open(path)?))
+    };
+    let mut filter = match options.get("where") {
+        Some(expression) => FieldFilter::parse(expression)?,
+        None => FieldFilter::new(),
+    };
+    if let Some(variable) = options.get("var") {
+        filter = filter.variable(variable.as_str());
+    }
+    if let Some(level) = options.get("level") {
+        filter = filter.level(level.as_str());
+    }
+    let mut out = BufWriter::new(std::io::stdout().lock());
+    match command.as_str() {
+        "inventory" => Idx::build(input(0)?)?.write(&mut out)?,
+        "dump" => tinygrib2::dump::dump(input(0)?, &mut out)?,
+        "extract" => {
+            let output = positional
+                .get(1)
+                .ok_or_else(|| usage("extract: missing output file"))?;
+            let mut writer = BufWriter::new(File::create(output)?);
+            let count = extract_fields(input(0)?, &mut writer, |message, field| {
+                filter.matches_field(message, field)
+            })?;
+            writer.flush()?;
+            eprintln!("{} message(s) written", count);
+        }
+        "convert" => {
+            let format = options
+                .get("format")
+                .ok_or_else(|| usage("convert: missing --format"))?;
+            let fields = DecodedFields::new(input(0)?).filter(|field| match field {
+                Ok(field) => filter.matches_decoded(field),
+                Err(_) => true,
+            });
+            match format.as_str() {
+                "csv" => write_csv(fields, &mut out)?,
+                "geojson" => write_geojson(fields, &mut out)?,
+                "netcdf" => return Err(usage("convert: NetCDF output is not supported")),
+                format => return Err(usage(format!("convert: unknown format {}", format))),
+            }
+        }
+        command => return Err(usage(format!("unknown command {}", command))),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Split arguments into positional ones and `--name value` options
+fn parse_options(
+    args: &[String],
+) -> std::result::Result<(Vec<&String>, std::collections::HashMap<&str, &String>), CliError> {
+    let mut positional = Vec::new();
+    let mut options = std::collections::HashMap::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--") {
+            Some(name @ ("var" | "level" | "where" | "format")) => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| usage(format!("--{} needs a value", name)))?;
+                options.insert(name, value);
+            }
+            Some(name) => return Err(usage(format!("unknown option --{}", name))),
+            None => positional.push(arg),
+        }
+    }
+    Ok((positional, options))
+}
+
+fn iso8601(t: &DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        t.year, t.month, t.day, t.hour, t.minute, t.second
+    )
+}
+
+/// Name, level and valid time of a field as in the inventory
+fn describe(field: &DecodedField) -> (String, String, String) {
+    let record = IdxRecord::new(
+        0,
+        None,
+        0,
+        field.discipline,
+        field.centre,
+        field.reference_time,
+        &field.product_definition.template,
+    );
+    let valid_time = field
+        .valid_time
+        .map(|t| iso8601(&t.end))
+        .unwrap_or_default();
+    (record.variable, record.level, valid_time)
+}
+
+/// Points of a field as (latitude, longitude, value), without missing values
+fn points(field: &DecodedField) -> impl Iterator<Item = (Option<(f64, f64)>, f32)> + '_ {
+    let ni = field.shape.1;
+    field
+        .values
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| !v.is_nan())
+        .map(move |(k, &v)| {
+            let lat_lon = field
+                .grid_definition
+                .lat_lon((k / ni) as f64, (k % ni) as f64);
+            (lat_lon, v)
+        })
+}
+
+fn write_csv(
+    fields: impl Iterator<Item = Result<DecodedField>>,
+    out: &mut impl Write,
+) -> Result<()> {
+    writeln!(out, "lat,lon,value,variable,level,valid_time")?;
+    for field in fields {
+        let field = field?;
+        let (variable, level, valid_time) = describe(&field);
+        for (lat_lon, value) in points(&field) {
+            let (lat, lon) = match lat_lon {
+                Some((lat, lon)) => (lat.to_string(), lon.to_string()),
+                None => (String::new(), String::new()),
+            };
+            writeln!(
+                out,
+                "{},{},{},{},\"{}\",{}",
+                lat, lon, value, variable, level, valid_time
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn write_geojson(
+    fields: impl Iterator<Item = Result<DecodedField>>,
+    out: &mut impl Write,
+) -> Result<()> {
+    write!(out, "{{\"type\":\"FeatureCollection\",\"features\":[")?;
+    let mut first = true;
+    for field in fields {
+        let field = field?;
+        let (variable, level, valid_time) = describe(&field);
+        for (lat_lon, value) in points(&field) {
+            let Some((lat, lon)) = lat_lon else {
+                return Err(Error::UnsupportedData(
+                    "coordinates of the grid definition template".to_string(),
+                ));
+            };
+            if !first {
+                write!(out, ",")?;
+            }
+            first = false;
+            write!(
+                out,
+                "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"Point\",\"coordinates\":[{},{}]}},\
+                 \"properties\":{{\"value\":{},\"variable\":{:?},\"level\":{:?},\"valid_time\":{:?}}}}}",
+                lon, lat, value, variable, level, valid_time
+            )?;
+        }
+    }
+    writeln!(out, "]}}")?;
+    Ok(())
+}
@@ -0,0 +1,527 @@
+//! C API (feature `capi`), declared in `include/tinygrib.h`
+//!
+//! Build a shared library with `cargo rustc --release --features capi --crate-type cdylib`.
+//! Functions that fail return NULL or a negative value, and [`tg_last_error`] describes the
+//! error.
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char, c_int};
+use std::fs::File;
+use std::io::BufReader;
+
+use crate::field::Message;
+use crate::idx::IdxRecord;
+use crate::{Error, Result};
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = message);
+}
+
+/// Messages of an opened file, with the fields numbered across messages
+pub struct TgFile {
+    messages: Vec<Message>,
+    /// Message and field index of each field
+    fields: Vec<(usize, usize)>,
+}
+
+/// Metadata of a field
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct TgFieldInfo {
+    /// Discipline (Code table 0.0)
+    pub discipline: u8,
+    /// Parameter category and number (Code table 4.2), 255 if unknown
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    /// Type of the first fixed surface (Code table 4.5), 255 if unknown
+    pub surface_type: u8,
+    /// Value of the first fixed surface, NAN if missing
+    pub surface_value: f64,
+    /// Perturbation number of an ensemble member, -1 otherwise
+    pub perturbation_number: c_int,
+    /// Seconds since 1970-01-01T00:00:00Z
+    pub reference_time: i64,
+    /// End of the valid time in seconds since 1970-01-01T00:00:00Z, `reference_time` if unknown
+    pub valid_time: i64,
+    /// Number of columns and rows, `ni` = number of values and `nj` = 1 for irregular grids
+    pub ni: u32,
+    pub nj: u32,
+    /// Number of values `tg_decode` writes
+    pub number_of_values: usize,
+}
+
+impl TgFile {
+    fn open(path: &CStr) -> Result<Self> {
+        let path = path
+            .to_str()
+            .map_err(|_| Error::InvalidData("path is not UTF-8".to_string()))?;
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut messages = Vec::new();
+        while let Some(message) = Message::read(&mut reader)? {
+            messages.push(message);
+        }
+        let fields = messages
+            .iter()
+            .enumerate()
+            .flat_map(|(m, message)| (0..message.fields.len()).map(move |f| (m, f)))
+            .collect();
+        Ok(Self { messages, fields })
+    }
+
+    fn field(&self, index: usize) -> Result<(&Message, &crate::field::Field)> {
+        let &(m, f) = self.fields.get(index).ok_or_else(|| {
+            Error::InvalidData(format!(
+                "field {} out of {} fields",
+                index,
+                self.fields.len()
+            ))
+        })?;
+        Ok((&self.messages[m], &self.messages[m].fields[f]))
+    }
+
+    fn info(&self, index: usize) -> Result<TgFieldInfo> {
+        let (message, field) = self.field(index)?;
+        let template = field.product_definition()?.template;
+        let reference_time = message.identification.reference_time();
+        let (parameter_category, parameter_number) = template.parameter().unwrap_or((255, 255));
+        let surface = template.fixed_surfaces().map(|(first, _)| first);
+        let number_of_values = field.grid_definition.number_of_data_points as usize;
+        let (nj, ni) = match field.grid_definition_template()?.shape() {
+            Some((nj, ni)) if nj as usize * ni as usize == number_of_values => (nj, ni),
+            _ => (1, field.grid_definition.number_of_data_points),
+        };
+        Ok(TgFieldInfo {
+            discipline: message.indicator.discipline,
+            parameter_category,
+            parameter_number,
+            surface_type: surface.map_or(255, |s| s.type_of_surface),
+            surface_value: surface.and_then(|s| s.value()).unwrap_or(f64::NAN),
+            perturbation_number: template
+                .ensemble_info()
+                .and_then(|i| i.perturbation_number)
+                .map_or(-1, c_int::from),
+            reference_time: reference_time.unix_timestamp(),
+            valid_time: template
+                .valid_time(reference_time)
+                .map_or(reference_time, |t| t.end)
+                .unix_timestamp(),
+            ni,
+            nj,
+            number_of_values,
+        })
+    }
+}
+
+/// Copy `s` as a NUL-terminated string into `buf` of `len` bytes, truncating if needed, and
+/// return the length of the whole string
+///
+/// # Safety
+///
+/// `buf` must be NULL or valid for writes of `len` bytes.
+unsafe fn copy_string(s: &str, buf: *mut c_char, len: usize) -> usize {
+    if !buf.is_null() && len > 0 {
+        let n = s.len().min(len - 1);
+        // SAFETY: `buf` is valid for `len` > `n` bytes
+        unsafe {
+            std::ptr::copy_nonoverlapping(s.as_ptr().cast(), buf, n);
+            *buf.add(n) = 0;
+        }
+    }
+    s.len()
+}
+
+/// Open a GRIB2 file and parse the headers of all its messages
+///
+/// Returns NULL on error.
+///
+/// # Safety
+///
+/// `path` must be a valid NUL-terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_open(path: *const c_char) -> *mut TgFile {
+    if path.is_null() {
+        set_last_error("path is NULL".to_string());
+        return std::ptr::null_mut();
+    }
+    // SAFETY: `path` is a valid NUL-terminated string
+    match TgFile::open(unsafe { CStr::from_ptr(path) }) {
+        Ok(file) => Box::into_raw(Box::new(file)),
+        Err(e) => {
+            set_last_error(e.to_string());
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Close a file opened by `tg_open`
+///
+/// # Safety
+///
+/// `file` must be NULL or returned by `tg_open` and not closed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_close(file: *mut TgFile) {
+    if !file.is_null() {
+        // SAFETY: `file` was created by Box::into_raw in `tg_open`
+        drop(unsafe { Box::from_raw(file) });
+    }
+}
+
+/// Number of fields in all messages of `file`
+///
+/// # Safety
+///
+/// `file` must be returned by `tg_open` and not closed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_field_count(file: *const TgFile) -> usize {
+    // SAFETY: `file` is valid
+    unsafe { file.as_ref() }.map_or(0, |file| file.fields.len())
+}
+
+/// Fill `info` with the metadata of field `index` (0-based); returns 0 on success
+///
+/// # Safety
+///
+/// `file` must be returned by `tg_open` and not closed, and `info` must be valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_field_info(
+    file: *const TgFile,
+    index: usize,
+    info: *mut TgFieldInfo,
+) -> c_int {
+    // SAFETY: `file` is valid
+    let Some(file) = (unsafe { file.as_ref() }) else {
+        set_last_error("file is NULL".to_string());
+        return -1;
+    };
+    match file.info(index) {
+        Ok(field_info) if !info.is_null() => {
+            // SAFETY: `info` is valid for writes
+            unsafe { info.write(field_info) };
+            0
+        }
+        Ok(_) => {
+            set_last_error("info is NULL".to_string());
+            -1
+        }
+        Err(e) => {
+            set_last_error(e.to_string());
+            -1
+        }
+    }
+}
+
+/// Write the wgrib2-style inventory line of field `index` into `buf` of `len` bytes
+///
+/// The line is NUL-terminated and truncated to fit, and its byte offset column is 0. Returns
+/// the length of the whole line, or -1 on error.
+///
+/// # Safety
+///
+/// `file` must be returned by `tg_open` and not closed, and `buf` must be NULL or valid for
+/// writes of `len` bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_field_inventory(
+    file: *const TgFile,
+    index: usize,
+    buf: *mut c_char,
+    len: usize,
+) -> isize {
+    // SAFETY: `file` is valid
+    let Some(file) = (unsafe { file.as_ref() }) else {
+        set_last_error("file is NULL".to_string());
+        return -1;
+    };
+    let line = file.field(index).and_then(|(message, field)| {
+        let (m, f) = file.fields[index];
+        let template = field.product_definition()?.template;
+        let record = IdxRecord::new(
+            m as u32 + 1,
+            (message.fields.len() > 1).then_some(f as u32 + 1),
+            0,
+            message.indicator.discipline,
+            message.identification.centre,
+            message.identification.reference_time(),
+            &template,
+        );
+        Ok(record.to_string())
+    });
+    match line {
+        // SAFETY: `buf` is NULL or valid for `len` bytes
+        Ok(line) => unsafe { copy_string(&line, buf, len) as isize },
+        Err(e) => {
+            set_last_error(e.to_string());
+            -1
+        }
+    }
+}
+
+/// Decode the values of field `index` into `values` of `len` elements, NAN for missing points
+///
+/// `len` must be at least `number_of_values` of `tg_field_info`. Returns 0 on success.
+///
+/// # Safety
+///
+/// `file` must be returned by `tg_open` and not closed, and `values` must be valid for writes
+/// of `len` elements.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tg_decode(
+    file: *const TgFile,
+    index: usize,
+    values: *mut f32,
+    len: usize,
+) -> c_int {
+    // SAFETY: `file` is valid
+    let Some(file) = (unsafe { file.as_ref() }) else {
+        set_last_error("file is NULL".to_string());
+        return -1;
+    };
+    let decoded = file.field(index).and_then(|(_, field)| field.values());
+    match decoded {
+        Ok(decoded) if decoded.len() <= len && !values.is_null() => {
+            // SAFETY: `values` is valid for `len` >= `decoded.len()` elements
+            unsafe { std::ptr::copy_nonoverlapping(decoded.as_ptr(), values, decoded.len()) };
+            0
+        }
+        Ok(decoded) => {
+            set_last_error(format!(
+                "buffer of {} values is too small for {} values",
+                len,
+                decoded.len()
+            ));
+            -1
+        }
+        Err(e) => {
+            set_last_error(e.to_string());
+            -1
+        }
+    }
+}
+
+/// Description of the last error on this thread, valid until the next call that fails
+#[unsafe(no_mangle)]
+pub extern "C" fn tg_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ptr())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Message of JMA from 2024-01-02T00Z with temperatures at 850 hPa, 6 hours later, on a
+    /// 2 x 3 grid, for each reference value of `fields`; the last point is missing
+    fn message(fields: &[f32]) -> Vec<u8> {
+        let mut body = section(1, &[0, 34, 0, 0, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        let mut grid = vec![0, 0, 0, 0, 6, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [3u32, 2, 0, u32::MAX, 36_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [35_000_000u32, 137_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0);
+        body.extend(section(3, &grid));
+        for &reference in fields {
+            body.extend(section(
+                4,
+                &[
+                    0, 0, 0, 0, 0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 6, 100, 0, 0, 1, 76, 8, 255,
+                    255, 255, 255, 255, 255,
+                ],
+            ));
+            let mut representation = vec![0, 0, 0, 5, 0, 0];
+            representation.extend(reference.to_be_bytes());
+            representation.extend([0, 0, 0, 0, 8, 0]);
+            body.extend(section(5, &representation));
+            body.extend(section(6, &[0, 0b1111_1000]));
+            body.extend(section(7, &[0, 1, 2, 3, 4]));
+        }
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    /// File of a single-field message and a two-field message, removed when dropped
+    struct TestFile(PathBuf);
+
+    impl TestFile {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "tinygrib2-capi-{}-{}.grib2",
+                name,
+                std::process::id()
+            ));
+            let bytes = [message(&[250.0]), message(&[260.0, 270.0])].concat();
+            std::fs::write(&path, bytes).unwrap();
+            Self(path)
+        }
+
+        fn open(&self) -> *mut TgFile {
+            let path = CString::new(self.0.to_str().unwrap()).unwrap();
+            let file = unsafe { tg_open(path.as_ptr()) };
+            assert!(!file.is_null(), "{}", last_error());
+            file
+        }
+    }
+
+    impl Drop for TestFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn last_error() -> String {
+        unsafe { CStr::from_ptr(tg_last_error()) }
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    /// Inventory line of field `index` written into a buffer of `len` bytes, followed by
+    /// sentinel bytes, and the return value
+    fn inventory(file: *const TgFile, index: usize, len: usize) -> (isize, Vec<u8>) {
+        let mut buf = vec![0x7f as c_char; len + 4];
+        let n = unsafe { tg_field_inventory(file, index, buf.as_mut_ptr(), len) };
+        (n, buf.into_iter().map(|c| c as u8).collect())
+    }
+
+    #[test]
+    fn copy_string() {
+        let copy = |s: &str, len: usize| {
+            let mut buf = [0x7f as c_char; 8];
+            let n = unsafe { super::copy_string(s, buf.as_mut_ptr(), len) };
+            (n, buf.map(|c| c as u8))
+        };
+        assert_eq!(copy("abc", 8), (3, *b"abc\0\x7f\x7f\x7f\x7f"));
+        assert_eq!(copy("abc", 4), (3, *b"abc\0\x7f\x7f\x7f\x7f"));
+        assert_eq!(copy("abc", 3), (3, *b"ab\0\x7f\x7f\x7f\x7f\x7f"));
+        assert_eq!(copy("abc", 1), (3, *b"\0\x7f\x7f\x7f\x7f\x7f\x7f\x7f"));
+        assert_eq!(copy("abc", 0), (3, [0x7f; 8]));
+        assert_eq!(copy("", 8), (0, *b"\0\x7f\x7f\x7f\x7f\x7f\x7f\x7f"));
+        assert_eq!(
+            unsafe { super::copy_string("abc", std::ptr::null_mut(), 8) },
+            3
+        );
+    }
+
+    #[test]
+    fn open_errors() {
+        assert!(unsafe { tg_open(std::ptr::null()) }.is_null());
+        assert_eq!(last_error(), "path is NULL");
+        let path = CString::new("/nonexistent/tinygrib2.grib2").unwrap();
+        assert!(unsafe { tg_open(path.as_ptr()) }.is_null());
+        assert!(!last_error().is_empty());
+        unsafe { tg_close(std::ptr::null_mut()) };
+    }
+
+    #[test]
+    fn field_info() {
+        let test_file = TestFile::new("info");
+        let file = test_file.open();
+        assert_eq!(unsafe { tg_field_count(file) }, 3);
+        assert_eq!(unsafe { tg_field_count(std::ptr::null()) }, 0);
+        let mut info = std::mem::MaybeUninit::<TgFieldInfo>::uninit();
+        assert_eq!(unsafe { tg_field_info(file, 2, info.as_mut_ptr()) }, 0);
+        let info = unsafe { info.assume_init() };
+        assert_eq!(
+            (
+                info.discipline,
+                info.parameter_category,
+                info.parameter_number
+            ),
+            (0, 0, 0)
+        );
+        assert_eq!((info.surface_type, info.surface_value), (100, 85000.0));
+        assert_eq!(info.perturbation_number, -1);
+        assert_eq!(info.reference_time, 1_704_153_600);
+        assert_eq!(info.valid_time, 1_704_153_600 + 6 * 3600);
+        assert_eq!((info.ni, info.nj, info.number_of_values), (3, 2, 6));
+        assert_eq!(unsafe { tg_field_info(file, 2, std::ptr::null_mut()) }, -1);
+        assert_eq!(last_error(), "info is NULL");
+        let mut info = std::mem::MaybeUninit::<TgFieldInfo>::uninit();
+        assert_eq!(unsafe { tg_field_info(file, 3, info.as_mut_ptr()) }, -1);
+        assert_eq!(last_error(), "Invalid format: field 3 out of 3 fields");
+        assert_eq!(
+            unsafe { tg_field_info(std::ptr::null(), 0, info.as_mut_ptr()) },
+            -1
+        );
+        assert_eq!(last_error(), "file is NULL");
+        unsafe { tg_close(file) };
+    }
+
+    #[test]
+    fn field_inventory() {
+        let test_file = TestFile::new("inventory");
+        let file = test_file.open();
+        let line = b"2.2:0:d=2024010200:TMP:850 mb:6 hour fcst:";
+        let n = line.len();
+        let (len, buf) = inventory(file, 2, 64);
+        assert_eq!(len, n as isize);
+        assert_eq!(&buf[..=n], [&line[..], b"\0"].concat());
+        // exactly the line and its NUL
+        let (len, buf) = inventory(file, 2, n + 1);
+        assert_eq!(len, n as isize);
+        assert_eq!(buf, [&line[..], b"\0\x7f\x7f\x7f\x7f"].concat());
+        // one byte short: truncated, still NUL-terminated
+        let (len, buf) = inventory(file, 2, n);
+        assert_eq!(len, n as isize);
+        assert_eq!(buf, [&line[..n - 1], b"\0\x7f\x7f\x7f\x7f"].concat());
+        let (len, buf) = inventory(file, 2, 0);
+        assert_eq!((len, buf), (n as isize, vec![0x7f; 4]));
+        let size = unsafe { tg_field_inventory(file, 2, std::ptr::null_mut(), 64) };
+        assert_eq!(size, n as isize);
+        // single-field messages are not numbered by field
+        let (len, buf) = inventory(file, 0, 64);
+        assert_eq!(
+            &buf[..=len as usize],
+            b"1:0:d=2024010200:TMP:850 mb:6 hour fcst:\0"
+        );
+        assert_eq!(inventory(file, 3, 64), (-1, vec![0x7f; 68]));
+        assert_eq!(last_error(), "Invalid format: field 3 out of 3 fields");
+        let null = unsafe { tg_field_inventory(std::ptr::null(), 0, std::ptr::null_mut(), 0) };
+        assert_eq!(null, -1);
+        unsafe { tg_close(file) };
+    }
+
+    #[test]
+    fn decode() {
+        let test_file = TestFile::new("decode");
+        let file = test_file.open();
+        let mut values = [-1.0f32; 7];
+        assert_eq!(unsafe { tg_decode(file, 1, values.as_mut_ptr(), 6) }, 0);
+        assert_eq!(values[..5], [260.0, 261.0, 262.0, 263.0, 264.0]);
+        assert!(values[5].is_nan());
+        assert_eq!(values[6], -1.0);
+        let mut values = [-1.0f32; 6];
+        assert_eq!(unsafe { tg_decode(file, 2, values.as_mut_ptr(), 5) }, -1);
+        assert_eq!(last_error(), "buffer of 5 values is too small for 6 values");
+        assert_eq!(values, [-1.0; 6]);
+        assert_eq!(unsafe { tg_decode(file, 2, std::ptr::null_mut(), 6) }, -1);
+        assert_eq!(unsafe { tg_decode(file, 3, values.as_mut_ptr(), 6) }, -1);
+        assert_eq!(last_error(), "Invalid format: field 3 out of 3 fields");
+        assert_eq!(
+            unsafe { tg_decode(std::ptr::null(), 0, values.as_mut_ptr(), 6) },
+            -1
+        );
+        assert_eq!(last_error(), "file is NULL");
+        unsafe { tg_close(file) };
+    }
+}
@@ -0,0 +1,140 @@
+//! Indexes of many files (e.g. all files of a model cycle) scanned in parallel
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use crate::index::{Index, IndexEntry};
+use crate::parallel::map_parallel;
+use crate::{Error, Result};
+
+/// Index of a file in a [`Catalog`], or the error that stopped its scan
+#[derive(Debug)]
+pub struct CatalogFile {
+    pub path: PathBuf,
+    pub index: Result<Index>,
+}
+
+/// Indexes of a list of files
+///
+/// Files are scanned on all available cores. A file that cannot be opened or parsed keeps its
+/// error in [`CatalogFile::index`] and does not affect the other files.
+///
+/// ```ignore
+/// let catalog = Catalog::glob("gfs.20240101/gfs.t00z.pgrb2.0p25.f*")?;
+/// for (path, error) in catalog.errors() {
+///     eprintln!("{}: {}", path.display(), error);
+/// }
+/// for (path, entry) in catalog.entries() {
+///     // ...
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Catalog {
+    /// Files in the order they were given
+    pub files: Vec<CatalogFile>,
+}
+
+impl Catalog {
+    /// Scan the files at `paths`
+    pub fn build<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Self {
+        let paths = paths
+            .into_iter()
+            .map(|p| p.as_ref().to_path_buf())
+            .collect::<Vec<_>>();
+        let indexes = map_parallel(paths.len(), |i| {
+            Index::build(BufReader::new(File::open(&paths[i])?))
+        });
+        Self {
+            files: paths
+                .into_iter()
+                .zip(indexes)
+                .map(|(path, index)| CatalogFile { path, index })
+                .collect(),
+        }
+    }
+
+    /// Scan the files matching `pattern`, see [`glob`]
+    pub fn glob(pattern: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::build(glob(pattern)?))
+    }
+
+    /// Entries of all files scanned successfully, with the path of their file
+    pub fn entries(&self) -> impl Iterator<Item = (&Path, &IndexEntry)> {
+        self.files.iter().flat_map(|file| {
+            let entries = match &file.index {
+                Ok(index) => index.entries.as_slice(),
+                Err(_) => &[],
+            };
+            entries.iter().map(|entry| (file.path.as_path(), entry))
+        })
+    }
+
+    /// Files that could not be scanned
+    pub fn errors(&self) -> impl Iterator<Item = (&Path, &Error)> {
+        self.files
+            .iter()
+            .filter_map(|file| file.index.as_ref().err().map(|e| (file.path.as_path(), e)))
+    }
+}
+
+/// Paths of the files matching `pattern`, sorted
+///
+/// Only the last component of the pattern may contain wildcards: `*` matches any run of
+/// characters and `?` a single character, e.g. `data/gfs.t00z.pgrb2.0p25.f0??`.
+pub fn glob(pattern: impl AsRef<Path>) -> Result<Vec<PathBuf>> {
+    let pattern = pattern.as_ref();
+    let name = pattern
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| Error::InvalidData(format!("invalid pattern {}", pattern.display())))?;
+    let dir = match pattern.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir()
+            && entry
+                .file_name()
+                .to_str()
+                .is_some_and(|file_name| wildcard_match(name, file_name))
+        {
+            paths.push(dir.join(entry.file_name()));
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let (pattern, name) = (
+        pattern.chars().collect::<Vec<_>>(),
+        name.chars().collect::<Vec<_>>(),
+    );
+    // position after the last '*' and the name position it was tried at, for backtracking
+    let mut star = None;
+    let (mut p, mut n) = (0, 0);
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((after, tried)) => {
+                    p = after;
+                    n = tried + 1;
+                    star = Some((after, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
@@ -0,0 +1,296 @@
+//! Typed values of frequently used code tables
+//!
+//! Each enum converts from and to the `u8` stored in the section structs, keeping values
+//! that are not listed (reserved or local use) as `Unknown`.
+
+macro_rules! code_table {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $value:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// Value not listed in the table (reserved or local use)
+            Unknown(u8),
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    value => Self::Unknown(value),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(value) => value,
+                }
+            }
+        }
+    };
+}
+
+code_table! {
+    /// Discipline (Code table 0.0)
+    Discipline {
+        Meteorological = 0,
+        Hydrological = 1,
+        LandSurface = 2,
+        SatelliteRemoteSensing = 3,
+        SpaceWeather = 4,
+        Oceanographic = 10,
+        HealthAndSocioeconomicImpacts = 20,
+        Missing = 255,
+    }
+}
+
+code_table! {
+    /// Significance of reference time (Code table 1.2)
+    SignificanceOfReferenceTime {
+        Analysis = 0,
+        StartOfForecast = 1,
+        VerifyingTimeOfForecast = 2,
+        ObservationTime = 3,
+        LocalTime = 4,
+        SimulationStart = 5,
+        Missing = 255,
+    }
+}
+
+code_table! {
+    /// Type of processed data (Code table 1.4)
+    TypeOfProcessedData {
+        Analysis = 0,
+        Forecast = 1,
+        AnalysisAndForecast = 2,
+        ControlForecast = 3,
+        PerturbedForecast = 4,
+        ControlAndPerturbedForecast = 5,
+        ProcessedSatelliteObservations = 6,
+        ProcessedRadarObservations = 7,
+        EventProbability = 8,
+        Missing = 255,
+    }
+}
+
+code_table! {
+    /// Type of generating process (Code table 4.3)
+    TypeOfGeneratingProcess {
+        Analysis = 0,
+        Initialization = 1,
+        Forecast = 2,
+        BiasCorrectedForecast = 3,
+        EnsembleForecast = 4,
+        ProbabilityForecast = 5,
+        ForecastError = 6,
+        AnalysisError = 7,
+        Observation = 8,
+        Climatological = 9,
+        ProbabilityWeightedForecast = 10,
+        BiasCorrectedEnsembleForecast = 11,
+        PostProcessedAnalysis = 12,
+        PostProcessedForecast = 13,
+        Nowcast = 14,
+        Hindcast = 15,
+        PhysicalRetrieval = 16,
+        RegressionAnalysis = 17,
+        DifferenceBetweenTwoForecasts = 18,
+        FirstGuess = 19,
+        AnalysisIncrement = 20,
+        InitializationIncrement = 21,
+        Missing = 255,
+    }
+}
+
+code_table! {
+    /// Indicator of unit of time range (Code table 4.4)
+    TimeUnit {
+        Minute = 0,
+        Hour = 1,
+        Day = 2,
+        Month = 3,
+        Year = 4,
+        Decade = 5,
+        /// 30 years
+        Normal = 6,
+        Century = 7,
+        ThreeHours = 10,
+        SixHours = 11,
+        TwelveHours = 12,
+        Second = 13,
+        Missing = 255,
+    }
+}
+
+impl TimeUnit {
+    /// Length of the unit, `None` for months and longer units and for unknown units
+    pub fn duration(self) -> Option<std::time::Duration> {
+        crate::time::unit_seconds(self.into()).map(std::time::Duration::from_secs)
+    }
+}
+
+code_table! {
+    /// Fixed surface type (Code table 4.5)
+    FixedSurfaceType {
+        GroundOrWaterSurface = 1,
+        CloudBase = 2,
+        CloudTops = 3,
+        ZeroDegreeIsotherm = 4,
+        AdiabaticCondensationLevel = 5,
+        MaximumWindLevel = 6,
+        Tropopause = 7,
+        NominalTopOfAtmosphere = 8,
+        SeaBottom = 9,
+        EntireAtmosphere = 10,
+        CumulonimbusBase = 11,
+        CumulonimbusTop = 12,
+        LowestCloudCoverLevel = 13,
+        LevelOfFreeConvection = 14,
+        ConvectiveCondensationLevel = 15,
+        LevelOfNeutralBuoyancy = 16,
+        IsothermalLevel = 20,
+        LowestMassDensityLevel = 21,
+        HighestMassDensityLevel = 22,
+        IsobaricSurface = 100,
+        MeanSeaLevel = 101,
+        AltitudeAboveMeanSeaLevel = 102,
+        HeightAboveGround = 103,
+        SigmaLevel = 104,
+        HybridLevel = 105,
+        DepthBelowLandSurface = 106,
+        IsentropicLevel = 107,
+        PressureDifferenceFromGround = 108,
+        PotentialVorticitySurface = 109,
+        EtaLevel = 111,
+        LogarithmicHybridLevel = 113,
+        SnowLevel = 114,
+        MixedLayerDepth = 117,
+        HybridHeightLevel = 118,
+        HybridPressureLevel = 119,
+        GeneralizedVerticalHeight = 150,
+        SoilLevel = 151,
+        DepthBelowSeaLevel = 160,
+        DepthBelowWaterSurface = 161,
+        LakeOrRiverBottom = 162,
+        BottomOfSedimentLayer = 163,
+        BottomOfThermallyActiveSedimentLayer = 164,
+        BottomOfSedimentLayerPenetratedByThermalWave = 165,
+        MixingLayer = 166,
+        BottomOfRootZone = 167,
+        OceanModelLevel = 168,
+        TopSurfaceOfIce = 174,
+        TopSurfaceOfIceUnderSnow = 175,
+        BottomSurfaceOfIce = 176,
+        DeepSoil = 177,
+        Missing = 255,
+    }
+}
+
+code_table! {
+    /// Type of statistical processing (Code table 4.10)
+    StatisticalProcess {
+        Average = 0,
+        Accumulation = 1,
+        Maximum = 2,
+        Minimum = 3,
+        /// Value at the end of the time range minus value at the beginning
+        Difference = 4,
+        RootMeanSquare = 5,
+        StandardDeviation = 6,
+        Covariance = 7,
+        /// Value at the beginning of the time range minus value at the end
+        DifferenceReversed = 8,
+        Ratio = 9,
+        StandardizedAnomaly = 10,
+        Summation = 11,
+        ReturnPeriod = 12,
+        Missing = 255,
+    }
+}
+
+/// Nature of a product, combining the identification section and the product definition
+///
+/// The type of generating process of the product definition (Code table 4.3) describes the
+/// field itself and takes precedence. Without one, the type of processed data of the
+/// identification section (Code table 1.4) applies to all fields of the message, and for
+/// mixed "analysis and forecast" messages the significance of the reference time (Code table
+/// 1.2) decides.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProductKind {
+    /// Analysis or initialized state, valid at the analysis time
+    Analysis,
+    /// Forecast, including ensemble, probability and post-processed forecasts and first
+    /// guesses, valid at the start of forecast plus the forecast time
+    Forecast,
+    /// Observation or retrieval, valid at the observation time
+    Observation,
+    /// Climatological product
+    Climatology,
+    /// Not determined by the codes
+    Unknown,
+}
+
+impl ProductKind {
+    pub fn new(
+        type_of_processed_data: TypeOfProcessedData,
+        significance_of_reference_time: SignificanceOfReferenceTime,
+        generating_process: Option<TypeOfGeneratingProcess>,
+    ) -> Self {
+        use TypeOfGeneratingProcess as G;
+        match generating_process {
+            Some(
+                G::Analysis
+                | G::Initialization
+                | G::AnalysisError
+                | G::PostProcessedAnalysis
+                | G::RegressionAnalysis
+                | G::AnalysisIncrement
+                | G::InitializationIncrement,
+            ) => return Self::Analysis,
+            Some(
+                G::Forecast
+                | G::BiasCorrectedForecast
+                | G::EnsembleForecast
+                | G::ProbabilityForecast
+                | G::ForecastError
+                | G::ProbabilityWeightedForecast
+                | G::BiasCorrectedEnsembleForecast
+                | G::PostProcessedForecast
+                | G::Nowcast
+                | G::Hindcast
+                | G::DifferenceBetweenTwoForecasts
+                | G::FirstGuess,
+            ) => return Self::Forecast,
+            Some(G::Observation | G::PhysicalRetrieval) => return Self::Observation,
+            Some(G::Climatological) => return Self::Climatology,
+            Some(G::Missing | G::Unknown(_)) | None => {}
+        }
+        use TypeOfProcessedData as P;
+        match type_of_processed_data {
+            P::Analysis => return Self::Analysis,
+            P::Forecast
+            | P::ControlForecast
+            | P::PerturbedForecast
+            | P::ControlAndPerturbedForecast
+            | P::EventProbability => return Self::Forecast,
+            P::ProcessedSatelliteObservations | P::ProcessedRadarObservations => {
+                return Self::Observation;
+            }
+            P::AnalysisAndForecast | P::Missing | P::Unknown(_) => {}
+        }
+        match significance_of_reference_time {
+            SignificanceOfReferenceTime::Analysis => Self::Analysis,
+            SignificanceOfReferenceTime::StartOfForecast => Self::Forecast,
+            SignificanceOfReferenceTime::ObservationTime => Self::Observation,
+            _ => Self::Unknown,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Sections in effect for the current field while scanning a stream
+//!
+//! A field repeats only some of sections 2 to 7 of the previous field of its message, the
+//! others remaining in effect. [`FieldContext`] keeps track of them:
+//!
+//! ```ignore
+//! let mut context = FieldContext::new();
+//! for event in SectionReader::new(reader) {
+//!     if let Some(field) = context.update(event?)? {
+//!         let grid = context.grid_definition()?;
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use crate::field::Field;
+use crate::lazy::LazyTemplate;
+use crate::message::*;
+use crate::reader::SectionEvent;
+use crate::templates::{DataRepresentationTemplate, GridDefinitionTemplate};
+use crate::{Error, Result};
+
+/// Sections of the current message and field, updated from [`SectionEvent`]s
+///
+/// Templates are parsed when first asked for. The grid definition and local use sections stay
+/// in effect for the following fields of the message until they are repeated; the bit-map
+/// defined last is kept for fields reusing it (bit-map indicator 254).
+#[derive(Debug, Default)]
+pub struct FieldContext {
+    indicator: Option<IndicatorSectionHeader>,
+    identification: Option<IdentificationSectionHeader>,
+    local_use: Option<(LocalUseSectionHeader, Vec<u8>)>,
+    grid_definition: Option<LazyTemplate<GridDefinitionSectionHeader>>,
+    product_definition: Option<LazyTemplate<ProductDefinitionSectionHeader>>,
+    data_representation: Option<LazyTemplate<DataRepresentationSectionHeader>>,
+    bitmap: Option<(BitmapSectionHeader, Vec<u8>)>,
+    previous_bitmap: Option<Vec<u8>>,
+}
+
+impl FieldContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the next section event of a stream, and return the field completed by its data
+    /// section
+    ///
+    /// Sections 4 to 6 are moved into the returned field, while the sections still in effect
+    /// for the next field are copied into it. An indicator section starts a new message and
+    /// clears all sections.
+    pub fn update(&mut self, event: SectionEvent) -> Result<Option<Field>> {
+        match event {
+            SectionEvent::Indicator(is) => {
+                *self = Self::new();
+                self.indicator = Some(is);
+            }
+            SectionEvent::Identification { header, .. } => self.identification = Some(header),
+            SectionEvent::LocalUse { header, body } => self.local_use = Some((header, body)),
+            SectionEvent::GridDefinition { header, body } => {
+                self.grid_definition = Some(LazyTemplate::new(header, body))
+            }
+            SectionEvent::ProductDefinition { header, body } => {
+                self.product_definition = Some(LazyTemplate::new(header, body))
+            }
+            SectionEvent::DataRepresentation { header, body } => {
+                self.data_representation = Some(LazyTemplate::new(header, body))
+            }
+            SectionEvent::Bitmap { header, body } => {
+                let body = match header.bit_map_indicator {
+                    254 => self.previous_bitmap.clone().ok_or_else(|| {
+                        Error::InvalidData("no bit-map has been defined".to_string())
+                    })?,
+                    _ => body,
+                };
+                if header.bit_map_indicator == 0 {
+                    self.previous_bitmap = Some(body.clone());
+                }
+                self.bitmap = Some((header, body));
+            }
+            SectionEvent::Data { header, body } => {
+                let missing = |section: u8| {
+                    Error::InvalidData(format!("data section without section {}", section))
+                };
+                let (grid_definition, grid_definition_body) = self
+                    .grid_definition
+                    .as_ref()
+                    .map(|t| (t.header.clone(), t.body().to_vec()))
+                    .ok_or_else(|| missing(3))?;
+                let (product_definition, product_definition_body) = self
+                    .product_definition
+                    .take()
+                    .ok_or_else(|| missing(4))?
+                    .into_parts();
+                let (data_representation, data_representation_body) = self
+                    .data_representation
+                    .take()
+                    .ok_or_else(|| missing(5))?
+                    .into_parts();
+                let (bitmap, bitmap_body) = self.bitmap.take().ok_or_else(|| missing(6))?;
+                return Ok(Some(Field {
+                    local_use: self.local_use.clone(),
+                    grid_definition,
+                    grid_definition_body,
+                    product_definition,
+                    product_definition_body,
+                    data_representation,
+                    data_representation_body,
+                    bitmap,
+                    bitmap_body,
+                    data: header,
+                    data_body: body,
+                }));
+            }
+            SectionEvent::MessageEnd => {}
+        }
+        Ok(None)
+    }
+
+    /// Indicator section of the current message
+    pub fn indicator(&self) -> Option<&IndicatorSectionHeader> {
+        self.indicator.as_ref()
+    }
+
+    /// Identification section of the current message
+    pub fn identification(&self) -> Option<&IdentificationSectionHeader> {
+        self.identification.as_ref()
+    }
+
+    /// Local use section in effect, if any
+    pub fn local_use(&self) -> Option<&(LocalUseSectionHeader, Vec<u8>)> {
+        self.local_use.as_ref()
+    }
+
+    /// Grid definition template in effect
+    pub fn grid_definition(&self) -> Result<&GridDefinitionTemplate> {
+        self.grid_definition
+            .as_ref()
+            .ok_or_else(|| Error::InvalidData("no grid definition section".to_string()))?
+            .get()
+    }
+
+    /// Product definition of the current field, until its data section
+    pub fn product_definition(&self) -> Result<&ProductDefinition> {
+        self.product_definition
+            .as_ref()
+            .ok_or_else(|| Error::InvalidData("no product definition section".to_string()))?
+            .get()
+    }
+
+    /// Data representation template of the current field, until its data section
+    pub fn data_representation(&self) -> Result<&DataRepresentationTemplate> {
+        self.data_representation
+            .as_ref()
+            .ok_or_else(|| Error::InvalidData("no data representation section".to_string()))?
+            .get()
+    }
+
+    /// Bit-map section of the current field, until its data section, with the bit-map it
+    /// reuses in place of an empty body (indicator 254)
+    pub fn bitmap(&self) -> Option<&(BitmapSectionHeader, Vec<u8>)> {
+        self.bitmap.as_ref()
+    }
+}
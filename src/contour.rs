@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use crate::field::DecodedField;
+use crate::{Error, Result};
+
+/// Coordinates of isoline points, given as `(x, y)`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Projection {
+    /// Fractional column and row indices `(i, j)` in scanning order
+    Grid,
+    /// Longitude and latitude in degrees
+    LatLon,
+    /// EPSG:3857 metres
+    WebMercator,
+}
+
+/// Line along which a field equals `level`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Isoline {
+    pub level: f32,
+    pub points: Vec<(f64, f64)>,
+    /// Whether the last point connects back to the first one
+    pub closed: bool,
+}
+
+/// Isolines of `field` at each of `levels` by marching squares
+///
+/// Grid cells with a missing corner are skipped, so lines end at missing points and at the
+/// edges of the grid. Saddle cells are resolved by the mean of their corners.
+pub fn isolines(
+    field: &DecodedField,
+    levels: &[f32],
+    projection: Projection,
+) -> Result<Vec<Isoline>> {
+    if projection != Projection::Grid && field.grid_definition.lat_lon(0.0, 0.0).is_none() {
+        return Err(Error::UnsupportedData(
+            "coordinates of the grid definition template".to_string(),
+        ));
+    }
+    let mut isolines = Vec::new();
+    for &level in levels {
+        for (points, closed) in trace(&field.values, field.shape, level) {
+            let points = points
+                .into_iter()
+                .map(|(j, i)| match projection {
+                    Projection::Grid => (i, j),
+                    Projection::LatLon => {
+                        let (lat, lon) = field.grid_definition.lat_lon(j, i).unwrap();
+                        (lon, lat)
+                    }
+                    Projection::WebMercator => {
+                        let (lat, lon) = field.grid_definition.lat_lon(j, i).unwrap();
+                        web_mercator(lat, lon)
+                    }
+                })
+                .collect();
+            isolines.push(Isoline {
+                level,
+                points,
+                closed,
+            });
+        }
+    }
+    Ok(isolines)
+}
+
+/// Radius in metres of the sphere of EPSG:3857
+const WEB_MERCATOR_RADIUS: f64 = 6378137.0;
+
+/// EPSG:3857 coordinates of a point, with latitudes clamped to the projection's limit
+pub(crate) fn web_mercator(lat: f64, lon: f64) -> (f64, f64) {
+    const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+    let lat = lat.clamp(-MAX_LATITUDE, MAX_LATITUDE).to_radians();
+    (
+        WEB_MERCATOR_RADIUS * lon.to_radians(),
+        WEB_MERCATOR_RADIUS * (std::f64::consts::FRAC_PI_4 + lat / 2.0).tan().ln(),
+    )
+}
+
+/// Latitude and longitude in degrees of EPSG:3857 coordinates
+pub(crate) fn web_mercator_inverse(x: f64, y: f64) -> (f64, f64) {
+    (
+        (2.0 * (y / WEB_MERCATOR_RADIUS).exp().atan() - std::f64::consts::FRAC_PI_2).to_degrees(),
+        (x / WEB_MERCATOR_RADIUS).to_degrees(),
+    )
+}
+
+/// Grid edge crossed by an isoline: the edge from point `(j, i)` to the next column, or to
+/// the next row if `vertical`
+type Edge = (usize, usize, bool);
+
+/// Polylines of fractional `(j, i)` indices along which `values` equal `level`
+fn trace(values: &[f32], (nj, ni): (usize, usize), level: f32) -> Vec<(Vec<(f64, f64)>, bool)> {
+    let value = |j: usize, i: usize| values[j * ni + i];
+    let crossing = |(j, i, vertical): Edge| {
+        let (a, b) = match vertical {
+            false => (value(j, i), value(j, i + 1)),
+            true => (value(j, i), value(j + 1, i)),
+        };
+        let t = ((level - a) / (b - a)) as f64;
+        match vertical {
+            false => (j as f64, i as f64 + t),
+            true => (j as f64 + t, i as f64),
+        }
+    };
+
+    let mut segments: Vec<[Edge; 2]> = Vec::new();
+    for j in 0..nj.saturating_sub(1) {
+        for i in 0..ni.saturating_sub(1) {
+            let corners = [
+                value(j, i),
+                value(j, i + 1),
+                value(j + 1, i + 1),
+                value(j + 1, i),
+            ];
+            if corners.iter().any(|v| v.is_nan()) {
+                continue;
+            }
+            let case = corners
+                .iter()
+                .enumerate()
+                .fold(0, |case, (k, &v)| case | ((v >= level) as u8) << k);
+            let top = (j, i, false);
+            let right = (j, i + 1, true);
+            let bottom = (j + 1, i, false);
+            let left = (j, i, true);
+            let center_high = corners.iter().sum::<f32>() / 4.0 >= level;
+            match case {
+                0 | 15 => {}
+                1 | 14 => segments.push([left, top]),
+                2 | 13 => segments.push([top, right]),
+                3 | 12 => segments.push([left, right]),
+                4 | 11 => segments.push([right, bottom]),
+                6 | 9 => segments.push([top, bottom]),
+                7 | 8 => segments.push([left, bottom]),
+                5 if center_high => segments.extend([[top, right], [bottom, left]]),
+                10 if !center_high => segments.extend([[top, right], [bottom, left]]),
+                5 | 10 => segments.extend([[left, top], [right, bottom]]),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    // each edge is shared by at most two segments
+    let mut by_edge: HashMap<Edge, Vec<usize>> = HashMap::new();
+    for (k, segment) in segments.iter().enumerate() {
+        for edge in segment {
+            by_edge.entry(*edge).or_default().push(k);
+        }
+    }
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+    // open lines start at an edge of a single segment, then the rest are closed loops
+    let starts = segments
+        .iter()
+        .enumerate()
+        .flat_map(|(k, s)| s.iter().map(move |edge| (k, *edge)))
+        .filter(|(_, edge)| by_edge[edge].len() == 1)
+        .chain(segments.iter().enumerate().map(|(k, s)| (k, s[0])))
+        .collect::<Vec<_>>();
+    for (first, start) in starts {
+        if used[first] {
+            continue;
+        }
+        let mut points = vec![crossing(start)];
+        let (mut k, mut edge) = (first, start);
+        loop {
+            used[k] = true;
+            edge = if segments[k][0] == edge {
+                segments[k][1]
+            } else {
+                segments[k][0]
+            };
+            if edge == start {
+                break;
+            }
+            points.push(crossing(edge));
+            match by_edge[&edge].iter().find(|&&next| !used[next]) {
+                Some(&next) => k = next,
+                None => break,
+            }
+        }
+        let closed = edge == start;
+        lines.push((points, closed));
+    }
+    lines
+}
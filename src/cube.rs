@@ -0,0 +1,123 @@
+use crate::field::DecodedField;
+use crate::templates::EnsembleInfo;
+use crate::time::{DateTime, ValidTime};
+use crate::{Error, Result};
+
+/// Fields of one parameter and time stacked over the levels of one surface type
+#[derive(Debug)]
+pub struct VerticalCube {
+    /// Discipline (Code table 0.0)
+    pub discipline: u8,
+    /// Parameter category and number (Code table 4.2)
+    pub parameter: (u8, u8),
+    pub reference_time: DateTime,
+    pub valid_time: Option<ValidTime>,
+    pub ensemble_info: Option<EnsembleInfo>,
+    /// Type of the fixed surfaces (Code table 4.5)
+    pub surface_type: u8,
+    /// Values of the surfaces in increasing order, in the unit of the surface type
+    pub levels: Vec<f64>,
+    /// Number of rows and columns (nj, ni) of each level
+    pub shape: (usize, usize),
+    /// Values ordered by level, row and column
+    pub values: Vec<f32>,
+}
+
+impl VerticalCube {
+    /// Number of levels, rows and columns
+    pub fn dims(&self) -> (usize, usize, usize) {
+        (self.levels.len(), self.shape.0, self.shape.1)
+    }
+
+    /// Value at level `k`, row `j` and column `i`
+    pub fn get(&self, k: usize, j: usize, i: usize) -> Option<f32> {
+        let (nk, nj, ni) = self.dims();
+        (k < nk && j < nj && i < ni).then(|| self.values[(k * nj + j) * ni + i])
+    }
+
+    /// Values of level `k` in scanning order
+    pub fn level(&self, k: usize) -> Option<&[f32]> {
+        let n = self.shape.0 * self.shape.1;
+        self.values.get(k * n..(k + 1) * n)
+    }
+
+    /// Values at row `j` and column `i` for every level (a sounding)
+    pub fn column(&self, j: usize, i: usize) -> Option<Vec<f32>> {
+        (0..self.levels.len()).map(|k| self.get(k, j, i)).collect()
+    }
+}
+
+/// Level and values of a field, before the levels are sorted
+type Level = (f64, Vec<f32>);
+
+/// Stack `fields` into cubes of the same parameter, times, ensemble member and surface type
+///
+/// Only fields at a single level (no second fixed surface) with a known parameter are used.
+/// Cubes are returned in the order their first field appears. Fields of a cube must have the
+/// same shape and distinct levels.
+pub fn vertical_cubes(fields: impl IntoIterator<Item = DecodedField>) -> Result<Vec<VerticalCube>> {
+    let mut cubes: Vec<(VerticalCube, Vec<Level>)> = Vec::new();
+    for field in fields {
+        let template = &field.product_definition.template;
+        let (Some(parameter), Some((first, second))) =
+            (template.parameter(), template.fixed_surfaces())
+        else {
+            continue;
+        };
+        let Some(level) = first.value() else {
+            continue;
+        };
+        if !second.is_missing() {
+            continue;
+        }
+        let ensemble_info = template.ensemble_info();
+        let index = cubes.iter().position(|(cube, _)| {
+            cube.discipline == field.discipline
+                && cube.parameter == parameter
+                && cube.reference_time == field.reference_time
+                && cube.valid_time == field.valid_time
+                && cube.ensemble_info == ensemble_info
+                && cube.surface_type == first.type_of_surface
+        });
+        let index = index.unwrap_or_else(|| {
+            cubes.push((
+                VerticalCube {
+                    discipline: field.discipline,
+                    parameter,
+                    reference_time: field.reference_time,
+                    valid_time: field.valid_time,
+                    ensemble_info,
+                    surface_type: first.type_of_surface,
+                    levels: Vec::new(),
+                    shape: field.shape,
+                    values: Vec::new(),
+                },
+                Vec::new(),
+            ));
+            cubes.len() - 1
+        });
+        let (cube, levels) = &mut cubes[index];
+        if cube.shape != field.shape {
+            return Err(Error::InvalidData(format!(
+                "level {} has shape {:?}, but other levels have {:?}",
+                level, field.shape, cube.shape
+            )));
+        }
+        if levels.iter().any(|(l, _)| *l == level) {
+            return Err(Error::InvalidData(format!("level {} appears twice", level)));
+        }
+        levels.push((level, field.values));
+    }
+
+    Ok(cubes
+        .into_iter()
+        .map(|(mut cube, mut levels)| {
+            levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (level, values) in levels {
+                cube.levels.push(level);
+                cube.values.extend(values);
+            }
+            cube
+        })
+        .collect())
+}
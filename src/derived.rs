@@ -0,0 +1,112 @@
+//! Variables derived from decoded fields
+//!
+//! Point functions take and return SI units as used by GRIB2 (K, Pa, m/s, %). The `*_field`
+//! functions apply them to each point of fields on the same grid, giving NAN where any input
+//! is missing.
+
+use crate::arithmetic::zip_with;
+use crate::codes::FixedSurfaceType;
+use crate::field::DecodedField;
+use crate::{Error, Result};
+
+const ZERO_CELSIUS: f32 = 273.15;
+
+/// Saturation vapour pressure over water in hPa (Bolton, 1980)
+fn saturation_vapour_pressure(t: f32) -> f32 {
+    let t = t - ZERO_CELSIUS;
+    6.112 * (17.67 * t / (t + 243.5)).exp()
+}
+
+/// Relative humidity (%) from temperature and dewpoint (K)
+pub fn relative_humidity(t: f32, td: f32) -> f32 {
+    100.0 * saturation_vapour_pressure(td) / saturation_vapour_pressure(t)
+}
+
+/// Dewpoint (K) from temperature (K) and relative humidity (%)
+pub fn dewpoint(t: f32, rh: f32) -> f32 {
+    let x = (rh / 100.0 * saturation_vapour_pressure(t) / 6.112).ln();
+    243.5 * x / (17.67 - x) + ZERO_CELSIUS
+}
+
+/// Wind chill temperature (K) from temperature (K) and wind speed at 10 m (m/s)
+///
+/// Uses the 2001 North American formula, which is defined for temperatures at or below
+/// 10 °C and wind speeds above 4.8 km/h; the temperature is returned unchanged otherwise.
+pub fn wind_chill(t: f32, speed: f32) -> f32 {
+    let (tc, v) = (t - ZERO_CELSIUS, speed * 3.6);
+    if tc > 10.0 || v <= 4.8 {
+        return t;
+    }
+    let v = v.powf(0.16);
+    13.12 + 0.6215 * tc - 11.37 * v + 0.3965 * tc * v + ZERO_CELSIUS
+}
+
+/// Heat index (K) from temperature (K) and relative humidity (%)
+///
+/// Uses the NWS algorithm: Steadman's simple formula, replaced by the Rothfusz regression
+/// with its low and high humidity adjustments from 80 °F.
+pub fn heat_index(t: f32, rh: f32) -> f32 {
+    let f = (t - ZERO_CELSIUS) * 1.8 + 32.0;
+    let simple = 0.5 * (f + 61.0 + (f - 68.0) * 1.2 + rh * 0.094);
+    let hi = if (simple + f) / 2.0 < 80.0 {
+        simple
+    } else {
+        let mut hi = -42.379 + 2.049_015_2 * f + 10.143_331 * rh
+            - 0.224_755_4 * f * rh
+            - 0.006_837_83 * f * f
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * f * f * rh
+            + 0.000_852_82 * f * rh * rh
+            - 0.000_001_99 * f * f * rh * rh;
+        if rh < 13.0 && (80.0..=112.0).contains(&f) {
+            hi -= (13.0 - rh) / 4.0 * ((17.0 - (f - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && (80.0..=87.0).contains(&f) {
+            hi += (rh - 85.0) / 10.0 * (87.0 - f) / 5.0;
+        }
+        hi
+    };
+    (hi - 32.0) / 1.8 + ZERO_CELSIUS
+}
+
+/// Potential temperature (K) from temperature (K) and pressure (Pa)
+pub fn potential_temperature(t: f32, p: f32) -> f32 {
+    const KAPPA: f32 = 287.05 / 1004.0;
+    t * (100000.0 / p).powf(KAPPA)
+}
+
+/// Relative humidity (%) at each point from temperature and dewpoint fields
+pub fn relative_humidity_field(t: &DecodedField, td: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(t, td, relative_humidity)
+}
+
+/// Dewpoint (K) at each point from temperature and relative humidity fields
+pub fn dewpoint_field(t: &DecodedField, rh: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(t, rh, dewpoint)
+}
+
+/// Wind chill (K) at each point from temperature and wind speed fields
+pub fn wind_chill_field(t: &DecodedField, speed: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(t, speed, wind_chill)
+}
+
+/// Heat index (K) at each point from temperature and relative humidity fields
+pub fn heat_index_field(t: &DecodedField, rh: &DecodedField) -> Result<Vec<f32>> {
+    zip_with(t, rh, heat_index)
+}
+
+/// Potential temperature (K) at each point of a temperature field on an isobaric surface
+pub fn potential_temperature_field(t: &DecodedField) -> Result<Vec<f32>> {
+    let pressure = t
+        .product_definition
+        .template
+        .fixed_surfaces()
+        .and_then(|(first, _)| match first.surface_type() {
+            FixedSurfaceType::IsobaricSurface => first.value(),
+            _ => None,
+        })
+        .ok_or_else(|| Error::InvalidData("field is not on an isobaric surface".to_string()))?;
+    Ok(t.values
+        .iter()
+        .map(|&t| potential_temperature(t, pressure as f32))
+        .collect())
+}
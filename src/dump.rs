@@ -0,0 +1,424 @@
+//! Human-readable reports of messages, in the spirit of ecCodes' `grib_dump`
+//!
+//! Every section header and template is listed field by field, with the descriptions of
+//! the code tables this crate knows about:
+//!
+//! ```text
+//! message 1 (248 octets)
+//!   section 0: indicator
+//!     discipline = 0 [Meteorological]
+//!   ...
+//!   field 1
+//!     section 4: product definition (template 4.0)
+//!       parameter_category = 0
+//!       parameter_number = 0 [TMP: Temperature (K)]
+//! ```
+
+use std::fmt::Debug;
+use std::io::{Read, Write};
+
+use crate::Result;
+use crate::codes::{
+    Discipline, SignificanceOfReferenceTime, StatisticalProcess, TimeUnit, TypeOfGeneratingProcess,
+    TypeOfProcessedData,
+};
+use crate::field::{Field, Message};
+use crate::parameter::Parameter;
+use crate::surface::FixedSurface;
+use crate::templates::LocalUse;
+
+/// Lists longer than this are shortened to their first elements and length
+const MAX_LIST: usize = 16;
+
+/// Write a report of every message read from `reader`
+pub fn dump<R: Read, W: Write>(mut reader: R, out: &mut W) -> Result<()> {
+    let mut number = 0;
+    while let Some(message) = Message::read(&mut reader)? {
+        number += 1;
+        write_message(out, number, &message)?;
+    }
+    Ok(())
+}
+
+/// Write a report of `message`, the `number`-th (1-based) of its file
+///
+/// Templates that cannot be parsed are reported with their error instead of stopping the
+/// report.
+pub fn write_message<W: Write>(out: &mut W, number: usize, message: &Message) -> Result<()> {
+    let mut dumper = Dumper {
+        out,
+        discipline: message.indicator.discipline,
+        centre: message.identification.centre,
+        parameter_category: None,
+    };
+    writeln!(
+        dumper.out,
+        "message {} ({} octets)",
+        number, message.indicator.total_length
+    )?;
+    dumper.section(1, "section 0: indicator", &message.indicator)?;
+    dumper.section(1, "section 1: identification", &message.identification)?;
+    if message.identification.template_number.is_some() {
+        dumper.template(2, message.identification_template())?;
+    }
+    for (i, field) in message.fields.iter().enumerate() {
+        writeln!(dumper.out, "  field {}", i + 1)?;
+        dumper.field(field)?;
+    }
+    Ok(())
+}
+
+struct Dumper<'a, W: Write> {
+    out: &'a mut W,
+    discipline: u8,
+    centre: u16,
+    /// Last parameter category listed, to describe the parameter number that follows it
+    parameter_category: Option<u8>,
+}
+
+impl<W: Write> Dumper<'_, W> {
+    fn field(&mut self, field: &Field) -> Result<()> {
+        if let Some((header, body)) = &field.local_use {
+            self.section(2, "section 2: local use", header)?;
+            let local_use = LocalUse::read_any(self.centre, &mut body.as_slice());
+            self.template(3, local_use)?;
+        }
+        let header = &field.grid_definition;
+        let title = format!(
+            "section 3: grid definition (template 3.{})",
+            header.template_number
+        );
+        self.section(2, &title, header)?;
+        self.template(3, field.grid_definition_template())?;
+
+        let title = format!(
+            "section 4: product definition (template 4.{})",
+            field.product_definition.template_number
+        );
+        self.section(2, &title, &field.product_definition)?;
+        self.template(3, field.product_definition())?;
+
+        let header = &field.data_representation;
+        let title = format!(
+            "section 5: data representation (template 5.{})",
+            header.template_number
+        );
+        self.section(2, &title, header)?;
+        self.template(3, field.data_representation_template())?;
+
+        self.section(2, "section 6: bit-map", &field.bitmap)?;
+        self.section(2, "section 7: data", &field.data)?;
+        Ok(())
+    }
+
+    /// Write `title` and the fields of `header` below it
+    fn section(&mut self, depth: usize, title: &str, header: &impl Debug) -> Result<()> {
+        writeln!(self.out, "{}{}", indent(depth), title)?;
+        self.template(depth + 1, Ok(header))
+    }
+
+    /// Write the fields of a parsed template, or the error that prevented parsing it
+    fn template(&mut self, depth: usize, template: Result<impl Debug>) -> Result<()> {
+        let template = match template {
+            Ok(template) => template,
+            Err(e) => {
+                writeln!(self.out, "{}(not parsed: {})", indent(depth), e)?;
+                return Ok(());
+            }
+        };
+        let debug = format!("{:?}", template);
+        match Value::parse(&debug) {
+            Some(value) => self.members(depth, &value),
+            None => Ok(writeln!(self.out, "{}{}", indent(depth), debug)?),
+        }
+    }
+
+    /// Write the members of a struct, looking through enum variants wrapping one value
+    fn members(&mut self, depth: usize, value: &Value) -> Result<()> {
+        match value {
+            Value::Struct(_, members) => {
+                for (name, member) in members {
+                    self.member(depth, name, member)?;
+                }
+                Ok(())
+            }
+            Value::Tuple(Some(_), items) if items.len() == 1 => self.members(depth, &items[0]),
+            value => Ok(writeln!(self.out, "{}{}", indent(depth), value.inline())?),
+        }
+    }
+
+    fn member(&mut self, depth: usize, name: &str, value: &Value) -> Result<()> {
+        let value = match value {
+            Value::Tuple(Some(variant), items) if variant == "Some" && items.len() == 1 => {
+                &items[0]
+            }
+            value => value,
+        };
+        match value {
+            Value::Struct(type_name, _) => {
+                writeln!(self.out, "{}{}: {}", indent(depth), name, type_name)?;
+                self.members(depth + 1, value)
+            }
+            Value::Tuple(Some(variant), items)
+                if items.len() == 1 && matches!(items[0], Value::Struct(..)) =>
+            {
+                writeln!(self.out, "{}{}: {}", indent(depth), name, variant)?;
+                self.members(depth + 1, &items[0])
+            }
+            Value::List(items) if !items.iter().all(Value::is_flat) => {
+                writeln!(self.out, "{}{}: {} items", indent(depth), name, items.len())?;
+                for (i, item) in items.iter().take(MAX_LIST).enumerate() {
+                    self.member(depth + 1, &format!("[{}]", i), item)?;
+                }
+                if items.len() > MAX_LIST {
+                    writeln!(self.out, "{}...", indent(depth + 1))?;
+                }
+                Ok(())
+            }
+            value => {
+                let text = value.inline();
+                match self.describe(name, value) {
+                    Some(description) => writeln!(
+                        self.out,
+                        "{}{} = {} [{}]",
+                        indent(depth),
+                        name,
+                        text,
+                        description
+                    )?,
+                    None => writeln!(self.out, "{}{} = {}", indent(depth), name, text)?,
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Description of a code table value, by the name of the member holding it
+    fn describe(&mut self, name: &str, value: &Value) -> Option<String> {
+        let Value::Atom(atom) = value else {
+            return None;
+        };
+        let code = atom.parse::<u8>().ok();
+        let known =
+            |description: String| (!description.starts_with("Unknown(")).then_some(description);
+        match name {
+            "discipline" => known(format!("{:?}", Discipline::from(code?))),
+            "centre" => centre_name(atom.parse().ok()?).map(str::to_string),
+            "significance_of_reference_time" => {
+                known(format!("{:?}", SignificanceOfReferenceTime::from(code?)))
+            }
+            "type_of_processed_data" => known(format!("{:?}", TypeOfProcessedData::from(code?))),
+            "type_of_generating_process" => {
+                known(format!("{:?}", TypeOfGeneratingProcess::from(code?)))
+            }
+            "indicator_of_unit_of_time_range" | "indicator_of_unit_for_time_range" => {
+                known(format!("{:?}", TimeUnit::from(code?)))
+            }
+            "type_of_statistical_processing" => {
+                known(format!("{:?}", StatisticalProcess::from(code?)))
+            }
+            "type_of_first_fixed_surface" | "type_of_second_fixed_surface" => {
+                Some(FixedSurface::new(code?, 0, 0).name().to_string())
+            }
+            "parameter_category" => {
+                self.parameter_category = code;
+                None
+            }
+            "parameter_number" => {
+                let category = self.parameter_category.take()?;
+                Parameter::lookup_for_centre(self.centre, self.discipline, category, code?)
+                    .map(|p| format!("{}: {} ({})", p.abbreviation, p.name, p.unit))
+            }
+            "bit_map_indicator" => match code? {
+                0 => Some("bit-map follows".to_string()),
+                254 => Some("previously defined bit-map".to_string()),
+                255 => Some("no bit-map".to_string()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn indent(depth: usize) -> String {
+    "  ".repeat(depth)
+}
+
+/// Name of an originating centre (Common Code Table C-11)
+fn centre_name(centre: u16) -> Option<&'static str> {
+    Some(match centre {
+        7 => "US National Weather Service - NCEP",
+        34 => "Japanese Meteorological Agency - Tokyo",
+        54 => "Canadian Meteorological Service - Montreal",
+        74 => "UK Meteorological Office - Exeter",
+        78 => "Offenbach (DWD)",
+        85 => "French Weather Service - Toulouse",
+        98 => "European Centre for Medium-Range Weather Forecasts",
+        161 => "US NOAA Office of Oceanic and Atmospheric Research",
+        _ => return None,
+    })
+}
+
+/// Value in the `Debug` output of a struct
+#[derive(Debug)]
+enum Value {
+    /// Number, identifier or string
+    Atom(String),
+    Struct(String, Vec<(String, Value)>),
+    /// Tuple or tuple struct, e.g. `Some(1)`
+    Tuple(Option<String>, Vec<Value>),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Parse the single-line `Debug` output of a value, `None` if it is not understood
+    fn parse(s: &str) -> Option<Value> {
+        let mut parser = DebugParser { s, position: 0 };
+        let value = parser.value()?;
+        (parser.position == s.len()).then_some(value)
+    }
+
+    /// Whether the value fits on one line: an atom, or a tuple or list of atoms
+    fn is_flat(&self) -> bool {
+        match self {
+            Self::Atom(_) => true,
+            Self::Tuple(_, items) | Self::List(items) => {
+                items.iter().all(|item| matches!(item, Self::Atom(_)))
+            }
+            Self::Struct(..) => false,
+        }
+    }
+
+    fn inline(&self) -> String {
+        let join = |items: &[Value]| {
+            items
+                .iter()
+                .map(Value::inline)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Self::Atom(atom) => atom.clone(),
+            Self::Tuple(name, items) => {
+                format!("{}({})", name.as_deref().unwrap_or_default(), join(items))
+            }
+            Self::List(items) if items.len() > MAX_LIST => format!(
+                "[{}, ...] ({} values)",
+                join(&items[..MAX_LIST]),
+                items.len()
+            ),
+            Self::List(items) => format!("[{}]", join(items)),
+            Self::Struct(name, members) => format!(
+                "{} {{ {} }}",
+                name,
+                members
+                    .iter()
+                    .map(|(n, v)| format!("{}: {}", n, v.inline()))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+struct DebugParser<'a> {
+    s: &'a str,
+    position: usize,
+}
+
+impl DebugParser<'_> {
+    fn rest(&self) -> &str {
+        &self.s[self.position..]
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        if self.eat("[") {
+            return Some(Value::List(self.items("]")?));
+        }
+        if self.eat("(") {
+            return Some(Value::Tuple(None, self.items(")")?));
+        }
+        if self.rest().starts_with('"') {
+            return Some(Value::Atom(self.string()?));
+        }
+        let atom = self.atom();
+        if atom.is_empty() {
+            return None;
+        }
+        if self.eat(" { ") {
+            let mut members = Vec::new();
+            loop {
+                let name = self.atom();
+                if name.is_empty() || !self.eat(": ") {
+                    return None;
+                }
+                members.push((name, self.value()?));
+                if self.eat(" }") {
+                    return Some(Value::Struct(atom, members));
+                }
+                if !self.eat(", ") {
+                    return None;
+                }
+            }
+        }
+        if self.eat("(") {
+            return Some(Value::Tuple(Some(atom), self.items(")")?));
+        }
+        Some(Value::Atom(atom))
+    }
+
+    /// Comma-separated values up to `close`
+    fn items(&mut self, close: &str) -> Option<Vec<Value>> {
+        let mut items = Vec::new();
+        if self.eat(close) {
+            return Some(items);
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(close) {
+                return Some(items);
+            }
+            if !self.eat(", ") {
+                return None;
+            }
+        }
+    }
+
+    /// Characters up to the next delimiter
+    fn atom(&mut self) -> String {
+        let len = self
+            .rest()
+            .find([' ', ',', ':', '(', ')', '[', ']', '{', '}'])
+            .unwrap_or(self.rest().len());
+        let atom = self.rest()[..len].to_string();
+        self.position += len;
+        atom
+    }
+
+    /// A quoted string with its escapes, kept quoted
+    fn string(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let mut escaped = false;
+        for (i, c) in rest.char_indices().skip(1) {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    let string = rest[..=i].to_string();
+                    self.position += i + 1;
+                    return Some(string);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
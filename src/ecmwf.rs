@@ -0,0 +1,279 @@
+//! ECMWF parameter IDs (feature `ecmwf`)
+//!
+//! ECMWF identifies parameters by a `paramId` and a short name (e.g. 167 "2t"), which select a
+//! Code table 4.2 entry together with the fixed surface. Fields of the ECMWF open data are
+//! named this way, and are packed with CCSDS (Template 5.42), which needs no feature.
+
+use crate::field::DecodedField;
+use crate::templates::ProductDefinitionTemplate;
+
+/// Parameter of the ECMWF parameter database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EcmwfParameter {
+    pub param_id: u32,
+    /// Short name used by MARS and ecCodes (e.g. "2t")
+    pub short_name: &'static str,
+    pub name: &'static str,
+    pub unit: &'static str,
+}
+
+impl EcmwfParameter {
+    /// Look up a parameter by its `paramId`
+    pub fn lookup(param_id: u32) -> Option<&'static EcmwfParameter> {
+        ECMWF_PARAMETERS
+            .iter()
+            .map(|e| &e.parameter)
+            .find(|p| p.param_id == param_id)
+    }
+
+    /// Find a parameter by short name (e.g. "10u")
+    pub fn from_short_name(short_name: &str) -> Option<&'static EcmwfParameter> {
+        ECMWF_PARAMETERS
+            .iter()
+            .map(|e| &e.parameter)
+            .find(|p| p.short_name == short_name)
+    }
+
+    /// Look up the parameter of a product definition template
+    ///
+    /// Entries tied to a surface (e.g. "2t" at 2 m above ground) take precedence over the
+    /// entry of any other surface (e.g. "t").
+    pub fn for_product(
+        discipline: u8,
+        template: &ProductDefinitionTemplate,
+    ) -> Option<&'static EcmwfParameter> {
+        let (category, number) = template.parameter()?;
+        let surface = template.fixed_surfaces().map(|(first, _)| first);
+        ECMWF_PARAMETERS
+            .iter()
+            .find(|e| {
+                (e.discipline, e.category, e.number) == (discipline, category, number)
+                    && match e.surface {
+                        Some((type_of_surface, value)) => surface.is_some_and(|s| {
+                            s.type_of_surface == type_of_surface
+                                && value.is_none_or(|v| s.value() == Some(v))
+                        }),
+                        None => true,
+                    }
+            })
+            .map(|e| &e.parameter)
+    }
+}
+
+/// ECMWF parameter of a decoded field
+pub fn ecmwf_parameter(field: &DecodedField) -> Option<&'static EcmwfParameter> {
+    EcmwfParameter::for_product(field.discipline, &field.product_definition.template)
+}
+
+struct Entry {
+    discipline: u8,
+    category: u8,
+    number: u8,
+    /// Type and value of the first fixed surface, `None` for any surface
+    surface: Option<(u8, Option<f64>)>,
+    parameter: EcmwfParameter,
+}
+
+#[allow(clippy::too_many_arguments)]
+const fn e(
+    discipline: u8,
+    category: u8,
+    number: u8,
+    surface: Option<(u8, Option<f64>)>,
+    param_id: u32,
+    short_name: &'static str,
+    name: &'static str,
+    unit: &'static str,
+) -> Entry {
+    Entry {
+        discipline,
+        category,
+        number,
+        surface,
+        parameter: EcmwfParameter {
+            param_id,
+            short_name,
+            name,
+            unit,
+        },
+    }
+}
+
+const SURFACE: Option<(u8, Option<f64>)> = Some((1, None));
+const MEAN_SEA_LEVEL: Option<(u8, Option<f64>)> = Some((101, None));
+const fn above_ground(metres: f64) -> Option<(u8, Option<f64>)> {
+    Some((103, Some(metres)))
+}
+
+/// Parameters of the ECMWF open data (IFS and AIFS), surface-specific entries first
+static ECMWF_PARAMETERS: &[Entry] = &[
+    e(
+        0,
+        0,
+        0,
+        above_ground(2.0),
+        167,
+        "2t",
+        "2 metre temperature",
+        "K",
+    ),
+    e(
+        0,
+        0,
+        6,
+        above_ground(2.0),
+        168,
+        "2d",
+        "2 metre dewpoint temperature",
+        "K",
+    ),
+    e(
+        0,
+        2,
+        2,
+        above_ground(10.0),
+        165,
+        "10u",
+        "10 metre U wind component",
+        "m s-1",
+    ),
+    e(
+        0,
+        2,
+        3,
+        above_ground(10.0),
+        166,
+        "10v",
+        "10 metre V wind component",
+        "m s-1",
+    ),
+    e(
+        0,
+        2,
+        2,
+        above_ground(100.0),
+        228246,
+        "100u",
+        "100 metre U wind component",
+        "m s-1",
+    ),
+    e(
+        0,
+        2,
+        3,
+        above_ground(100.0),
+        228247,
+        "100v",
+        "100 metre V wind component",
+        "m s-1",
+    ),
+    e(0, 3, 0, SURFACE, 134, "sp", "Surface pressure", "Pa"),
+    e(
+        0,
+        3,
+        0,
+        MEAN_SEA_LEVEL,
+        151,
+        "msl",
+        "Mean sea level pressure",
+        "Pa",
+    ),
+    e(0, 0, 17, SURFACE, 235, "skt", "Skin temperature", "K"),
+    e(
+        0,
+        1,
+        64,
+        SURFACE,
+        137,
+        "tcwv",
+        "Total column vertically-integrated water vapour",
+        "kg m-2",
+    ),
+    e(0, 6, 1, SURFACE, 228164, "tcc", "Total cloud cover", "%"),
+    e(0, 19, 0, SURFACE, 3020, "vis", "Visibility", "m"),
+    e(2, 0, 0, SURFACE, 172, "lsm", "Land-sea mask", "(0 - 1)"),
+    e(0, 0, 0, None, 130, "t", "Temperature", "K"),
+    e(0, 1, 0, None, 133, "q", "Specific humidity", "kg kg-1"),
+    e(0, 1, 1, None, 157, "r", "Relative humidity", "%"),
+    e(0, 2, 2, None, 131, "u", "U component of wind", "m s-1"),
+    e(0, 2, 3, None, 132, "v", "V component of wind", "m s-1"),
+    e(0, 2, 8, None, 135, "w", "Vertical velocity", "Pa s-1"),
+    e(0, 2, 12, None, 138, "vo", "Vorticity (relative)", "s-1"),
+    e(0, 2, 13, None, 155, "d", "Divergence", "s-1"),
+    e(0, 3, 0, None, 54, "pres", "Pressure", "Pa"),
+    e(0, 3, 4, None, 129, "z", "Geopotential", "m2 s-2"),
+    e(0, 3, 5, None, 156, "gh", "Geopotential height", "gpm"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::ProductDefinitionTemplate4_0;
+
+    /// Template 4.0 of a parameter on the first fixed surface `type_of_surface` at `value`
+    fn product(
+        category: u8,
+        number: u8,
+        type_of_surface: u8,
+        value: u32,
+    ) -> ProductDefinitionTemplate {
+        ProductDefinitionTemplate::Template4_0(ProductDefinitionTemplate4_0 {
+            parameter_category: category,
+            parameter_number: number,
+            type_of_generating_process: 2,
+            background_process: 0,
+            generating_process_identifier: 0,
+            hours_after_data_cutoff: 0,
+            minutes_after_data_cutoff: 0,
+            indicator_of_unit_of_time_range: 1,
+            forecast_time: 0,
+            type_of_first_fixed_surface: type_of_surface,
+            scale_factor_of_first_fixed_surface: 0,
+            scaled_value_of_first_fixed_surface: value,
+            type_of_second_fixed_surface: 255,
+            scale_factor_of_second_fixed_surface: 0,
+            scaled_value_of_second_fixed_surface: 0,
+        })
+    }
+
+    #[test]
+    fn parameters_of_products() {
+        for (discipline, category, number, type_of_surface, value, param_id, short_name) in [
+            (0, 0, 0, 103, 2, 167, "2t"),
+            (0, 0, 0, 100, 85000, 130, "t"),
+            (0, 0, 6, 103, 2, 168, "2d"),
+            (0, 2, 2, 103, 10, 165, "10u"),
+            (0, 2, 3, 103, 100, 228247, "100v"),
+            // winds at other heights are the generic components
+            (0, 2, 2, 103, 50, 131, "u"),
+            (0, 3, 0, 1, 0, 134, "sp"),
+            (0, 3, 0, 101, 0, 151, "msl"),
+            (0, 3, 5, 100, 50000, 156, "gh"),
+            (2, 0, 0, 1, 0, 172, "lsm"),
+        ] {
+            let template = product(category, number, type_of_surface, value);
+            let parameter = EcmwfParameter::for_product(discipline, &template).unwrap();
+            assert_eq!(
+                (parameter.param_id, parameter.short_name),
+                (param_id, short_name)
+            );
+        }
+        assert!(EcmwfParameter::for_product(0, &product(0, 17, 103, 2)).is_none());
+        assert!(EcmwfParameter::for_product(10, &product(0, 0, 103, 2)).is_none());
+    }
+
+    #[test]
+    fn table_is_consistent() {
+        for entry in ECMWF_PARAMETERS {
+            let parameter = &entry.parameter;
+            assert_eq!(EcmwfParameter::lookup(parameter.param_id), Some(parameter));
+            assert_eq!(
+                EcmwfParameter::from_short_name(parameter.short_name),
+                Some(parameter)
+            );
+        }
+        assert_eq!(EcmwfParameter::lookup(167).unwrap().unit, "K");
+        assert!(EcmwfParameter::lookup(0).is_none());
+        assert!(EcmwfParameter::from_short_name("tp2").is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use crate::field::DecodedField;
+use crate::{Error, Result};
+
+/// Statistics over ensemble members at each grid point
+///
+/// Missing members are left out at each point; a point is NAN if all members are missing.
+#[derive(Debug, Clone)]
+pub struct EnsembleStats {
+    /// Number of members
+    pub members: usize,
+    pub mean: Vec<f32>,
+    /// Standard deviation around the mean
+    pub spread: Vec<f32>,
+    pub min: Vec<f32>,
+    pub max: Vec<f32>,
+}
+
+/// Group individual members (templates 4.1, 4.11 and the like) of the same product
+///
+/// Members are grouped by discipline, parameter, fixed surfaces, reference time and valid
+/// time, in the order the groups first appear. Fields that are not individual members, such
+/// as derived forecasts, are left out.
+pub fn group_members(fields: &[DecodedField]) -> Vec<Vec<&DecodedField>> {
+    let mut groups: Vec<Vec<&DecodedField>> = Vec::new();
+    for field in fields.iter().filter(|f| perturbation_number(f).is_some()) {
+        match groups
+            .iter_mut()
+            .find(|group| same_product(group[0], field))
+        {
+            Some(group) => group.push(field),
+            None => groups.push(vec![field]),
+        }
+    }
+    groups
+}
+
+/// Mean, spread, minimum and maximum of `members` at each point
+pub fn ensemble_stats(members: &[&DecodedField]) -> Result<EnsembleStats> {
+    check_members(members)?;
+    let n = members[0].values.len();
+    let mut stats = EnsembleStats {
+        members: members.len(),
+        mean: Vec::with_capacity(n),
+        spread: Vec::with_capacity(n),
+        min: Vec::with_capacity(n),
+        max: Vec::with_capacity(n),
+    };
+    let mut values = Vec::with_capacity(members.len());
+    for p in 0..n {
+        values.clear();
+        values.extend(members.iter().map(|m| m.values[p]).filter(|v| !v.is_nan()));
+        if values.is_empty() {
+            for v in [
+                &mut stats.mean,
+                &mut stats.spread,
+                &mut stats.min,
+                &mut stats.max,
+            ] {
+                v.push(f32::NAN);
+            }
+            continue;
+        }
+        let count = values.len() as f64;
+        let mean = values.iter().map(|&v| v as f64).sum::<f64>() / count;
+        let variance = values
+            .iter()
+            .map(|&v| (v as f64 - mean).powi(2))
+            .sum::<f64>()
+            / count;
+        stats.mean.push(mean as f32);
+        stats.spread.push(variance.sqrt() as f32);
+        stats
+            .min
+            .push(values.iter().copied().fold(f32::INFINITY, f32::min));
+        stats
+            .max
+            .push(values.iter().copied().fold(f32::NEG_INFINITY, f32::max));
+    }
+    Ok(stats)
+}
+
+/// Fraction of `members` above `threshold` at each point (0 to 1)
+pub fn exceedance_probability(members: &[&DecodedField], threshold: f32) -> Result<Vec<f32>> {
+    check_members(members)?;
+    Ok((0..members[0].values.len())
+        .map(|p| {
+            let (count, above) = members
+                .iter()
+                .map(|m| m.values[p])
+                .filter(|v| !v.is_nan())
+                .fold((0, 0), |(count, above), v| {
+                    (count + 1, above + (v > threshold) as usize)
+                });
+            match count {
+                0 => f32::NAN,
+                count => above as f32 / count as f32,
+            }
+        })
+        .collect())
+}
+
+fn perturbation_number(field: &DecodedField) -> Option<u8> {
+    field
+        .product_definition
+        .template
+        .ensemble_info()
+        .and_then(|info| info.perturbation_number)
+}
+
+fn same_product(a: &DecodedField, b: &DecodedField) -> bool {
+    let (ta, tb) = (
+        &a.product_definition.template,
+        &b.product_definition.template,
+    );
+    a.discipline == b.discipline
+        && ta.parameter() == tb.parameter()
+        && ta.fixed_surfaces() == tb.fixed_surfaces()
+        && a.reference_time == b.reference_time
+        && a.valid_time == b.valid_time
+}
+
+/// Check that `members` are distinct members of the same product on the same grid
+fn check_members(members: &[&DecodedField]) -> Result<()> {
+    let Some(first) = members.first() else {
+        return Err(Error::InvalidData("no ensemble members".to_string()));
+    };
+    let mut numbers = Vec::with_capacity(members.len());
+    for member in members {
+        let Some(number) = perturbation_number(member) else {
+            return Err(Error::InvalidData(
+                "field is not an individual ensemble member".to_string(),
+            ));
+        };
+        if numbers.contains(&number) {
+            return Err(Error::InvalidData(format!(
+                "member {} appears twice",
+                number
+            )));
+        }
+        numbers.push(number);
+        if !same_product(first, member) {
+            return Err(Error::InvalidData(format!(
+                "member {} is a different product",
+                number
+            )));
+        }
+        if !first.same_grid(member)? {
+            return Err(Error::InvalidData(format!(
+                "member {} is on a different grid",
+                number
+            )));
+        }
+    }
+    Ok(())
+}
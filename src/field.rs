@@ -0,0 +1,909 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use crate::codes::{ProductKind, SignificanceOfReferenceTime};
+use crate::context::FieldContext;
+use crate::message::*;
+use crate::parameter::Parameter;
+use crate::reader::{SectionEvent, SectionReader};
+use crate::templates::*;
+use crate::time::{DateTime, ValidTime};
+use crate::writer::MessageWriter;
+use crate::{Error, Result};
+
+/// A complete GRIB2 message read into memory
+#[derive(Debug)]
+pub struct Message {
+    pub indicator: IndicatorSectionHeader,
+    pub identification: IdentificationSectionHeader,
+    /// Identification template (Section 1, octets 24-nn), empty without a template
+    pub identification_body: Vec<u8>,
+    pub fields: Vec<Field>,
+}
+
+/// A field (one repetition of sections 2 to 7) of a message
+///
+/// Section bodies are kept as bytes and templates are parsed on demand.
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub local_use: Option<(LocalUseSectionHeader, Vec<u8>)>,
+    pub grid_definition: GridDefinitionSectionHeader,
+    pub grid_definition_body: Vec<u8>,
+    pub product_definition: ProductDefinitionSectionHeader,
+    pub product_definition_body: Vec<u8>,
+    pub data_representation: DataRepresentationSectionHeader,
+    pub data_representation_body: Vec<u8>,
+    pub bitmap: BitmapSectionHeader,
+    /// Bit-map in effect for this field (for indicator 254, the previously defined bit-map)
+    pub bitmap_body: Vec<u8>,
+    pub data: DataSectionHeader,
+    pub data_body: Vec<u8>,
+}
+
+impl Message {
+    /// Read the next message, or `None` at the end of input
+    pub fn read<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        Self::read_sections(&mut SectionReader::new(reader))
+    }
+
+    pub(crate) fn read_sections<R: Read>(sections: &mut SectionReader<R>) -> Result<Option<Self>> {
+        let indicator = match sections.next_event()? {
+            None => return Ok(None),
+            Some(SectionEvent::Indicator(is)) => is,
+            Some(_) => unreachable!("a message starts with section 0"),
+        };
+        let Some(SectionEvent::Identification {
+            header: identification,
+            body: identification_body,
+        }) = sections.next_event()?
+        else {
+            unreachable!("section 1 follows section 0")
+        };
+
+        let mut fields = Vec::new();
+        let mut context = FieldContext::new();
+        loop {
+            let Some(event) = sections.next_event()? else {
+                return Err(Error::InvalidData("message is truncated".to_string()));
+            };
+            match event {
+                SectionEvent::MessageEnd => break,
+                SectionEvent::Indicator(_) | SectionEvent::Identification { .. } => {
+                    unreachable!("sections 0 and 1 appear once per message")
+                }
+                event => fields.extend(context.update(event)?),
+            }
+        }
+
+        Ok(Some(Self {
+            indicator,
+            identification,
+            identification_body,
+            fields,
+        }))
+    }
+
+    /// Decode every field with its grid, product definition and times
+    ///
+    /// Fields repeating the grid definition of a previous field share its parsed template.
+    pub fn decode(&self) -> Result<Vec<DecodedField>> {
+        self.decode_with_cache(&mut GridCache::new())
+    }
+
+    /// Decode every field, sharing the grid definitions found in `cache` and adding new ones
+    pub fn decode_with_cache(&self, cache: &mut GridCache) -> Result<Vec<DecodedField>> {
+        self.fields
+            .iter()
+            .map(|field| {
+                DecodedField::with_grid(
+                    &self.indicator,
+                    &self.identification,
+                    field,
+                    cache.get(field)?,
+                )
+            })
+            .collect()
+    }
+
+    /// Identification template (Section 1, octets 24-nn), `None` without a template
+    pub fn identification_template(&self) -> Result<Option<IdentificationTemplate>> {
+        self.identification
+            .read_template(&mut self.identification_body.as_slice())
+    }
+
+    /// Start writing a message with the indicator and identification sections of this one
+    pub fn writer(&self) -> Result<MessageWriter> {
+        MessageWriter::with_identification_template(
+            self.indicator.discipline,
+            &self.identification,
+            &self.identification_body,
+        )
+    }
+
+    /// Write the message with all section lengths recomputed and return its total length
+    ///
+    /// Section bodies are written as they are, so header fields can be edited without
+    /// touching the packed data.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<u64> {
+        let mut output = self.writer()?;
+        for field in &self.fields {
+            output.write_field(field)?;
+        }
+        output.finish(writer)
+    }
+}
+
+impl Field {
+    pub fn grid_definition_template(&self) -> Result<GridDefinitionTemplate> {
+        self.grid_definition
+            .read_template(&mut self.grid_definition_body.as_slice())
+    }
+
+    pub fn product_definition(&self) -> Result<ProductDefinition> {
+        self.product_definition
+            .read_template(&mut self.product_definition_body.as_slice())
+    }
+
+    /// Replace Section 4 with an edited product definition of the same template number
+    ///
+    /// ```ignore
+    /// let mut pd = field.product_definition()?;
+    /// if let ProductDefinitionTemplate::Template4_0(t) = &mut pd.template {
+    ///     t.generating_process_identifier = 96;
+    /// }
+    /// field.set_product_definition(&pd)?;
+    /// ```
+    pub fn set_product_definition(&mut self, product_definition: &ProductDefinition) -> Result<()> {
+        let nv = u16::try_from(product_definition.coordinate_values.len())
+            .map_err(|_| Error::InvalidData("too many coordinate values".to_string()))?;
+        let mut body = Vec::new();
+        product_definition.write(&mut body)?;
+        self.product_definition.nv = nv;
+        self.product_definition.section_length = 9 + body.len() as u32;
+        self.product_definition_body = body;
+        Ok(())
+    }
+
+    pub fn data_representation_template(&self) -> Result<DataRepresentationTemplate> {
+        self.data_representation
+            .read_template(&mut self.data_representation_body.as_slice())
+    }
+
+    /// Decode the packed values for every grid point
+    ///
+    /// NAN is represented as i32::MIN
+    pub fn raw_values(&self) -> Result<Vec<i32>> {
+        let number_of_values = self.data_representation.number_of_values;
+        let mut reader = self.data_body.as_slice();
+        let values = match self.data_representation_template()? {
+            DataRepresentationTemplate::Template5_0(tmpl) => {
+                read_data_7_0(&mut reader, number_of_values, &tmpl)?
+            }
+            DataRepresentationTemplate::Template5_2(tmpl) => read_data_7_2(&mut reader, &tmpl)?,
+            DataRepresentationTemplate::Template5_3(tmpl) => read_data_7_3(&mut reader, &tmpl)?,
+            DataRepresentationTemplate::Template5_41(tmpl) => {
+                read_data_7_41(&mut reader, number_of_values, &tmpl)?
+            }
+            DataRepresentationTemplate::Template5_42(tmpl) => {
+                read_data_7_42(&mut reader, number_of_values, &tmpl)?
+            }
+            DataRepresentationTemplate::Template5_200(tmpl) => {
+                read_data_7_200(&mut reader, self.data_body.len(), number_of_values, &tmpl)?
+            }
+            // JPEG 2000 code streams are not decoded
+            DataRepresentationTemplate::Template5_40(_) => {
+                return Err(Error::UnsupportedTemplate {
+                    section: 5,
+                    number: 40,
+                });
+            }
+            _ => {
+                return Err(Error::UnsupportedTemplate {
+                    section: 5,
+                    number: self.data_representation.template_number,
+                });
+            }
+        };
+        if values.len() != number_of_values as usize {
+            return Err(Error::InvalidData(format!(
+                "{} values decoded, but Section 5 declares {}",
+                values.len(),
+                number_of_values
+            )));
+        }
+        let values = self.apply_bitmap(values)?;
+        let number_of_data_points = self.grid_definition.number_of_data_points;
+        if self.bitmap.bit_map_indicator == 255 && values.len() != number_of_data_points as usize {
+            return Err(Error::InvalidData(format!(
+                "{} values without a bit-map, but Section 3 declares {} data points",
+                values.len(),
+                number_of_data_points
+            )));
+        }
+        Ok(values)
+    }
+
+    /// Decode the physical values for every grid point
+    ///
+    /// NAN is represented as f32::NAN. For run length packing (Template 5.200), the
+    /// representative values are divided by 10^D; `raw_values` returns them unscaled.
+    pub fn values(&self) -> Result<Vec<f32>> {
+        let template = self.data_representation_template()?;
+        if let DataRepresentationTemplate::Template5_200(tmpl) = &template {
+            return Ok(self
+                .raw_values()?
+                .into_iter()
+                .map(|v| tmpl.unpack(v))
+                .collect());
+        }
+        let tmpl0 = template.template_0().ok_or(Error::UnsupportedTemplate {
+            section: 5,
+            number: self.data_representation.template_number,
+        })?;
+        Ok(self
+            .raw_values()?
+            .into_iter()
+            .map(|v| tmpl0.unpack(v))
+            .collect())
+    }
+
+    fn apply_bitmap(&self, values: Vec<i32>) -> Result<Vec<i32>> {
+        match self.bitmap.bit_map_indicator {
+            0 | 254 => apply_bitmap(
+                &values,
+                &read_bitmap(
+                    &mut self.bitmap_body.as_slice(),
+                    self.grid_definition.number_of_data_points,
+                )?,
+            ),
+            _ => Ok(values),
+        }
+    }
+}
+
+/// A field with its metadata parsed and values decoded
+#[derive(Debug)]
+pub struct DecodedField {
+    /// Discipline (Code table 0.0)
+    pub discipline: u8,
+    /// Originating centre (Common Code Table C-11)
+    pub centre: u16,
+    /// Shared between fields decoded with the same [`GridCache`]
+    pub grid_definition: Arc<GridDefinitionTemplate>,
+    pub product_definition: ProductDefinition,
+    pub reference_time: DateTime,
+    /// Significance of the reference time (Code table 1.2)
+    pub significance_of_reference_time: SignificanceOfReferenceTime,
+    /// Nature of the product, see [`ProductKind`]
+    pub kind: ProductKind,
+    /// `None` if the product definition template has no forecast time, or if it cannot be
+    /// added to the reference time (e.g. in an unknown unit)
+    pub valid_time: Option<ValidTime>,
+    /// Number of rows and columns (nj, ni); irregular grids are a single row
+    pub shape: (usize, usize),
+    /// Physical values in scanning order, NAN for missing points
+    pub values: Vec<f32>,
+}
+
+impl DecodedField {
+    pub fn new(message: &Message, field: &Field) -> Result<Self> {
+        Self::with_grid(
+            &message.indicator,
+            &message.identification,
+            field,
+            Arc::new(field.grid_definition_template()?),
+        )
+    }
+
+    fn with_grid(
+        indicator: &IndicatorSectionHeader,
+        identification: &IdentificationSectionHeader,
+        field: &Field,
+        grid_definition: Arc<GridDefinitionTemplate>,
+    ) -> Result<Self> {
+        let product_definition = field.product_definition()?;
+        let reference_time = identification.reference_time();
+        // a time unit that cannot be added does not stop the values from being decoded
+        let valid_time = product_definition.template.valid_time(reference_time).ok();
+        let kind = identification.product_kind(&product_definition.template);
+        let values = field.values()?;
+        let shape = match grid_definition.shape() {
+            Some((nj, ni)) if nj as usize * ni as usize == values.len() => {
+                (nj as usize, ni as usize)
+            }
+            // the number of points per row of quasi-regular grids is missing
+            Some((nj, ni)) if nj != u32::MAX && ni != u32::MAX => {
+                return Err(Error::InvalidData(format!(
+                    "grid of {} x {} points, but {} values",
+                    nj,
+                    ni,
+                    values.len()
+                )));
+            }
+            _ => (1, values.len()),
+        };
+        Ok(Self {
+            discipline: indicator.discipline,
+            centre: identification.centre,
+            grid_definition,
+            product_definition,
+            reference_time,
+            significance_of_reference_time: identification.significance_of_reference_time(),
+            kind,
+            valid_time,
+            shape,
+            values,
+        })
+    }
+
+    /// Entry of Code table 4.2 for the field's parameter, including the local entries of
+    /// its originating centre, if known
+    pub fn parameter(&self) -> Option<&'static Parameter> {
+        Parameter::for_product_of_centre(
+            self.centre,
+            self.discipline,
+            &self.product_definition.template,
+        )
+    }
+
+    /// Value at row `j` and column `i`
+    pub fn get(&self, j: usize, i: usize) -> Option<f32> {
+        let (nj, ni) = self.shape;
+        (j < nj && i < ni).then(|| self.values[j * ni + i])
+    }
+
+    /// Whether `other` is on the same grid, comparing the encoded grid definition templates
+    pub fn same_grid(&self, other: &DecodedField) -> Result<bool> {
+        if Arc::ptr_eq(&self.grid_definition, &other.grid_definition) {
+            return Ok(self.shape == other.shape);
+        }
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        self.grid_definition.write(&mut a)?;
+        other.grid_definition.write(&mut b)?;
+        Ok(self.shape == other.shape && a == b)
+    }
+
+    /// Statistics of the values that are not missing
+    pub fn stats(&self) -> FieldStats {
+        FieldStats::new(&self.values, None)
+    }
+
+    /// Statistics with a histogram of `bins` equal bins over `min..max`
+    ///
+    /// Values outside the range are counted in `below` and `above`, and `max` itself in the
+    /// last bin.
+    pub fn stats_with_histogram(&self, min: f32, max: f32, bins: usize) -> Result<FieldStats> {
+        if bins == 0 || !min.is_finite() || !max.is_finite() || min >= max {
+            return Err(Error::InvalidData(format!(
+                "invalid histogram: {} bins over {}..{}",
+                bins, min, max
+            )));
+        }
+        let histogram = Histogram {
+            min,
+            max,
+            counts: vec![0; bins],
+            below: 0,
+            above: 0,
+        };
+        Ok(FieldStats::new(&self.values, Some(histogram)))
+    }
+}
+
+/// Statistics of a field, computed in a single pass over its values
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldStats {
+    /// Number of values that are not missing
+    pub count: usize,
+    /// Number of missing (NAN) values
+    pub missing: usize,
+    /// NAN if all values are missing
+    pub min: f32,
+    pub max: f32,
+    pub mean: f64,
+    /// Standard deviation around the mean
+    pub stddev: f64,
+    pub histogram: Option<Histogram>,
+}
+
+/// Counts of values in equal bins over `min..max`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    pub min: f32,
+    pub max: f32,
+    pub counts: Vec<usize>,
+    pub below: usize,
+    pub above: usize,
+}
+
+impl FieldStats {
+    fn new(values: &[f32], mut histogram: Option<Histogram>) -> Self {
+        let mut stats = Self {
+            count: 0,
+            missing: 0,
+            min: f32::NAN,
+            max: f32::NAN,
+            mean: f64::NAN,
+            stddev: f64::NAN,
+            histogram: None,
+        };
+        // Welford's algorithm
+        let (mut mean, mut m2) = (0.0, 0.0);
+        for &v in values {
+            if v.is_nan() {
+                stats.missing += 1;
+                continue;
+            }
+            stats.count += 1;
+            stats.min = stats.min.min(v);
+            stats.max = stats.max.max(v);
+            let delta = v as f64 - mean;
+            mean += delta / stats.count as f64;
+            m2 += delta * (v as f64 - mean);
+            if let Some(h) = &mut histogram {
+                if v < h.min {
+                    h.below += 1;
+                } else if v > h.max {
+                    h.above += 1;
+                } else {
+                    let bins = h.counts.len();
+                    let bin = ((v - h.min) / (h.max - h.min) * bins as f32) as usize;
+                    h.counts[bin.min(bins - 1)] += 1;
+                }
+            }
+        }
+        if stats.count > 0 {
+            stats.mean = mean;
+            stats.stddev = (m2 / stats.count as f64).sqrt();
+        }
+        stats.histogram = histogram;
+        stats
+    }
+}
+
+/// Parsed grid definitions, keyed by their template number and section 3 body
+///
+/// Many files repeat the same grid definition in every message. Decoding through a cache
+/// parses each distinct grid once and shares it between the decoded fields. Entries are
+/// kept until [`clear`](Self::clear) is called.
+#[derive(Debug, Default)]
+pub struct GridCache {
+    grids: HashMap<u16, HashMap<Vec<u8>, Arc<GridDefinitionTemplate>>>,
+}
+
+impl GridCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grid definition of `field`, parsed only if no field with the same one was seen before
+    pub fn get(&mut self, field: &Field) -> Result<Arc<GridDefinitionTemplate>> {
+        let grids = self
+            .grids
+            .entry(field.grid_definition.template_number)
+            .or_default();
+        if let Some(grid) = grids.get(field.grid_definition_body.as_slice()) {
+            return Ok(grid.clone());
+        }
+        let grid = Arc::new(field.grid_definition_template()?);
+        grids.insert(field.grid_definition_body.clone(), grid.clone());
+        Ok(grid)
+    }
+
+    /// Number of distinct grid definitions
+    pub fn len(&self) -> usize {
+        self.grids.values().map(|grids| grids.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.grids.clear();
+    }
+}
+
+/// Iterator over the decoded fields of a stream, reading one message at a time
+///
+/// Grid definitions are cached for the whole stream, see [`GridCache`].
+pub struct DecodedFields<R: Read> {
+    sections: SectionReader<R>,
+    pending: VecDeque<DecodedField>,
+    grids: GridCache,
+    failed: bool,
+}
+
+impl<R: Read> DecodedFields<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            sections: SectionReader::new(reader),
+            pending: VecDeque::new(),
+            grids: GridCache::new(),
+            failed: false,
+        }
+    }
+}
+
+impl<R: Read> Iterator for DecodedFields<R> {
+    type Item = Result<DecodedField>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() && !self.failed {
+            match Message::read_sections(&mut self.sections).and_then(|message| {
+                message
+                    .map(|message| message.decode_with_cache(&mut self.grids))
+                    .transpose()
+            }) {
+                Ok(Some(fields)) => self.pending.extend(fields),
+                Ok(None) => return None,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            }
+        }
+        self.pending.pop_front().map(Ok)
+    }
+}
+
+/// Field read by [`fields`], with the sections of its message
+///
+/// Like [`Field`], templates are parsed and values decoded only when asked for.
+#[derive(Debug, Clone)]
+pub struct MessageField {
+    pub indicator: IndicatorSectionHeader,
+    pub identification: IdentificationSectionHeader,
+    /// Index (0-based) of the message within the stream
+    pub message_index: u64,
+    /// Index (0-based) of the field within its message
+    pub field_index: usize,
+    pub field: Field,
+}
+
+impl MessageField {
+    /// Decode the field with its grid, product definition and times
+    pub fn decode(&self) -> Result<DecodedField> {
+        DecodedField::with_grid(
+            &self.indicator,
+            &self.identification,
+            &self.field,
+            Arc::new(self.field.grid_definition_template()?),
+        )
+    }
+}
+
+/// Iterate over the fields of a stream, one at a time and without decoding their values
+///
+/// ```ignore
+/// let temperatures = fields(&mut reader)
+///     .filter(|f| f.as_ref().map_or(true, |f| {
+///         f.field.product_definition().is_ok_and(|pd| pd.template.parameter() == Some((0, 0)))
+///     }))
+///     .map(|f| f?.decode())
+///     .collect::<Result<Vec<_>>>()?;
+/// ```
+///
+/// Reading stops after the first error.
+pub fn fields<R: Read>(reader: R) -> Fields<R> {
+    Fields {
+        sections: SectionReader::new(reader),
+        context: FieldContext::new(),
+        message_index: None,
+        field_index: 0,
+        failed: false,
+    }
+}
+
+/// Iterator returned by [`fields`]
+pub struct Fields<R: Read> {
+    sections: SectionReader<R>,
+    context: FieldContext,
+    /// Index of the current message, `None` before the first one
+    message_index: Option<u64>,
+    field_index: usize,
+    failed: bool,
+}
+
+impl<R: Read> Iterator for Fields<R> {
+    type Item = Result<MessageField>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        for event in self.sections.by_ref() {
+            if let Ok(SectionEvent::Indicator(_)) = event {
+                self.message_index = Some(self.message_index.map_or(0, |i| i + 1));
+                self.field_index = 0;
+            }
+            let field = match event.and_then(|event| self.context.update(event)) {
+                Ok(Some(field)) => field,
+                Ok(None) => continue,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
+            };
+            // the context holds sections 0 and 1 from the start of each message
+            let field = MessageField {
+                indicator: self.context.indicator().unwrap().clone(),
+                identification: self.context.identification().unwrap().clone(),
+                message_index: self.message_index.unwrap_or(0),
+                field_index: self.field_index,
+                field,
+            };
+            self.field_index += 1;
+            return Some(Ok(field));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Template 4.0 at 2 m above ground, `forecast_time` hours after the reference time
+    fn template_4_0(category: u8, number: u8, forecast_time: u32) -> Vec<u8> {
+        let mut body = vec![category, number, 2, 0, 31, 0, 0, 0, 1];
+        body.extend(forecast_time.to_be_bytes());
+        body.extend([103, 0, 0, 0, 0, 2, 255, 255, 255, 255, 255, 255]);
+        body
+    }
+
+    /// Simple packing (Template 5.0) of 4 values in 8 bits
+    #[cfg(any(feature = "jma", feature = "ccsds"))]
+    fn simple_packing(reference_value: f32, decimal_scale_factor: u16, packed: [u8; 4]) -> Vec<u8> {
+        let mut sections = section(5, &[0, 0, 0, 4, 0, 0]);
+        sections.extend(reference_value.to_be_bytes());
+        sections.extend([0, 0]);
+        sections.extend(decimal_scale_factor.to_be_bytes());
+        sections.extend([8, 0]);
+        let length = sections.len() as u8;
+        sections[3] = length;
+        sections.extend(section(6, &[255]));
+        sections.extend(section(7, &packed));
+        sections
+    }
+
+    /// Section 3 of a 2 x 2 grid of 1 degree (Template 3.0) from 35N 135E, north to south
+    fn grid_2_x_2() -> Vec<u8> {
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 35_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [34_000_000u32, 136_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0);
+        section(3, &grid)
+    }
+
+    /// Message of Section 0 and 1 (reference time 2024-01-02T03Z) around `sections`
+    fn message(centre: u16, sections: &[u8]) -> Vec<u8> {
+        let mut identification = centre.to_be_bytes().to_vec();
+        identification.extend([0, 0, 2, 1, 1, 7, 232, 1, 2, 3, 0, 0, 0, 1]);
+        let mut body = section(1, &identification);
+        body.extend_from_slice(sections);
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    /// JMA message on a 2 x 2 grid (Template 3.0) with a temperature forecast (Template 4.0)
+    /// and a precipitation amount in JMA level values accumulated over 3 hours (Template 4.8)
+    #[cfg(feature = "jma")]
+    fn jma_message() -> Vec<u8> {
+        let mut body = grid_2_x_2();
+
+        let mut product = vec![0, 0, 0, 0];
+        product.extend(template_4_0(0, 0, 3));
+        body.extend(section(4, &product));
+        body.extend(simple_packing(250.0, 0, [0, 1, 2, 3]));
+
+        let mut product = vec![0, 0, 0, 8];
+        product.extend(template_4_0(1, 200, 0));
+        product.extend([
+            7, 232, 1, 2, 6, 0, 0, 1, 0, 0, 0, 0, 1, 2, 1, 0, 0, 0, 3, 255,
+        ]);
+        product.extend([0, 0, 0, 0]);
+        body.extend(section(4, &product));
+        body.extend(simple_packing(0.0, 1, [0, 5, 10, 25]));
+        message(34, &body)
+    }
+
+    /// Message of the WMO tables only: 2 m temperature with a missing point (simple
+    /// packing and a bit-map) and 2 m relative humidity (complex packing), 6 hours ahead
+    fn wmo_message() -> Vec<u8> {
+        let mut body = grid_2_x_2();
+
+        let mut product = vec![0, 0, 0, 0];
+        product.extend(template_4_0(0, 0, 6));
+        body.extend(section(4, &product));
+        let mut representation = vec![0, 0, 0, 3, 0, 0];
+        representation.extend(2731f32.to_be_bytes());
+        representation.extend([0, 0, 0, 1, 8, 0]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[0, 0b1101_0000]));
+        body.extend(section(7, &[0, 5, 10]));
+
+        let mut product = vec![0, 0, 0, 0];
+        product.extend(template_4_0(1, 1, 6));
+        body.extend(section(4, &product));
+        let (template_0, packed) =
+            DataRepresentationTemplate5_0::pack(&[40.0, 55.0, 70.0, 85.0], 0, 0).unwrap();
+        let mut data = Vec::new();
+        let template = write_data_7_2(&mut data, &packed, &template_0).unwrap();
+        let mut representation = vec![0, 0, 0, 4, 0, 2];
+        template.write(&mut representation).unwrap();
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[255]));
+        body.extend(section(7, &data));
+        message(7, &body)
+    }
+
+    #[test]
+    fn wmo_message_decodes() {
+        let bytes = wmo_message();
+        let message = Message::read(&mut bytes.as_slice()).unwrap().unwrap();
+        assert!(Message::read(&mut &bytes[bytes.len()..]).unwrap().is_none());
+        assert_eq!(message.indicator.total_length, bytes.len() as u64);
+        assert_eq!(message.identification.centre, 7);
+        assert_eq!(
+            message.identification.reference_time(),
+            DateTime::new(2024, 1, 2, 3, 0, 0)
+        );
+        assert_eq!(message.fields.len(), 2);
+
+        let [temperature, humidity] = &message.fields[..] else {
+            unreachable!()
+        };
+        let grid = temperature.grid_definition_template().unwrap();
+        assert_eq!(grid.lat_lon(0.0, 1.0), Some((35.0, 136.0)));
+        assert_eq!(grid.lat_lon(1.0, 0.0), Some((34.0, 135.0)));
+        let values = temperature.values().unwrap();
+        assert_eq!(values.len(), 4);
+        assert!(values[2].is_nan());
+        for (value, expected) in values.iter().zip([273.1, 273.6, f32::NAN, 274.1]) {
+            assert!(
+                value.is_nan() || (value - expected).abs() < 1e-4,
+                "{values:?}"
+            );
+        }
+        assert_eq!(humidity.values().unwrap(), [40.0, 55.0, 70.0, 85.0]);
+
+        let fields = DecodedFields::new(bytes.as_slice())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let names = fields
+            .iter()
+            .map(|field| field.parameter().unwrap().abbreviation)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["TMP", "RH"]);
+        let valid_time = fields[1].valid_time.unwrap();
+        assert_eq!(valid_time.end, DateTime::new(2024, 1, 2, 9, 0, 0));
+    }
+
+    #[cfg(feature = "jma")]
+    #[test]
+    fn jma_message_decodes() {
+        let fields = DecodedFields::new(jma_message().as_slice())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(fields.len(), 2);
+        for field in &fields {
+            assert_eq!(field.centre, 34);
+            assert_eq!(field.shape, (2, 2));
+            assert_eq!(field.reference_time, DateTime::new(2024, 1, 2, 3, 0, 0));
+        }
+
+        let temperature = &fields[0];
+        let parameter = temperature.parameter().unwrap();
+        assert_eq!((parameter.name, parameter.unit), ("Temperature", "K"));
+        assert_eq!(temperature.values, [250.0, 251.0, 252.0, 253.0]);
+        assert_eq!(
+            temperature.valid_time.unwrap().end,
+            DateTime::new(2024, 1, 2, 6, 0, 0)
+        );
+
+        let precipitation = &fields[1];
+        let parameter = precipitation.parameter().unwrap();
+        assert_eq!(
+            (parameter.abbreviation, parameter.name, parameter.unit),
+            ("JPRLV", "Precipitation amount (level value)", "kg m-2")
+        );
+        assert_eq!(precipitation.values, [0.0, 0.5, 1.0, 2.5]);
+        let valid_time = precipitation.valid_time.unwrap();
+        assert_eq!(valid_time.start, DateTime::new(2024, 1, 2, 3, 0, 0));
+        assert_eq!(valid_time.end, DateTime::new(2024, 1, 2, 6, 0, 0));
+    }
+
+    #[cfg(feature = "jma")]
+    #[test]
+    fn unknown_time_unit() {
+        let mut bytes = jma_message();
+        // indicator of unit of time of the temperature forecast
+        let offset = 16 + 21 + 72 + 5 + 4 + 8;
+        assert_eq!(bytes[offset], 1);
+        bytes[offset] = 200;
+        let fields = DecodedFields::new(bytes.as_slice())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(fields[0].valid_time.is_none());
+        assert_eq!(fields[0].values, [250.0, 251.0, 252.0, 253.0]);
+        assert!(fields[1].valid_time.is_some());
+    }
+
+    #[test]
+    fn jpeg_2000_is_rejected() {
+        let mut body = grid_2_x_2();
+        let mut product = vec![0, 0, 0, 0];
+        product.extend(template_4_0(0, 0, 3));
+        body.extend(section(4, &product));
+        let mut representation = vec![0, 0, 0, 4, 0, 40];
+        representation.extend(250f32.to_be_bytes());
+        representation.extend([0, 0, 0, 0, 8, 0, 0, 255]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[255]));
+        // start of a JPEG 2000 code stream (SOC and SIZ markers)
+        body.extend(section(7, &[0xff, 0x4f, 0xff, 0x51]));
+        let bytes = message(7, &body);
+
+        let message = Message::read(&mut bytes.as_slice()).unwrap().unwrap();
+        let field = &message.fields[0];
+        let DataRepresentationTemplate::Template5_40(template) =
+            field.data_representation_template().unwrap()
+        else {
+            panic!("template 5.40 is parsed");
+        };
+        assert_eq!(template.template_0.reference_value, 250.0);
+        assert_eq!(template.type_of_compression_used, 0);
+        assert_eq!(template.target_compression_ratio, 255);
+        assert!(matches!(
+            field.values(),
+            Err(Error::UnsupportedTemplate {
+                section: 5,
+                number: 40
+            })
+        ));
+        let mut written = Vec::new();
+        message.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+
+    #[cfg(feature = "ccsds")]
+    #[test]
+    fn ccsds_transcode() {
+        let mut body = grid_2_x_2();
+        let mut product = vec![0, 0, 0, 0];
+        product.extend(template_4_0(0, 0, 3));
+        body.extend(section(4, &product));
+        body.extend(simple_packing(250.0, 0, [0, 1, 2, 3]));
+        let bytes = message(7, &body);
+
+        let message = Message::read(&mut bytes.as_slice()).unwrap().unwrap();
+        let mut transcoded = Vec::new();
+        crate::transcode::transcode_message(&message, crate::packing::PackingMethod::Ccsds)
+            .unwrap()
+            .finish(&mut transcoded)
+            .unwrap();
+        let message = Message::read(&mut transcoded.as_slice()).unwrap().unwrap();
+        let field = &message.fields[0];
+        assert_eq!(field.data_representation.template_number, 42);
+        assert_eq!(field.values().unwrap(), [250.0, 251.0, 252.0, 253.0]);
+    }
+}
@@ -0,0 +1,362 @@
+use crate::field::{DecodedField, Field, Message};
+use crate::idx::IdxRecord;
+use crate::templates::ProductDefinitionTemplate;
+use crate::time::DateTime;
+use crate::{Error, Result};
+
+/// Predicate selecting fields by parameter, level, forecast hour and ensemble member
+///
+/// Names follow the wgrib2 inventory (see [`IdxRecord`]). Each criterion matches if the field
+/// has any of its values; unset criteria match every field.
+///
+/// ```ignore
+/// let filter = FieldFilter::new()
+///     .variable("TMP")
+///     .level("850 mb")
+///     .forecast_hours(0.0, 24.0);
+/// // or equivalently
+/// let filter = FieldFilter::parse(r#"var=TMP level="850 mb" fhour=0-24"#)?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FieldFilter {
+    variables: Vec<String>,
+    levels: Vec<String>,
+    forecast_hours: Vec<(f64, f64)>,
+    members: Vec<u8>,
+    patterns: Vec<String>,
+}
+
+impl FieldFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Variable abbreviation, e.g. "TMP"
+    pub fn variable(mut self, variable: impl Into<String>) -> Self {
+        self.variables.push(variable.into());
+        self
+    }
+
+    /// Level description, e.g. "850 mb" or "2 m above ground"
+    pub fn level(mut self, level: impl Into<String>) -> Self {
+        self.levels.push(level.into());
+        self
+    }
+
+    /// Hours from the reference time to the end of the valid time, within `min..=max`
+    pub fn forecast_hours(mut self, min: f64, max: f64) -> Self {
+        self.forecast_hours.push((min, max));
+        self
+    }
+
+    /// Perturbation number of an ensemble member
+    pub fn member(mut self, perturbation_number: u8) -> Self {
+        self.members.push(perturbation_number);
+        self
+    }
+
+    /// Text contained in the inventory columns from `d=` on, like `wgrib2 -match` without
+    /// regular expressions, e.g. ":TMP:850 mb:"
+    pub fn matching(mut self, pattern: impl Into<String>) -> Self {
+        self.patterns.push(pattern.into());
+        self
+    }
+
+    /// Parse space-separated `key=value[,value...]` terms
+    ///
+    /// Keys are `var`, `level`, `fhour` (`N` or `MIN-MAX`), `member` and `match`. Values
+    /// containing spaces or commas are quoted with `"`.
+    pub fn parse(expression: &str) -> Result<Self> {
+        let invalid =
+            |message: &str| Error::InvalidData(format!("filter {:?}: {}", expression, message));
+        let mut filter = Self::new();
+        for (key, values) in terms(expression).map_err(invalid)? {
+            for value in values {
+                filter = match key.as_str() {
+                    "var" => filter.variable(value),
+                    "level" => filter.level(value),
+                    "match" => filter.matching(value),
+                    "member" => filter.member(
+                        value
+                            .parse()
+                            .map_err(|_| invalid("member must be a number"))?,
+                    ),
+                    "fhour" => {
+                        let (min, max) = value.split_once('-').unwrap_or((&value, &value));
+                        let hour = |s: &str| {
+                            s.trim()
+                                .parse()
+                                .map_err(|_| invalid("fhour must be N or MIN-MAX"))
+                        };
+                        filter.forecast_hours(hour(min)?, hour(max)?)
+                    }
+                    key => return Err(invalid(&format!("unknown key {}", key))),
+                };
+            }
+        }
+        Ok(filter)
+    }
+
+    /// Whether a field with the given discipline, originating centre, reference time and
+    /// template is selected
+    pub fn matches(
+        &self,
+        discipline: u8,
+        centre: u16,
+        reference_time: DateTime,
+        template: &ProductDefinitionTemplate,
+    ) -> bool {
+        let record = IdxRecord::new(0, None, 0, discipline, centre, reference_time, template);
+        let any =
+            |values: &[String], value: &str| values.is_empty() || values.iter().any(|v| v == value);
+        if !any(&self.variables, &record.variable) || !any(&self.levels, &record.level) {
+            return false;
+        }
+        if !self.members.is_empty() {
+            let member = template.ensemble_info().and_then(|i| i.perturbation_number);
+            if !member.is_some_and(|m| self.members.contains(&m)) {
+                return false;
+            }
+        }
+        if !self.forecast_hours.is_empty() {
+            let Ok(valid_time) = template.valid_time(reference_time) else {
+                return false;
+            };
+            let hours =
+                (valid_time.end.unix_timestamp() - reference_time.unix_timestamp()) as f64 / 3600.0;
+            if !self
+                .forecast_hours
+                .iter()
+                .any(|(min, max)| (min..=max).contains(&&hours))
+            {
+                return false;
+            }
+        }
+        if !self.patterns.is_empty() {
+            let line = record.to_string();
+            let from_date = line.find(":d=").map_or(line.as_str(), |i| &line[i..]);
+            if !self.patterns.iter().all(|p| from_date.contains(p.as_str())) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether `field` of `message` is selected, e.g. for [`extract_fields`](crate::split::extract_fields)
+    pub fn matches_field(&self, message: &Message, field: &Field) -> bool {
+        field.product_definition().is_ok_and(|pd| {
+            self.matches(
+                message.indicator.discipline,
+                message.identification.centre,
+                message.identification.reference_time(),
+                &pd.template,
+            )
+        })
+    }
+
+    /// Whether a decoded field is selected
+    pub fn matches_decoded(&self, field: &DecodedField) -> bool {
+        self.matches(
+            field.discipline,
+            field.centre,
+            field.reference_time,
+            &field.product_definition.template,
+        )
+    }
+}
+
+/// Split an expression into keys and their comma-separated values
+fn terms(expression: &str) -> std::result::Result<Vec<(String, Vec<String>)>, &'static str> {
+    let mut terms = Vec::new();
+    let mut chars = expression.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(terms);
+        }
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|&c| c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if chars.next() != Some('=') {
+            return Err("expected key=value");
+        }
+        let mut values = Vec::new();
+        loop {
+            let mut value = String::new();
+            if chars.next_if_eq(&'"').is_some() {
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err("unterminated quote"),
+                    }
+                }
+            } else {
+                while let Some(c) = chars.next_if(|&c| c != ',' && !c.is_whitespace()) {
+                    value.push(c);
+                }
+                if value.is_empty() {
+                    return Err("empty value");
+                }
+            }
+            values.push(value);
+            if chars.next_if_eq(&',').is_none() {
+                break;
+            }
+        }
+        terms.push((key, values));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REFERENCE_TIME: DateTime = DateTime {
+        year: 2024,
+        month: 1,
+        day: 2,
+        hour: 0,
+        minute: 0,
+        second: 0,
+    };
+
+    /// Template 4.0 of a parameter of category 0 (temperature) at a surface, `hour` hours
+    /// after the reference time
+    fn template_0(number: u8, surface: u8, value: u32, hour: u8) -> Vec<u8> {
+        let mut bytes = vec![0, number, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, hour, surface, 0];
+        bytes.extend(value.to_be_bytes());
+        bytes.extend([255; 6]);
+        bytes
+    }
+
+    fn temperature(hour: u8) -> ProductDefinitionTemplate {
+        ProductDefinitionTemplate::read_any(0, &mut &template_0(0, 100, 85000, hour)[..]).unwrap()
+    }
+
+    /// Maximum temperature at 2 m over the 6 hours up to `hour` of ensemble member `member`
+    fn maximum_of_member(hour: u8, member: u8) -> ProductDefinitionTemplate {
+        let mut bytes = template_0(4, 103, 2, hour - 6);
+        bytes.extend([3, member, 21, 7, 232, 1, 2, hour, 0, 0, 1]);
+        bytes.extend([0, 0, 0, 0, 2, 2, 1, 0, 0, 0, 6, 255, 0, 0, 0, 0]);
+        ProductDefinitionTemplate::read_any(11, &mut &bytes[..]).unwrap()
+    }
+
+    fn matches(filter: &str, template: &ProductDefinitionTemplate) -> bool {
+        FieldFilter::parse(filter)
+            .unwrap()
+            .matches(0, 7, REFERENCE_TIME, template)
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(FieldFilter::parse("").unwrap(), FieldFilter::new());
+        assert_eq!(
+            FieldFilter::parse(
+                r#"  var=TMP,RH level="850 mb",surface fhour=6 member=3 match=":anl:" "#
+            )
+            .unwrap(),
+            FieldFilter::new()
+                .variable("TMP")
+                .variable("RH")
+                .level("850 mb")
+                .level("surface")
+                .forecast_hours(6.0, 6.0)
+                .member(3)
+                .matching(":anl:")
+        );
+        assert_eq!(
+            FieldFilter::parse("fhour=0-6,12 fhour=1.5-3 level=\"a,b\"").unwrap(),
+            FieldFilter::new()
+                .forecast_hours(0.0, 6.0)
+                .forecast_hours(12.0, 12.0)
+                .forecast_hours(1.5, 3.0)
+                .level("a,b")
+        );
+    }
+
+    #[test]
+    fn bad_input() {
+        for expression in [
+            "var",
+            "var TMP",
+            "var=",
+            "var=TMP,",
+            "var=,TMP",
+            "level=850 mb",
+            "level=\"850 mb",
+            "fhour=six",
+            "fhour=0-",
+            "fhour=0-6-12",
+            "fhour=1.5 - 3",
+            "member=-1",
+            "member=300",
+            "member=3.0",
+        ] {
+            let result = FieldFilter::parse(expression);
+            assert!(
+                matches!(result, Err(Error::InvalidData(_))),
+                "{}",
+                expression
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_keys() {
+        for expression in ["=TMP", "variable=TMP", "VAR=TMP", "var=TMP lev=surface"] {
+            let Err(Error::InvalidData(message)) = FieldFilter::parse(expression) else {
+                panic!("{} is accepted", expression);
+            };
+            assert!(message.contains("unknown key"), "{}", message);
+        }
+    }
+
+    #[test]
+    fn criteria() {
+        let tmp = temperature(6);
+        assert!(matches("", &tmp));
+        assert!(matches("var=TMP", &tmp));
+        assert!(matches("var=RH,TMP", &tmp));
+        assert!(!matches("var=RH", &tmp));
+        assert!(!matches("var=tmp", &tmp));
+        assert!(matches("level=\"850 mb\"", &tmp));
+        assert!(!matches("level=\"500 mb\"", &tmp));
+        assert!(matches("fhour=6", &tmp));
+        assert!(matches("fhour=0-6", &tmp));
+        assert!(!matches("fhour=0-5", &tmp));
+        assert!(matches("match=:TMP:850", &tmp));
+        assert!(!matches("match=:TMP: match=:anl:", &tmp));
+        assert!(matches("match=d=2024010200 match=fcst", &tmp));
+        // the message number and offset come before d= and are not matched
+        assert!(!matches("match=0:d=", &tmp));
+        // not an ensemble member
+        assert!(!matches("member=0", &tmp));
+    }
+
+    #[test]
+    fn values_are_alternatives_and_keys_all_apply() {
+        let tmp = temperature(6);
+        assert!(matches("var=TMP level=\"850 mb\" fhour=6", &tmp));
+        assert!(!matches("var=TMP level=surface", &tmp));
+        assert!(!matches("var=RH level=\"850 mb\"", &tmp));
+        assert!(matches("fhour=0-3,6", &tmp));
+        assert!(matches("fhour=12 fhour=3-9", &tmp));
+        assert!(matches("level=surface level=\"850 mb\"", &tmp));
+        assert!(!matches("fhour=0-3,12", &tmp));
+    }
+
+    #[test]
+    fn statistics_of_ensemble_members() {
+        let tmax = maximum_of_member(12, 5);
+        assert!(matches("var=TMAX level=\"2 m above ground\"", &tmax));
+        assert!(matches("member=5", &tmax));
+        assert!(matches("member=1,5", &tmax));
+        assert!(!matches("member=6", &tmax));
+        // the forecast hour is that of the end of the time interval
+        assert!(matches("fhour=12", &tmax));
+        assert!(!matches("fhour=6", &tmax));
+        assert!(matches("match=\"6-12 hour max fcst:ENS=+5\"", &tmax));
+    }
+}
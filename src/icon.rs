@@ -0,0 +1,543 @@
+//! Coordinates of unstructured grids (Template 3.101), such as the ICON grids of DWD
+//!
+//! A message on an unstructured grid only carries the UUID of its grid. The coordinates come
+//! from the grid description, loaded from the ICON grid file or given as arrays:
+//!
+//! ```ignore
+//! let mut grids = IconGrids::new();
+//! grids.insert(IconGrid::open("icon_grid_0026_R03B07_G.nc")?);
+//! for field in DecodedFields::new(reader) {
+//!     let field = field?;
+//!     let grid = grids.for_field(&field)?;
+//!     for (value, (lat, lon)) in field.values.iter().zip(grid.coordinates()) {
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::field::DecodedField;
+use crate::limits::check_allocation;
+use crate::templates::{GridDefinitionTemplate, format_uuid, parse_uuid};
+use crate::{Error, Result};
+
+/// Points of an unstructured grid with their latitudes and longitudes in degrees
+#[derive(Debug, Clone)]
+pub struct IconGrid {
+    pub uuid: [u8; 16],
+    pub lat: Vec<f64>,
+    pub lon: Vec<f64>,
+}
+
+impl IconGrid {
+    /// Grid of the given coordinates in degrees
+    pub fn new(uuid: [u8; 16], lat: Vec<f64>, lon: Vec<f64>) -> Result<Self> {
+        if lat.len() != lon.len() {
+            return Err(Error::InvalidData(format!(
+                "{} latitudes and {} longitudes",
+                lat.len(),
+                lon.len()
+            )));
+        }
+        Ok(Self { uuid, lat, lon })
+    }
+
+    /// Load the cell centres (`clat` and `clon`) of an ICON grid file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_netcdf(BufReader::new(File::open(path)?), "clat", "clon")
+    }
+
+    /// Load the coordinates in the variables `lat_name` and `lon_name` of an ICON grid file
+    /// (e.g. "vlat" and "vlon" for vertices), with the UUID of its `uuidOfHGrid` attribute
+    ///
+    /// Only the classic NetCDF formats are read. NetCDF-4 files can be converted with
+    /// `nccopy -k cdf5`.
+    pub fn from_netcdf<R: Read + Seek>(
+        mut reader: R,
+        lat_name: &str,
+        lon_name: &str,
+    ) -> Result<Self> {
+        let header = NetcdfHeader::read(&mut reader)?;
+        let uuid = header
+            .attributes
+            .get("uuidOfHGrid")
+            .and_then(|uuid| parse_uuid(uuid))
+            .ok_or_else(|| Error::InvalidData("grid file without uuidOfHGrid".to_string()))?;
+        let lat = header.read_coordinates(&mut reader, lat_name)?;
+        let lon = header.read_coordinates(&mut reader, lon_name)?;
+        Self::new(uuid, lat, lon)
+    }
+
+    /// Number of points
+    pub fn len(&self) -> usize {
+        self.lat.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lat.is_empty()
+    }
+
+    /// Latitude and longitude in degrees of point `index`
+    pub fn lat_lon(&self, index: usize) -> Option<(f64, f64)> {
+        Some((*self.lat.get(index)?, self.lon[index]))
+    }
+
+    /// Latitudes and longitudes in degrees of all points
+    pub fn coordinates(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.lat.iter().copied().zip(self.lon.iter().copied())
+    }
+}
+
+/// Grid descriptions keyed by UUID
+#[derive(Debug, Clone, Default)]
+pub struct IconGrids {
+    grids: HashMap<[u8; 16], Arc<IconGrid>>,
+}
+
+impl IconGrids {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a grid, replacing the grid of the same UUID
+    pub fn insert(&mut self, grid: IconGrid) {
+        self.grids.insert(grid.uuid, Arc::new(grid));
+    }
+
+    pub fn get(&self, uuid: &[u8; 16]) -> Option<&Arc<IconGrid>> {
+        self.grids.get(uuid)
+    }
+
+    /// Grid of a field on an unstructured grid, checked against its number of values
+    pub fn for_field(&self, field: &DecodedField) -> Result<&Arc<IconGrid>> {
+        let GridDefinitionTemplate::Template3_101(template) = &*field.grid_definition else {
+            return Err(Error::InvalidData(
+                "field is not on an unstructured grid (Template 3.101)".to_string(),
+            ));
+        };
+        let grid = self.get(&template.uuid).ok_or_else(|| {
+            Error::InvalidData(format!("grid {} is not loaded", template.uuid_string()))
+        })?;
+        if grid.len() != field.values.len() {
+            return Err(Error::InvalidData(format!(
+                "grid {} has {} points for {} values",
+                format_uuid(&grid.uuid),
+                grid.len(),
+                field.values.len()
+            )));
+        }
+        Ok(grid)
+    }
+}
+
+/// Variable of a classic NetCDF file
+struct NetcdfVariable {
+    dimensions: Vec<usize>,
+    nc_type: u32,
+    /// Value of the `units` attribute
+    units: Option<String>,
+    begin: u64,
+}
+
+/// Global text attributes and variables of a classic NetCDF file
+struct NetcdfHeader {
+    version: u8,
+    dimensions: Vec<(String, u64)>,
+    attributes: HashMap<String, String>,
+    variables: HashMap<String, NetcdfVariable>,
+}
+
+const NC_DIMENSION: u32 = 10;
+const NC_VARIABLE: u32 = 11;
+const NC_ATTRIBUTE: u32 = 12;
+const NC_CHAR: u32 = 2;
+const NC_FLOAT: u32 = 5;
+const NC_DOUBLE: u32 = 6;
+
+impl NetcdfHeader {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        let version = match magic {
+            [b'C', b'D', b'F', version @ (1 | 2 | 5)] => version,
+            [0x89, b'H', b'D', b'F'] => {
+                return Err(Error::UnsupportedData(
+                    "NetCDF-4 grid file, convert it with `nccopy -k cdf5`".to_string(),
+                ));
+            }
+            _ => return Err(Error::InvalidData("not a NetCDF file".to_string())),
+        };
+        let mut header = Self {
+            version,
+            dimensions: Vec::new(),
+            attributes: HashMap::new(),
+            variables: HashMap::new(),
+        };
+        header.read_size(reader)?; // number of records
+        for _ in 0..header.read_list(reader, NC_DIMENSION)? {
+            let name = header.read_name(reader)?;
+            let length = header.read_size(reader)?;
+            header.dimensions.push((name, length));
+        }
+        header.attributes = header.read_attributes(reader)?;
+        for _ in 0..header.read_list(reader, NC_VARIABLE)? {
+            let name = header.read_name(reader)?;
+            let mut dimensions = Vec::new();
+            for _ in 0..header.read_size(reader)? {
+                dimensions.push(header.read_size(reader)? as usize);
+            }
+            let units = header.read_attributes(reader)?.remove("units");
+            let nc_type = reader.read_u32::<BigEndian>()?;
+            header.read_size(reader)?; // vsize
+            let begin = match version {
+                1 => reader.read_u32::<BigEndian>()? as u64,
+                _ => reader.read_u64::<BigEndian>()?,
+            };
+            header.variables.insert(
+                name,
+                NetcdfVariable {
+                    dimensions,
+                    nc_type,
+                    units,
+                    begin,
+                },
+            );
+        }
+        Ok(header)
+    }
+
+    /// Read a count or length, 8 bytes in CDF-5 and 4 bytes otherwise
+    fn read_size<R: Read>(&self, reader: &mut R) -> Result<u64> {
+        Ok(match self.version {
+            5 => reader.read_u64::<BigEndian>()?,
+            _ => reader.read_u32::<BigEndian>()? as u64,
+        })
+    }
+
+    /// Read the tag and number of elements of a list, 0 if the list is absent
+    fn read_list<R: Read>(&self, reader: &mut R, tag: u32) -> Result<u64> {
+        match (reader.read_u32::<BigEndian>()?, self.read_size(reader)?) {
+            (0, 0) => Ok(0),
+            (t, n) if t == tag => Ok(n),
+            _ => Err(Error::InvalidData("invalid NetCDF header".to_string())),
+        }
+    }
+
+    /// Read `n` bytes followed by the padding to a multiple of 4
+    fn read_padded<R: Read>(&self, reader: &mut R, n: u64) -> Result<Vec<u8>> {
+        let len = check_allocation::<u8>(n as usize, "NetCDF header")?;
+        let mut buf = vec![0; len.next_multiple_of(4)];
+        reader.read_exact(&mut buf)?;
+        buf.truncate(len);
+        Ok(buf)
+    }
+
+    fn read_name<R: Read>(&self, reader: &mut R) -> Result<String> {
+        let n = self.read_size(reader)?;
+        Ok(String::from_utf8_lossy(&self.read_padded(reader, n)?).into_owned())
+    }
+
+    /// Read an attribute list, keeping the text attributes
+    fn read_attributes<R: Read>(&self, reader: &mut R) -> Result<HashMap<String, String>> {
+        let mut attributes = HashMap::new();
+        for _ in 0..self.read_list(reader, NC_ATTRIBUTE)? {
+            let name = self.read_name(reader)?;
+            let nc_type = reader.read_u32::<BigEndian>()?;
+            let n = self.read_size(reader)?;
+            let size = type_size(nc_type)
+                .ok_or_else(|| Error::InvalidData(format!("NetCDF type {}", nc_type)))?;
+            let values = self.read_padded(reader, n * size as u64)?;
+            if nc_type == NC_CHAR {
+                let text = String::from_utf8_lossy(&values);
+                attributes.insert(name, text.trim_end_matches('\0').to_string());
+            }
+        }
+        Ok(attributes)
+    }
+
+    /// Read a one-dimensional float or double variable in degrees
+    fn read_coordinates<R: Read + Seek>(&self, reader: &mut R, name: &str) -> Result<Vec<f64>> {
+        let variable = self
+            .variables
+            .get(name)
+            .ok_or_else(|| Error::InvalidData(format!("no variable {} in the grid file", name)))?;
+        let &[dimension] = variable.dimensions.as_slice() else {
+            return Err(Error::InvalidData(format!(
+                "{} is not one-dimensional",
+                name
+            )));
+        };
+        let len = match self.dimensions.get(dimension) {
+            Some(&(_, len)) if len > 0 => len as usize,
+            _ => {
+                return Err(Error::InvalidData(format!(
+                    "{} is not a fixed-size variable",
+                    name
+                )));
+            }
+        };
+        let len = check_allocation::<f64>(len, "grid coordinates")?;
+        reader.seek(SeekFrom::Start(variable.begin))?;
+        let mut values = vec![0.0; len];
+        match variable.nc_type {
+            NC_DOUBLE => reader.read_f64_into::<BigEndian>(&mut values)?,
+            NC_FLOAT => {
+                for value in &mut values {
+                    *value = reader.read_f32::<BigEndian>()? as f64;
+                }
+            }
+            t => {
+                return Err(Error::InvalidData(format!(
+                    "{} has NetCDF type {}",
+                    name, t
+                )));
+            }
+        }
+        // ICON grid files give the coordinates in radians
+        if variable
+            .units
+            .as_deref()
+            .is_none_or(|units| units.starts_with("rad"))
+        {
+            values.iter_mut().for_each(|v| *v = v.to_degrees());
+        }
+        Ok(values)
+    }
+}
+
+/// Size in bytes of a value of NetCDF type `nc_type`
+fn type_size(nc_type: u32) -> Option<usize> {
+    Some(match nc_type {
+        1 | 2 | 7 => 1,
+        3 | 8 => 2,
+        4 | 5 | 9 => 4,
+        6 | 10 | 11 => 8,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::field::DecodedFields;
+
+    /// UUID of the ICON global R3B7 grid
+    const UUID: [u8; 16] = [
+        0xa2, 0x7b, 0x8d, 0xe6, 0x18, 0xc4, 0x11, 0xe4, 0x82, 0x0a, 0xb5, 0xb0, 0x98, 0xc6, 0xa5,
+        0xc0,
+    ];
+
+    /// Template 3.101 of grid 26, cell centres, with `uuid`
+    fn template_3_101(uuid: [u8; 16]) -> Vec<u8> {
+        let mut bytes = vec![6, 0, 0, 26, 1];
+        bytes.extend(uuid);
+        bytes
+    }
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Message of DWD with the temperature at 2 m on the 5 points of the grid `uuid`
+    fn message(uuid: [u8; 16]) -> Vec<u8> {
+        let mut body = section(1, &[0, 78, 0, 255, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        let mut grid = vec![0, 0, 0, 0, 5, 0, 0, 0, 101];
+        grid.extend(template_3_101(uuid));
+        body.extend(section(3, &grid));
+        body.extend(section(
+            4,
+            &[
+                0, 0, 0, 0, 0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 0, 103, 0, 0, 0, 0, 2, 255, 255,
+                255, 255, 255, 255,
+            ],
+        ));
+        let mut representation = vec![0, 0, 0, 5, 0, 0];
+        representation.extend(270f32.to_be_bytes());
+        representation.extend([0, 0, 0, 0, 8, 0]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[255]));
+        body.extend(section(7, &[0, 1, 2, 3, 4]));
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn decoded_field(uuid: [u8; 16]) -> DecodedField {
+        DecodedFields::new(message(uuid).as_slice())
+            .next()
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn template() {
+        let bytes = template_3_101(UUID);
+        let GridDefinitionTemplate::Template3_101(template) =
+            GridDefinitionTemplate::read_any(101, &mut bytes.as_slice()).unwrap()
+        else {
+            panic!("not Template 3.101");
+        };
+        assert_eq!(template.shape_of_earth, 6);
+        assert_eq!(template.number_of_grid_used, 26);
+        assert_eq!(template.number_of_grid_in_reference, 1);
+        assert_eq!(template.uuid, UUID);
+        assert_eq!(
+            template.uuid_string(),
+            "a27b8de6-18c4-11e4-820a-b5b098c6a5c0"
+        );
+        assert_eq!(parse_uuid(&template.uuid_string()), Some(UUID));
+        let mut written = Vec::new();
+        template.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+        assert!(GridDefinitionTemplate::read_any(101, &mut &bytes[..20]).is_err());
+    }
+
+    #[test]
+    fn grid_of_field() {
+        let lat = vec![0.0, 10.0, 20.0, 30.0, 40.0];
+        let lon = vec![-10.0, 0.0, 10.0, 20.0, 30.0];
+        let mut grids = IconGrids::new();
+        grids.insert(IconGrid::new(UUID, lat, lon).unwrap());
+        let field = decoded_field(UUID);
+        assert_eq!(field.values, [270.0, 271.0, 272.0, 273.0, 274.0]);
+        let grid = grids.for_field(&field).unwrap();
+        assert_eq!(grid.lat_lon(3), Some((30.0, 20.0)));
+        assert_eq!(grid.lat_lon(5), None);
+        assert_eq!(grid.coordinates().next(), Some((0.0, -10.0)));
+
+        let mut other = UUID;
+        other[15] ^= 1;
+        let Err(Error::InvalidData(message)) = grids.for_field(&decoded_field(other)) else {
+            panic!("grid of another UUID is found");
+        };
+        assert_eq!(
+            message,
+            "grid a27b8de6-18c4-11e4-820a-b5b098c6a5c1 is not loaded"
+        );
+        grids.insert(IconGrid::new(UUID, vec![0.0; 4], vec![0.0; 4]).unwrap());
+        assert!(grids.for_field(&field).is_err());
+        assert!(IconGrid::new(UUID, vec![0.0; 4], vec![0.0; 5]).is_err());
+    }
+
+    /// Name, or any other counted text, padded to a multiple of 4 bytes
+    fn padded(bytes: &mut Vec<u8>, text: &[u8]) {
+        bytes.extend((text.len() as u32).to_be_bytes());
+        bytes.extend(text);
+        bytes.resize(bytes.len().next_multiple_of(4), 0);
+    }
+
+    /// Classic NetCDF file with the cell centres `clat` (double, radians) and `clon` (float,
+    /// `lon_units`) of 3 cells
+    fn grid_file(lon_units: &str) -> Vec<u8> {
+        let mut bytes = b"CDF\x01\0\0\0\0".to_vec();
+        bytes.extend([0, 0, 0, 10, 0, 0, 0, 1]);
+        padded(&mut bytes, b"cell");
+        bytes.extend(3u32.to_be_bytes());
+        bytes.extend([0, 0, 0, 12, 0, 0, 0, 2]);
+        padded(&mut bytes, b"number_of_grid_used");
+        bytes.extend([0, 0, 0, 4, 0, 0, 0, 1, 0, 0, 0, 26]);
+        padded(&mut bytes, b"uuidOfHGrid");
+        bytes.extend(NC_CHAR.to_be_bytes());
+        padded(&mut bytes, b"a27b8de6-18c4-11e4-820a-b5b098c6a5c0");
+        bytes.extend([0, 0, 0, 11, 0, 0, 0, 2]);
+        let mut begins = Vec::new();
+        for (name, units, nc_type, vsize) in [
+            (&b"clat"[..], "radian", NC_DOUBLE, 24u32),
+            (b"clon", lon_units, NC_FLOAT, 12),
+        ] {
+            padded(&mut bytes, name);
+            bytes.extend([0, 0, 0, 1, 0, 0, 0, 0]);
+            bytes.extend([0, 0, 0, 12, 0, 0, 0, 1]);
+            padded(&mut bytes, b"units");
+            bytes.extend(NC_CHAR.to_be_bytes());
+            padded(&mut bytes, units.as_bytes());
+            bytes.extend(nc_type.to_be_bytes());
+            bytes.extend(vsize.to_be_bytes());
+            begins.push(bytes.len());
+            bytes.extend([0; 4]);
+        }
+        let clat = bytes.len();
+        bytes[begins[0]..begins[0] + 4].copy_from_slice(&(clat as u32).to_be_bytes());
+        for value in [
+            0.0,
+            std::f64::consts::FRAC_PI_6,
+            -std::f64::consts::FRAC_PI_2,
+        ] {
+            bytes.extend(value.to_be_bytes());
+        }
+        let clon = bytes.len();
+        bytes[begins[1]..begins[1] + 4].copy_from_slice(&(clon as u32).to_be_bytes());
+        for value in [0.0f32, 1.5, -3.0] {
+            bytes.extend(value.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn netcdf_grid_file() {
+        let grid = IconGrid::from_netcdf(Cursor::new(grid_file("radian")), "clat", "clon").unwrap();
+        assert_eq!(grid.uuid, UUID);
+        assert_eq!(grid.len(), 3);
+        let expected = [(0.0, 0.0), (30.0, 85.94), (-90.0, -171.89)];
+        for ((lat, lon), (expected_lat, expected_lon)) in grid.coordinates().zip(expected) {
+            assert!((lat - expected_lat).abs() < 1e-9, "{}", lat);
+            assert!((lon - expected_lon).abs() < 0.01, "{}", lon);
+        }
+        // coordinates in degrees are kept
+        let grid =
+            IconGrid::from_netcdf(Cursor::new(grid_file("degrees_east")), "clat", "clon").unwrap();
+        assert_eq!(grid.lon, [0.0, 1.5, -3.0]);
+        let mut grids = IconGrids::new();
+        grids.insert(grid);
+        assert_eq!(grids.get(&UUID).unwrap().len(), 3);
+        // the field has 5 values
+        assert!(grids.for_field(&decoded_field(UUID)).is_err());
+    }
+
+    #[test]
+    fn netcdf_errors() {
+        let from_netcdf =
+            |bytes: Vec<u8>, lat: &str| IconGrid::from_netcdf(Cursor::new(bytes), lat, "clon");
+        assert!(matches!(
+            from_netcdf(grid_file("radian"), "vlat"),
+            Err(Error::InvalidData(_))
+        ));
+        let mut hdf5 = grid_file("radian");
+        hdf5[..4].copy_from_slice(b"\x89HDF");
+        assert!(matches!(
+            from_netcdf(hdf5, "clat"),
+            Err(Error::UnsupportedData(_))
+        ));
+        assert!(matches!(
+            from_netcdf(b"GRIB".to_vec(), "clat"),
+            Err(Error::InvalidData(_))
+        ));
+        let without_uuid = grid_file("radian")
+            .windows(11)
+            .position(|w| w == b"uuidOfHGrid")
+            .map(|i| {
+                let mut bytes = grid_file("radian");
+                bytes[i] = b'x';
+                bytes
+            })
+            .unwrap();
+        assert!(from_netcdf(without_uuid, "clat").is_err());
+        let truncated = grid_file("radian")[..100].to_vec();
+        assert!(from_netcdf(truncated, "clat").is_err());
+    }
+}
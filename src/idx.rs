@@ -0,0 +1,573 @@
+use std::fmt;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+use crate::field::{Field, Message};
+use crate::parameter::Parameter;
+use crate::reader::{SectionEvent, SectionReader};
+use crate::surface::FixedSurface;
+use crate::templates::ProductDefinitionTemplate;
+use crate::time::DateTime;
+use crate::{Error, Result};
+
+/// Record of a wgrib2-style `.idx` inventory
+///
+/// e.g. `12:4051823:d=2024010200:TMP:850 mb:6 hour fcst:`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdxRecord {
+    /// Message number (1-based)
+    pub message_number: u32,
+    /// Field number within the message (1-based), for records like `12.2`
+    pub field_number: Option<u32>,
+    /// Byte offset of the message
+    pub offset: u64,
+    /// Length of the message, derived from the next record (`None` for the last message)
+    pub length: Option<u64>,
+    pub reference_time: DateTime,
+    /// Variable abbreviation (e.g. "TMP")
+    pub variable: String,
+    /// Level description (e.g. "850 mb")
+    pub level: String,
+    /// Forecast time description (e.g. "6 hour fcst")
+    pub forecast: String,
+    /// Remaining columns (e.g. ensemble member descriptions)
+    pub extra: Vec<String>,
+}
+
+impl IdxRecord {
+    /// Record of a field from its parsed headers, formatted like wgrib2 does
+    ///
+    /// Local parameters are named from the tables of the originating `centre` (see
+    /// [`Parameter::lookup_for_centre`]). `length` is left unknown.
+    pub fn new(
+        message_number: u32,
+        field_number: Option<u32>,
+        offset: u64,
+        discipline: u8,
+        centre: u16,
+        reference_time: DateTime,
+        template: &ProductDefinitionTemplate,
+    ) -> Self {
+        Self {
+            message_number,
+            field_number,
+            offset,
+            length: None,
+            reference_time,
+            variable: variable_name(discipline, centre, template),
+            level: level_name(template),
+            forecast: forecast_name(template),
+            extra: ensemble_name(template).into_iter().collect(),
+        }
+    }
+
+    /// Records of every field of `message`, which starts at `offset`
+    ///
+    /// Fields are numbered only when the message has more than one.
+    pub fn for_message(message: &Message, message_number: u32, offset: u64) -> Result<Vec<Self>> {
+        let single = message.fields.len() == 1;
+        message
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                let template = field.product_definition()?.template;
+                Ok(Self {
+                    length: Some(message.indicator.total_length),
+                    ..Self::new(
+                        message_number,
+                        (!single).then_some(i as u32 + 1),
+                        offset,
+                        message.indicator.discipline,
+                        message.identification.centre,
+                        message.identification.reference_time(),
+                        &template,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Byte range of the message, suitable for an HTTP Range request (`end` is exclusive)
+    pub fn range(&self) -> (u64, Option<u64>) {
+        (self.offset, self.length.map(|len| self.offset + len))
+    }
+
+    /// Read the field of this record by seeking directly to its message
+    pub fn read_field<R: Read + Seek>(&self, reader: &mut R) -> Result<Field> {
+        reader.seek(SeekFrom::Start(self.offset))?;
+        let message = match self.length {
+            Some(len) => Message::read(&mut reader.take(len))?,
+            None => Message::read(reader)?,
+        }
+        .ok_or_else(|| Error::InvalidData("no message at inventory offset".to_string()))?;
+        let n = self.field_number.unwrap_or(1).saturating_sub(1) as usize;
+        message
+            .fields
+            .into_iter()
+            .nth(n)
+            .ok_or_else(|| Error::InvalidData("no field at inventory position".to_string()))
+    }
+}
+
+/// One line of the inventory, without the line break
+impl fmt::Display for IdxRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let t = &self.reference_time;
+        match self.field_number {
+            Some(n) => write!(f, "{}.{}", self.message_number, n)?,
+            None => write!(f, "{}", self.message_number)?,
+        }
+        write!(
+            f,
+            ":{}:d={:04}{:02}{:02}{:02}:{}:{}:{}:",
+            self.offset, t.year, t.month, t.day, t.hour, self.variable, self.level, self.forecast
+        )?;
+        write!(f, "{}", self.extra.join(":"))
+    }
+}
+
+/// wgrib2-style `.idx` inventory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Idx {
+    pub records: Vec<IdxRecord>,
+}
+
+impl Idx {
+    /// Read an `.idx` inventory (the reader is buffered internally)
+    pub fn read<R: Read>(reader: R) -> Result<Self> {
+        let mut records = BufReader::new(reader)
+            .lines()
+            .filter(|line| !matches!(line, Ok(line) if line.trim().is_empty()))
+            .map(|line| parse_record(&line?))
+            .collect::<Result<Vec<_>>>()?;
+        // records of the same message share an offset
+        for i in 0..records.len() {
+            records[i].length = records[i + 1..]
+                .iter()
+                .find(|r| r.offset != records[i].offset)
+                .map(|r| r.offset - records[i].offset);
+        }
+        Ok(Self { records })
+    }
+
+    /// Scan a GRIB2 stream and build a wgrib2-compatible inventory
+    pub fn build<R: Read>(reader: R) -> Result<Self> {
+        let mut sections = SectionReader::new(reader);
+        let mut records: Vec<IdxRecord> = Vec::new();
+        let mut message_number = 0;
+        let mut message_start = 0;
+        let mut offset = 0;
+        let mut length = 0;
+        let mut discipline = 0;
+        let mut centre = 0;
+        let mut reference_time = DateTime::new(0, 0, 0, 0, 0, 0);
+        loop {
+            let position = sections.position();
+            let Some(event) = sections.next_event()? else {
+                break;
+            };
+            match event {
+                SectionEvent::Indicator(is) => {
+                    message_number += 1;
+                    message_start = records.len();
+                    offset = position;
+                    length = is.total_length;
+                    discipline = is.discipline;
+                }
+                SectionEvent::Identification { header, .. } => {
+                    centre = header.centre;
+                    reference_time = header.reference_time();
+                }
+                SectionEvent::ProductDefinition { header, body } => {
+                    let template = header.read_template(&mut body.as_slice())?.template;
+                    records.push(IdxRecord {
+                        length: Some(length),
+                        ..IdxRecord::new(
+                            message_number,
+                            Some((records.len() - message_start + 1) as u32),
+                            offset,
+                            discipline,
+                            centre,
+                            reference_time,
+                            &template,
+                        )
+                    });
+                    // the inventory needs nothing from the data of the field
+                    sections.skip_field();
+                }
+                // single-field messages are numbered without a field number
+                SectionEvent::MessageEnd if records.len() - message_start == 1 => {
+                    records[message_start].field_number = None;
+                }
+                _ => {}
+            }
+        }
+        Ok(Self { records })
+    }
+
+    /// Write the inventory in the wgrib2 `.idx` format
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        for r in &self.records {
+            writeln!(writer, "{}", r)?;
+        }
+        Ok(())
+    }
+
+    /// Find the first record with the given variable and level (e.g. "TMP", "850 mb")
+    pub fn find(&self, variable: &str, level: &str) -> Option<&IdxRecord> {
+        self.records
+            .iter()
+            .find(|r| r.variable == variable && r.level == level)
+    }
+}
+
+fn parse_record(line: &str) -> Result<IdxRecord> {
+    let invalid = || Error::InvalidData(format!("invalid inventory line: {}", line));
+    let mut cols = line.split(':');
+    let mut next = || cols.next().ok_or_else(invalid);
+    let number = next()?;
+    let (message_number, field_number) = match number.split_once('.') {
+        Some((m, f)) => (m, Some(f.parse().map_err(|_| invalid())?)),
+        None => (number, None),
+    };
+    let message_number = message_number.parse().map_err(|_| invalid())?;
+    let offset = next()?.parse().map_err(|_| invalid())?;
+    // d=YYYYMMDDHH, or d=YYYYMMDDHHMM
+    let d = next()?
+        .strip_prefix("d=")
+        .filter(|d| matches!(d.len(), 10 | 12) && d.bytes().all(|b| b.is_ascii_digit()))
+        .ok_or_else(invalid)?;
+    let digits = |i: usize, n: usize| -> Result<u16> {
+        d.get(i..i + n)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(invalid)
+    };
+    let reference_time = DateTime::new(
+        digits(0, 4)?,
+        digits(4, 2)? as u8,
+        digits(6, 2)? as u8,
+        digits(8, 2)? as u8,
+        if d.len() >= 12 {
+            digits(10, 2)? as u8
+        } else {
+            0
+        },
+        0,
+    );
+    let variable = next()?.to_string();
+    let level = next()?.to_string();
+    let forecast = next()?.to_string();
+    let extra = cols
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect();
+    Ok(IdxRecord {
+        message_number,
+        field_number,
+        offset,
+        length: None,
+        reference_time,
+        variable,
+        level,
+        forecast,
+        extra,
+    })
+}
+
+fn variable_name(discipline: u8, centre: u16, template: &ProductDefinitionTemplate) -> String {
+    if let Some(parameter) = Parameter::for_product_of_centre(centre, discipline, template) {
+        return parameter.abbreviation.to_string();
+    }
+    match template.parameter() {
+        Some((category, number)) => format!(
+            "var discipline={} parmcat={} parm={}",
+            discipline, category, number
+        ),
+        None => "var".to_string(),
+    }
+}
+
+fn level_name(template: &ProductDefinitionTemplate) -> String {
+    let Some((first, second)) = template.fixed_surfaces() else {
+        return "unknown level".to_string();
+    };
+    let name = |s: &FixedSurface| match (s.type_of_surface, s.value()) {
+        (1, _) => "surface".to_string(),
+        (10, _) => "entire atmosphere".to_string(),
+        (101, _) => "mean sea level".to_string(),
+        (100, Some(v)) => format!("{} mb", v / 100.0),
+        (108, Some(v)) => format!("{} mb above ground", v / 100.0),
+        (106, Some(v)) => format!("{} m below ground", v),
+        _ => s.description(),
+    };
+    match (first.type_of_surface, second.type_of_surface) {
+        (100, 100) => format!(
+            "{}-{} mb",
+            first.value().unwrap_or(f64::NAN) / 100.0,
+            second.value().unwrap_or(f64::NAN) / 100.0
+        ),
+        (106, 106) => format!(
+            "{}-{} m below ground",
+            first.value().unwrap_or(f64::NAN),
+            second.value().unwrap_or(f64::NAN)
+        ),
+        (_, 255) => name(&first),
+        _ => format!("{} - {}", name(&first), name(&second)),
+    }
+}
+
+fn forecast_name(template: &ProductDefinitionTemplate) -> String {
+    let Some((unit, forecast_time)) = template.forecast_time() else {
+        return "unknown fcst".to_string();
+    };
+    let unit_name = match unit {
+        0 => "min",
+        1 => "hour",
+        2 => "day",
+        3 => "month",
+        4 => "year",
+        13 => "sec",
+        _ => "unit",
+    };
+    let Some(range) = template.time_interval().and_then(|i| i.time_ranges.first()) else {
+        return match forecast_time {
+            0 => "anl".to_string(),
+            _ => format!("{} {} fcst", forecast_time, unit_name),
+        };
+    };
+    let process = match range.statistical_process {
+        0 => "ave",
+        1 => "acc",
+        2 => "max",
+        3 => "min",
+        _ => "stat",
+    };
+    if range.indicator_of_unit_of_time == unit {
+        format!(
+            "{}-{} {} {} fcst",
+            forecast_time,
+            forecast_time as i64 + range.length_of_the_time_range as i64,
+            unit_name,
+            process
+        )
+    } else {
+        format!("{} {} {} fcst", forecast_time, unit_name, process)
+    }
+}
+
+/// wgrib2's description of an ensemble member (Code table 4.6)
+fn ensemble_name(template: &ProductDefinitionTemplate) -> Option<String> {
+    let info = template.ensemble_info()?;
+    let number = info.perturbation_number?;
+    Some(match info.type_of_ensemble_forecast? {
+        0 => "ENS=hi-res ctl".to_string(),
+        1 => "ENS=low-res ctl".to_string(),
+        2 => format!("ENS=-{}", number),
+        3 => format!("ENS=+{}", number),
+        _ => format!("ENS={}", number),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    const INVENTORY: &str = "\
+1:0:d=2024010200:PRMSL:mean sea level:anl:
+2:1200:d=2024010200:UGRD:10 m above ground:6 hour fcst:
+
+2.2:1200:d=2024010200:VGRD:10 m above ground:6 hour fcst:
+3:2100:d=202401020030:APCP:surface:0-6 hour acc fcst:ENS=+1:
+";
+
+    #[test]
+    fn read() {
+        let idx = Idx::read(INVENTORY.as_bytes()).unwrap();
+        assert_eq!(idx.records.len(), 4);
+        let numbers = idx
+            .records
+            .iter()
+            .map(|r| (r.message_number, r.field_number))
+            .collect::<Vec<_>>();
+        assert_eq!(numbers, [(1, None), (2, None), (2, Some(2)), (3, None)]);
+        let ranges = idx.records.iter().map(IdxRecord::range).collect::<Vec<_>>();
+        assert_eq!(
+            ranges,
+            [
+                (0, Some(1200)),
+                (1200, Some(2100)),
+                (1200, Some(2100)),
+                (2100, None)
+            ]
+        );
+        let last = &idx.records[3];
+        assert_eq!(last.reference_time, DateTime::new(2024, 1, 2, 0, 30, 0));
+        assert_eq!(last.variable, "APCP");
+        assert_eq!(last.level, "surface");
+        assert_eq!(last.forecast, "0-6 hour acc fcst");
+        assert_eq!(last.extra, ["ENS=+1"]);
+        assert_eq!(
+            idx.find("VGRD", "10 m above ground").unwrap().field_number,
+            Some(2)
+        );
+        assert_eq!(idx.find("VGRD", "surface"), None);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        for line in [
+            "1",
+            "1:0",
+            "1:0:d=2024010200:TMP:850 mb",
+            "x:0:d=2024010200:TMP:850 mb:anl:",
+            "1.:0:d=2024010200:TMP:850 mb:anl:",
+            "1.x:0:d=2024010200:TMP:850 mb:anl:",
+            ":0:d=2024010200:TMP:850 mb:anl:",
+            "1:-5:d=2024010200:TMP:850 mb:anl:",
+            "1:0:2024010200:TMP:850 mb:anl:",
+            "1:0:d=20240102:TMP:850 mb:anl:",
+            "1:0:d=2024010200x:TMP:850 mb:anl:",
+            "1:0:d=20240102xx:TMP:850 mb:anl:",
+            "1:0:d=+024010200:TMP:850 mb:anl:",
+        ] {
+            let result = Idx::read(format!("1:0:d=2024010200:TMP:2 m:anl:\n{}\n", line).as_bytes());
+            assert!(matches!(result, Err(Error::InvalidData(_))), "{}", line);
+        }
+    }
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Message of meteorological products of NCEP from 2024-01-02T00Z on a 2 x 2 grid, with
+    /// one field for each parameter (category and number), surface (type and scaled value)
+    /// and forecast hour of `fields`
+    fn message(fields: &[(u8, u8, u8, u32, u8)]) -> Vec<u8> {
+        let mut body = section(1, &[0, 7, 0, 0, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 35_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [36_000_000u32, 136_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x40);
+        body.extend(section(3, &grid));
+        for &(category, number, surface, value, hour) in fields {
+            let mut product = vec![0, 0, 0, 0, category, number, 2, 0, 96, 0, 0, 0, 1];
+            product.extend([0, 0, 0, hour, surface, 0]);
+            product.extend(value.to_be_bytes());
+            product.extend([255; 6]);
+            body.extend(section(4, &product));
+            let mut representation = vec![0, 0, 0, 4, 0, 0];
+            representation.extend((category as f32).to_be_bytes());
+            representation.extend([0, 0, 0, 0, 8, 0]);
+            body.extend(section(5, &representation));
+            body.extend(section(6, &[255]));
+            body.extend(section(7, &[0, 1, 2, number]));
+        }
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    fn stream() -> Vec<Vec<u8>> {
+        vec![
+            message(&[(3, 1, 101, 0, 0)]),
+            message(&[(2, 2, 103, 10, 6), (2, 3, 103, 10, 6)]),
+            message(&[(0, 0, 100, 85000, 6)]),
+        ]
+    }
+
+    #[test]
+    fn build() {
+        let messages = stream();
+        let idx = Idx::build(messages.concat().as_slice()).unwrap();
+        let (first, second) = (messages[0].len() as u64, messages[1].len() as u64);
+        let mut text = Vec::new();
+        idx.write(&mut text).unwrap();
+        assert_eq!(
+            String::from_utf8(text).unwrap(),
+            format!(
+                "1:0:d=2024010200:PRMSL:mean sea level:anl:\n\
+                 2.1:{0}:d=2024010200:UGRD:10 m above ground:6 hour fcst:\n\
+                 2.2:{0}:d=2024010200:VGRD:10 m above ground:6 hour fcst:\n\
+                 3:{1}:d=2024010200:TMP:850 mb:6 hour fcst:\n",
+                first,
+                first + second
+            )
+        );
+        let lengths = idx.records.iter().map(|r| r.length).collect::<Vec<_>>();
+        assert_eq!(
+            lengths,
+            [
+                Some(first),
+                Some(second),
+                Some(second),
+                Some(messages[2].len() as u64)
+            ]
+        );
+    }
+
+    #[test]
+    fn read_what_is_built() {
+        let messages = stream();
+        let bytes = messages.concat();
+        let built = Idx::build(bytes.as_slice()).unwrap();
+        let mut text = Vec::new();
+        built.write(&mut text).unwrap();
+        let mut read = Idx::read(text.as_slice()).unwrap();
+        // the length of the last message is unknown to the inventory
+        assert_eq!(
+            read.records[3].range(),
+            (bytes.len() as u64 - messages[2].len() as u64, None)
+        );
+        read.records[3].length = built.records[3].length;
+        assert_eq!(read, built);
+        for (record, line) in read
+            .records
+            .iter()
+            .zip(String::from_utf8(text).unwrap().lines())
+        {
+            assert_eq!(record.to_string(), line);
+        }
+    }
+
+    #[test]
+    fn read_field() {
+        let bytes = stream().concat();
+        let mut text = Vec::new();
+        Idx::build(bytes.as_slice())
+            .unwrap()
+            .write(&mut text)
+            .unwrap();
+        let idx = Idx::read(text.as_slice()).unwrap();
+        let mut reader = Cursor::new(bytes);
+        let vgrd = idx.find("VGRD", "10 m above ground").unwrap();
+        let field = vgrd.read_field(&mut reader).unwrap();
+        let template = field.product_definition().unwrap().template;
+        assert_eq!(template.parameter(), Some((2, 3)));
+        assert_eq!(field.values().unwrap(), [2.0, 3.0, 4.0, 5.0]);
+        let tmp = idx.records.last().unwrap();
+        assert_eq!(tmp.length, None);
+        assert_eq!(
+            tmp.read_field(&mut reader).unwrap().values().unwrap(),
+            [0.0, 1.0, 2.0, 0.0]
+        );
+        let missing = IdxRecord {
+            field_number: Some(3),
+            ..vgrd.clone()
+        };
+        assert!(missing.read_field(&mut reader).is_err());
+    }
+}
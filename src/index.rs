@@ -0,0 +1,236 @@
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+
+use crate::field::{Field, Message};
+use crate::reader::{SectionEvent, SectionReader};
+use crate::surface::FixedSurface;
+use crate::time::DateTime;
+use crate::{Error, Result};
+
+const INDEX_HEADER: &str = "#tinygrib2-index v1";
+
+/// Location and key metadata of a field within a GRIB2 file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Position of the message in the file (0-based)
+    pub message_index: usize,
+    /// Position of the field in the message (0-based)
+    pub field_index: usize,
+    /// Byte offset of the message (Section 0)
+    pub message_offset: u64,
+    /// Total length of the message
+    pub message_length: u64,
+    /// Byte offset of the first section of the field repetition (Section 2, 3 or 4)
+    pub offset: u64,
+    /// Length from `offset` to the end of Section 7
+    pub length: u64,
+    pub discipline: u8,
+    pub reference_time: DateTime,
+    /// Parameter category and parameter number
+    pub parameter: Option<(u8, u8)>,
+    pub first_fixed_surface: Option<FixedSurface>,
+    /// Indicator of unit of time range and forecast time
+    pub forecast_time: Option<(u8, i32)>,
+}
+
+impl IndexEntry {
+    /// Read this field by seeking directly to its message
+    pub fn read_field<R: Read + Seek>(&self, reader: &mut R) -> Result<Field> {
+        reader.seek(SeekFrom::Start(self.message_offset))?;
+        let message = Message::read(&mut reader.take(self.message_length))?
+            .ok_or_else(|| Error::InvalidData("no message at indexed offset".to_string()))?;
+        message
+            .fields
+            .into_iter()
+            .nth(self.field_index)
+            .ok_or_else(|| Error::InvalidData("no field at indexed position".to_string()))
+    }
+}
+
+/// Index of all fields in a GRIB2 file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Index {
+    pub entries: Vec<IndexEntry>,
+}
+
+impl Index {
+    /// Scan a GRIB2 stream and collect the location and metadata of every field
+    pub fn build<R: Read>(reader: R) -> Result<Self> {
+        let mut sections = SectionReader::new(reader);
+        let mut entries = Vec::new();
+        let mut message_index = 0;
+        let mut field_index = 0;
+        let mut message_offset = 0;
+        let mut message_length = 0;
+        let mut discipline = 0;
+        let mut reference_time = DateTime::new(0, 0, 0, 0, 0, 0);
+        let mut field_offset = None;
+        let mut template = None;
+        loop {
+            let position = sections.position();
+            let Some(event) = sections.next_event()? else {
+                break;
+            };
+            match event {
+                SectionEvent::Indicator(is) => {
+                    message_offset = position;
+                    message_length = is.total_length;
+                    discipline = is.discipline;
+                    field_index = 0;
+                }
+                SectionEvent::Identification { header, .. } => {
+                    reference_time = header.reference_time()
+                }
+                SectionEvent::LocalUse { .. } | SectionEvent::GridDefinition { .. } => {
+                    field_offset.get_or_insert(position);
+                }
+                SectionEvent::ProductDefinition { header, body } => {
+                    field_offset.get_or_insert(position);
+                    template = Some(header.read_template(&mut body.as_slice())?.template);
+                }
+                SectionEvent::DataRepresentation { .. } | SectionEvent::Bitmap { .. } => {}
+                SectionEvent::Data { .. } => {
+                    let offset = field_offset.take().unwrap();
+                    let template = template.take();
+                    entries.push(IndexEntry {
+                        message_index,
+                        field_index,
+                        message_offset,
+                        message_length,
+                        offset,
+                        length: sections.position() - offset,
+                        discipline,
+                        reference_time,
+                        parameter: template.as_ref().and_then(|t| t.parameter()),
+                        first_fixed_surface: template
+                            .as_ref()
+                            .and_then(|t| t.fixed_surfaces())
+                            .map(|(first, _)| first),
+                        forecast_time: template.as_ref().and_then(|t| t.forecast_time()),
+                    });
+                    field_index += 1;
+                }
+                SectionEvent::MessageEnd => message_index += 1,
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    /// Read the field of the `n`-th entry by seeking directly to its message
+    pub fn read_field<R: Read + Seek>(&self, reader: &mut R, n: usize) -> Result<Field> {
+        let entry = self
+            .entries
+            .get(n)
+            .ok_or_else(|| Error::InvalidData(format!("index has no entry {}", n)))?;
+        entry.read_field(reader)
+    }
+
+    /// Write the index as tab-separated text, one field per line
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "{}", INDEX_HEADER)?;
+        for e in &self.entries {
+            let t = &e.reference_time;
+            writeln!(
+                writer,
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{:04}{:02}{:02}{:02}{:02}{:02}\t{}\t{}\t{}",
+                e.message_index,
+                e.field_index,
+                e.message_offset,
+                e.message_length,
+                e.offset,
+                e.length,
+                e.discipline,
+                t.year,
+                t.month,
+                t.day,
+                t.hour,
+                t.minute,
+                t.second,
+                e.parameter
+                    .map_or("-".to_string(), |(c, n)| format!("{},{}", c, n)),
+                e.first_fixed_surface.map_or("-".to_string(), |s| format!(
+                    "{},{},{}",
+                    s.type_of_surface, s.scale_factor, s.scaled_value
+                )),
+                e.forecast_time
+                    .map_or("-".to_string(), |(u, v)| format!("{},{}", u, v)),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Read an index written by [`Index::write`] (the reader is buffered internally)
+    pub fn read<R: Read>(reader: R) -> Result<Self> {
+        let mut lines = BufReader::new(reader).lines();
+        if lines.next().transpose()?.as_deref() != Some(INDEX_HEADER) {
+            return Err(Error::InvalidData("not a tinygrib2 index".to_string()));
+        }
+        let entries = lines
+            .map(|line| parse_entry(&line?))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { entries })
+    }
+}
+
+fn parse_entry(line: &str) -> Result<IndexEntry> {
+    let invalid = || Error::InvalidData(format!("invalid index line: {}", line));
+    let cols: Vec<&str> = line.split('\t').collect();
+    let [
+        message_index,
+        field_index,
+        message_offset,
+        message_length,
+        offset,
+        length,
+        discipline,
+        reference_time,
+        parameter,
+        surface,
+        forecast_time,
+    ] = cols[..]
+    else {
+        return Err(invalid());
+    };
+    fn num<T: std::str::FromStr>(s: &str) -> Option<T> {
+        s.parse().ok()
+    }
+    fn list(s: &str) -> Option<Vec<&str>> {
+        (s != "-").then(|| s.split(',').collect())
+    }
+    let time = |i: usize, n: usize| reference_time.get(i..i + n).and_then(num::<u16>);
+    Ok(IndexEntry {
+        message_index: num(message_index).ok_or_else(invalid)?,
+        field_index: num(field_index).ok_or_else(invalid)?,
+        message_offset: num(message_offset).ok_or_else(invalid)?,
+        message_length: num(message_length).ok_or_else(invalid)?,
+        offset: num(offset).ok_or_else(invalid)?,
+        length: num(length).ok_or_else(invalid)?,
+        discipline: num(discipline).ok_or_else(invalid)?,
+        reference_time: DateTime::new(
+            time(0, 4).ok_or_else(invalid)?,
+            time(4, 2).ok_or_else(invalid)? as u8,
+            time(6, 2).ok_or_else(invalid)? as u8,
+            time(8, 2).ok_or_else(invalid)? as u8,
+            time(10, 2).ok_or_else(invalid)? as u8,
+            time(12, 2).ok_or_else(invalid)? as u8,
+        ),
+        parameter: match list(parameter).as_deref() {
+            None => None,
+            Some(&[c, n]) => Some((num(c).ok_or_else(invalid)?, num(n).ok_or_else(invalid)?)),
+            Some(_) => return Err(invalid()),
+        },
+        first_fixed_surface: match list(surface).as_deref() {
+            None => None,
+            Some(&[t, f, v]) => Some(FixedSurface::new(
+                num(t).ok_or_else(invalid)?,
+                num(f).ok_or_else(invalid)?,
+                num(v).ok_or_else(invalid)?,
+            )),
+            Some(_) => return Err(invalid()),
+        },
+        forecast_time: match list(forecast_time).as_deref() {
+            None => None,
+            Some(&[u, v]) => Some((num(u).ok_or_else(invalid)?, num(v).ok_or_else(invalid)?)),
+            Some(_) => return Err(invalid()),
+        },
+    })
+}
@@ -0,0 +1,85 @@
+//! Tables of JMA (Japan Meteorological Agency) products (feature `jma`)
+
+use std::fmt;
+
+use crate::field::DecodedField;
+
+/// Weather category of the JMA weather distribution forecast (天気分布予報)
+///
+/// The field holds the category code at each point, e.g. as a run length packed
+/// (Template 5.200) product.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WeatherCategory {
+    /// 晴れ
+    Sunny = 1,
+    /// 曇り
+    Cloudy = 2,
+    /// 雨
+    Rain = 3,
+    /// 雨または雪
+    RainOrSnow = 4,
+    /// 雪
+    Snow = 5,
+}
+
+impl WeatherCategory {
+    pub fn from_code(code: u8) -> Option<Self> {
+        Some(match code {
+            1 => Self::Sunny,
+            2 => Self::Cloudy,
+            3 => Self::Rain,
+            4 => Self::RainOrSnow,
+            5 => Self::Snow,
+            _ => return None,
+        })
+    }
+
+    /// Category of a decoded value, `None` if it is missing or not a category code
+    pub fn from_value(value: f32) -> Option<Self> {
+        match value.round() {
+            v if (v - value).abs() < 1e-3 && (0.0..=255.0).contains(&v) => Self::from_code(v as u8),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> u8 {
+        self as u8
+    }
+
+    /// English description, e.g. "rain or snow"
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Sunny => "sunny",
+            Self::Cloudy => "cloudy",
+            Self::Rain => "rain",
+            Self::RainOrSnow => "rain or snow",
+            Self::Snow => "snow",
+        }
+    }
+
+    /// Japanese description as used by JMA, e.g. "雨または雪"
+    pub fn description_ja(self) -> &'static str {
+        match self {
+            Self::Sunny => "晴れ",
+            Self::Cloudy => "曇り",
+            Self::Rain => "雨",
+            Self::RainOrSnow => "雨または雪",
+            Self::Snow => "雪",
+        }
+    }
+}
+
+impl fmt::Display for WeatherCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// Weather category at each point of a weather distribution forecast, `None` where missing
+pub fn weather_categories(field: &DecodedField) -> Vec<Option<WeatherCategory>> {
+    field
+        .values
+        .iter()
+        .map(|&v| WeatherCategory::from_value(v))
+        .collect()
+}
@@ -0,0 +1,118 @@
+//! Section bodies captured during a scan, with their templates parsed on first use
+//!
+//! ```ignore
+//! let mut grids = Vec::new();
+//! for event in SectionReader::new(reader) {
+//!     if let SectionEvent::GridDefinition { header, body } = event? {
+//!         grids.push(LazyTemplate::new(header, body));
+//!     }
+//! }
+//! // only the grids asked for are parsed, each at most once
+//! let shape = grids[0].get()?.shape();
+//! ```
+
+use std::fmt;
+use std::sync::OnceLock;
+
+use crate::Result;
+use crate::message::{
+    DataRepresentationSectionHeader, GridDefinitionSectionHeader, ProductDefinition,
+    ProductDefinitionSectionHeader,
+};
+use crate::templates::{DataRepresentationTemplate, GridDefinitionTemplate};
+
+/// Header of a section whose body holds a template
+pub trait TemplateSection {
+    type Template;
+
+    /// Parse the template from the section body
+    fn parse_template(&self, body: &[u8]) -> Result<Self::Template>;
+}
+
+impl TemplateSection for GridDefinitionSectionHeader {
+    type Template = GridDefinitionTemplate;
+
+    fn parse_template(&self, mut body: &[u8]) -> Result<GridDefinitionTemplate> {
+        self.read_template(&mut body)
+    }
+}
+
+impl TemplateSection for ProductDefinitionSectionHeader {
+    type Template = ProductDefinition;
+
+    fn parse_template(&self, mut body: &[u8]) -> Result<ProductDefinition> {
+        self.read_template(&mut body)
+    }
+}
+
+impl TemplateSection for DataRepresentationSectionHeader {
+    type Template = DataRepresentationTemplate;
+
+    fn parse_template(&self, mut body: &[u8]) -> Result<DataRepresentationTemplate> {
+        self.read_template(&mut body)
+    }
+}
+
+/// Section header and body whose template is parsed when first asked for and then cached
+///
+/// Capturing a section costs no more than keeping its bytes, so a pass over many messages
+/// only pays for the templates it actually reads. A failed parse is not cached.
+pub struct LazyTemplate<H: TemplateSection> {
+    pub header: H,
+    body: Vec<u8>,
+    parsed: OnceLock<H::Template>,
+}
+
+impl<H: TemplateSection> LazyTemplate<H> {
+    pub fn new(header: H, body: Vec<u8>) -> Self {
+        Self {
+            header,
+            body,
+            parsed: OnceLock::new(),
+        }
+    }
+
+    /// Section body following the section header
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Whether the template has been parsed already
+    pub fn is_parsed(&self) -> bool {
+        self.parsed.get().is_some()
+    }
+
+    /// Parsed template, parsing it on the first call
+    pub fn get(&self) -> Result<&H::Template> {
+        if let Some(template) = self.parsed.get() {
+            return Ok(template);
+        }
+        let template = self.header.parse_template(&self.body)?;
+        Ok(self.parsed.get_or_init(|| template))
+    }
+
+    /// Header and body, dropping the parsed template
+    pub fn into_parts(self) -> (H, Vec<u8>) {
+        (self.header, self.body)
+    }
+}
+
+/// Clones the header and body; the clone parses its template again when asked for
+impl<H: TemplateSection + Clone> Clone for LazyTemplate<H> {
+    fn clone(&self) -> Self {
+        Self::new(self.header.clone(), self.body.clone())
+    }
+}
+
+impl<H: TemplateSection + fmt::Debug> fmt::Debug for LazyTemplate<H>
+where
+    H::Template: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyTemplate")
+            .field("header", &self.header)
+            .field("body_len", &self.body.len())
+            .field("parsed", &self.parsed.get())
+            .finish()
+    }
+}
@@ -1,7 +1,48 @@
+pub mod aggregate;
+pub mod arithmetic;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+#[cfg(feature = "capi")]
+pub mod capi;
+pub mod catalog;
+pub mod codes;
+pub mod context;
+pub mod contour;
+pub mod cube;
+pub mod derived;
+pub mod dump;
+#[cfg(feature = "ecmwf")]
+pub mod ecmwf;
+pub mod ensemble;
+pub mod field;
+pub mod filter;
+pub mod icon;
+pub mod idx;
+pub mod index;
+#[cfg(feature = "jma")]
+pub mod jma;
+pub mod lazy;
+pub mod limits;
 pub mod message;
+#[cfg(feature = "ncep")]
+pub mod mrms;
+pub mod packing;
+pub mod parallel;
+pub mod parameter;
+pub mod parser;
+pub mod radar;
+pub mod range;
 pub mod reader;
+pub mod render;
+pub mod split;
+pub mod surface;
 pub mod templates;
+pub mod time;
+pub mod transcode;
+pub mod wind;
+pub mod writer;
 
+pub use field::*;
 pub use reader::*;
 use thiserror::Error;
 
@@ -13,6 +54,37 @@ pub enum Error {
     InvalidData(String),
     #[error("Unsupported: {0}")]
     UnsupportedData(String),
+    /// Input ended before the end of a message at `offset`
+    ///
+    /// The offset is counted from the start of the stream for `SectionReader` and
+    /// from the start of the message for `MessageReader`.
+    #[error("Truncated: input ended at offset {offset} before the end of the message")]
+    Truncated { offset: u64 },
+    /// Template not implemented by this crate
+    #[error("Unsupported: template {section}.{number}")]
+    UnsupportedTemplate { section: u8, number: u16 },
+    /// Section body ended before the end of its template, e.g. a template with more
+    /// variable-length entries than its section holds
+    #[error("Invalid format: section {section} ends within template {section}.{number}")]
+    TemplateTruncated { section: u8, number: u16 },
+    /// Error in the `message`-th message (0-based) of a stream, which starts at `offset`
+    #[error("message {message} at offset {offset}: {source}")]
+    InMessage {
+        message: u64,
+        offset: u64,
+        #[source]
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// The underlying error, without the message context of `InMessage`
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::InMessage { source, .. } => source.root(),
+            e => e,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
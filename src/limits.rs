@@ -0,0 +1,33 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Error, Result};
+
+/// Default cap of a single allocation sized from values read off the wire (1 GiB)
+pub const DEFAULT_ALLOCATION_LIMIT: usize = 1 << 30;
+
+static ALLOCATION_LIMIT: AtomicUsize = AtomicUsize::new(DEFAULT_ALLOCATION_LIMIT);
+
+/// Set the cap, in bytes, of a single allocation sized from section lengths and value counts
+///
+/// Reading a section or decoding values that would need more fails with `Error::InvalidData`,
+/// so a crafted file cannot make the reader allocate gigabytes.
+pub fn set_allocation_limit(bytes: usize) {
+    ALLOCATION_LIMIT.store(bytes, Ordering::Relaxed);
+}
+
+/// Current cap set by [`set_allocation_limit`]
+pub fn allocation_limit() -> usize {
+    ALLOCATION_LIMIT.load(Ordering::Relaxed)
+}
+
+/// Check that `count` elements of `T` fit within the allocation limit
+pub(crate) fn check_allocation<T>(count: usize, what: &str) -> Result<usize> {
+    let limit = allocation_limit();
+    match count.checked_mul(std::mem::size_of::<T>()) {
+        Some(bytes) if bytes <= limit => Ok(count),
+        _ => Err(Error::InvalidData(format!(
+            "{} ({} elements) exceeds the allocation limit of {} bytes",
+            what, count, limit
+        ))),
+    }
+}
@@ -1,12 +1,21 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use byteorder::{BigEndian, NativeEndian, ReadBytesExt};
+use byteorder::{BigEndian, NativeEndian, ReadBytesExt, WriteBytesExt};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::templates::GribRead;
+use crate::codes::{Discipline, ProductKind, SignificanceOfReferenceTime, TypeOfProcessedData};
+use crate::limits::check_allocation;
+use crate::templates::{
+    DataRepresentationTemplate, GribRead, GribWrite, GridDefinitionTemplate,
+    IdentificationTemplate, ProductDefinitionTemplate,
+};
+use crate::time::DateTime;
 use crate::{Error, Result};
 
 /// Section 0: INDICATOR SECTION (IS)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IndicatorSectionHeader {
     pub identifier: u32,
     pub reserved: u16,
@@ -35,10 +44,26 @@ impl IndicatorSectionHeader {
             total_length: reader.read_u64::<BigEndian>()?,
         })
     }
+
+    /// Write Section 0 including the 'GRIB' identifier
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(b"GRIB")?;
+        writer.write_u16::<NativeEndian>(self.reserved)?;
+        writer.write_grib_value(self.discipline)?;
+        writer.write_grib_value(self.edition_number)?;
+        writer.write_grib_value(self.total_length)?;
+        Ok(())
+    }
+
+    /// Discipline of the processed data (Code table 0.0)
+    pub fn discipline(&self) -> Discipline {
+        self.discipline.into()
+    }
 }
 
 /// Common header fields for section 1 to 8
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SectionHeader {
     pub section_length: u32,
     pub number_of_section: u8,
@@ -61,6 +86,28 @@ impl SectionHeader {
         })
     }
 
+    /// Write the section length and number (octets 1-5), or '7777' for the end section
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.number_of_section == 8 {
+            writer.write_all(b"7777")?;
+        } else {
+            writer.write_grib_value(self.section_length)?;
+            writer.write_grib_value(self.number_of_section)?;
+        }
+        Ok(())
+    }
+
+    /// Ensure the section is long enough for its fixed-length header
+    pub fn ensure_min_length(&self, length: u32) -> Result<()> {
+        if self.section_length < length {
+            return Err(Error::InvalidData(format!(
+                "section {} must be at least {} octets long, but got {}",
+                self.number_of_section, length, self.section_length
+            )));
+        }
+        Ok(())
+    }
+
     pub fn ensure_section_number(&self, number: u8) -> Result<()> {
         if self.number_of_section != number {
             return Err(Error::InvalidData(format!(
@@ -73,7 +120,8 @@ impl SectionHeader {
 }
 
 /// Section 1: IDENTIFICATION SECTION (IDS)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct IdentificationSectionHeader {
     pub section_length: u32,
     pub centre: u16,
@@ -89,6 +137,7 @@ pub struct IdentificationSectionHeader {
     pub second: u8,
     pub production_status_of_processed_data: u8,
     pub type_of_processed_data: u8,
+    /// Identification template number (octets 22-23), if the section has a template
     pub template_number: Option<u16>,
 }
 
@@ -96,6 +145,10 @@ impl IdentificationSectionHeader {
     /// Read Section 1: IDENTIFICATION SECTION (IDS)
     pub fn read<R: Read>(header: SectionHeader, reader: &mut R) -> Result<Self> {
         header.ensure_section_number(1)?;
+        header.ensure_min_length(21)?;
+        if header.section_length != 21 {
+            header.ensure_min_length(23)?;
+        }
         Ok(Self {
             section_length: header.section_length,
             centre: reader.read_grib_value()?,
@@ -118,16 +171,115 @@ impl IdentificationSectionHeader {
         })
     }
 
+    /// Read the identification template from the section body, `None` without a template
+    pub fn read_template<R: Read>(&self, reader: &mut R) -> Result<Option<IdentificationTemplate>> {
+        let Some(template_number) = self.template_number else {
+            return Ok(None);
+        };
+        let body = read_section_body(reader, self.body_len(), "section 1 body")?;
+        template_truncated(
+            1,
+            template_number,
+            IdentificationTemplate::read_any_for_centre(
+                template_number,
+                Some(self.centre),
+                &mut body.as_slice(),
+            ),
+        )
+        .map(Some)
+    }
+
+    /// Write the fields following the common section header (octets 6-21), and the template
+    /// number (octets 22-23) if any
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.centre)?;
+        writer.write_grib_value(self.sub_centre)?;
+        writer.write_grib_value(self.tables_version)?;
+        writer.write_grib_value(self.local_tables_version)?;
+        writer.write_grib_value(self.significance_of_reference_time)?;
+        writer.write_grib_value(self.year)?;
+        writer.write_grib_value(self.month)?;
+        writer.write_grib_value(self.day)?;
+        writer.write_grib_value(self.hour)?;
+        writer.write_grib_value(self.minute)?;
+        writer.write_grib_value(self.second)?;
+        writer.write_grib_value(self.production_status_of_processed_data)?;
+        writer.write_grib_value(self.type_of_processed_data)?;
+        if let Some(template_number) = self.template_number {
+            writer.write_grib_value(template_number)?;
+        }
+        Ok(())
+    }
+
+    /// Reference time (octets 13-19)
+    ///
+    /// Convert it with `TryFrom` to `chrono::DateTime<Utc>` (feature `chrono`) or
+    /// `time::OffsetDateTime` (feature `time`).
+    pub fn reference_time(&self) -> DateTime {
+        DateTime::new(
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        )
+    }
+
+    /// Significance of the reference time (Code table 1.2)
+    pub fn significance_of_reference_time(&self) -> SignificanceOfReferenceTime {
+        self.significance_of_reference_time.into()
+    }
+
+    /// Type of processed data (Code table 1.4)
+    pub fn type_of_processed_data(&self) -> TypeOfProcessedData {
+        self.type_of_processed_data.into()
+    }
+
+    /// Nature of a product of the message with the given product definition template
+    pub fn product_kind(&self, template: &ProductDefinitionTemplate) -> ProductKind {
+        ProductKind::new(
+            self.type_of_processed_data(),
+            self.significance_of_reference_time(),
+            template.generating_process(),
+        )
+    }
+
+    /// Set the reference time (octets 13-19)
+    pub fn set_reference_time(&mut self, time: DateTime) {
+        (self.year, self.month, self.day) = (time.year, time.month, time.day);
+        (self.hour, self.minute, self.second) = (time.hour, time.minute, time.second);
+    }
+
+    /// Length of the remaining section body: the template (octets 24-nn), if any
     pub fn body_len(&self) -> u32 {
-        match self.section_length {
-            21 => 0,
-            _ => self.section_length - 23,
+        match self.template_number {
+            Some(_) => self.section_length.saturating_sub(23),
+            None => self.section_length.saturating_sub(21),
+        }
+    }
+}
+
+/// Read the rest of a section body, at most `len` bytes
+fn read_section_body<R: Read>(reader: &mut R, len: u32, what: &str) -> Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(check_allocation::<u8>(len as usize, what)?);
+    reader.read_to_end(&mut body)?;
+    Ok(body)
+}
+
+/// Report the end of a section body within its template as `Error::TemplateTruncated`
+fn template_truncated<T>(section: u8, number: u16, result: Result<T>) -> Result<T> {
+    match result {
+        Err(Error::IO(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+            Err(Error::TemplateTruncated { section, number })
         }
+        result => result,
     }
 }
 
 /// Section 2: LOCAL USE SECTION (LOC)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct LocalUseSectionHeader {
     pub section_length: u32,
 }
@@ -136,18 +288,20 @@ impl LocalUseSectionHeader {
     /// Read Section 2: LOCAL USE SECTION (LOC)
     pub fn read<R: Read>(header: SectionHeader, _reader: &mut R) -> Result<LocalUseSectionHeader> {
         header.ensure_section_number(2)?;
+        header.ensure_min_length(5)?;
         Ok(Self {
             section_length: header.section_length,
         })
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - 5
+        self.section_length.saturating_sub(5)
     }
 }
 
 /// Section 3: GRID DEFINITION SECTION (GDS)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GridDefinitionSectionHeader {
     pub section_length: u32,
     pub source_of_grid_definition: u8,
@@ -161,6 +315,7 @@ impl GridDefinitionSectionHeader {
     /// Read Section 3: GRID DEFINITION SECTION (GDS)
     pub fn read<R: Read>(header: &SectionHeader, reader: &mut R) -> Result<Self> {
         header.ensure_section_number(3)?;
+        header.ensure_min_length(14)?;
         Ok(Self {
             section_length: header.section_length,
             source_of_grid_definition: reader.read_grib_value()?,
@@ -172,12 +327,33 @@ impl GridDefinitionSectionHeader {
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - 14
+        self.section_length.saturating_sub(14)
+    }
+
+    /// Read the grid definition template from the section body
+    pub fn read_template<R: Read>(&self, reader: &mut R) -> Result<GridDefinitionTemplate> {
+        let body = read_section_body(reader, self.body_len(), "section 3 body")?;
+        template_truncated(
+            3,
+            self.template_number,
+            GridDefinitionTemplate::read_any(self.template_number, &mut body.as_slice()),
+        )
+    }
+
+    /// Write the fields following the common section header (octets 6-14)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.source_of_grid_definition)?;
+        writer.write_grib_value(self.number_of_data_points)?;
+        writer.write_grib_value(self.number_of_octects_for_number_of_points)?;
+        writer.write_grib_value(self.interpretation_of_number_of_points)?;
+        writer.write_grib_value(self.template_number)?;
+        Ok(())
     }
 }
 
 /// Section 4: PRODUCT DEFINITION SECTION (PDS)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProductDefinitionSectionHeader {
     pub section_length: u32,
     pub nv: u16,
@@ -188,6 +364,7 @@ impl ProductDefinitionSectionHeader {
     /// Read Section 4: PRODUCT DEFINITION SECTION (PDS)
     pub fn read<R: Read>(header: &SectionHeader, reader: &mut R) -> Result<Self> {
         header.ensure_section_number(4)?;
+        header.ensure_min_length(9)?;
         Ok(ProductDefinitionSectionHeader {
             section_length: header.section_length,
             nv: reader.read_grib_value()?,
@@ -196,12 +373,63 @@ impl ProductDefinitionSectionHeader {
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - 9
+        self.section_length.saturating_sub(9)
+    }
+
+    /// Write the fields following the common section header (octets 6-9)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.nv)?;
+        writer.write_grib_value(self.template_number)?;
+        Ok(())
+    }
+
+    /// Read the product definition template and the `nv` coordinate values that follow it
+    ///
+    /// The coordinate values are the vertical coordinate parameters (e.g. hybrid level coefficients).
+    pub fn read_template<R: Read>(&self, reader: &mut R) -> Result<ProductDefinition> {
+        let buf = read_section_body(reader, self.body_len(), "section 4 body")?;
+        let Some(template_len) = buf.len().checked_sub(self.nv as usize * 4) else {
+            return Err(Error::InvalidData(format!(
+                "section 4 is too short for {} coordinate values",
+                self.nv
+            )));
+        };
+        let (mut template, mut coordinates) = buf.split_at(template_len);
+        Ok(ProductDefinition {
+            template: template_truncated(
+                4,
+                self.template_number,
+                ProductDefinitionTemplate::read_any(self.template_number, &mut template),
+            )?,
+            coordinate_values: (0..self.nv)
+                .map(|_| coordinates.read_grib_value())
+                .collect::<std::io::Result<Vec<f32>>>()?,
+        })
     }
 }
 
-/// Section 5: Data Representation Section (DRS)
+/// Contents of Section 4: product definition template and optional list of coordinate values
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinition {
+    pub template: ProductDefinitionTemplate,
+    pub coordinate_values: Vec<f32>,
+}
+
+impl ProductDefinition {
+    /// Write the template followed by the coordinate values (octets 10-nn)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template.write(writer)?;
+        for &value in &self.coordinate_values {
+            writer.write_grib_value(value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Section 5: Data Representation Section (DRS)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataRepresentationSectionHeader {
     pub section_length: u32,
     pub number_of_values: u32,
@@ -215,6 +443,7 @@ impl DataRepresentationSectionHeader {
         reader: &mut R,
     ) -> Result<DataRepresentationSectionHeader> {
         header.ensure_section_number(5)?;
+        header.ensure_min_length(11)?;
         Ok(Self {
             section_length: header.section_length,
             number_of_values: reader.read_grib_value()?,
@@ -223,12 +452,30 @@ impl DataRepresentationSectionHeader {
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - 11
+        self.section_length.saturating_sub(11)
+    }
+
+    /// Read the data representation template from the section body
+    pub fn read_template<R: Read>(&self, reader: &mut R) -> Result<DataRepresentationTemplate> {
+        let body = read_section_body(reader, self.body_len(), "section 5 body")?;
+        template_truncated(
+            5,
+            self.template_number,
+            DataRepresentationTemplate::read_any(self.template_number, &mut body.as_slice()),
+        )
+    }
+
+    /// Write the fields following the common section header (octets 6-11)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.number_of_values)?;
+        writer.write_grib_value(self.template_number)?;
+        Ok(())
     }
 }
 
 /// Section 6: BIT-MAP SECTION (BITMAP)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BitmapSectionHeader {
     pub section_length: u32,
     pub bit_map_indicator: u8,
@@ -238,6 +485,7 @@ impl BitmapSectionHeader {
     /// Read Section 6: BIT-MAP SECTION (BITMAP)
     pub fn read<R: Read>(header: &SectionHeader, reader: &mut R) -> Result<Self> {
         header.ensure_section_number(6)?;
+        header.ensure_min_length(6)?;
         Ok(Self {
             section_length: header.section_length,
             bit_map_indicator: reader.read_grib_value()?,
@@ -245,12 +493,13 @@ impl BitmapSectionHeader {
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - (5 + 1)
+        self.section_length.saturating_sub(5 + 1)
     }
 }
 
 /// Section 7: DATA SECTION (DATA)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataSectionHeader {
     pub section_length: u32,
 }
@@ -259,12 +508,52 @@ impl DataSectionHeader {
     /// Read Section 7: DATA SECTION (DATA)
     pub fn read(header: &SectionHeader) -> Result<Self> {
         header.ensure_section_number(7)?;
+        header.ensure_min_length(5)?;
         Ok(Self {
             section_length: header.section_length,
         })
     }
 
     pub fn body_len(&self) -> u32 {
-        self.section_length - 5
+        self.section_length.saturating_sub(5)
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    /// Serialize `value` to JSON and back, returning the JSON and the deserialized value
+    fn json_round_trip<T: Serialize + for<'de> Deserialize<'de>>(value: &T) -> (String, T) {
+        let json = serde_json::to_string(value).unwrap();
+        (json.clone(), serde_json::from_str(&json).unwrap())
+    }
+
+    #[test]
+    fn serde_section_header() {
+        let bytes = [0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 1, 0];
+        let header = IndicatorSectionHeader::read(&mut &bytes[..]).unwrap();
+        let (json, decoded) = json_round_trip(&header);
+        assert!(json.contains(r#""discipline":0"#), "{json}");
+        assert!(json.contains(r#""total_length":256"#), "{json}");
+        assert_eq!(decoded.total_length, 256);
+    }
+
+    #[test]
+    fn serde_templates() {
+        let body: Vec<u8> = (1..=25).collect();
+        let template = ProductDefinitionTemplate::read_any(0, &mut &body[..]).unwrap();
+        let (_, decoded) = json_round_trip(&template);
+        let mut written = Vec::new();
+        decoded.write(&mut written).unwrap();
+        assert_eq!(written, body);
+
+        let body = [0x42, 0x28, 0, 0, 0x80, 1, 0, 2, 12, 0];
+        let template = DataRepresentationTemplate::read_any(0, &mut &body[..]).unwrap();
+        let (json, decoded) = json_round_trip(&template);
+        assert!(json.contains(r#""reference_value":42.0"#), "{json}");
+        let mut written = Vec::new();
+        decoded.write(&mut written).unwrap();
+        assert_eq!(written, body);
     }
 }
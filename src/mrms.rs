@@ -0,0 +1,140 @@
+//! NOAA MRMS (Multi-Radar Multi-Sensor) products (feature `ncep`)
+//!
+//! MRMS products use the local discipline 209 from the originating centre 161 (NOAA OAR),
+//! whose parameters are named by [`crate::parameter::Parameter::for_product_of_centre`]. The
+//! distributed files are gzip-compressed and need to be decompressed before reading. Their
+//! data is PNG packed (Template 5.41), which [`crate::field::Field::values`] decodes.
+
+use crate::field::DecodedField;
+use crate::templates::CENTRE_NOAA_OAR;
+
+/// Local discipline of MRMS products
+pub const DISCIPLINE_MRMS: u8 = 209;
+
+/// Value of points without data in MRMS products
+pub const MISSING: f32 = -999.0;
+
+/// Value of points outside the radar coverage in MRMS products
+pub const NO_COVERAGE: f32 = -99.0;
+
+/// Whether `field` is an MRMS product
+pub fn is_mrms(field: &DecodedField) -> bool {
+    field.centre == CENTRE_NOAA_OAR && field.discipline == DISCIPLINE_MRMS
+}
+
+/// Replace the [`MISSING`] and [`NO_COVERAGE`] values of an MRMS product with NAN, and
+/// return the number of points replaced
+///
+/// Fields of other products are left as they are.
+pub fn mask_missing(field: &mut DecodedField) -> usize {
+    if !is_mrms(field) {
+        return 0;
+    }
+    let mut masked = 0;
+    for value in field.values.iter_mut() {
+        if *value == MISSING || *value == NO_COVERAGE {
+            *value = f32::NAN;
+            masked += 1;
+        }
+    }
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::DecodedFields;
+    use crate::parameter::Parameter;
+    use crate::templates::png;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// 1-hour radar QPE on a 2 x 2 grid, PNG packed from the reference value -999 mm
+    fn qpe_message(centre: u16, samples: &[u32]) -> Vec<u8> {
+        let mut identification = centre.to_be_bytes().to_vec();
+        identification.extend([0, 0, 0, 0, 1, 7, 232, 7, 1, 12, 0, 0, 0, 1]);
+        let mut body = section(1, &identification);
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 55_000_000, 230_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [54_990_000u32, 230_010_000, 10_000, 10_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0);
+        body.extend(section(3, &grid));
+        body.extend(section(
+            4,
+            &[
+                0, 0, 0, 0, 6, 2, 2, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 255, 255, 255,
+                255, 255, 255,
+            ],
+        ));
+        let mut representation = vec![0, 0, 0, 4, 0, 41];
+        representation.extend((-999f32).to_be_bytes());
+        representation.extend([0, 0, 0, 0, 16, 0]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[255]));
+        body.extend(section(7, &png::encode(samples, 2, 2, 16)));
+        body.extend(b"7777");
+        let mut bytes = vec![b'G', b'R', b'I', b'B', 0, 0, DISCIPLINE_MRMS, 2];
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    #[test]
+    fn parameters() {
+        let centre = CENTRE_NOAA_OAR;
+        for (category, number, abbreviation) in [
+            (2, 0, "NLDN_CG_001min_AvgDensity"),
+            (3, 28, "MESH"),
+            (6, 0, "PrecipFlag"),
+            (6, 2, "RadarOnly_QPE_01H"),
+            (6, 8, "RadarOnly_QPE_72H"),
+            (9, 0, "MergedReflectivityQC"),
+            (10, 0, "MergedReflectivityQCComposite"),
+        ] {
+            let parameter =
+                Parameter::lookup_for_centre(centre, DISCIPLINE_MRMS, category, number).unwrap();
+            assert_eq!(parameter.abbreviation, abbreviation);
+        }
+        assert!(Parameter::lookup_for_centre(centre, DISCIPLINE_MRMS, 6, 200).is_none());
+        // the MRMS table is local to NOAA OAR
+        assert!(Parameter::lookup_for_centre(7, DISCIPLINE_MRMS, 3, 28).is_none());
+    }
+
+    #[test]
+    fn missing_values() {
+        let samples = [0, 900, 1004, 1011];
+        let bytes = qpe_message(CENTRE_NOAA_OAR, &samples);
+        let mut field = DecodedFields::new(bytes.as_slice())
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(is_mrms(&field));
+        assert_eq!(field.parameter().unwrap().unit, "mm");
+        assert_eq!(field.values, [MISSING, NO_COVERAGE, 5.0, 12.0]);
+        assert_eq!(mask_missing(&mut field), 2);
+        assert!(field.values[0].is_nan() && field.values[1].is_nan());
+        assert_eq!(field.values[2..], [5.0, 12.0]);
+
+        // fields of other centres are not MRMS products, whatever their discipline
+        let bytes = qpe_message(7, &samples);
+        let mut field = DecodedFields::new(bytes.as_slice())
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(!is_mrms(&field));
+        assert_eq!(mask_missing(&mut field), 0);
+        assert_eq!(field.values[0], MISSING);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::templates::*;
+use crate::{Error, Result};
+
+/// How physical values are packed into Sections 5 and 7
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Packing {
+    pub method: PackingMethod,
+    /// Values are multiplied by 10^D before packing
+    pub decimal_scale_factor: i16,
+    /// Packed values are in units of 2^E
+    pub binary_scale_factor: i16,
+}
+
+/// Data representation template used by [`Packing`]
+///
+/// JPEG 2000 packing (Template 5.40) cannot be written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackingMethod {
+    /// Template 5.0
+    Simple,
+    /// Template 5.2
+    Complex,
+    /// Template 5.3 with spatial differencing of order 1 or 2
+    SpatialDifferencing { order: u8 },
+    /// Template 5.41 with rows of `width` values
+    #[cfg(feature = "png")]
+    Png { width: u32 },
+    /// Template 5.42 (CCSDS)
+    #[cfg(feature = "ccsds")]
+    Ccsds,
+}
+
+impl Packing {
+    pub fn new(method: PackingMethod, decimal_scale_factor: i16) -> Self {
+        Self {
+            method,
+            decimal_scale_factor,
+            binary_scale_factor: 0,
+        }
+    }
+
+    /// Pack `values` and return the Section 5 template and the Section 7 body
+    ///
+    /// NAN is only allowed with complex packing, which writes it as a missing value; with
+    /// other methods, leave missing points out with a bit-map.
+    pub fn encode(&self, values: &[f32]) -> Result<(DataRepresentationTemplate, Vec<u8>)> {
+        let complex = matches!(
+            self.method,
+            PackingMethod::Complex | PackingMethod::SpatialDifferencing { .. }
+        );
+        if !complex && values.iter().any(|v| v.is_nan()) {
+            return Err(Error::InvalidData(
+                "missing values must be left out with a bit-map".to_string(),
+            ));
+        }
+        let (template_0, packed) = DataRepresentationTemplate5_0::pack(
+            values,
+            self.decimal_scale_factor,
+            self.binary_scale_factor,
+        )?;
+        let mut data = Vec::new();
+        let template = match self.method {
+            PackingMethod::Simple => {
+                write_data_7_0(&mut data, &packed, &template_0)?;
+                DataRepresentationTemplate::Template5_0(template_0)
+            }
+            PackingMethod::Complex => DataRepresentationTemplate::Template5_2(write_data_7_2(
+                &mut data,
+                &packed,
+                &template_0,
+            )?),
+            PackingMethod::SpatialDifferencing { order } => {
+                DataRepresentationTemplate::Template5_3(write_data_7_3(
+                    &mut data,
+                    &packed,
+                    &template_0,
+                    order,
+                )?)
+            }
+            #[cfg(feature = "png")]
+            PackingMethod::Png { width } => DataRepresentationTemplate::Template5_41(
+                write_data_7_41(&mut data, &packed, &template_0, width)?,
+            ),
+            #[cfg(feature = "ccsds")]
+            PackingMethod::Ccsds => DataRepresentationTemplate::Template5_42(write_data_7_42(
+                &mut data,
+                &packed,
+                &template_0,
+            )?),
+        };
+        Ok((template, data))
+    }
+}
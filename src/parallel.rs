@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::Result;
+use crate::field::Message;
+use crate::split::{MessageRanges, parse_message};
+
+/// Parse every message of a file in memory on all available cores, in file order
+pub fn read_messages_parallel(data: &[u8]) -> Result<Vec<Message>> {
+    map_messages_parallel(data, Ok)
+}
+
+/// Parse every message of a file in memory and apply `f` to it on all available cores
+///
+/// The messages are first located using Section 0 `total_length` only, then parsed and
+/// passed to `f` by worker threads. Results are returned in file order. With a single core, or
+/// on `wasm32` where threads cannot be spawned, the messages are parsed on the calling thread.
+pub fn map_messages_parallel<T, F>(data: &[u8], f: F) -> Result<Vec<T>>
+where
+    T: Send,
+    F: Fn(Message) -> Result<T> + Sync,
+{
+    let ranges = MessageRanges::new(data).collect::<Result<Vec<_>>>()?;
+    map_parallel(ranges.len(), |i| {
+        let range = &ranges[i];
+        let bytes = &data[range.offset as usize..range.end() as usize];
+        parse_message(bytes, i as u64, range.offset).and_then(&f)
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Call `f` for `0..len` on all available cores and return the results in order
+pub(crate) fn map_parallel<T, F>(len: usize, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let threads = match cfg!(target_arch = "wasm32") {
+        true => 1,
+        false => std::thread::available_parallelism().map_or(1, |n| n.get()),
+    }
+    .min(len);
+    if threads <= 1 {
+        return (0..len).map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+
+    let mut results = std::thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        if i >= len {
+                            break;
+                        }
+                        results.push((i, f(i)));
+                    }
+                    results
+                })
+            })
+            .collect::<Vec<_>>();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker thread panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
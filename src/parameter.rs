@@ -0,0 +1,1163 @@
+use crate::templates::ProductDefinitionTemplate;
+
+/// Parameter of Code table 4.2, identified by discipline, category and number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parameter {
+    pub discipline: u8,
+    pub category: u8,
+    pub number: u8,
+    /// Abbreviation used by NCEP and wgrib2 (e.g. "TMP")
+    pub abbreviation: &'static str,
+    pub name: &'static str,
+    pub unit: &'static str,
+}
+
+impl Parameter {
+    /// Look up a parameter of the WMO tables (numbers 192-254 are local and not included)
+    pub fn lookup(discipline: u8, category: u8, number: u8) -> Option<&'static Parameter> {
+        PARAMETERS
+            .binary_search_by_key(&(discipline, category, number), |p| {
+                (p.discipline, p.category, p.number)
+            })
+            .ok()
+            .map(|i| &PARAMETERS[i])
+    }
+
+    /// Look up the parameter of a product definition template
+    pub fn for_product(
+        discipline: u8,
+        template: &ProductDefinitionTemplate,
+    ) -> Option<&'static Parameter> {
+        let (category, number) = template.parameter()?;
+        Self::lookup(discipline, category, number)
+    }
+
+    /// Look up a parameter, including the local entries (192-254) of the originating `centre`
+    ///
+    /// Local tables are included for the centres enabled by the `jma` and `ncep` features, the
+    /// latter also covering the MRMS products of NOAA OAR.
+    pub fn lookup_for_centre(
+        centre: u16,
+        discipline: u8,
+        category: u8,
+        number: u8,
+    ) -> Option<&'static Parameter> {
+        Self::lookup(discipline, category, number).or_else(|| {
+            let local = local_parameters(centre);
+            local
+                .binary_search_by_key(&(discipline, category, number), |p| {
+                    (p.discipline, p.category, p.number)
+                })
+                .ok()
+                .map(|i| &local[i])
+        })
+    }
+
+    /// Look up the parameter of a product definition template from the originating `centre`
+    pub fn for_product_of_centre(
+        centre: u16,
+        discipline: u8,
+        template: &ProductDefinitionTemplate,
+    ) -> Option<&'static Parameter> {
+        let (category, number) = template.parameter()?;
+        Self::lookup_for_centre(centre, discipline, category, number)
+    }
+
+    /// Find a parameter by abbreviation (e.g. "UGRD")
+    pub fn from_abbreviation(abbreviation: &str) -> Option<&'static Parameter> {
+        PARAMETERS.iter().find(|p| p.abbreviation == abbreviation)
+    }
+}
+
+const fn p(
+    discipline: u8,
+    category: u8,
+    number: u8,
+    abbreviation: &'static str,
+    name: &'static str,
+    unit: &'static str,
+) -> Parameter {
+    Parameter {
+        discipline,
+        category,
+        number,
+        abbreviation,
+        name,
+        unit,
+    }
+}
+
+/// Local entries of Code table 4.2 of `centre`, sorted by (discipline, category, number)
+fn local_parameters(centre: u16) -> &'static [Parameter] {
+    match centre {
+        #[cfg(feature = "jma")]
+        crate::templates::CENTRE_JMA => JMA_PARAMETERS,
+        #[cfg(feature = "ncep")]
+        crate::templates::CENTRE_NCEP => NCEP_PARAMETERS,
+        #[cfg(feature = "ncep")]
+        crate::templates::CENTRE_NOAA_OAR => MRMS_PARAMETERS,
+        _ => &[],
+    }
+}
+
+/// Local entries of JMA, used by its radar precipitation products
+#[cfg(feature = "jma")]
+static JMA_PARAMETERS: &[Parameter] = &[
+    p(
+        0,
+        1,
+        200,
+        "JPRLV",
+        "Precipitation amount (level value)",
+        "kg m-2",
+    ),
+    p(
+        0,
+        1,
+        201,
+        "JPRILV",
+        "Precipitation intensity (level value)",
+        "kg m-2 h-1",
+    ),
+];
+
+/// Local discipline 209 of MRMS (Multi-Radar Multi-Sensor), named as in the MRMS tables
+///
+/// Source: MRMS GRIB2 tables of NSSL (version 12),
+/// <https://www.nssl.noaa.gov/projects/mrms/operational/tables.php>
+#[cfg(feature = "ncep")]
+static MRMS_PARAMETERS: &[Parameter] = &[
+    p(
+        209,
+        2,
+        0,
+        "NLDN_CG_001min_AvgDensity",
+        "CG average lightning density 1-min",
+        "flashes km-2 min-1",
+    ),
+    p(
+        209,
+        2,
+        1,
+        "NLDN_CG_005min_AvgDensity",
+        "CG average lightning density 5-min",
+        "flashes km-2 min-1",
+    ),
+    p(
+        209,
+        2,
+        2,
+        "NLDN_CG_015min_AvgDensity",
+        "CG average lightning density 15-min",
+        "flashes km-2 min-1",
+    ),
+    p(
+        209,
+        2,
+        3,
+        "NLDN_CG_030min_AvgDensity",
+        "CG average lightning density 30-min",
+        "flashes km-2 min-1",
+    ),
+    p(209, 3, 28, "MESH", "Maximum estimated size of hail", "mm"),
+    p(
+        209,
+        6,
+        0,
+        "PrecipFlag",
+        "Surface precipitation type",
+        "flag",
+    ),
+    p(
+        209,
+        6,
+        1,
+        "PrecipRate",
+        "Radar precipitation rate",
+        "mm hr-1",
+    ),
+    p(
+        209,
+        6,
+        2,
+        "RadarOnly_QPE_01H",
+        "Radar precipitation accumulation 1-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        3,
+        "RadarOnly_QPE_03H",
+        "Radar precipitation accumulation 3-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        4,
+        "RadarOnly_QPE_06H",
+        "Radar precipitation accumulation 6-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        5,
+        "RadarOnly_QPE_12H",
+        "Radar precipitation accumulation 12-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        6,
+        "RadarOnly_QPE_24H",
+        "Radar precipitation accumulation 24-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        7,
+        "RadarOnly_QPE_48H",
+        "Radar precipitation accumulation 48-hour",
+        "mm",
+    ),
+    p(
+        209,
+        6,
+        8,
+        "RadarOnly_QPE_72H",
+        "Radar precipitation accumulation 72-hour",
+        "mm",
+    ),
+    p(
+        209,
+        9,
+        0,
+        "MergedReflectivityQC",
+        "3D reflectivity mosaic",
+        "dBZ",
+    ),
+    p(
+        209,
+        10,
+        0,
+        "MergedReflectivityQCComposite",
+        "Composite reflectivity mosaic",
+        "dBZ",
+    ),
+    p(
+        209,
+        10,
+        1,
+        "HeightCompositeReflectivity",
+        "Height of composite reflectivity",
+        "m",
+    ),
+    p(
+        209,
+        10,
+        2,
+        "LowLevelCompositeReflectivity",
+        "Low-level composite reflectivity",
+        "dBZ",
+    ),
+    p(
+        209,
+        10,
+        3,
+        "HeightLowLevelCompositeReflectivity",
+        "Height of low-level composite reflectivity",
+        "m",
+    ),
+];
+
+/// Local entries of NCEP, used by GFS, HRRR and other NCEP models
+#[cfg(feature = "ncep")]
+static NCEP_PARAMETERS: &[Parameter] = &[
+    p(0, 0, 192, "SNOHF", "Snow phase change heat flux", "W m-2"),
+    p(
+        0,
+        0,
+        193,
+        "TTRAD",
+        "Temperature tendency by all radiation",
+        "K s-1",
+    ),
+    p(0, 0, 194, "REV", "Relative error variance", "-"),
+    p(
+        0,
+        0,
+        195,
+        "LRGHR",
+        "Large scale condensate heating rate",
+        "K s-1",
+    ),
+    p(0, 0, 196, "CNVHR", "Deep convective heating rate", "K s-1"),
+    p(0, 1, 192, "CRAIN", "Categorical rain", "Code table 4.222"),
+    p(
+        0,
+        1,
+        193,
+        "CFRZR",
+        "Categorical freezing rain",
+        "Code table 4.222",
+    ),
+    p(
+        0,
+        1,
+        194,
+        "CICEP",
+        "Categorical ice pellets",
+        "Code table 4.222",
+    ),
+    p(0, 1, 195, "CSNOW", "Categorical snow", "Code table 4.222"),
+    p(
+        0,
+        1,
+        196,
+        "CPRAT",
+        "Convective precipitation rate",
+        "kg m-2 s-1",
+    ),
+    p(
+        0,
+        1,
+        197,
+        "MCONV",
+        "Horizontal moisture divergence",
+        "kg kg-1 s-1",
+    ),
+    p(0, 1, 198, "MINRH", "Minimum relative humidity", "%"),
+    p(0, 1, 199, "PEVAP", "Potential evaporation", "kg m-2"),
+    p(0, 1, 200, "PEVPR", "Potential evaporation rate", "W m-2"),
+    p(0, 1, 201, "SNOWC", "Snow cover", "%"),
+    p(0, 1, 225, "FRZR", "Freezing rain", "kg m-2"),
+    p(
+        0,
+        1,
+        242,
+        "RHPW",
+        "Relative humidity with respect to precipitable water",
+        "%",
+    ),
+    p(0, 2, 192, "VWSH", "Vertical speed shear", "s-1"),
+    p(0, 2, 193, "MFLX", "Horizontal momentum flux", "N m-2"),
+    p(0, 2, 194, "USTM", "U-component storm motion", "m s-1"),
+    p(0, 2, 195, "VSTM", "V-component storm motion", "m s-1"),
+    p(0, 2, 196, "CD", "Drag coefficient", "Numeric"),
+    p(0, 2, 197, "FRICV", "Frictional velocity", "m s-1"),
+    p(
+        0,
+        2,
+        220,
+        "MAXUVV",
+        "Hourly maximum of upward vertical velocity",
+        "m s-1",
+    ),
+    p(
+        0,
+        2,
+        221,
+        "MAXDVV",
+        "Hourly maximum of downward vertical velocity",
+        "m s-1",
+    ),
+    p(
+        0,
+        2,
+        222,
+        "MAXUW",
+        "U component of hourly maximum 10m wind speed",
+        "m s-1",
+    ),
+    p(
+        0,
+        2,
+        223,
+        "MAXVW",
+        "V component of hourly maximum 10m wind speed",
+        "m s-1",
+    ),
+    p(0, 3, 192, "MSLET", "MSLP (Eta model reduction)", "Pa"),
+    p(0, 3, 193, "5WAVH", "5-wave geopotential height", "gpm"),
+    p(
+        0,
+        3,
+        194,
+        "U-GWD",
+        "Zonal flux of gravity wave stress",
+        "N m-2",
+    ),
+    p(
+        0,
+        3,
+        195,
+        "V-GWD",
+        "Meridional flux of gravity wave stress",
+        "N m-2",
+    ),
+    p(0, 3, 196, "HPBL", "Planetary boundary layer height", "m"),
+    p(
+        0,
+        3,
+        197,
+        "5WAVA",
+        "5-wave geopotential height anomaly",
+        "gpm",
+    ),
+    p(0, 3, 198, "MSLMA", "MSLP (MAPS system reduction)", "Pa"),
+    p(
+        0,
+        3,
+        200,
+        "PLPL",
+        "Pressure of level from which parcel was lifted",
+        "Pa",
+    ),
+    p(
+        0,
+        4,
+        192,
+        "DSWRF",
+        "Downward short-wave radiation flux",
+        "W m-2",
+    ),
+    p(
+        0,
+        4,
+        193,
+        "USWRF",
+        "Upward short-wave radiation flux",
+        "W m-2",
+    ),
+    p(
+        0,
+        5,
+        192,
+        "DLWRF",
+        "Downward long-wave radiation flux",
+        "W m-2",
+    ),
+    p(
+        0,
+        5,
+        193,
+        "ULWRF",
+        "Upward long-wave radiation flux",
+        "W m-2",
+    ),
+    p(0, 6, 192, "CDLYR", "Non-convective cloud cover", "%"),
+    p(0, 6, 193, "CWORK", "Cloud work function", "J kg-1"),
+    p(0, 6, 201, "SUNSD", "Sunshine duration", "s"),
+    p(0, 7, 192, "LFTX", "Surface lifted index", "K"),
+    p(0, 7, 193, "4LFTX", "Best (4 layer) lifted index", "K"),
+    p(0, 7, 194, "RI", "Richardson number", "Numeric"),
+    p(0, 7, 195, "CWDI", "Convective weather detection index", "-"),
+    p(0, 7, 196, "UVI", "Ultra violet index", "W m-2"),
+    p(0, 7, 197, "UPHL", "Updraft helicity", "m2 s-2"),
+    p(0, 7, 198, "LAI", "Leaf area index", "Numeric"),
+    p(
+        0,
+        7,
+        199,
+        "MXUPHL",
+        "Hourly maximum of updraft helicity",
+        "m2 s-2",
+    ),
+    p(0, 14, 192, "O3MR", "Ozone mixing ratio", "kg kg-1"),
+    p(0, 14, 193, "OZCON", "Ozone concentration", "ppb"),
+    p(
+        0,
+        16,
+        192,
+        "REFZR",
+        "Equivalent radar reflectivity factor for rain",
+        "mm6 m-3",
+    ),
+    p(
+        0,
+        16,
+        193,
+        "REFZI",
+        "Equivalent radar reflectivity factor for snow",
+        "mm6 m-3",
+    ),
+    p(
+        0,
+        16,
+        194,
+        "REFZC",
+        "Equivalent radar reflectivity factor for parameterized convection",
+        "mm6 m-3",
+    ),
+    p(0, 16, 195, "REFD", "Reflectivity", "dB"),
+    p(0, 16, 196, "REFC", "Composite reflectivity", "dB"),
+    p(0, 16, 197, "RETOP", "Echo top", "m"),
+    p(
+        0,
+        16,
+        198,
+        "MAXREF",
+        "Hourly maximum of simulated reflectivity at 1 km AGL",
+        "dB",
+    ),
+    p(0, 17, 192, "LTNG", "Lightning", "Numeric"),
+    p(0, 19, 192, "MXSALB", "Maximum snow albedo", "%"),
+    p(0, 19, 193, "SNFALB", "Snow-free albedo", "%"),
+    p(0, 191, 192, "NLAT", "Latitude (-90 to 90)", "deg"),
+    p(0, 191, 193, "ELON", "East longitude (0 to 360)", "deg"),
+    p(
+        0,
+        191,
+        195,
+        "MLYNO",
+        "Model layer number (from bottom up)",
+        "Numeric",
+    ),
+    p(
+        2,
+        0,
+        192,
+        "SOILW",
+        "Volumetric soil moisture content",
+        "Fraction",
+    ),
+    p(2, 0, 193, "GFLUX", "Ground heat flux", "W m-2"),
+    p(2, 0, 194, "MSTAV", "Moisture availability", "%"),
+    p(2, 0, 195, "SFEXC", "Exchange coefficient", "kg m-2 s-1"),
+    p(2, 0, 196, "CNWAT", "Plant canopy surface water", "kg m-2"),
+    p(2, 0, 197, "BMIXL", "Blackadar's mixing length scale", "m"),
+    p(2, 0, 198, "VGTYP", "Vegetation type", "Integer (0-13)"),
+    p(10, 3, 192, "SURGE", "Hurricane storm surge", "m"),
+    p(10, 3, 193, "ETSRG", "Extra tropical storm surge", "m"),
+];
+
+/// Commonly used entries of Code table 4.2, sorted by (discipline, category, number)
+static PARAMETERS: &[Parameter] = &[
+    p(0, 0, 0, "TMP", "Temperature", "K"),
+    p(0, 0, 1, "VTMP", "Virtual temperature", "K"),
+    p(0, 0, 2, "POT", "Potential temperature", "K"),
+    p(
+        0,
+        0,
+        3,
+        "EPOT",
+        "Pseudo-adiabatic potential temperature or equivalent potential temperature",
+        "K",
+    ),
+    p(0, 0, 4, "TMAX", "Maximum temperature", "K"),
+    p(0, 0, 5, "TMIN", "Minimum temperature", "K"),
+    p(0, 0, 6, "DPT", "Dew point temperature", "K"),
+    p(0, 0, 7, "DEPR", "Dew point depression (or deficit)", "K"),
+    p(0, 0, 8, "LAPR", "Lapse rate", "K m-1"),
+    p(0, 0, 9, "TMPA", "Temperature anomaly", "K"),
+    p(0, 0, 10, "LHTFL", "Latent heat net flux", "W m-2"),
+    p(0, 0, 11, "SHTFL", "Sensible heat net flux", "W m-2"),
+    p(0, 0, 12, "HEATX", "Heat index", "K"),
+    p(0, 0, 13, "WCF", "Wind chill factor", "K"),
+    p(0, 0, 14, "MINDPD", "Minimum dew point depression", "K"),
+    p(0, 0, 15, "VPTMP", "Virtual potential temperature", "K"),
+    p(0, 0, 16, "SNOHF", "Snow phase change heat flux", "W m-2"),
+    p(0, 0, 17, "SKINT", "Skin temperature", "K"),
+    p(0, 0, 18, "SNOT", "Snow temperature (top of snow)", "K"),
+    p(0, 0, 21, "APTMP", "Apparent temperature", "K"),
+    p(0, 1, 0, "SPFH", "Specific humidity", "kg kg-1"),
+    p(0, 1, 1, "RH", "Relative humidity", "%"),
+    p(0, 1, 2, "MIXR", "Humidity mixing ratio", "kg kg-1"),
+    p(0, 1, 3, "PWAT", "Precipitable water", "kg m-2"),
+    p(0, 1, 4, "VAPP", "Vapour pressure", "Pa"),
+    p(0, 1, 5, "SATD", "Saturation deficit", "Pa"),
+    p(0, 1, 6, "EVP", "Evaporation", "kg m-2"),
+    p(0, 1, 7, "PRATE", "Precipitation rate", "kg m-2 s-1"),
+    p(0, 1, 8, "APCP", "Total precipitation", "kg m-2"),
+    p(
+        0,
+        1,
+        9,
+        "NCPCP",
+        "Large-scale precipitation (non-convective)",
+        "kg m-2",
+    ),
+    p(0, 1, 10, "ACPCP", "Convective precipitation", "kg m-2"),
+    p(0, 1, 11, "SNOD", "Snow depth", "m"),
+    p(
+        0,
+        1,
+        12,
+        "SRWEQ",
+        "Snowfall rate water equivalent",
+        "kg m-2 s-1",
+    ),
+    p(
+        0,
+        1,
+        13,
+        "WEASD",
+        "Water equivalent of accumulated snow depth",
+        "kg m-2",
+    ),
+    p(0, 1, 14, "SNOC", "Convective snow", "kg m-2"),
+    p(0, 1, 15, "SNOL", "Large-scale snow", "kg m-2"),
+    p(0, 1, 16, "SNOM", "Snow melt", "kg m-2"),
+    p(0, 1, 17, "SNOAG", "Snow age", "d"),
+    p(0, 1, 18, "ABSH", "Absolute humidity", "kg m-3"),
+    p(0, 1, 19, "PTYPE", "Precipitation type", "Code table 4.201"),
+    p(0, 1, 20, "ILIQW", "Integrated liquid water", "kg m-2"),
+    p(0, 1, 21, "TCOND", "Condensate", "kg kg-1"),
+    p(0, 1, 22, "CLMR", "Cloud mixing ratio", "kg kg-1"),
+    p(0, 1, 23, "ICMR", "Ice water mixing ratio", "kg kg-1"),
+    p(0, 1, 24, "RWMR", "Rain mixing ratio", "kg kg-1"),
+    p(0, 1, 25, "SNMR", "Snow mixing ratio", "kg kg-1"),
+    p(
+        0,
+        1,
+        26,
+        "MCONV",
+        "Horizontal moisture convergence",
+        "kg kg-1 s-1",
+    ),
+    p(0, 1, 27, "MAXRH", "Maximum relative humidity", "%"),
+    p(0, 1, 28, "MAXAH", "Maximum absolute humidity", "kg m-3"),
+    p(0, 1, 29, "ASNOW", "Total snowfall", "m"),
+    p(
+        0,
+        1,
+        30,
+        "PWCAT",
+        "Precipitable water category",
+        "Code table 4.202",
+    ),
+    p(0, 1, 31, "HAIL", "Hail", "m"),
+    p(0, 1, 32, "GRLE", "Graupel (snow pellets)", "kg kg-1"),
+    p(
+        0,
+        1,
+        37,
+        "CPRAT",
+        "Convective precipitation rate",
+        "kg m-2 s-1",
+    ),
+    p(0, 1, 39, "CPOFP", "Percent frozen precipitation", "%"),
+    p(0, 1, 52, "TPRATE", "Total precipitation rate", "kg m-2 s-1"),
+    p(
+        0,
+        1,
+        53,
+        "TSRWE",
+        "Total snowfall rate water equivalent",
+        "kg m-2 s-1",
+    ),
+    p(0, 1, 60, "SDWE", "Snow depth water equivalent", "kg m-2"),
+    p(
+        0,
+        1,
+        64,
+        "TCIWV",
+        "Total column integrated water vapour",
+        "kg m-2",
+    ),
+    p(0, 1, 65, "RPRATE", "Rain precipitation rate", "kg m-2 s-1"),
+    p(0, 1, 66, "SPRATE", "Snow precipitation rate", "kg m-2 s-1"),
+    p(
+        0,
+        1,
+        69,
+        "TCOLW",
+        "Total column integrated cloud water",
+        "kg m-2",
+    ),
+    p(
+        0,
+        1,
+        70,
+        "TCOLI",
+        "Total column integrated cloud ice",
+        "kg m-2",
+    ),
+    p(
+        0,
+        2,
+        0,
+        "WDIR",
+        "Wind direction (from which blowing)",
+        "degree true",
+    ),
+    p(0, 2, 1, "WIND", "Wind speed", "m s-1"),
+    p(0, 2, 2, "UGRD", "u-component of wind", "m s-1"),
+    p(0, 2, 3, "VGRD", "v-component of wind", "m s-1"),
+    p(0, 2, 4, "STRM", "Stream function", "m2 s-1"),
+    p(0, 2, 5, "VPOT", "Velocity potential", "m2 s-1"),
+    p(0, 2, 6, "MNTSF", "Montgomery stream function", "m2 s-2"),
+    p(
+        0,
+        2,
+        7,
+        "SGCVV",
+        "Sigma coordinate vertical velocity",
+        "s-1",
+    ),
+    p(0, 2, 8, "VVEL", "Vertical velocity (pressure)", "Pa s-1"),
+    p(0, 2, 9, "DZDT", "Vertical velocity (geometric)", "m s-1"),
+    p(0, 2, 10, "ABSV", "Absolute vorticity", "s-1"),
+    p(0, 2, 11, "ABSD", "Absolute divergence", "s-1"),
+    p(0, 2, 12, "RELV", "Relative vorticity", "s-1"),
+    p(0, 2, 13, "RELD", "Relative divergence", "s-1"),
+    p(0, 2, 14, "PVORT", "Potential vorticity", "K m2 kg-1 s-1"),
+    p(0, 2, 15, "VUCSH", "Vertical u-component shear", "s-1"),
+    p(0, 2, 16, "VVCSH", "Vertical v-component shear", "s-1"),
+    p(0, 2, 17, "UFLX", "Momentum flux, u-component", "N m-2"),
+    p(0, 2, 18, "VFLX", "Momentum flux, v-component", "N m-2"),
+    p(0, 2, 19, "WMIXE", "Wind mixing energy", "J"),
+    p(0, 2, 20, "BLYDP", "Boundary layer dissipation", "W m-2"),
+    p(0, 2, 21, "MAXGUST", "Maximum wind speed", "m s-1"),
+    p(0, 2, 22, "GUST", "Wind speed (gust)", "m s-1"),
+    p(0, 2, 23, "UGUST", "u-component of wind (gust)", "m s-1"),
+    p(0, 2, 24, "VGUST", "v-component of wind (gust)", "m s-1"),
+    p(0, 2, 25, "VWSH", "Vertical speed shear", "s-1"),
+    p(0, 2, 26, "MFLX", "Horizontal momentum flux", "N m-2"),
+    p(0, 2, 27, "USTM", "u-component storm motion", "m s-1"),
+    p(0, 2, 28, "VSTM", "v-component storm motion", "m s-1"),
+    p(0, 2, 29, "CD", "Drag coefficient", "Numeric"),
+    p(0, 2, 30, "FRICV", "Frictional velocity", "m s-1"),
+    p(0, 3, 0, "PRES", "Pressure", "Pa"),
+    p(0, 3, 1, "PRMSL", "Pressure reduced to MSL", "Pa"),
+    p(0, 3, 2, "PTEND", "Pressure tendency", "Pa s-1"),
+    p(
+        0,
+        3,
+        3,
+        "ICAHT",
+        "ICAO Standard Atmosphere reference height",
+        "m",
+    ),
+    p(0, 3, 4, "GP", "Geopotential", "m2 s-2"),
+    p(0, 3, 5, "HGT", "Geopotential height", "gpm"),
+    p(0, 3, 6, "DIST", "Geometric height", "m"),
+    p(0, 3, 7, "HSTDV", "Standard deviation of height", "m"),
+    p(0, 3, 8, "PRESA", "Pressure anomaly", "Pa"),
+    p(0, 3, 9, "GPA", "Geopotential height anomaly", "gpm"),
+    p(0, 3, 10, "DEN", "Density", "kg m-3"),
+    p(0, 3, 11, "ALTS", "Altimeter setting", "Pa"),
+    p(0, 3, 12, "THICK", "Thickness", "m"),
+    p(0, 3, 13, "PRESALT", "Pressure altitude", "m"),
+    p(0, 3, 14, "DENALT", "Density altitude", "m"),
+    p(0, 3, 15, "5WAVH", "5-wave geopotential height", "gpm"),
+    p(
+        0,
+        3,
+        16,
+        "U-GWD",
+        "Zonal flux of gravity wave stress",
+        "N m-2",
+    ),
+    p(
+        0,
+        3,
+        17,
+        "V-GWD",
+        "Meridional flux of gravity wave stress",
+        "N m-2",
+    ),
+    p(0, 3, 18, "HPBL", "Planetary boundary layer height", "m"),
+    p(
+        0,
+        3,
+        19,
+        "5WAVA",
+        "5-wave geopotential height anomaly",
+        "gpm",
+    ),
+    p(
+        0,
+        3,
+        20,
+        "SDSGSO",
+        "Standard deviation of sub-grid scale orography",
+        "m",
+    ),
+    p(
+        0,
+        4,
+        0,
+        "NSWRS",
+        "Net short-wave radiation flux (surface)",
+        "W m-2",
+    ),
+    p(
+        0,
+        4,
+        1,
+        "NSWRT",
+        "Net short-wave radiation flux (top of atmosphere)",
+        "W m-2",
+    ),
+    p(0, 4, 2, "SWAVR", "Short-wave radiation flux", "W m-2"),
+    p(0, 4, 3, "GRAD", "Global radiation flux", "W m-2"),
+    p(0, 4, 4, "BRTMP", "Brightness temperature", "K"),
+    p(
+        0,
+        4,
+        5,
+        "LWRAD",
+        "Radiance (with respect to wave number)",
+        "W m-1 sr-1",
+    ),
+    p(
+        0,
+        4,
+        6,
+        "SWRAD",
+        "Radiance (with respect to wavelength)",
+        "W m-3 sr-1",
+    ),
+    p(
+        0,
+        4,
+        7,
+        "DSWRF",
+        "Downward short-wave radiation flux",
+        "W m-2",
+    ),
+    p(
+        0,
+        4,
+        8,
+        "USWRF",
+        "Upward short-wave radiation flux",
+        "W m-2",
+    ),
+    p(0, 4, 9, "NSWRF", "Net short-wave radiation flux", "W m-2"),
+    p(
+        0,
+        4,
+        10,
+        "PHOTAR",
+        "Photosynthetically active radiation",
+        "W m-2",
+    ),
+    p(
+        0,
+        4,
+        11,
+        "NSWRFCS",
+        "Net short-wave radiation flux, clear sky",
+        "W m-2",
+    ),
+    p(0, 4, 12, "DWUVR", "Downward UV radiation", "W m-2"),
+    p(
+        0,
+        5,
+        0,
+        "NLWRS",
+        "Net long-wave radiation flux (surface)",
+        "W m-2",
+    ),
+    p(
+        0,
+        5,
+        1,
+        "NLWRT",
+        "Net long-wave radiation flux (top of atmosphere)",
+        "W m-2",
+    ),
+    p(0, 5, 2, "LWAVR", "Long-wave radiation flux", "W m-2"),
+    p(
+        0,
+        5,
+        3,
+        "DLWRF",
+        "Downward long-wave radiation flux",
+        "W m-2",
+    ),
+    p(0, 5, 4, "ULWRF", "Upward long-wave radiation flux", "W m-2"),
+    p(0, 5, 5, "NLWRF", "Net long-wave radiation flux", "W m-2"),
+    p(
+        0,
+        5,
+        6,
+        "NLWRCS",
+        "Net long-wave radiation flux, clear sky",
+        "W m-2",
+    ),
+    p(0, 6, 0, "CICE", "Cloud ice", "kg m-2"),
+    p(0, 6, 1, "TCDC", "Total cloud cover", "%"),
+    p(0, 6, 2, "CDCON", "Convective cloud cover", "%"),
+    p(0, 6, 3, "LCDC", "Low cloud cover", "%"),
+    p(0, 6, 4, "MCDC", "Medium cloud cover", "%"),
+    p(0, 6, 5, "HCDC", "High cloud cover", "%"),
+    p(0, 6, 6, "CWAT", "Cloud water", "kg m-2"),
+    p(0, 6, 7, "CDCA", "Cloud amount", "%"),
+    p(0, 6, 8, "CDCT", "Cloud type", "Code table 4.203"),
+    p(0, 6, 9, "TMAXT", "Thunderstorm maximum tops", "m"),
+    p(
+        0,
+        6,
+        10,
+        "THUNC",
+        "Thunderstorm coverage",
+        "Code table 4.204",
+    ),
+    p(0, 6, 11, "CDCB", "Cloud base", "m"),
+    p(0, 6, 12, "CDCTOP", "Cloud top", "m"),
+    p(0, 6, 13, "CEIL", "Ceiling", "m"),
+    p(0, 6, 14, "CDLYR", "Non-convective cloud cover", "%"),
+    p(0, 6, 15, "CWORK", "Cloud work function", "J kg-1"),
+    p(
+        0,
+        6,
+        16,
+        "CUEFI",
+        "Convective cloud efficiency",
+        "Proportion",
+    ),
+    p(0, 7, 0, "PLI", "Parcel lifted index (to 500 hPa)", "K"),
+    p(0, 7, 1, "BLI", "Best lifted index (to 500 hPa)", "K"),
+    p(0, 7, 2, "KX", "K index", "K"),
+    p(0, 7, 3, "KOX", "KO index", "K"),
+    p(0, 7, 4, "TOTALX", "Total totals index", "K"),
+    p(0, 7, 5, "SX", "Sweat index", "Numeric"),
+    p(
+        0,
+        7,
+        6,
+        "CAPE",
+        "Convective available potential energy",
+        "J kg-1",
+    ),
+    p(0, 7, 7, "CIN", "Convective inhibition", "J kg-1"),
+    p(0, 7, 8, "HLCY", "Storm relative helicity", "m2 s-2"),
+    p(0, 7, 9, "EHLX", "Energy helicity index", "Numeric"),
+    p(0, 7, 10, "LFTX", "Surface lifted index", "K"),
+    p(0, 7, 11, "4LFTX", "Best (4-layer) lifted index", "K"),
+    p(0, 7, 12, "RI", "Richardson number", "Numeric"),
+    p(0, 14, 0, "TOZNE", "Total ozone", "DU"),
+    p(0, 14, 1, "O3MR", "Ozone mixing ratio", "kg kg-1"),
+    p(0, 14, 2, "TCIOZ", "Total column integrated ozone", "DU"),
+    p(0, 15, 0, "BSWID", "Base spectrum width", "m s-1"),
+    p(0, 15, 1, "BREF", "Base reflectivity", "dB"),
+    p(0, 15, 2, "BRVEL", "Base radial velocity", "m s-1"),
+    p(
+        0,
+        15,
+        3,
+        "VIL",
+        "Vertically integrated liquid water",
+        "kg m-2",
+    ),
+    p(0, 15, 4, "LMAXBR", "Layer maximum base reflectivity", "dB"),
+    p(0, 15, 5, "PREC", "Precipitation", "kg m-2"),
+    p(
+        0,
+        16,
+        0,
+        "REFZR",
+        "Equivalent radar reflectivity factor for rain",
+        "mm6 m-3",
+    ),
+    p(
+        0,
+        16,
+        1,
+        "REFZI",
+        "Equivalent radar reflectivity factor for snow",
+        "mm6 m-3",
+    ),
+    p(
+        0,
+        16,
+        2,
+        "REFZC",
+        "Equivalent radar reflectivity factor for parameterized convection",
+        "mm6 m-3",
+    ),
+    p(0, 16, 3, "RETOP", "Echo top", "m"),
+    p(0, 16, 4, "REFD", "Reflectivity", "dB"),
+    p(0, 16, 5, "REFC", "Composite reflectivity", "dB"),
+    p(0, 19, 0, "VIS", "Visibility", "m"),
+    p(0, 19, 1, "ALBDO", "Albedo", "%"),
+    p(0, 19, 2, "TSTM", "Thunderstorm probability", "%"),
+    p(0, 19, 3, "MIXHT", "Mixed layer depth", "m"),
+    p(0, 19, 4, "VOLASH", "Volcanic ash", "Code table 4.206"),
+    p(0, 19, 5, "ICIT", "Icing top", "m"),
+    p(0, 19, 6, "ICIB", "Icing base", "m"),
+    p(0, 19, 7, "ICI", "Icing", "Code table 4.207"),
+    p(0, 19, 8, "TURBT", "Turbulence top", "m"),
+    p(0, 19, 9, "TURBB", "Turbulence base", "m"),
+    p(0, 19, 10, "TURB", "Turbulence", "Code table 4.208"),
+    p(0, 19, 11, "TKE", "Turbulent kinetic energy", "J kg-1"),
+    p(
+        0,
+        19,
+        12,
+        "PBLREG",
+        "Planetary boundary layer regime",
+        "Code table 4.209",
+    ),
+    p(0, 19, 13, "CONTI", "Contrail intensity", "Code table 4.210"),
+    p(
+        0,
+        19,
+        14,
+        "CONTET",
+        "Contrail engine type",
+        "Code table 4.211",
+    ),
+    p(0, 19, 15, "CONTT", "Contrail top", "m"),
+    p(0, 19, 16, "CONTB", "Contrail base", "m"),
+    p(0, 19, 17, "MXSALB", "Maximum snow albedo", "%"),
+    p(0, 19, 18, "SNFALB", "Snow free albedo", "%"),
+    p(
+        0,
+        20,
+        0,
+        "MASSDEN",
+        "Mass density (concentration)",
+        "kg m-3",
+    ),
+    p(
+        0,
+        20,
+        1,
+        "COLMD",
+        "Column-integrated mass density",
+        "kg m-2",
+    ),
+    p(
+        0,
+        20,
+        2,
+        "MASSMR",
+        "Mass mixing ratio (mass fraction in air)",
+        "kg kg-1",
+    ),
+    p(1, 0, 0, "FFLDG", "Flash flood guidance", "kg m-2"),
+    p(1, 0, 1, "FFLDRO", "Flash flood runoff", "kg m-2"),
+    p(
+        1,
+        0,
+        2,
+        "RSSC",
+        "Remotely sensed snow cover",
+        "Code table 4.215",
+    ),
+    p(
+        1,
+        0,
+        3,
+        "ESCT",
+        "Elevation of snow-covered terrain",
+        "Code table 4.216",
+    ),
+    p(
+        1,
+        0,
+        4,
+        "SWEPON",
+        "Snow water equivalent percent of normal",
+        "%",
+    ),
+    p(1, 0, 5, "BGRUN", "Baseflow-groundwater runoff", "kg m-2"),
+    p(1, 0, 6, "SSRUN", "Storm surface runoff", "kg m-2"),
+    p(
+        1,
+        1,
+        0,
+        "CPPOP",
+        "Conditional percent precipitation amount fractile for an overall period",
+        "kg m-2",
+    ),
+    p(
+        1,
+        1,
+        1,
+        "PPOSP",
+        "Percent precipitation in a sub-period of an overall period",
+        "%",
+    ),
+    p(
+        1,
+        1,
+        2,
+        "POP",
+        "Probability of 0.01 inch of precipitation (POP)",
+        "%",
+    ),
+    p(
+        2,
+        0,
+        0,
+        "LAND",
+        "Land cover (1 = land, 0 = sea)",
+        "Proportion",
+    ),
+    p(2, 0, 1, "SFCR", "Surface roughness", "m"),
+    p(2, 0, 2, "TSOIL", "Soil temperature", "K"),
+    p(2, 0, 3, "SOILM", "Soil moisture content", "kg m-2"),
+    p(2, 0, 4, "VEG", "Vegetation", "%"),
+    p(2, 0, 5, "WATR", "Water runoff", "kg m-2"),
+    p(2, 0, 6, "EVAPT", "Evapotranspiration", "kg-2 s-1"),
+    p(2, 0, 7, "MTERH", "Model terrain height", "m"),
+    p(2, 0, 8, "LANDU", "Land use", "Code table 4.212"),
+    p(
+        2,
+        0,
+        9,
+        "SOILW",
+        "Volumetric soil moisture content",
+        "Proportion",
+    ),
+    p(2, 0, 10, "GFLUX", "Ground heat flux", "W m-2"),
+    p(2, 0, 11, "MSTAV", "Moisture availability", "%"),
+    p(2, 0, 12, "SFEXC", "Exchange coefficient", "kg m-2 s-1"),
+    p(2, 0, 13, "CNWAT", "Plant canopy surface water", "kg m-2"),
+    p(2, 0, 14, "BMIXL", "Blackadar mixing length scale", "m"),
+    p(2, 0, 15, "CCOND", "Canopy conductance", "m s-1"),
+    p(2, 0, 16, "RSMIN", "Minimal stomatal resistance", "s m-1"),
+    p(2, 0, 17, "WILT", "Wilting point", "Proportion"),
+    p(2, 3, 0, "SOTYP", "Soil type", "Code table 4.213"),
+    p(10, 0, 0, "WVSP1", "Wave spectra (1)", "-"),
+    p(10, 0, 1, "WVSP2", "Wave spectra (2)", "-"),
+    p(10, 0, 2, "WVSP3", "Wave spectra (3)", "-"),
+    p(
+        10,
+        0,
+        3,
+        "HTSGW",
+        "Significant height of combined wind waves and swell",
+        "m",
+    ),
+    p(10, 0, 4, "WVDIR", "Direction of wind waves", "degree true"),
+    p(10, 0, 5, "WVHGT", "Significant height of wind waves", "m"),
+    p(10, 0, 6, "WVPER", "Mean period of wind waves", "s"),
+    p(10, 0, 7, "SWDIR", "Direction of swell waves", "degree true"),
+    p(10, 0, 8, "SWELL", "Significant height of swell waves", "m"),
+    p(10, 0, 9, "SWPER", "Mean period of swell waves", "s"),
+    p(10, 0, 10, "DIRPW", "Primary wave direction", "degree true"),
+    p(10, 0, 11, "PERPW", "Primary wave mean period", "s"),
+    p(
+        10,
+        0,
+        12,
+        "DIRSW",
+        "Secondary wave direction",
+        "degree true",
+    ),
+    p(10, 0, 13, "PERSW", "Secondary wave mean period", "s"),
+    p(10, 1, 0, "DIRC", "Current direction", "degree true"),
+    p(10, 1, 1, "SPC", "Current speed", "m s-1"),
+    p(10, 1, 2, "UOGRD", "u-component of current", "m s-1"),
+    p(10, 1, 3, "VOGRD", "v-component of current", "m s-1"),
+    p(10, 2, 0, "ICEC", "Ice cover", "Proportion"),
+    p(10, 2, 1, "ICETK", "Ice thickness", "m"),
+    p(10, 2, 2, "DICED", "Direction of ice drift", "degree true"),
+    p(10, 2, 3, "SICED", "Speed of ice drift", "m s-1"),
+    p(10, 2, 4, "UICE", "u-component of ice drift", "m s-1"),
+    p(10, 2, 5, "VICE", "v-component of ice drift", "m s-1"),
+    p(10, 2, 6, "ICEG", "Ice growth rate", "m s-1"),
+    p(10, 2, 7, "ICED", "Ice divergence", "s-1"),
+    p(10, 3, 0, "WTMP", "Water temperature", "K"),
+    p(10, 3, 1, "DSLM", "Deviation of sea level from mean", "m"),
+];
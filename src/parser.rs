@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use crate::reader::{SectionEvent, SectionReader};
+use crate::{Error, Result};
+
+/// Push parser that can be fed input in arbitrary chunks (sans-io)
+///
+/// Input is buffered until a whole section is available, which is then parsed and validated
+/// like [`SectionReader`] does. Useful in non-blocking servers and WASM, where the caller
+/// owns the I/O.
+pub struct Parser {
+    sections: SectionReader<VecDeque<u8>>,
+    pending: VecDeque<u8>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self {
+            sections: SectionReader::new(VecDeque::new()),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Number of bytes fed so far that are not yet parsed into events
+    pub fn buffered(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Feed the next chunk of input and return the sections it completes
+    ///
+    /// After an error the parser cannot continue, as with [`SectionReader`].
+    pub fn feed(&mut self, data: &[u8]) -> Result<Vec<SectionEvent>> {
+        self.pending.extend(data);
+        let mut events = Vec::new();
+        while let Some(len) = self.next_section_len() {
+            if self.pending.len() < len {
+                break;
+            }
+            self.sections.get_mut().extend(self.pending.drain(..len));
+            match self.sections.next_event()? {
+                Some(event) => events.push(event),
+                None => unreachable!("a complete section has been buffered"),
+            }
+        }
+        Ok(events)
+    }
+
+    /// Signal the end of input, failing if it ends within a message
+    pub fn finish(self) -> Result<()> {
+        if self.sections.last_section().is_some() || !self.pending.is_empty() {
+            return Err(Error::Truncated {
+                offset: self.sections.position() + self.pending.len() as u64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Length of the next section, or `None` if more input is needed to know it
+    fn next_section_len(&self) -> Option<usize> {
+        match self.sections.last_section() {
+            // 'GRIB' and the rest of the indicator section
+            None => Some(16),
+            Some(last_section) => {
+                let mut octets = self.pending.iter().copied();
+                let header = [
+                    octets.next()?,
+                    octets.next()?,
+                    octets.next()?,
+                    octets.next()?,
+                ];
+                if last_section == 7 && &header == b"7777" {
+                    return Some(4);
+                }
+                // shorter lengths are rejected by SectionReader once the header is buffered
+                Some((u32::from_be_bytes(header) as usize).max(5))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Temperature at 850 hPa on a 2 x 2 grid, with a local use section
+    fn message() -> Vec<u8> {
+        let mut body = section(1, &[0, 7, 0, 0, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        body.extend(section(2, b"local"));
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 35_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [36_000_000u32, 136_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x40);
+        body.extend(section(3, &grid));
+        body.extend(section(
+            4,
+            &[
+                0, 0, 0, 0, 0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 6, 100, 0, 0, 1, 76, 8, 255, 255,
+                255, 255, 255, 255,
+            ],
+        ));
+        let mut representation = vec![0, 0, 0, 4, 0, 0];
+        representation.extend(250f32.to_be_bytes());
+        representation.extend([0, 0, 0, 0, 8, 0]);
+        body.extend(section(5, &representation));
+        body.extend(section(6, &[255]));
+        body.extend(section(7, &[0, 1, 2, 3]));
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    /// Events of `bytes` read by `SectionReader`, formatted for comparison
+    fn read_events(bytes: &[u8]) -> Vec<String> {
+        let mut reader = SectionReader::new(bytes);
+        let mut events = Vec::new();
+        while let Some(event) = reader.next_event().unwrap() {
+            events.push(format!("{:?}", event));
+        }
+        events
+    }
+
+    /// Events of `bytes` fed in chunks ending at `splits`, formatted for comparison
+    fn fed_events(bytes: &[u8], splits: &[usize]) -> Vec<String> {
+        let mut parser = Parser::new();
+        let mut events = Vec::new();
+        let mut start = 0;
+        for &end in splits.iter().chain([&bytes.len()]) {
+            events.extend(parser.feed(&bytes[start..end]).unwrap());
+            start = end;
+        }
+        parser.finish().unwrap();
+        events.iter().map(|e| format!("{:?}", e)).collect()
+    }
+
+    #[test]
+    fn every_split_point() {
+        let bytes = [message(), message()].concat();
+        let expected = read_events(&bytes);
+        assert_eq!(expected.len(), 18);
+        for split in 0..=bytes.len() {
+            assert_eq!(fed_events(&bytes, &[split]), expected, "split at {}", split);
+        }
+    }
+
+    #[test]
+    fn byte_by_byte() {
+        let bytes = [message(), message()].concat();
+        let splits = (1..bytes.len()).collect::<Vec<_>>();
+        assert_eq!(fed_events(&bytes, &splits), read_events(&bytes));
+    }
+
+    #[test]
+    fn suspends_within_sections() {
+        let bytes = message();
+        let grid = 16 + 21 + 10;
+        let end = bytes.len() - 4;
+        let mut parser = Parser::new();
+        // within the length of the grid definition section
+        assert_eq!(parser.feed(&bytes[..grid + 2]).unwrap().len(), 3);
+        assert_eq!(parser.buffered(), 2);
+        assert_eq!(parser.feed(&bytes[grid + 2..grid + 5]).unwrap().len(), 0);
+        assert_eq!(parser.buffered(), 5);
+        // within the end section
+        assert_eq!(parser.feed(&bytes[grid + 5..end + 2]).unwrap().len(), 5);
+        assert_eq!(parser.buffered(), 2);
+        let events = parser.feed(&bytes[end + 2..]).unwrap();
+        assert!(matches!(events[..], [SectionEvent::MessageEnd]));
+        assert_eq!(parser.buffered(), 0);
+        parser.finish().unwrap();
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = message();
+        for end in [1, 16, 40, bytes.len() - 1] {
+            let mut parser = Parser::new();
+            parser.feed(&bytes[..end]).unwrap();
+            assert!(
+                matches!(parser.finish(), Err(Error::Truncated { offset }) if offset == end as u64),
+                "end at {}",
+                end
+            );
+        }
+        Parser::new().finish().unwrap();
+    }
+
+    #[test]
+    fn errors_like_section_reader() {
+        let mut bytes = message();
+        // a section 3 of length 4
+        bytes[16 + 21 + 10..16 + 21 + 14].copy_from_slice(&4u32.to_be_bytes());
+        let mut reader = SectionReader::new(bytes.as_slice());
+        let expected = std::iter::from_fn(|| reader.next_event().transpose())
+            .find_map(|event| event.err())
+            .unwrap()
+            .to_string();
+        for split in [0, 16 + 21 + 10 + 1, 16 + 21 + 10 + 5, bytes.len()] {
+            let mut parser = Parser::new();
+            let error = parser
+                .feed(&bytes[..split])
+                .and_then(|_| parser.feed(&bytes[split..]))
+                .unwrap_err();
+            assert_eq!(error.to_string(), expected, "split at {}", split);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Resampling of radar fields on azimuth-range grids (Template 3.120)
+
+use crate::contour::{Projection, web_mercator, web_mercator_inverse};
+use crate::field::DecodedField;
+use crate::templates::GridDefinitionTemplate;
+use crate::{Error, Result};
+
+/// Radius in metres of the spherical earth used to locate points around the radar
+const EARTH_RADIUS: f64 = 6371229.0;
+
+/// Values on a regular grid in a projection, rows north to south and columns west to east
+#[derive(Debug, Clone)]
+pub struct ResampledGrid {
+    pub projection: Projection,
+    /// Coordinates `(x, y)` of the centre of the first (north-west) cell, in degrees of
+    /// longitude and latitude or in EPSG:3857 metres
+    pub origin: (f64, f64),
+    /// Spacing of the columns eastwards and of the rows southwards
+    pub spacing: (f64, f64),
+    /// Number of rows and columns (nj, ni)
+    pub shape: (usize, usize),
+    /// NAN outside the radials and range of the radar and where the field is missing
+    pub values: Vec<f32>,
+}
+
+/// Resample a field on an azimuth-range grid onto `size` x `size` cells around the radar
+///
+/// The cells cover the maximum range of the radar in each direction, and each takes the
+/// value of the bin containing its centre. Ranges are measured along the surface of a
+/// spherical earth, without accounting for the height of the beam.
+pub fn resample_polar(
+    field: &DecodedField,
+    projection: Projection,
+    size: usize,
+) -> Result<ResampledGrid> {
+    let GridDefinitionTemplate::Template3_120(grid) = &*field.grid_definition else {
+        return Err(Error::InvalidData(
+            "field is not on an azimuth-range grid (Template 3.120)".to_string(),
+        ));
+    };
+    let n_b = grid.n_b as usize;
+    if field.values.len() != grid.radials.len() * n_b {
+        return Err(Error::InvalidData(format!(
+            "{} values for {} radials of {} bins",
+            field.values.len(),
+            grid.radials.len(),
+            n_b
+        )));
+    }
+    if size == 0 {
+        return Err(Error::InvalidData("grid size must not be zero".to_string()));
+    }
+    let (lat0, lon0) = grid.site();
+    let max_range = grid.max_range();
+    // the extent of the range in latitude, and in longitude at the latitude of the radar
+    let half_lat = (max_range / EARTH_RADIUS).to_degrees();
+    let half_lon = half_lat / lat0.to_radians().cos().max(0.01);
+    let (centre, half_width, half_height) = match projection {
+        Projection::LatLon => ((lon0, lat0), half_lon, half_lat),
+        Projection::WebMercator => {
+            let (x, y) = web_mercator(lat0, lon0);
+            let (_, north) = web_mercator(lat0 + half_lat, lon0);
+            let (east, _) = web_mercator(lat0, lon0 + half_lon);
+            ((x, y), east - x, north - y)
+        }
+        Projection::Grid => {
+            return Err(Error::UnsupportedData(
+                "resampling onto grid coordinates".to_string(),
+            ));
+        }
+    };
+    let spacing = (
+        2.0 * half_width / size as f64,
+        2.0 * half_height / size as f64,
+    );
+    let origin = (
+        centre.0 - half_width + spacing.0 / 2.0,
+        centre.1 + half_height - spacing.1 / 2.0,
+    );
+
+    let mut values = Vec::with_capacity(size * size);
+    for j in 0..size {
+        let y = origin.1 - j as f64 * spacing.1;
+        for i in 0..size {
+            let x = origin.0 + i as f64 * spacing.0;
+            let (lat, lon) = match projection {
+                Projection::WebMercator => web_mercator_inverse(x, y),
+                _ => (y, x),
+            };
+            let (azimuth, range) = azimuth_range(lat0, lon0, lat, lon);
+            values.push(match grid.locate(azimuth, range) {
+                Some((radial, bin)) => field.values[radial * n_b + bin],
+                None => f32::NAN,
+            });
+        }
+    }
+    Ok(ResampledGrid {
+        projection,
+        origin,
+        spacing,
+        shape: (size, size),
+        values,
+    })
+}
+
+/// Initial bearing in degrees clockwise from north and great-circle distance in metres from
+/// `(lat0, lon0)` to `(lat, lon)`
+fn azimuth_range(lat0: f64, lon0: f64, lat: f64, lon: f64) -> (f64, f64) {
+    let (phi0, phi) = (lat0.to_radians(), lat.to_radians());
+    let dlambda = (lon - lon0).to_radians();
+    let a =
+        ((phi - phi0) / 2.0).sin().powi(2) + phi0.cos() * phi.cos() * (dlambda / 2.0).sin().powi(2);
+    let range = 2.0 * EARTH_RADIUS * a.sqrt().min(1.0).asin();
+    let azimuth = (dlambda.sin() * phi.cos())
+        .atan2(phi0.cos() * phi.sin() - phi0.sin() * phi.cos() * dlambda.cos())
+        .to_degrees()
+        .rem_euclid(360.0);
+    (azimuth, range)
+}
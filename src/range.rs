@@ -0,0 +1,555 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::field::{Field, Message};
+use crate::idx::IdxRecord;
+use crate::index::IndexEntry;
+#[cfg(feature = "http")]
+use crate::limits::allocation_limit;
+#[cfg(any(feature = "http", feature = "object_store"))]
+use crate::limits::check_allocation;
+use crate::{Error, Result};
+
+/// Source that can read arbitrary byte ranges (local files, HTTP servers, object stores)
+pub trait RangeSource {
+    /// Read the bytes in `start..end`, or from `start` to the end of the resource if `end` is `None`
+    fn read_range(&mut self, start: u64, end: Option<u64>) -> Result<Vec<u8>>;
+}
+
+impl<T: Read + Seek> RangeSource for T {
+    fn read_range(&mut self, start: u64, end: Option<u64>) -> Result<Vec<u8>> {
+        self.seek(SeekFrom::Start(start))?;
+        let mut buf = Vec::new();
+        match end {
+            Some(end) => self.take(end.saturating_sub(start)).read_to_end(&mut buf)?,
+            None => self.read_to_end(&mut buf)?,
+        };
+        Ok(buf)
+    }
+}
+
+/// Read a single message located by a byte range and return its `n`-th field
+fn read_field_in_range<S: RangeSource + ?Sized>(
+    source: &mut S,
+    start: u64,
+    end: Option<u64>,
+    n: usize,
+) -> Result<Field> {
+    let buf = source.read_range(start, end)?;
+    let message = Message::read(&mut buf.as_slice())?
+        .ok_or_else(|| Error::InvalidData("no message in the requested range".to_string()))?;
+    message
+        .fields
+        .into_iter()
+        .nth(n)
+        .ok_or_else(|| Error::InvalidData(format!("message has no field {}", n)))
+}
+
+/// Read the field of an `.idx` record, fetching only its message
+pub fn read_idx_field<S: RangeSource + ?Sized>(
+    source: &mut S,
+    record: &IdxRecord,
+) -> Result<Field> {
+    let (start, end) = record.range();
+    let n = record.field_number.unwrap_or(1).saturating_sub(1) as usize;
+    read_field_in_range(source, start, end, n)
+}
+
+/// Read the field of an index entry, fetching only its message
+pub fn read_index_field<S: RangeSource + ?Sized>(
+    source: &mut S,
+    entry: &IndexEntry,
+) -> Result<Field> {
+    read_field_in_range(
+        source,
+        entry.message_offset,
+        Some(entry.message_offset + entry.message_length),
+        entry.field_index,
+    )
+}
+
+/// Minimal HTTP/1.1 client issuing Range requests (plain `http://` only)
+///
+/// For `https://` sources, implement [`RangeSource`] on top of an HTTP client of your choice.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone)]
+pub struct HttpSource {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+#[cfg(feature = "http")]
+impl HttpSource {
+    pub fn new(url: &str) -> Result<Self> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            Error::UnsupportedData(format!("only http:// URLs are supported: {}", url))
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse()
+                    .map_err(|_| Error::InvalidData(format!("invalid port in URL: {}", url)))?,
+            ),
+            None => (authority, 80),
+        };
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+#[cfg(feature = "http")]
+impl RangeSource for HttpSource {
+    /// Request `start..end` with a Range header
+    ///
+    /// Bodies are bounded by the allocation limit. When the server ignores the Range header
+    /// and answers 200 with the whole resource, the bytes before `start` are skipped and the
+    /// connection is closed after `end`, so only what precedes `end` is downloaded.
+    fn read_range(&mut self, start: u64, end: Option<u64>) -> Result<Vec<u8>> {
+        use std::io::{BufRead, BufReader, Write};
+
+        let range = match end {
+            Some(end) if end <= start => return Ok(Vec::new()),
+            Some(end) => format!("bytes={}-{}", start, end - 1),
+            None => format!("bytes={}-", start),
+        };
+        let mut stream = std::net::TcpStream::connect((self.host.as_str(), self.port))?;
+        write!(
+            stream,
+            "GET {} HTTP/1.1\r\nHost: {}\r\nRange: {}\r\nConnection: close\r\n\r\n",
+            self.path, self.host, range
+        )?;
+
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let status: u16 = line
+            .split_whitespace()
+            .nth(1)
+            .and_then(|s| s.parse().ok())
+            .ok_or_else(|| Error::InvalidData(format!("invalid HTTP status line: {}", line)))?;
+        let skip = match status {
+            206 => 0,
+            // the server ignored the Range header and sends the whole resource
+            200 => start,
+            _ => return Err(Error::InvalidData(format!("HTTP status {}", status))),
+        };
+        let mut chunked = false;
+        let mut content_length = None;
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let header = line.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                let value = value.trim();
+                if name.eq_ignore_ascii_case("transfer-encoding") {
+                    chunked = value.eq_ignore_ascii_case("chunked");
+                } else if name.eq_ignore_ascii_case("content-length") {
+                    content_length = Some(value.parse::<u64>().map_err(|_| {
+                        Error::InvalidData(format!("invalid HTTP content length: {}", value))
+                    })?);
+                }
+            }
+        }
+
+        let mut body: Box<dyn Read> = match (chunked, content_length) {
+            (true, _) => Box::new(ChunkedReader {
+                reader,
+                remaining: 0,
+                done: false,
+            }),
+            (false, Some(length)) => Box::new(reader.take(length)),
+            (false, None) => Box::new(reader),
+        };
+        std::io::copy(&mut body.by_ref().take(skip), &mut std::io::sink())?;
+        let expected = match (end, content_length) {
+            (Some(end), _) => Some(end - start),
+            (None, Some(length)) if !chunked => Some(length.saturating_sub(skip)),
+            (None, _) => None,
+        };
+        // one byte more than allowed tells an oversized body of unknown length
+        let limit = match expected {
+            Some(expected) => check_allocation::<u8>(expected as usize, "HTTP response body")?,
+            None => allocation_limit().saturating_add(1),
+        };
+        let mut buf = Vec::with_capacity(limit.min(1 << 20));
+        body.take(limit as u64).read_to_end(&mut buf)?;
+        check_allocation::<u8>(buf.len(), "HTTP response body")?;
+        Ok(buf)
+    }
+}
+
+/// Body of a response with `Transfer-Encoding: chunked`
+#[cfg(feature = "http")]
+struct ChunkedReader<R> {
+    reader: R,
+    /// Bytes left in the current chunk
+    remaining: u64,
+    done: bool,
+}
+
+#[cfg(feature = "http")]
+impl<R: std::io::BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let invalid = || std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid HTTP chunk");
+        while self.remaining == 0 && !self.done {
+            let mut line = String::new();
+            self.reader.read_line(&mut line)?;
+            if line.trim().is_empty() {
+                // the line break that ends the previous chunk
+                self.reader.read_line(&mut line)?;
+            }
+            let size = line.trim().split(';').next().unwrap_or("");
+            self.remaining = u64::from_str_radix(size, 16).map_err(|_| invalid())?;
+            self.done = self.remaining == 0;
+        }
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        let len = buf
+            .len()
+            .min(self.remaining.min(usize::MAX as u64) as usize);
+        let n = self.reader.read(&mut buf[..len])?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Object of an [`object_store`] store (S3, GCS, Azure, ...), read with ranged GETs
+///
+/// Requests are run to completion on the Tokio runtime of `runtime`, so `read_range` must
+/// not be called from a task of that runtime. A current-thread runtime only drives I/O
+/// within its own `block_on`; give network stores the handle of a multi-thread runtime.
+#[cfg(feature = "object_store")]
+#[derive(Debug, Clone)]
+pub struct ObjectStoreSource {
+    store: std::sync::Arc<dyn object_store::ObjectStore>,
+    path: object_store::path::Path,
+    runtime: tokio::runtime::Handle,
+}
+
+#[cfg(feature = "object_store")]
+impl ObjectStoreSource {
+    pub fn new(
+        store: std::sync::Arc<dyn object_store::ObjectStore>,
+        path: impl Into<object_store::path::Path>,
+        runtime: tokio::runtime::Handle,
+    ) -> Self {
+        Self {
+            store,
+            path: path.into(),
+            runtime,
+        }
+    }
+}
+
+#[cfg(feature = "object_store")]
+impl RangeSource for ObjectStoreSource {
+    /// Get `start..end`, or everything from `start`, in a single request
+    ///
+    /// Bodies are bounded by the allocation limit, checked before they are downloaded.
+    fn read_range(&mut self, start: u64, end: Option<u64>) -> Result<Vec<u8>> {
+        use object_store::{GetOptions, GetRange};
+
+        let range = match end {
+            Some(end) if end <= start => return Ok(Vec::new()),
+            Some(end) => GetRange::Bounded(start..end),
+            None => GetRange::Offset(start),
+        };
+        let options = GetOptions {
+            range: Some(range),
+            ..Default::default()
+        };
+        let store_error = |e| Error::IO(std::io::Error::other(e));
+        self.runtime.block_on(async {
+            let result = self
+                .store
+                .get_opts(&self.path, options)
+                .await
+                .map_err(store_error)?;
+            let len = result.range.end.saturating_sub(result.range.start);
+            check_allocation::<u8>(
+                usize::try_from(len).unwrap_or(usize::MAX),
+                "object store response body",
+            )?;
+            Ok(result.bytes().await.map_err(store_error)?.to_vec())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::idx::Idx;
+    use crate::index::Index;
+
+    /// Section `number` with `body`, preceded by its length
+    fn section(number: u8, body: &[u8]) -> Vec<u8> {
+        let mut bytes = (5 + body.len() as u32).to_be_bytes().to_vec();
+        bytes.push(number);
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Message of temperature fields on a 2 x 2 grid, one per value set of `fields`
+    fn message(fields: &[[u8; 4]]) -> Vec<u8> {
+        let mut body = section(1, &[0, 7, 0, 0, 2, 1, 1, 7, 232, 1, 2, 0, 0, 0, 0, 1]);
+        let mut grid = vec![0, 0, 0, 0, 4, 0, 0, 0, 0, 6];
+        grid.extend([0; 15]);
+        for value in [2u32, 2, 0, u32::MAX, 35_000_000, 135_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x30);
+        for value in [36_000_000u32, 136_000_000, 1_000_000, 1_000_000] {
+            grid.extend(value.to_be_bytes());
+        }
+        grid.push(0x40);
+        body.extend(section(3, &grid));
+        for packed in fields {
+            body.extend(section(
+                4,
+                &[
+                    0, 0, 0, 0, 0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 6, 100, 0, 0, 1, 76, 8, 255,
+                    255, 255, 255, 255, 255,
+                ],
+            ));
+            let mut representation = vec![0, 0, 0, 4, 0, 0];
+            representation.extend(250f32.to_be_bytes());
+            representation.extend([0, 0, 0, 0, 8, 0]);
+            body.extend(section(5, &representation));
+            body.extend(section(6, &[255]));
+            body.extend(section(7, packed));
+        }
+        body.extend(b"7777");
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    /// A message of one field followed by a message of two
+    fn file() -> Vec<u8> {
+        let mut bytes = message(&[[0, 1, 2, 3]]);
+        bytes.extend(message(&[[4, 5, 6, 7], [8, 9, 10, 11]]));
+        bytes
+    }
+
+    #[test]
+    fn idx_fields() {
+        let bytes = file();
+        let idx = Idx::build(bytes.as_slice()).unwrap();
+        assert_eq!(idx.records.len(), 3);
+        let mut source = Cursor::new(bytes);
+        let values = read_idx_field(&mut source, &idx.records[1])
+            .unwrap()
+            .values()
+            .unwrap();
+        assert_eq!(values, [254.0, 255.0, 256.0, 257.0]);
+        // the last line of an inventory has no length and reads to the end of the file
+        let mut last = idx.records[2].clone();
+        last.length = None;
+        assert_eq!(last.range().1, None);
+        let values = read_idx_field(&mut source, &last)
+            .unwrap()
+            .values()
+            .unwrap();
+        assert_eq!(values, [258.0, 259.0, 260.0, 261.0]);
+        last.field_number = Some(3);
+        assert!(read_idx_field(&mut source, &last).is_err());
+    }
+
+    #[test]
+    fn index_fields() {
+        let bytes = file();
+        let index = Index::build(bytes.as_slice()).unwrap();
+        let mut source = Cursor::new(bytes);
+        let values = index
+            .entries
+            .iter()
+            .map(|entry| read_index_field(&mut source, entry)?.values())
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(values[0], [250.0, 251.0, 252.0, 253.0]);
+        assert_eq!(values[2], [258.0, 259.0, 260.0, 261.0]);
+        let mut entry = index.entries[0].clone();
+        entry.message_offset += 1;
+        assert!(read_index_field(&mut source, &entry).is_err());
+    }
+
+    #[cfg(feature = "object_store")]
+    #[test]
+    fn object_store_fields() {
+        use object_store::ObjectStore;
+        use object_store::memory::InMemory;
+
+        // the in-memory store needs no I/O driver
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let bytes = file();
+        let store = std::sync::Arc::new(InMemory::new());
+        runtime
+            .block_on(store.put(&"gfs/file.grib2".into(), bytes.clone().into()))
+            .unwrap();
+        let handle = runtime.handle().clone();
+        let mut source = ObjectStoreSource::new(store.clone(), "gfs/file.grib2", handle.clone());
+        assert_eq!(source.read_range(0, Some(4)).unwrap(), b"GRIB");
+        assert_eq!(source.read_range(4, Some(4)).unwrap(), b"");
+        let tail = source.read_range(bytes.len() as u64 - 4, None).unwrap();
+        assert_eq!(tail, b"7777");
+
+        let idx = Idx::build(bytes.as_slice()).unwrap();
+        let values = read_idx_field(&mut source, &idx.records[1])
+            .unwrap()
+            .values()
+            .unwrap();
+        assert_eq!(values, [254.0, 255.0, 256.0, 257.0]);
+        let mut last = idx.records[2].clone();
+        last.length = None;
+        let values = read_idx_field(&mut source, &last)
+            .unwrap()
+            .values()
+            .unwrap();
+        assert_eq!(values, [258.0, 259.0, 260.0, 261.0]);
+        let index = Index::build(bytes.as_slice()).unwrap();
+        let values = read_index_field(&mut source, &index.entries[0])
+            .unwrap()
+            .values()
+            .unwrap();
+        assert_eq!(values, [250.0, 251.0, 252.0, 253.0]);
+
+        let mut missing = ObjectStoreSource::new(store, "gfs/missing.grib2", handle);
+        assert!(matches!(missing.read_range(0, Some(4)), Err(Error::IO(_))));
+    }
+
+    /// Serve `response` to a single connection on a local port, returning the request
+    #[cfg(feature = "http")]
+    fn serve(response: Vec<u8>) -> (HttpSource, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            while !request.ends_with("\r\n\r\n") {
+                reader.read_line(&mut request).unwrap();
+            }
+            // the client may hang up once it has what it asked for
+            let _ = reader.get_mut().write_all(&response);
+            request
+        });
+        let source = HttpSource::new(&format!("http://127.0.0.1:{}/data/file.grib2", port));
+        (source.unwrap(), server)
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_partial_content() {
+        let (mut source, server) =
+            serve(b"HTTP/1.1 206 Partial Content\r\nContent-Length: 5\r\n\r\nhello".to_vec());
+        assert_eq!(source.read_range(10, Some(15)).unwrap(), b"hello");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /data/file.grib2 HTTP/1.1\r\n"));
+        assert!(request.contains("\r\nHost: 127.0.0.1\r\n"));
+        assert!(request.contains("\r\nRange: bytes=10-14\r\n"));
+
+        let (mut source, server) = serve(
+            b"HTTP/1.1 206 Partial Content\r\nTransfer-Encoding: chunked\r\n\r\n\
+              3\r\nabc\r\n2;name=value\r\nde\r\n0\r\n\r\n"
+                .to_vec(),
+        );
+        assert_eq!(source.read_range(7, None).unwrap(), b"abcde");
+        assert!(server.join().unwrap().contains("\r\nRange: bytes=7-\r\n"));
+
+        assert!(source.read_range(5, Some(5)).unwrap().is_empty());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_whole_resource() {
+        let alphabet = b"abcdefghijklmnopqrstuvwxyz";
+        let mut response = b"HTTP/1.1 200 OK\r\nContent-Length: 26\r\n\r\n".to_vec();
+        response.extend(alphabet);
+        let (mut source, server) = serve(response.clone());
+        assert_eq!(source.read_range(3, Some(6)).unwrap(), b"def");
+        server.join().unwrap();
+        let (mut source, server) = serve(response);
+        assert_eq!(source.read_range(20, None).unwrap(), b"uvwxyz");
+        server.join().unwrap();
+
+        // only the requested part of a large resource is read before hanging up
+        let mut response = b"HTTP/1.1 200 OK\r\n\r\n".to_vec();
+        response.extend(alphabet.repeat(1 << 16));
+        let (mut source, server) = serve(response);
+        assert_eq!(
+            source
+                .read_range(26 * 1000 + 1, Some(26 * 1000 + 4))
+                .unwrap(),
+            b"bcd"
+        );
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_errors() {
+        let (mut source, server) = serve(b"HTTP/1.1 404 Not Found\r\n\r\n".to_vec());
+        assert!(source.read_range(0, Some(10)).is_err());
+        server.join().unwrap();
+
+        let (mut source, server) = serve(b"HTTP/1.1 206 Partial Content\r\n\r\n".to_vec());
+        assert_eq!(
+            source.read_range(0, Some(1 << 62)).unwrap_err().to_string(),
+            check_allocation::<u8>(1 << 62, "HTTP response body")
+                .unwrap_err()
+                .to_string()
+        );
+        server.join().unwrap();
+
+        let (mut source, server) = serve(
+            b"HTTP/1.1 206 Partial Content\r\nContent-Length: 99999999999999999\r\n\r\n".to_vec(),
+        );
+        assert!(source.read_range(0, None).is_err());
+        server.join().unwrap();
+
+        let (mut source, server) = serve(
+            b"HTTP/1.1 206 Partial Content\r\nTransfer-Encoding: chunked\r\n\r\nxyz\r\n".to_vec(),
+        );
+        assert!(source.read_range(0, None).is_err());
+        server.join().unwrap();
+
+        let (mut source, server) = serve(b"garbage\r\n\r\n".to_vec());
+        assert!(source.read_range(0, None).is_err());
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_urls() {
+        let source = HttpSource::new("http://example.com:8080/a/b.grib2").unwrap();
+        assert_eq!(
+            (source.host.as_str(), source.port, source.path.as_str()),
+            ("example.com", 8080, "/a/b.grib2")
+        );
+        let source = HttpSource::new("http://example.com").unwrap();
+        assert_eq!((source.port, source.path.as_str()), (80, "/"));
+        assert!(HttpSource::new("https://example.com/").is_err());
+        assert!(HttpSource::new("http://example.com:http/").is_err());
+    }
+}
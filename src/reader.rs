@@ -1,10 +1,17 @@
 use std::io::Read;
+use std::sync::Arc;
 
-use byteorder::ReadBytesExt;
-
+use crate::limits::check_allocation;
 use crate::message::*;
+use crate::templates::{
+    DataRepresentationTemplate, GridDefinitionTemplate, ProductDefinitionTemplate,
+};
 use crate::{Error, Result};
 
+/// Callback-style reader calling a handler for each section of a message
+///
+/// Only `Read` is required of the source, so network streams and decompressors can be used
+/// directly; wrapping unbuffered sources in a `BufReader` is still recommended for speed.
 pub trait MessageReader<R: Read> {
     fn handle_indicator(&mut self, _is: IndicatorSectionHeader) -> Result<()> {
         // do nothing
@@ -20,6 +27,26 @@ pub trait MessageReader<R: Read> {
         Ok(())
     }
 
+    /// Called before the handler of each section (0 to 8), with the offset of the section from
+    /// the start of its message
+    ///
+    /// The stream offset of the message is not known to a `MessageReader`;
+    /// [`SectionReader::message_offset`] and [`SectionReader::section_offset`] give absolute
+    /// offsets.
+    fn handle_section_start(&mut self, _section_number: u8, _offset: u64) -> Result<()> {
+        // do nothing
+        Ok(())
+    }
+
+    /// Called before the first section of each field (sections 2 or 3 to 7, repeated for each
+    /// field of a message), with the 0-based index of the field within its message
+    ///
+    /// The sections handled until the next call belong to this field.
+    fn handle_field_start(&mut self, _field_index: usize) -> Result<()> {
+        // do nothing
+        Ok(())
+    }
+
     fn handle_local_use(
         &mut self,
         _loc: LocalUseSectionHeader,
@@ -74,103 +101,1121 @@ pub trait MessageReader<R: Read> {
         Ok(())
     }
 
+    /// Called for sections 3 to 5 whose template this crate cannot parse, before the section handler
+    ///
+    /// `header` holds the raw bytes of the section before its template, starting with the
+    /// section length. The section handler receives whatever part of the body this leaves unread.
+    fn handle_raw_section(
+        &mut self,
+        _section_number: u8,
+        _header: &[u8],
+        _reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        // do nothing
+        Ok(())
+    }
+
+    /// Whether to skip bytes between messages until the next 'GRIB' identifier
+    /// instead of failing (e.g. for feeds with padding or garbage between messages)
+    fn resync(&self) -> bool {
+        false
+    }
+
+    /// Whether to skip the rest of the current field, asked after each handler of sections 2
+    /// to 6
+    ///
+    /// The remaining sections of the field (e.g. sections 5 to 7 after an unwanted product
+    /// definition) are then passed over without reading their bodies or calling any handler,
+    /// including `handle_section_start`. Reading resumes with the next field or the end
+    /// section. A handler typically sets a flag in `handle_product_definition` and clears it
+    /// in `handle_field_start`.
+    fn skip_field(&self) -> bool {
+        false
+    }
+
+    /// Called when a message fails to parse after its indicator section has been read
+    ///
+    /// Return `Ok(())` to skip the rest of the message (using `total_length`) and continue
+    /// with the next one. The default implementation aborts by returning the error.
+    fn handle_error(&mut self, error: Error) -> Result<()> {
+        Err(error)
+    }
+
     fn read_next_message(&mut self, reader: &mut R) -> Result<Option<()>> {
-        match reader.read_u32::<byteorder::LittleEndian>() {
-            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
-            Err(e) => return Err(e.into()),
-            Ok(0x42495247) => {} // b"GRIB"
-            Ok(_) => {
-                return Err(Error::InvalidData(
-                    "message identifier must be 'GRIB'".to_string(),
-                ));
-            }
-        };
+        if !read_identifier(reader, self.resync())? {
+            return Ok(None);
+        }
 
         // Indicator Section (0)
         let is: IndicatorSectionHeader = IndicatorSectionHeader::read(reader)?;
-        self.handle_indicator(is)?;
+        let total_length = is.total_length;
+        let mut message = reader.take(total_length.saturating_sub(16));
+        let result = match read_sections(self, is, &mut message) {
+            Err(Error::IO(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                Err(match message.limit() {
+                    0 => Error::InvalidData(format!(
+                        "sections exceed the total length of the message ({})",
+                        total_length
+                    )),
+                    remaining => Error::Truncated {
+                        offset: total_length - remaining,
+                    },
+                })
+            }
+            Ok(()) if message.limit() > 0 => Err(Error::InvalidData(format!(
+                "sections end {} bytes before the total length of the message ({})",
+                message.limit(),
+                total_length
+            ))),
+            result => result,
+        };
+        if let Err(e) = result {
+            self.handle_error(e)?;
+            std::io::copy(&mut message, &mut std::io::sink())?;
+        }
+
+        Ok(Some(()))
+    }
+}
 
-        // Identification Section (1)
-        let ids = IdentificationSectionHeader::read(SectionHeader::read(reader, false)?, reader)?;
-        {
-            let mut reader = reader.take(ids.body_len() as u64);
-            self.handle_identification(ids, &mut reader)?;
-            std::io::copy(&mut reader, &mut std::io::sink())?;
+/// Sections of a field with their templates parsed, passed to [`FieldReader::handle_field`]
+///
+/// Sections not repeated by the field are those in effect from the previous field of the
+/// message.
+#[derive(Debug)]
+pub struct FieldHeaders {
+    pub indicator: IndicatorSectionHeader,
+    pub identification: IdentificationSectionHeader,
+    /// Index (0-based) of the field within its message
+    pub field_index: usize,
+    pub local_use: Option<(LocalUseSectionHeader, Vec<u8>)>,
+    pub grid_definition: GridDefinitionSectionHeader,
+    /// Shared with the following fields of the message until section 3 is repeated
+    pub grid_definition_template: Arc<GridDefinitionTemplate>,
+    pub product_definition: ProductDefinitionSectionHeader,
+    pub product_definition_template: ProductDefinition,
+    pub data_representation: DataRepresentationSectionHeader,
+    pub data_representation_template: DataRepresentationTemplate,
+    pub bitmap: BitmapSectionHeader,
+    /// Bit-map of the field, the previously defined one for bit-map indicator 254
+    pub bitmap_body: Vec<u8>,
+    pub data: DataSectionHeader,
+}
+
+/// Callback-style reader calling a single handler for each field, with its sections parsed
+///
+/// A higher-level alternative to [`MessageReader`]: sections 2 to 6 are read into memory and
+/// their templates parsed before `handle_field` receives them with the data section.
+///
+/// ```ignore
+/// struct Temperatures(Vec<Vec<f32>>);
+///
+/// impl<R: Read> FieldReader<R> for Temperatures {
+///     fn handle_field(
+///         &mut self,
+///         headers: &FieldHeaders,
+///         reader: &mut std::io::Take<&mut R>,
+///     ) -> Result<()> {
+///         if headers.product_definition_template.template.parameter() == Some((0, 0)) {
+///             // decode the data section from `reader`
+///         }
+///         Ok(())
+///     }
+/// }
+/// ```
+pub trait FieldReader<R: Read> {
+    /// Called once per field with its sections and the body of its data section
+    fn handle_field(
+        &mut self,
+        headers: &FieldHeaders,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()>;
+
+    /// Whether to skip bytes between messages until the next 'GRIB' identifier
+    /// instead of failing (e.g. for feeds with padding or garbage between messages)
+    fn resync(&self) -> bool {
+        false
+    }
+
+    /// Called when a message fails to parse after its indicator section has been read, see
+    /// [`MessageReader::handle_error`]
+    fn handle_error(&mut self, error: Error) -> Result<()> {
+        Err(error)
+    }
+
+    /// Read the next message, calling `handle_field` for each of its fields
+    ///
+    /// Returns `None` at the end of input.
+    fn read_next_message(&mut self, reader: &mut R) -> Result<Option<()>>
+    where
+        Self: Sized,
+    {
+        FieldAssembler {
+            handler: self,
+            indicator: None,
+            identification: None,
+            field_index: 0,
+            local_use: None,
+            grid_definition: None,
+            product_definition: None,
+            data_representation: None,
+            bitmap: None,
+            previous_bitmap: None,
         }
+        .read_next_message(reader)
+    }
+}
 
-        let mut next_header = SectionHeader::read(reader, false)?;
+/// Section 0 to 6 state of the message read for a [`FieldReader`]
+struct FieldAssembler<'a, H> {
+    handler: &'a mut H,
+    indicator: Option<IndicatorSectionHeader>,
+    identification: Option<IdentificationSectionHeader>,
+    field_index: usize,
+    local_use: Option<(LocalUseSectionHeader, Vec<u8>)>,
+    grid_definition: Option<(GridDefinitionSectionHeader, Arc<GridDefinitionTemplate>)>,
+    product_definition: Option<(ProductDefinitionSectionHeader, ProductDefinition)>,
+    data_representation: Option<(DataRepresentationSectionHeader, DataRepresentationTemplate)>,
+    bitmap: Option<(BitmapSectionHeader, Vec<u8>)>,
+    previous_bitmap: Option<Vec<u8>>,
+}
 
-        'outer: loop {
-            // Local Use Section (2)
-            if next_header.number_of_section == 2 {
-                let loc = LocalUseSectionHeader::read(next_header, reader)?;
-                {
-                    let mut reader = reader.take(loc.body_len() as u64);
-                    self.handle_local_use(loc, &mut reader)?;
-                    std::io::copy(&mut reader, &mut std::io::sink())?;
-                }
+/// Read the rest of a section body
+fn read_to_vec<R: Read>(reader: &mut std::io::Take<&mut R>) -> Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(check_allocation::<u8>(
+        reader.limit() as usize,
+        "section body",
+    )?);
+    reader.read_to_end(&mut body)?;
+    Ok(body)
+}
+
+impl<R: Read, H: FieldReader<R>> MessageReader<R> for FieldAssembler<'_, H> {
+    fn handle_indicator(&mut self, is: IndicatorSectionHeader) -> Result<()> {
+        self.indicator = Some(is);
+        Ok(())
+    }
+
+    fn handle_identification(
+        &mut self,
+        ids: IdentificationSectionHeader,
+        _reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        self.identification = Some(ids);
+        Ok(())
+    }
+
+    fn handle_field_start(&mut self, field_index: usize) -> Result<()> {
+        self.field_index = field_index;
+        Ok(())
+    }
+
+    fn handle_local_use(
+        &mut self,
+        loc: LocalUseSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        self.local_use = Some((loc, read_to_vec(reader)?));
+        Ok(())
+    }
+
+    fn handle_grid_definition(
+        &mut self,
+        gds: GridDefinitionSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        let template = gds.read_template(reader)?;
+        self.grid_definition = Some((gds, Arc::new(template)));
+        Ok(())
+    }
+
+    fn handle_product_definition(
+        &mut self,
+        pds: ProductDefinitionSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        let template = pds.read_template(reader)?;
+        self.product_definition = Some((pds, template));
+        Ok(())
+    }
+
+    fn handle_data_representation(
+        &mut self,
+        drs: DataRepresentationSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        let template = drs.read_template(reader)?;
+        self.data_representation = Some((drs, template));
+        Ok(())
+    }
+
+    fn handle_bitmap(
+        &mut self,
+        bitmap: BitmapSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        let body = match bitmap.bit_map_indicator {
+            254 => self
+                .previous_bitmap
+                .clone()
+                .ok_or_else(|| Error::InvalidData("no bit-map has been defined".to_string()))?,
+            _ => read_to_vec(reader)?,
+        };
+        if bitmap.bit_map_indicator == 0 {
+            self.previous_bitmap = Some(body.clone());
+        }
+        self.bitmap = Some((bitmap, body));
+        Ok(())
+    }
+
+    fn handle_data(
+        &mut self,
+        data: DataSectionHeader,
+        reader: &mut std::io::Take<&mut R>,
+    ) -> Result<()> {
+        // read_sections guarantees sections 0 to 6 precede section 7
+        let (grid_definition, grid_definition_template) = self.grid_definition.clone().unwrap();
+        let (product_definition, product_definition_template) =
+            self.product_definition.take().unwrap();
+        let (data_representation, data_representation_template) =
+            self.data_representation.take().unwrap();
+        let (bitmap, bitmap_body) = self.bitmap.take().unwrap();
+        let headers = FieldHeaders {
+            indicator: self.indicator.clone().unwrap(),
+            identification: self.identification.clone().unwrap(),
+            field_index: self.field_index,
+            local_use: self.local_use.take(),
+            grid_definition,
+            grid_definition_template,
+            product_definition,
+            product_definition_template,
+            data_representation,
+            data_representation_template,
+            bitmap,
+            bitmap_body,
+            data,
+        };
+        let result = self.handler.handle_field(&headers, reader);
+        self.local_use = headers.local_use;
+        result
+    }
 
-                next_header = SectionHeader::read(reader, false)?;
+    fn resync(&self) -> bool {
+        self.handler.resync()
+    }
+
+    fn handle_error(&mut self, error: Error) -> Result<()> {
+        self.handler.handle_error(error)
+    }
+}
+
+/// Read sections 1 to 8 of a message, limited to the message by `reader`
+fn read_sections<R: Read, M: MessageReader<R> + ?Sized>(
+    handler: &mut M,
+    is: IndicatorSectionHeader,
+    reader: &mut std::io::Take<&mut R>,
+) -> Result<()> {
+    let total_length = is.total_length;
+    // offset from the start of the message of the next byte of `reader`
+    let offset = |reader: &std::io::Take<&mut R>| total_length - reader.limit();
+    handler.handle_section_start(0, 0)?;
+    handler.handle_indicator(is)?;
+
+    // Identification Section (1)
+    handler.handle_section_start(1, offset(reader))?;
+    let header = SectionHeader::read(reader, false)?;
+    // octets 6-23, without the template that is left to the handler
+    let raw = read_raw_header(reader, &header, 18)?;
+    let ids = IdentificationSectionHeader::read(header, &mut &raw[5..])?;
+    read_body(reader, ids.body_len(), |r| {
+        handler.handle_identification(ids, r)
+    })?;
+
+    // sections 2 to 7 follow in order, and a field may repeat them from section 2, 3 or 4
+    let mut last_section = 1;
+    let mut field_index = 0;
+    let mut skipping = false;
+    loop {
+        let section_start = offset(reader);
+        let header = SectionHeader::read(reader, allows_end(last_section, reader.limit()))?;
+        let section = header.number_of_section;
+        if !can_follow(last_section, section) {
+            return Err(order_error(last_section, section));
+        }
+        if skipping && matches!(last_section, 2..=6) {
+            read_body(reader, header.section_length.saturating_sub(5), |_| Ok(()))?;
+            last_section = section;
+            continue;
+        }
+        if section != 8 && matches!(last_section, 1 | 7) {
+            if last_section == 7 {
+                field_index += 1;
             }
+            handler.handle_field_start(field_index)?;
+        }
+        handler.handle_section_start(section, section_start)?;
+        last_section = section;
 
+        match section {
+            // Local Use Section (2)
+            2 => {
+                let loc = LocalUseSectionHeader::read(header, reader)?;
+                read_body(reader, loc.body_len(), |r| handler.handle_local_use(loc, r))?;
+            }
             // Grid Definition Section (3)
-            {
-                let gds = GridDefinitionSectionHeader::read(&next_header, reader)?;
-                let mut reader = reader.take(gds.body_len() as u64);
-                self.handle_grid_definition(gds, &mut reader)?;
-                std::io::copy(&mut reader, &mut std::io::sink())?;
-            }
-
-            next_header = SectionHeader::read(reader, false)?;
-
-            loop {
-                // Product Definition Section (4)
-                {
-                    let pds = ProductDefinitionSectionHeader::read(&next_header, reader)?;
-                    let mut reader = reader.take(pds.body_len() as u64);
-                    self.handle_product_definition(pds, &mut reader)?;
-                    std::io::copy(&mut reader, &mut std::io::sink())?;
-                }
+            3 => {
+                let raw = read_raw_header(reader, &header, 9)?;
+                let gds = GridDefinitionSectionHeader::read(&header, &mut &raw[5..])?;
+                read_body(reader, gds.body_len(), |r| {
+                    if !GridDefinitionTemplate::is_supported(gds.template_number) {
+                        handler.handle_raw_section(3, &raw, r)?;
+                    }
+                    handler.handle_grid_definition(gds, r)
+                })?;
+            }
+            // Product Definition Section (4)
+            4 => {
+                let raw = read_raw_header(reader, &header, 4)?;
+                let pds = ProductDefinitionSectionHeader::read(&header, &mut &raw[5..])?;
+                read_body(reader, pds.body_len(), |r| {
+                    if !ProductDefinitionTemplate::is_supported(pds.template_number) {
+                        handler.handle_raw_section(4, &raw, r)?;
+                    }
+                    handler.handle_product_definition(pds, r)
+                })?;
+            }
+            // Data Representation Section (5)
+            5 => {
+                let raw = read_raw_header(reader, &header, 6)?;
+                let drs = DataRepresentationSectionHeader::read(&header, &mut &raw[5..])?;
+                read_body(reader, drs.body_len(), |r| {
+                    if !DataRepresentationTemplate::is_supported(drs.template_number) {
+                        handler.handle_raw_section(5, &raw, r)?;
+                    }
+                    handler.handle_data_representation(drs, r)
+                })?;
+            }
+            // Bit-Map Section (6)
+            6 => {
+                let bitmap = BitmapSectionHeader::read(&header, reader)?;
+                read_body(reader, bitmap.body_len(), |r| {
+                    handler.handle_bitmap(bitmap, r)
+                })?;
+            }
+            // Data Section (7)
+            7 => {
+                let data = DataSectionHeader::read(&header)?;
+                read_body(reader, data.body_len(), |r| handler.handle_data(data, r))?;
+            }
+            // End Section (8)
+            _ => break,
+        }
+        skipping = section != 7 && handler.skip_field();
+    }
 
-                // Data Representation Section (5)
-                {
-                    let drs = DataRepresentationSectionHeader::read(
-                        &SectionHeader::read(reader, false)?,
-                        reader,
-                    )?;
-                    let mut reader = reader.take(drs.body_len() as u64);
-                    self.handle_data_representation(drs, &mut reader)?;
-                    std::io::copy(&mut reader, &mut std::io::sink())?;
-                }
+    Ok(())
+}
+
+/// Read the `len` octets following the common section header, and return the whole header
+///
+/// No more than the section holds is read, so a short section is reported by the header
+/// parser instead of swallowing the next section.
+fn read_raw_header<R: Read>(reader: &mut R, header: &SectionHeader, len: usize) -> Result<Vec<u8>> {
+    let len = len.min(header.section_length.saturating_sub(5) as usize);
+    let mut raw = Vec::with_capacity(5 + len);
+    raw.extend_from_slice(&header.section_length.to_be_bytes());
+    raw.push(header.number_of_section);
+    raw.resize(5 + len, 0);
+    reader.read_exact(&mut raw[5..])?;
+    Ok(raw)
+}
+
+/// Pass a section body of `len` bytes to `f` and skip what it leaves unread
+///
+/// The limit of `message` is kept in sync with the bytes consumed, even on errors.
+fn read_body<R: Read>(
+    message: &mut std::io::Take<&mut R>,
+    len: u32,
+    f: impl FnOnce(&mut std::io::Take<&mut R>) -> Result<()>,
+) -> Result<()> {
+    let len = (len as u64).min(message.limit());
+    let limit = message.limit();
+    let inner: &mut R = message.get_mut();
+    let mut reader = inner.take(len);
+    let result = f(&mut reader).and_then(|_| {
+        std::io::copy(&mut reader, &mut std::io::sink())?;
+        Ok(())
+    });
+    let consumed = len - reader.limit();
+    message.set_limit(limit - consumed);
+    result
+}
+
+/// Read the 'GRIB' identifier of the next message
+///
+/// Returns `false` at the end of input. With `resync`, bytes before the identifier are skipped.
+pub(crate) fn read_identifier<R: Read>(reader: &mut R, resync: bool) -> Result<bool> {
+    let mut window = [0u8; 4];
+    let mut filled = 0;
+    loop {
+        match reader.read(&mut window[filled..]) {
+            Ok(0) if filled == 0 || resync => return Ok(false),
+            Ok(0) => return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+        if filled < 4 {
+            continue;
+        }
+        if &window == b"GRIB" {
+            return Ok(true);
+        }
+        if !resync {
+            return Err(Error::InvalidData(
+                "message identifier must be 'GRIB'".to_string(),
+            ));
+        }
+        window.rotate_left(1);
+        filled = 3;
+    }
+}
+
+/// Section read by [`SectionReader`]
+#[derive(Debug)]
+pub enum SectionEvent {
+    Indicator(IndicatorSectionHeader),
+    Identification {
+        header: IdentificationSectionHeader,
+        body: Vec<u8>,
+    },
+    LocalUse {
+        header: LocalUseSectionHeader,
+        body: Vec<u8>,
+    },
+    GridDefinition {
+        header: GridDefinitionSectionHeader,
+        body: Vec<u8>,
+    },
+    ProductDefinition {
+        header: ProductDefinitionSectionHeader,
+        body: Vec<u8>,
+    },
+    DataRepresentation {
+        header: DataRepresentationSectionHeader,
+        body: Vec<u8>,
+    },
+    Bitmap {
+        header: BitmapSectionHeader,
+        body: Vec<u8>,
+    },
+    Data {
+        header: DataSectionHeader,
+        body: Vec<u8>,
+    },
+    MessageEnd,
+}
+
+/// Reader adapter that counts the bytes read through it
+struct CountingReader<R> {
+    inner: R,
+    position: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+/// Progress of a [`SectionReader`], reported after each section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of bytes read from the underlying reader so far
+    pub bytes_read: u64,
+    /// Number of messages read to their end section
+    pub messages_completed: u64,
+    /// Number of the section just read (8 at the end of a message)
+    pub section: u8,
+}
+
+/// Pull-style alternative to [`MessageReader`] that yields one [`SectionEvent`] per section
+pub struct SectionReader<R: Read> {
+    reader: CountingReader<R>,
+    /// Number of the last section read, or `None` between messages
+    last_section: Option<u8>,
+    /// End position of the current message, from its `total_length`
+    message_end: Option<u64>,
+    resync: bool,
+    failed: bool,
+    messages_completed: u64,
+    /// Start position of the current message, or of the data being scanned for one
+    message_start: u64,
+    /// Start position of the last section read
+    section_start: u64,
+    /// Index of the current field within its message, or `None` before its first field
+    field_index: Option<usize>,
+    /// Whether the remaining sections of the current field are skipped
+    skipping: bool,
+    progress: Option<Box<dyn FnMut(Progress) -> Result<()>>>,
+    /// Section header octets, reused across sections
+    buffer: Vec<u8>,
+}
+
+impl<R: Read> SectionReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: CountingReader {
+                inner: reader,
+                position: 0,
+            },
+            last_section: None,
+            message_end: None,
+            resync: false,
+            failed: false,
+            messages_completed: 0,
+            message_start: 0,
+            section_start: 0,
+            field_index: None,
+            skipping: false,
+            progress: None,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Call `progress` after each section, e.g. to drive a progress bar
+    ///
+    /// Returning an error from `progress` (e.g. on a timeout) aborts reading with that error.
+    pub fn with_progress(mut self, progress: impl FnMut(Progress) -> Result<()> + 'static) -> Self {
+        self.progress = Some(Box::new(progress));
+        self
+    }
+
+    /// Skip bytes between messages until the next 'GRIB' identifier instead of failing
+    pub fn resync(mut self, enabled: bool) -> Self {
+        self.resync = enabled;
+        self
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut R {
+        &mut self.reader.inner
+    }
+
+    /// Number of the last section read, or `None` between messages
+    pub(crate) fn last_section(&self) -> Option<u8> {
+        self.last_section
+    }
+
+    /// Offset from the start of the stream of the message of the last section read
+    pub fn message_offset(&self) -> u64 {
+        self.message_start
+    }
+
+    /// Offset from the start of the stream of the last section read
+    pub fn section_offset(&self) -> u64 {
+        self.section_start
+    }
+
+    /// Index (0-based) within its message of the field of the last section read
+    ///
+    /// `None` for sections 0, 1 and 8, which belong to no field.
+    pub fn field_index(&self) -> Option<usize> {
+        match self.last_section {
+            Some(2..=7) => self.field_index,
+            _ => None,
+        }
+    }
+
+    /// Number of bytes read from the underlying reader so far
+    pub fn position(&self) -> u64 {
+        self.reader.position
+    }
+
+    /// Skip the remaining sections of the current field, e.g. after an unwanted product
+    /// definition
+    ///
+    /// Their bodies are passed over without being read into memory, and no event is yielded
+    /// for them; the next event is the start of the next field or `MessageEnd`. Does nothing
+    /// outside a field or after its data section.
+    pub fn skip_field(&mut self) {
+        self.skipping = matches!(self.last_section, Some(2..=6));
+    }
+
+    /// Skip the rest of the current message using its `total_length`
+    ///
+    /// This recovers from an error in the body of a message so that reading can continue
+    /// with the next message.
+    pub fn skip_message(&mut self) -> Result<()> {
+        let Some(end) = self.message_end.take() else {
+            return Err(Error::InvalidData(
+                "no message to skip: its indicator section was not read".to_string(),
+            ));
+        };
+        let position = self.reader.position;
+        if position > end {
+            return Err(Error::InvalidData(
+                "message has been read past its total length".to_string(),
+            ));
+        }
+        std::io::copy(
+            &mut (&mut self.reader).take(end - position),
+            &mut std::io::sink(),
+        )?;
+        self.last_section = None;
+        self.field_index = None;
+        self.skipping = false;
+        self.failed = false;
+        Ok(())
+    }
+
+    /// Read the next section, or `None` at the end of input
+    ///
+    /// Errors are wrapped in `Error::InMessage` with the index and offset of the message.
+    pub fn next_event(&mut self) -> Result<Option<SectionEvent>> {
+        let event = self.read_event().map_err(|e| Error::InMessage {
+            message: self.messages_completed,
+            offset: self.message_start,
+            source: Box::new(e),
+        })?;
+        let section = match &event {
+            None => return Ok(None),
+            Some(SectionEvent::MessageEnd) => {
+                self.messages_completed += 1;
+                8
+            }
+            Some(_) => self.last_section.unwrap_or(0),
+        };
+        if let Some(progress) = &mut self.progress {
+            progress(Progress {
+                bytes_read: self.reader.position,
+                messages_completed: self.messages_completed,
+                section,
+            })?;
+        }
+        Ok(event)
+    }
+
+    fn read_event(&mut self) -> Result<Option<SectionEvent>> {
+        if self.last_section.is_none() {
+            return self.read_indicator();
+        }
+        self.read_section().map_err(|e| truncated(e, &self.reader))
+    }
+
+    fn read_indicator(&mut self) -> Result<Option<SectionEvent>> {
+        let reader = &mut self.reader;
+        self.message_end = None;
+        self.message_start = reader.position;
+        self.field_index = None;
+        self.skipping = false;
+        if !read_identifier(reader, self.resync)? {
+            return Ok(None);
+        }
+        // the identifier may have been preceded by skipped bytes
+        self.message_start = reader.position - 4;
+        self.section_start = self.message_start;
+        let is = IndicatorSectionHeader::read(reader).map_err(|e| truncated(e, reader))?;
+        self.message_end = Some(self.message_start + is.total_length);
+        self.last_section = Some(0);
+        Ok(Some(SectionEvent::Indicator(is)))
+    }
+
+    fn read_section(&mut self) -> Result<Option<SectionEvent>> {
+        let reader = &mut self.reader;
+        let Some(last_section) = self.last_section else {
+            unreachable!("read_indicator reads the first section of a message")
+        };
+
+        let section_start = reader.position;
+        self.section_start = section_start;
+        let message_end = self.message_end.unwrap_or(u64::MAX);
+        let header = SectionHeader::read(
+            reader,
+            allows_end(last_section, message_end - section_start),
+        )?;
+        if section_start + header.section_length as u64 > message_end {
+            return Err(Error::InvalidData(format!(
+                "section {} exceeds the end of the message at offset {}",
+                header.number_of_section, message_end
+            )));
+        }
+        if !can_follow(last_section, header.number_of_section) {
+            return Err(order_error(last_section, header.number_of_section));
+        }
+        self.last_section = Some(header.number_of_section);
+        if matches!(last_section, 1 | 7) && header.number_of_section != 8 {
+            self.field_index = Some(self.field_index.map_or(0, |i| i + 1));
+        }
+        if self.skipping && matches!(last_section, 2..=6) {
+            let len = header.section_length.saturating_sub(5) as u64;
+            let skipped = std::io::copy(&mut reader.take(len), &mut std::io::sink())?;
+            if skipped != len {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            return self.read_section();
+        }
+        self.skipping = false;
+
+        // read the rest of the section header at once and parse it from memory
+        let header_len = match header.number_of_section {
+            1 => 18,
+            3 => 9,
+            4 => 4,
+            5 => 6,
+            6 => 1,
+            _ => 0,
+        }
+        .min(header.section_length.saturating_sub(5));
+        let buffer = &mut self.buffer;
+        buffer.clear();
+        buffer.resize(
+            check_allocation::<u8>(header_len as usize, "section header")?,
+            0,
+        );
+        reader.read_exact(buffer)?;
+        let octets = &mut buffer.as_slice();
+
+        fn read_body<R: Read>(reader: &mut R, len: u32) -> Result<Vec<u8>> {
+            let mut body =
+                Vec::with_capacity(check_allocation::<u8>(len as usize, "section body")?);
+            reader.take(len as u64).read_to_end(&mut body)?;
+            if body.len() != len as usize {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            Ok(body)
+        }
 
-                // Bit-Map Section (6)
-                {
-                    let bitmap =
-                        BitmapSectionHeader::read(&SectionHeader::read(reader, false)?, reader)?;
-                    let mut reader = reader.take(bitmap.body_len() as u64);
-                    self.handle_bitmap(bitmap, &mut reader)?;
-                    std::io::copy(&mut reader, &mut std::io::sink())?;
+        Ok(Some(match header.number_of_section {
+            1 => {
+                let header = IdentificationSectionHeader::read(header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::Identification { header, body }
+            }
+            2 => {
+                let header = LocalUseSectionHeader::read(header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::LocalUse { header, body }
+            }
+            3 => {
+                let header = GridDefinitionSectionHeader::read(&header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::GridDefinition { header, body }
+            }
+            4 => {
+                let header = ProductDefinitionSectionHeader::read(&header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::ProductDefinition { header, body }
+            }
+            5 => {
+                let header = DataRepresentationSectionHeader::read(&header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::DataRepresentation { header, body }
+            }
+            6 => {
+                let header = BitmapSectionHeader::read(&header, octets)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::Bitmap { header, body }
+            }
+            7 => {
+                let header = DataSectionHeader::read(&header)?;
+                let body = read_body(reader, header.body_len())?;
+                SectionEvent::Data { header, body }
+            }
+            _ => {
+                if reader.position != message_end {
+                    return Err(Error::InvalidData(format!(
+                        "message ends at offset {}, but its total length ends it at offset {}",
+                        reader.position, message_end
+                    )));
                 }
+                self.last_section = None;
+                self.message_end = None;
+                SectionEvent::MessageEnd
+            }
+        }))
+    }
+}
+
+/// Sections that may follow section `last` within a message
+///
+/// Sections 2 to 7 follow in order, and after section 7 the next field may repeat them from
+/// section 2, 3 or 4, the sections not repeated remaining in effect.
+fn sections_after(last: u8) -> &'static [u8] {
+    match last {
+        0 => &[1],
+        1 => &[2, 3],
+        2 => &[3],
+        3 => &[4],
+        4 => &[5],
+        5 => &[6],
+        6 => &[7],
+        _ => &[2, 3, 4, 8],
+    }
+}
+
+/// Whether '7777' read after section `last`, with `remaining` octets left in the message, is
+/// the end section
+///
+/// Before section 7 it is only taken as the end section if a section of length 0x37373737
+/// would not fit, so that an end section out of order is reported as such.
+fn allows_end(last: u8, remaining: u64) -> bool {
+    last == 7 || remaining < 0x3737_3737
+}
 
-                // Data Section (7)
-                {
-                    let data = DataSectionHeader::read(&SectionHeader::read(reader, false)?)?;
-                    let mut reader = reader.take(data.body_len() as u64);
-                    self.handle_data(data, &mut reader)?;
-                    std::io::copy(&mut reader, &mut std::io::sink())?;
+/// Whether section `next` may follow section `last` within a message
+pub(crate) fn can_follow(last: u8, next: u8) -> bool {
+    sections_after(last).contains(&next)
+}
+
+/// Error for section `next` following section `last` against [`can_follow`]
+fn order_error(last: u8, next: u8) -> Error {
+    let expected = match sections_after(last) {
+        [rest @ .., final_section] if !rest.is_empty() => format!(
+            "{} or {}",
+            rest.iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            final_section
+        ),
+        sections => sections[0].to_string(),
+    };
+    Error::InvalidData(format!(
+        "section {} cannot follow section {} (expected section {})",
+        next, last, expected
+    ))
+}
+
+/// Report an unexpected end of input within a message as `Error::Truncated`
+fn truncated<R>(error: Error, reader: &CountingReader<R>) -> Error {
+    match error {
+        Error::IO(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => Error::Truncated {
+            offset: reader.position,
+        },
+        e => e,
+    }
+}
+
+impl<R: Read> Iterator for SectionReader<R> {
+    type Item = Result<SectionEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(e) => {
+                // the position within the stream is unknown after an error,
+                // unless the caller recovers with skip_message()
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::{IdentificationTemplate, IdentificationTemplate1_0};
+
+    /// Message made of an indicator and identification section followed by `sections`, each
+    /// with the minimum header for its number
+    fn message(sections: &[u8]) -> Vec<u8> {
+        let mut body = vec![
+            0, 0, 0, 21, 1, 0, 34, 0, 0, 2, 1, 1, 7, 232, 1, 1, 0, 0, 0, 0, 1,
+        ];
+        for &section in sections {
+            let octets: &[u8] = match section {
+                2 => &[0, 0, 0, 6, 2, 1],
+                3 => &[0, 0, 0, 14, 3, 0, 0, 0, 0, 1, 0, 0, 0, 0],
+                4 => &[0, 0, 0, 9, 4, 0, 0, 0, 0],
+                5 => &[0, 0, 0, 11, 5, 0, 0, 0, 1, 0, 0],
+                6 => &[0, 0, 0, 6, 6, 255],
+                7 => &[0, 0, 0, 5, 7],
+                _ => b"7777",
+            };
+            body.extend_from_slice(octets);
+        }
+        let mut bytes = b"GRIB\0\0\0\x02".to_vec();
+        bytes.extend((16 + body.len() as u64).to_be_bytes());
+        bytes.extend(body);
+        bytes
+    }
+
+    /// Sections handled for each field, by field index
+    #[derive(Default)]
+    struct Recorder {
+        fields: Vec<(usize, Vec<u8>)>,
+    }
+
+    impl<R: Read> MessageReader<R> for Recorder {
+        fn handle_field_start(&mut self, field_index: usize) -> Result<()> {
+            self.fields.push((field_index, Vec::new()));
+            Ok(())
+        }
+
+        fn handle_section_start(&mut self, section_number: u8, _offset: u64) -> Result<()> {
+            if let Some((_, sections)) = self.fields.last_mut() {
+                sections.push(section_number);
+            }
+            Ok(())
+        }
+    }
+
+    fn read_fields(sections: &[u8]) -> Result<Vec<(usize, Vec<u8>)>> {
+        let bytes = message(sections);
+        let mut recorder = Recorder::default();
+        recorder.read_next_message(&mut bytes.as_slice())?;
+        Ok(recorder.fields)
+    }
+
+    fn stream_error(sections: &[u8]) -> Error {
+        let bytes = message(sections);
+        let mut reader = SectionReader::new(bytes.as_slice());
+        loop {
+            match reader.next_event() {
+                Ok(Some(_)) => {}
+                Ok(None) => panic!("sections {:?} were accepted", sections),
+                Err(e) => return e,
+            }
+        }
+    }
+
+    /// Message of `sections` whose Section 1 carries template 1.0 (Gregorian calendar)
+    fn message_with_calendar(sections: &[u8]) -> Vec<u8> {
+        let mut bytes = message(sections);
+        bytes[19] = 24;
+        bytes.splice(37..37, [0, 0, 1]);
+        let total_length = bytes.len() as u64;
+        bytes[8..16].copy_from_slice(&total_length.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn identification_template() {
+        let bytes = message_with_calendar(&[3, 4, 5, 6, 7, 8]);
+
+        #[derive(Default)]
+        struct Calendar(Vec<u8>);
+        impl<R: Read> MessageReader<R> for Calendar {
+            fn handle_identification(
+                &mut self,
+                ids: IdentificationSectionHeader,
+                reader: &mut std::io::Take<&mut R>,
+            ) -> Result<()> {
+                assert_eq!(ids.template_number, Some(0));
+                assert_eq!(ids.body_len(), 1);
+                reader.read_to_end(&mut self.0)?;
+                Ok(())
+            }
+        }
+        let mut calendar = Calendar::default();
+        calendar.read_next_message(&mut bytes.as_slice()).unwrap();
+        assert_eq!(calendar.0, [1]);
+
+        let mut reader = SectionReader::new(bytes.as_slice());
+        reader.next_event().unwrap();
+        let Some(SectionEvent::Identification { header, body }) = reader.next_event().unwrap()
+        else {
+            panic!("section 1 follows section 0");
+        };
+        assert_eq!(body, [1]);
+        let template = header.read_template(&mut body.as_slice()).unwrap();
+        assert!(matches!(
+            template,
+            Some(IdentificationTemplate::Template1_0(
+                IdentificationTemplate1_0 {
+                    type_of_calendar: 1
                 }
+            ))
+        ));
+
+        let message = crate::field::Message::read(&mut bytes.as_slice())
+            .unwrap()
+            .unwrap();
+        let mut written = Vec::new();
+        message.write(&mut written).unwrap();
+        assert_eq!(written, bytes);
+    }
+
+    #[test]
+    fn short_section_headers() {
+        // sections 3, 4 and 5 cut right after their section number
+        for section in [3u8, 4, 5] {
+            let mut bytes = message(&[3, 4, 5, 6, 7, 8]);
+            let start = 37 + [0, 14, 23][section as usize - 3];
+            let length = [14, 9, 11][section as usize - 3];
+            bytes.splice(start..start + length, [0, 0, 0, 5, section]);
+            let total_length = bytes.len() as u64;
+            bytes[8..16].copy_from_slice(&total_length.to_be_bytes());
 
-                // Next Section
-                next_header = SectionHeader::read(reader, true)?;
-                match next_header.number_of_section {
-                    2 | 3 => break,
-                    4 => {}
-                    8 => break 'outer,
-                    _ => return Err(Error::InvalidData("invalid section number".to_string())),
+            let error = Recorder::default()
+                .read_next_message(&mut bytes.as_slice())
+                .unwrap_err();
+            let mut reader = SectionReader::new(bytes.as_slice());
+            let stream_error = loop {
+                match reader.next_event() {
+                    Ok(Some(_)) => {}
+                    Ok(None) => panic!("section {} was accepted", section),
+                    Err(e) => break e,
                 }
-            }
+            };
+            assert_eq!(
+                error.root().to_string(),
+                stream_error.root().to_string(),
+                "section {}",
+                section
+            );
         }
+    }
 
-        Ok(Some(()))
+    #[test]
+    fn legal_restarts() {
+        assert_eq!(
+            read_fields(&[2, 3, 4, 5, 6, 7, 4, 5, 6, 7, 8]).unwrap(),
+            [(0, vec![2, 3, 4, 5, 6, 7]), (1, vec![4, 5, 6, 7, 8])]
+        );
+        assert_eq!(
+            read_fields(&[3, 4, 5, 6, 7, 3, 4, 5, 6, 7, 8]).unwrap(),
+            [(0, vec![3, 4, 5, 6, 7]), (1, vec![3, 4, 5, 6, 7, 8])]
+        );
+        assert_eq!(
+            read_fields(&[2, 3, 4, 5, 6, 7, 2, 3, 4, 5, 6, 7, 4, 5, 6, 7, 8]).unwrap(),
+            [
+                (0, vec![2, 3, 4, 5, 6, 7]),
+                (1, vec![2, 3, 4, 5, 6, 7]),
+                (2, vec![4, 5, 6, 7, 8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn illegal_orderings() {
+        for (sections, last, next) in [
+            (&[3, 4, 5, 6, 7, 5, 6, 7, 8][..], 7, 5),
+            (&[2, 4, 5, 6, 7, 8][..], 2, 4),
+            (&[4, 5, 6, 7, 8][..], 1, 4),
+            (&[3, 3, 4, 5, 6, 7, 8][..], 3, 3),
+            (&[3, 4, 5, 6, 8][..], 6, 8),
+        ] {
+            let expected = order_error(last, next).to_string();
+            let error = read_fields(sections).unwrap_err();
+            assert_eq!(error.to_string(), expected, "sections {:?}", sections);
+            let error = stream_error(sections);
+            assert_eq!(
+                error.root().to_string(),
+                expected,
+                "sections {:?}",
+                sections
+            );
+        }
     }
 }
@@ -0,0 +1,164 @@
+use crate::field::DecodedField;
+use crate::templates::{GridDefinitionTemplate, png};
+use crate::{Error, Result};
+
+/// Mapping from values to RGBA colors by linear interpolation between stops
+///
+/// Values below the first stop or above the last one get the color of that stop; missing
+/// values (NAN) get `missing`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Colormap {
+    stops: Vec<(f32, [u8; 4])>,
+    pub missing: [u8; 4],
+}
+
+impl Colormap {
+    /// Colormap from `(value, color)` stops in increasing order of value
+    pub fn new(stops: Vec<(f32, [u8; 4])>) -> Result<Self> {
+        if stops.is_empty() {
+            return Err(Error::InvalidData("a colormap needs a stop".to_string()));
+        }
+        if stops.iter().any(|(v, _)| !v.is_finite()) || !stops.is_sorted_by(|a, b| a.0 < b.0) {
+            return Err(Error::InvalidData(
+                "colormap stops must be finite and strictly increasing".to_string(),
+            ));
+        }
+        Ok(Self {
+            stops,
+            missing: [0, 0, 0, 0],
+        })
+    }
+
+    /// Black to white over `min..=max`
+    pub fn grayscale(min: f32, max: f32) -> Result<Self> {
+        Self::new(vec![(min, [0, 0, 0, 255]), (max, [255, 255, 255, 255])])
+    }
+
+    /// Viridis (sampled at 9 points) over `min..=max`
+    pub fn viridis(min: f32, max: f32) -> Result<Self> {
+        const COLORS: [[u8; 3]; 9] = [
+            [68, 1, 84],
+            [71, 45, 123],
+            [59, 82, 139],
+            [44, 114, 142],
+            [33, 145, 140],
+            [40, 174, 128],
+            [94, 201, 98],
+            [173, 220, 48],
+            [253, 231, 37],
+        ];
+        let step = (max - min) / (COLORS.len() - 1) as f32;
+        Self::new(
+            COLORS
+                .iter()
+                .enumerate()
+                .map(|(i, &[r, g, b])| (min + step * i as f32, [r, g, b, 255]))
+                .collect(),
+        )
+    }
+
+    pub fn stops(&self) -> &[(f32, [u8; 4])] {
+        &self.stops
+    }
+
+    /// Color of `value`
+    pub fn color(&self, value: f32) -> [u8; 4] {
+        if value.is_nan() {
+            return self.missing;
+        }
+        let i = self.stops.partition_point(|(v, _)| *v <= value);
+        if i == 0 {
+            return self.stops[0].1;
+        }
+        if i == self.stops.len() {
+            return self.stops[i - 1].1;
+        }
+        let ((v0, c0), (v1, c1)) = (self.stops[i - 1], self.stops[i]);
+        let t = (value - v0) / (v1 - v0);
+        std::array::from_fn(|k| (c0[k] as f32 + (c1[k] as f32 - c0[k] as f32) * t).round() as u8)
+    }
+}
+
+/// How grid values are sampled at pixel centres
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    Nearest,
+    /// Linear in both directions between the four surrounding grid points; a pixel is missing
+    /// if any of them is
+    Bilinear,
+}
+
+/// Render `field` as an RGBA image of `width` x `height` pixels (4 octets per pixel, row by row)
+///
+/// The image covers the grid from the first to the last row and column without reprojection.
+/// For Template 3.0 the rows and columns are ordered north to south and west to east
+/// according to the scanning mode; other grids are drawn in scanning order.
+pub fn render_rgba(
+    field: &DecodedField,
+    colormap: &Colormap,
+    width: u32,
+    height: u32,
+    resampling: Resampling,
+) -> Result<Vec<u8>> {
+    if width == 0 || height == 0 {
+        return Err(Error::InvalidData(
+            "image size must not be zero".to_string(),
+        ));
+    }
+    let (nj, ni) = field.shape;
+    if nj == 0 || ni == 0 {
+        return Err(Error::InvalidData("field has no values".to_string()));
+    }
+    let (flip_i, flip_j) = match &*field.grid_definition {
+        GridDefinitionTemplate::Template3_0(t) => {
+            (t.scanning_mode & 0x80 != 0, t.scanning_mode & 0x40 != 0)
+        }
+        _ => (false, false),
+    };
+    let value = |j: usize, i: usize| {
+        let j = if flip_j { nj - 1 - j } else { j };
+        let i = if flip_i { ni - 1 - i } else { i };
+        field.values[j * ni + i]
+    };
+    // grid coordinate of the centre of pixel `p` out of `n`, for `size` grid points
+    let coordinate = |p: u32, n: u32, size: usize| {
+        ((p as f32 + 0.5) * size as f32 / n as f32 - 0.5).clamp(0.0, (size - 1) as f32)
+    };
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for y in 0..height {
+        let fj = coordinate(y, height, nj);
+        for x in 0..width {
+            let fi = coordinate(x, width, ni);
+            let v = match resampling {
+                Resampling::Nearest => value(fj.round() as usize, fi.round() as usize),
+                Resampling::Bilinear => {
+                    let (j0, i0) = (fj.floor() as usize, fi.floor() as usize);
+                    let (j1, i1) = ((j0 + 1).min(nj - 1), (i0 + 1).min(ni - 1));
+                    let (tj, ti) = (fj - j0 as f32, fi - i0 as f32);
+                    let top = value(j0, i0) * (1.0 - ti) + value(j0, i1) * ti;
+                    let bottom = value(j1, i0) * (1.0 - ti) + value(j1, i1) * ti;
+                    top * (1.0 - tj) + bottom * tj
+                }
+            };
+            rgba.extend_from_slice(&colormap.color(v));
+        }
+    }
+    Ok(rgba)
+}
+
+/// Render `field` as a PNG image (see [`render_rgba`])
+pub fn render_png(
+    field: &DecodedField,
+    colormap: &Colormap,
+    width: u32,
+    height: u32,
+    resampling: Resampling,
+) -> Result<Vec<u8>> {
+    let rgba = render_rgba(field, colormap, width, height, resampling)?;
+    let samples = rgba
+        .chunks_exact(4)
+        .map(|p| u32::from_be_bytes([p[0], p[1], p[2], p[3]]))
+        .collect::<Vec<_>>();
+    Ok(png::encode(&samples, width, height, 32))
+}
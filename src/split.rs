@@ -0,0 +1,282 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::field::{Field, Message};
+use crate::limits::check_allocation;
+use crate::message::IndicatorSectionHeader;
+use crate::reader::read_identifier;
+use crate::{Error, Result};
+
+/// Byte range of a message within a stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MessageRange {
+    /// Byte offset of the message (Section 0)
+    pub offset: u64,
+    /// Total length of the message
+    pub length: u64,
+}
+
+impl MessageRange {
+    /// End of the message (exclusive)
+    pub fn end(&self) -> u64 {
+        self.offset + self.length
+    }
+}
+
+/// Iterator over the messages of a concatenated file, located by Section 0 `total_length` only
+///
+/// Nothing but the indicator section is parsed, so messages are not decoded or validated.
+pub struct MessageRanges<R: Read> {
+    reader: R,
+    position: u64,
+    failed: bool,
+}
+
+impl<R: Read> MessageRanges<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            position: 0,
+            failed: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Locate the next message and copy its bytes to `output`, or skip them if `None`
+    pub fn next_range(&mut self, output: Option<&mut dyn Write>) -> Result<Option<MessageRange>> {
+        let Some((range, indicator)) = self.read_indicator()? else {
+            return Ok(None);
+        };
+        match output {
+            Some(output) => self.copy_message(&range, &indicator, output)?,
+            None => self.copy_message(&range, &indicator, &mut std::io::sink())?,
+        }
+        Ok(Some(range))
+    }
+
+    /// Read Section 0 and return the range of the message with its indicator bytes
+    fn read_indicator(&mut self) -> Result<Option<(MessageRange, [u8; 12])>> {
+        read_indicator(&mut self.reader, self.position)
+    }
+
+    /// Copy the message whose indicator section has just been read
+    fn copy_message(
+        &mut self,
+        range: &MessageRange,
+        indicator: &[u8; 12],
+        output: &mut dyn Write,
+    ) -> Result<()> {
+        output.write_all(b"GRIB")?;
+        output.write_all(indicator)?;
+        let copied = std::io::copy(&mut (&mut self.reader).take(range.length - 16), output)?;
+        if copied != range.length - 16 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        self.position += range.length;
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for MessageRanges<R> {
+    type Item = Result<MessageRange>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        match self.next_range(None) {
+            Ok(range) => range.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Read the Section 0 of a message found at `offset` and return its range with its indicator
+/// bytes, or `None` at the end of input
+fn read_indicator<R: Read>(
+    reader: &mut R,
+    offset: u64,
+) -> Result<Option<(MessageRange, [u8; 12])>> {
+    if !read_identifier(reader, false)? {
+        return Ok(None);
+    }
+    let mut buf = [0; 12];
+    reader.read_exact(&mut buf)?;
+    let is = IndicatorSectionHeader::read(&mut buf.as_slice())?;
+    if is.total_length < 16 {
+        return Err(Error::InvalidData(format!(
+            "total length of message is too short: {}",
+            is.total_length
+        )));
+    }
+    let range = MessageRange {
+        offset,
+        length: is.total_length,
+    };
+    Ok(Some((range, buf)))
+}
+
+/// Random access to the messages of a concatenated file
+///
+/// Messages are located by following the chain of Section 0 `total_length`, seeking over
+/// their contents, so that reading the `n`-th message only reads the indicator sections of
+/// the messages before it. The ranges located are remembered for later calls.
+pub struct MessageFile<R: Read + Seek> {
+    reader: R,
+    ranges: Vec<MessageRange>,
+    /// Whether the end of input has been reached
+    complete: bool,
+}
+
+impl<R: Read + Seek> MessageFile<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            ranges: Vec::new(),
+            complete: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Byte range of the `n`-th message (0-based), or `None` if there are fewer messages
+    pub fn message_range(&mut self, n: usize) -> Result<Option<MessageRange>> {
+        while self.ranges.len() <= n && !self.complete {
+            let offset = self.ranges.last().map_or(0, MessageRange::end);
+            self.reader.seek(SeekFrom::Start(offset))?;
+            match read_indicator(&mut self.reader, offset)? {
+                Some((range, _)) => self.ranges.push(range),
+                None => self.complete = true,
+            }
+        }
+        Ok(self.ranges.get(n).copied())
+    }
+
+    /// Number of messages in the file, locating all of them
+    pub fn message_count(&mut self) -> Result<usize> {
+        while !self.complete {
+            self.message_range(self.ranges.len())?;
+        }
+        Ok(self.ranges.len())
+    }
+
+    /// Read the bytes of the `n`-th message
+    pub fn message_bytes(&mut self, n: usize) -> Result<Option<Vec<u8>>> {
+        let Some(range) = self.message_range(n)? else {
+            return Ok(None);
+        };
+        let len = check_allocation::<u8>(range.length as usize, "message")?;
+        let mut buf = vec![0; len];
+        self.reader.seek(SeekFrom::Start(range.offset))?;
+        self.reader.read_exact(&mut buf)?;
+        Ok(Some(buf))
+    }
+
+    /// Read and parse the `n`-th message
+    pub fn message(&mut self, n: usize) -> Result<Option<Message>> {
+        let Some(bytes) = self.message_bytes(n)? else {
+            return Ok(None);
+        };
+        parse_message(&bytes, n as u64, self.ranges[n].offset).map(Some)
+    }
+
+    /// Read the `k`-th field of the `n`-th message
+    pub fn field(&mut self, n: usize, k: usize) -> Result<Option<Field>> {
+        Ok(self
+            .message(n)?
+            .and_then(|message| message.fields.into_iter().nth(k)))
+    }
+}
+
+/// Split a concatenated file into one output per message without decoding
+///
+/// `output` is called with the index and byte range of each message and returns its writer.
+pub fn split_messages<R: Read, W: Write>(
+    reader: R,
+    mut output: impl FnMut(usize, &MessageRange) -> Result<W>,
+) -> Result<Vec<MessageRange>> {
+    let mut ranges = MessageRanges::new(reader);
+    let mut result = Vec::new();
+    while let Some((range, indicator)) = ranges.read_indicator()? {
+        let mut writer = output(result.len(), &range)?;
+        ranges.copy_message(&range, &indicator, &mut writer)?;
+        writer.flush()?;
+        result.push(range);
+    }
+    Ok(result)
+}
+
+/// Copy the messages accepted by `predicate` to `writer` byte for byte
+///
+/// Returns the number of messages copied.
+pub fn extract_messages<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    mut predicate: impl FnMut(&Message) -> bool,
+) -> Result<u64> {
+    extract_fields(reader, writer, |message, _| predicate(message))
+}
+
+/// Copy the fields accepted by `predicate` to `writer` without re-encoding
+///
+/// Messages whose fields are all accepted are copied byte for byte. Otherwise the accepted
+/// fields are written as a new message with their sections unchanged. Returns the number of
+/// messages written.
+pub fn extract_fields<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    mut predicate: impl FnMut(&Message, &Field) -> bool,
+) -> Result<u64> {
+    let mut ranges = MessageRanges::new(reader);
+    let mut buf = Vec::new();
+    let mut index = 0;
+    let mut count = 0;
+    while let Some((range, indicator)) = ranges.read_indicator()? {
+        buf.clear();
+        ranges.copy_message(&range, &indicator, &mut buf)?;
+        let message = parse_message(&buf, index, range.offset)?;
+        index += 1;
+        let selected = message
+            .fields
+            .iter()
+            .filter(|field| predicate(&message, field))
+            .collect::<Vec<_>>();
+        if selected.is_empty() {
+            continue;
+        }
+        if selected.len() == message.fields.len() {
+            writer.write_all(&buf)?;
+        } else {
+            let mut output = message.writer()?;
+            for field in selected {
+                output.write_field(field)?;
+            }
+            output.finish(writer)?;
+        }
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Parse the message `bytes` found at `offset`, attributing errors to it
+pub(crate) fn parse_message(bytes: &[u8], index: u64, offset: u64) -> Result<Message> {
+    let in_message = |e: Error| Error::InMessage {
+        message: index,
+        offset,
+        source: Box::new(match e {
+            // errors from SectionReader are relative to the message
+            Error::InMessage { source, .. } => *source,
+            e => e,
+        }),
+    };
+    Message::read(&mut &bytes[..])
+        .map_err(in_message)?
+        .ok_or_else(|| in_message(Error::InvalidData("no message in range".to_string())))
+}
@@ -0,0 +1,212 @@
+use crate::codes::FixedSurfaceType;
+
+/// Fixed surface (type, scale factor and scaled value) of a product definition template
+///
+/// The physical value is `scaled_value * 10^-scale_factor` in the unit of the surface type (Code table 4.5).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedSurface {
+    pub type_of_surface: u8,
+    pub scale_factor: i8,
+    pub scaled_value: u32,
+}
+
+impl FixedSurface {
+    pub fn new(type_of_surface: u8, scale_factor: i8, scaled_value: u32) -> Self {
+        Self {
+            type_of_surface,
+            scale_factor,
+            scaled_value,
+        }
+    }
+
+    /// Type of the surface (Code table 4.5)
+    pub fn surface_type(&self) -> FixedSurfaceType {
+        self.type_of_surface.into()
+    }
+
+    /// Whether the surface itself is missing (type 255)
+    pub fn is_missing(&self) -> bool {
+        self.type_of_surface == 255
+    }
+
+    /// Physical value of the surface in `unit()`, or `None` if the surface or its value is missing
+    pub fn value(&self) -> Option<f64> {
+        // an all-ones scale factor octet is read as -127 (sign and magnitude)
+        if self.is_missing() || self.scale_factor == -127 || self.scaled_value == u32::MAX {
+            return None;
+        }
+        Some(self.scaled_value as f64 * 10f64.powi(-(self.scale_factor as i32)))
+    }
+
+    /// Name of the surface type (Code table 4.5)
+    pub fn name(&self) -> &'static str {
+        match self.type_of_surface {
+            1 => "Ground or water surface",
+            2 => "Cloud base level",
+            3 => "Level of cloud tops",
+            4 => "Level of 0 °C isotherm",
+            5 => "Level of adiabatic condensation lifted from the surface",
+            6 => "Maximum wind level",
+            7 => "Tropopause",
+            8 => "Nominal top of the atmosphere",
+            9 => "Sea bottom",
+            10 => "Entire atmosphere",
+            11 => "Cumulonimbus base",
+            12 => "Cumulonimbus top",
+            13 => {
+                "Lowest level where vertically integrated cloud cover exceeds the specified percentage"
+            }
+            14 => "Level of free convection",
+            15 => "Convective condensation level",
+            16 => "Level of neutral buoyancy or equilibrium level",
+            20 => "Isothermal level",
+            21 => "Lowest level where mass density exceeds the specified value",
+            22 => "Highest level where mass density exceeds the specified value",
+            100 => "Isobaric surface",
+            101 => "Mean sea level",
+            102 => "Specific altitude above mean sea level",
+            103 => "Specified height level above ground",
+            104 => "Sigma level",
+            105 => "Hybrid level",
+            106 => "Depth below land surface",
+            107 => "Isentropic (theta) level",
+            108 => "Level at specified pressure difference from ground to level",
+            109 => "Potential vorticity surface",
+            111 => "Eta level",
+            113 => "Logarithmic hybrid level",
+            114 => "Snow level",
+            117 => "Mixed layer depth",
+            118 => "Hybrid height level",
+            119 => "Hybrid pressure level",
+            150 => "Generalized vertical height coordinate",
+            151 => "Soil level",
+            160 => "Depth below sea level",
+            161 => "Depth below water surface",
+            162 => "Lake or river bottom",
+            163 => "Bottom of sediment layer",
+            164 => "Bottom of thermally active sediment layer",
+            165 => "Bottom of sediment layer penetrated by thermal wave",
+            166 => "Mixing layer",
+            167 => "Bottom of root zone",
+            168 => "Ocean model level",
+            174 => "Top surface of ice on sea, lake or river",
+            175 => "Top surface of ice, under snow, on sea, lake or river",
+            176 => "Bottom surface (underside) ice on sea, lake or river",
+            177 => "Deep soil",
+            255 => "Missing",
+            _ => "Unknown",
+        }
+    }
+
+    /// Unit of the surface value (Code table 4.5)
+    pub fn unit(&self) -> &'static str {
+        match self.type_of_surface {
+            4 | 20 | 107 => "K",
+            13 | 166 => "%",
+            21 | 22 => "kg m-3",
+            100 | 108 => "Pa",
+            102 | 103 | 106 | 117 | 160 | 161 => "m",
+            109 => "K m2 kg-1 s-1",
+            _ => "",
+        }
+    }
+
+    /// Human-readable description such as "850 hPa" or "2 m above ground"
+    pub fn description(&self) -> String {
+        let Some(value) = self.value() else {
+            return self.name().to_string();
+        };
+        match self.type_of_surface {
+            100 => format!("{} hPa", value / 100.0),
+            102 => format!("{} m above mean sea level", value),
+            103 => format!("{} m above ground", value),
+            106 => format!("{} m below land surface", value),
+            108 => format!("{} hPa above ground", value / 100.0),
+            160 => format!("{} m below sea level", value),
+            161 => format!("{} m below water surface", value),
+            // dimensionless vertical coordinates
+            104 | 105 | 111 | 113 | 118 | 119 | 150 | 151 | 168 => {
+                format!("{} {}", self.name(), value)
+            }
+            _ => match self.unit() {
+                "" => self.name().to_string(),
+                unit => format!("{} {} {}", self.name(), value, unit),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates::ProductDefinitionTemplate;
+
+    fn assert_value(surface: FixedSurface, expected: f64) {
+        let value = surface.value().unwrap();
+        assert!((value - expected).abs() < 1e-9, "{} != {}", value, expected);
+    }
+
+    #[test]
+    fn value() {
+        assert_value(FixedSurface::new(100, 0, 85000), 85000.0);
+        assert_value(FixedSurface::new(106, 1, 5), 0.5);
+        assert_value(FixedSurface::new(106, 2, 25), 0.25);
+        assert_value(FixedSurface::new(102, -2, 15), 1500.0);
+        assert_value(FixedSurface::new(104, 4, 9950), 0.995);
+        assert_value(FixedSurface::new(103, -126, 0), 0.0);
+    }
+
+    #[test]
+    fn missing_value() {
+        assert_eq!(FixedSurface::new(100, -127, 85000).value(), None);
+        assert_eq!(FixedSurface::new(100, 0, u32::MAX).value(), None);
+        assert_eq!(FixedSurface::new(255, 0, 0).value(), None);
+        assert!(FixedSurface::new(255, 0, 0).is_missing());
+        assert!(!FixedSurface::new(1, -127, u32::MAX).is_missing());
+        assert_eq!(
+            FixedSurface::new(1, -127, u32::MAX).description(),
+            "Ground or water surface"
+        );
+        assert_eq!(
+            FixedSurface::new(255, -127, u32::MAX).description(),
+            "Missing"
+        );
+    }
+
+    #[test]
+    fn description() {
+        assert_eq!(FixedSurface::new(100, 0, 85000).description(), "850 hPa");
+        assert_eq!(
+            FixedSurface::new(103, 0, 2).description(),
+            "2 m above ground"
+        );
+        assert_eq!(
+            FixedSurface::new(102, -2, 15).description(),
+            "1500 m above mean sea level"
+        );
+        assert_eq!(
+            FixedSurface::new(105, 0, 37).description(),
+            "Hybrid level 37"
+        );
+        assert_eq!(
+            FixedSurface::new(20, 0, 263).description(),
+            "Isothermal level 263 K"
+        );
+    }
+
+    #[test]
+    fn scale_factors_of_templates() {
+        // sign and magnitude scale factors: -2 for the first surface, missing for the second
+        let bytes = [
+            0, 0, 2, 0, 96, 0, 0, 0, 1, 0, 0, 0, 6, 102, 0x82, 0, 0, 0, 15, 1, 0xff, 0xff, 0xff,
+            0xff, 0xff,
+        ];
+        let template = ProductDefinitionTemplate::read_any(0, &mut &bytes[..]).unwrap();
+        let (first, second) = template.fixed_surfaces().unwrap();
+        assert_eq!(first, FixedSurface::new(102, -2, 15));
+        assert_value(first, 1500.0);
+        assert_eq!(second.scale_factor, -127);
+        assert_eq!(second.value(), None);
+        assert_eq!(second.description(), "Ground or water surface");
+    }
+}
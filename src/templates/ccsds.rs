@@ -0,0 +1,344 @@
+//! Decoder and encoder of CCSDS 121.0-B adaptive entropy coding for Template 7.42
+//!
+//! Reads the bit stream written by libaec, which ecCodes uses for `grid_ccsds` packing. The
+//! encoder (feature `ccsds`) writes a subset of it that libaec can read.
+
+#[cfg(feature = "ccsds")]
+use bitstream_io::{BigEndian, BitWrite, BitWriter};
+
+use crate::{Error, Result};
+
+/// Flags of Template 5.42 (octet 22), as defined by libaec
+const DATA_SIGNED: u8 = 1;
+#[cfg(feature = "ccsds")]
+const DATA_3BYTE: u8 = 2;
+#[cfg(feature = "ccsds")]
+const DATA_MSB: u8 = 4;
+const DATA_PREPROCESS: u8 = 8;
+const RESTRICTED: u8 = 16;
+const PAD_RSI: u8 = 32;
+
+/// Zero block count meaning "to the end of the segment or reference sample interval"
+const ROS: u32 = 5;
+
+/// Blocks in a segment, the unit a run of zero blocks ends at with [`ROS`]
+const SEGMENT_BLOCKS: usize = 64;
+
+/// Largest value of the second extension option, with β(β+1)/2 + β = 90 for β = 12
+const SE_MAX: u32 = 90;
+
+/// Decode `n` samples of `bits_per_sample` bits
+pub(crate) fn decode(
+    data: &[u8],
+    n: usize,
+    bits_per_sample: u8,
+    flags: u8,
+    block_size: usize,
+    rsi: usize,
+) -> Result<Vec<u32>> {
+    if !(1..=32).contains(&bits_per_sample) || ![8, 16, 32, 64].contains(&block_size) || rsi == 0 {
+        return Err(Error::InvalidData(format!(
+            "CCSDS parameters: {} bits per sample, block size {}, reference sample interval {}",
+            bits_per_sample, block_size, rsi
+        )));
+    }
+    if flags & DATA_SIGNED != 0 {
+        return Err(Error::UnsupportedData("signed CCSDS samples".to_string()));
+    }
+    let id_len = id_length(bits_per_sample, flags);
+    let uncompressed = (1u32 << id_len) - 1;
+    let preprocess = flags & DATA_PREPROCESS != 0;
+    let xmax = u32::MAX >> (32 - bits_per_sample as u32);
+
+    let mut reader = BitReader { data, position: 0 };
+    let mut samples = Vec::with_capacity(n + block_size);
+    while samples.len() < n {
+        let rsi_start = samples.len();
+        let mut block = 0;
+        while block < rsi && samples.len() < n {
+            // with preprocessing, the first block of an interval starts with a reference sample
+            let reference = preprocess && block == 0;
+            let id = reader.read(id_len)?;
+            if id == 0 {
+                let second_extension = reader.read(1)? == 1;
+                if reference {
+                    samples.push(reader.read(bits_per_sample as u32)?);
+                }
+                if second_extension {
+                    let mut i = reference as usize;
+                    while i < block_size {
+                        let m = reader.read_fs()?;
+                        if m > SE_MAX {
+                            return Err(Error::InvalidData(
+                                "invalid CCSDS second extension value".to_string(),
+                            ));
+                        }
+                        let (beta, ms) = second_extension_pair(m);
+                        let d1 = m - ms;
+                        if i.is_multiple_of(2) {
+                            samples.push(beta - d1);
+                            i += 1;
+                        }
+                        samples.push(d1);
+                        i += 1;
+                    }
+                    block += 1;
+                } else {
+                    let zero_blocks = match reader.read_fs()? + 1 {
+                        ROS => (rsi - block).min(SEGMENT_BLOCKS - block % SEGMENT_BLOCKS),
+                        count if count > ROS => count as usize - 1,
+                        count => count as usize,
+                    };
+                    if block + zero_blocks > rsi {
+                        return Err(Error::InvalidData(
+                            "CCSDS zero blocks exceed the reference sample interval".to_string(),
+                        ));
+                    }
+                    let zeros = zero_blocks * block_size - reference as usize;
+                    samples.resize(samples.len() + zeros, 0);
+                    block += zero_blocks;
+                }
+            } else if id == uncompressed {
+                for _ in 0..block_size {
+                    samples.push(reader.read(bits_per_sample as u32)?);
+                }
+                block += 1;
+            } else {
+                // split sample option: fundamental sequences of all samples, then k LSBs each
+                let k = id - 1;
+                if reference {
+                    samples.push(reader.read(bits_per_sample as u32)?);
+                }
+                let start = samples.len();
+                for _ in reference as usize..block_size {
+                    samples.push(reader.read_fs()?.wrapping_shl(k));
+                }
+                for sample in &mut samples[start..] {
+                    *sample |= reader.read(k)?;
+                }
+                block += 1;
+            }
+        }
+        if preprocess {
+            postprocess(&mut samples[rsi_start..], xmax);
+        }
+        if flags & PAD_RSI != 0 {
+            reader.align();
+        }
+    }
+    samples.truncate(n);
+    Ok(samples)
+}
+
+/// Number of bits of the option identifier of each block
+fn id_length(bits_per_sample: u8, flags: u8) -> u32 {
+    match bits_per_sample {
+        17.. => 5,
+        9..=16 => 4,
+        1..=2 if flags & RESTRICTED != 0 => 1,
+        3..=4 if flags & RESTRICTED != 0 => 2,
+        _ => 3,
+    }
+}
+
+/// Samples in a block written by [`encode`], the default of ecCodes
+#[cfg(feature = "ccsds")]
+pub(crate) const ENCODE_BLOCK_SIZE: usize = 32;
+
+/// Blocks in a reference sample interval written by [`encode`], the default of ecCodes
+#[cfg(feature = "ccsds")]
+pub(crate) const ENCODE_RSI: usize = 128;
+
+/// Flags of the stream written by [`encode`] for samples of `bits_per_sample` bits
+///
+/// As with ecCodes, samples are preprocessed and stored most significant byte first, in 3
+/// bytes when 17 to 24 bits long.
+#[cfg(feature = "ccsds")]
+pub(crate) fn encode_flags(bits_per_sample: u8) -> u8 {
+    match bits_per_sample {
+        17..=24 => DATA_PREPROCESS | DATA_MSB | DATA_3BYTE,
+        _ => DATA_PREPROCESS | DATA_MSB,
+    }
+}
+
+/// Encode samples of `bits_per_sample` (1 to 32) bits with the flags of [`encode_flags`]
+///
+/// Each block is coded with the split sample option of the best `k`, or left uncompressed
+/// if that is shorter. The last block is padded with the last sample.
+#[cfg(feature = "ccsds")]
+pub(crate) fn encode(samples: &[u32], bits_per_sample: u8) -> Vec<u8> {
+    let bits = bits_per_sample as u32;
+    let id_len = id_length(bits_per_sample, encode_flags(bits_per_sample));
+    let uncompressed = (1u32 << id_len) - 1;
+    let xmax = u32::MAX >> (32 - bits);
+    let mut writer = BitWriter::endian(Vec::new(), BigEndian);
+    for interval in samples.chunks(ENCODE_BLOCK_SIZE * ENCODE_RSI) {
+        let mut mapped = preprocess(interval, xmax);
+        // a mapped value of zero repeats the previous sample
+        mapped.resize(mapped.len().next_multiple_of(ENCODE_BLOCK_SIZE), 0);
+        for (i, block) in mapped.chunks(ENCODE_BLOCK_SIZE).enumerate() {
+            // the reference sample of the interval is written as is in the split sample option
+            let rest = &block[(i == 0) as usize..];
+            let split = (0..uncompressed - 1)
+                .map(|k| {
+                    let fs: u64 = rest.iter().map(|&d| (d >> k) as u64 + 1).sum();
+                    (fs + k as u64 * rest.len() as u64, k)
+                })
+                .min();
+            match split {
+                Some((size, k)) if size < bits as u64 * rest.len() as u64 => {
+                    write_bits(&mut writer, id_len, k + 1);
+                    if i == 0 {
+                        write_bits(&mut writer, bits, block[0]);
+                    }
+                    for &d in rest {
+                        write_fs(&mut writer, d >> k);
+                    }
+                    for &d in rest {
+                        write_bits(&mut writer, k, d & ((1u64 << k) - 1) as u32);
+                    }
+                }
+                _ => {
+                    write_bits(&mut writer, id_len, uncompressed);
+                    for &d in block {
+                        write_bits(&mut writer, bits, d);
+                    }
+                }
+            }
+        }
+    }
+    writer.byte_align().expect("writing to a Vec cannot fail");
+    writer.into_writer()
+}
+
+/// Map the differences of the samples of an interval from their predecessors to
+/// non-negative values, the inverse of [`postprocess`]
+///
+/// The first sample is kept as the reference sample.
+#[cfg(feature = "ccsds")]
+fn preprocess(samples: &[u32], xmax: u32) -> Vec<u32> {
+    let mut mapped = Vec::with_capacity(samples.len().next_multiple_of(ENCODE_BLOCK_SIZE));
+    let Some((&first, rest)) = samples.split_first() else {
+        return mapped;
+    };
+    mapped.push(first);
+    let mut last = first;
+    for &x in rest {
+        let theta = last.min(xmax - last) as i64;
+        let d = x as i64 - last as i64;
+        mapped.push(match d {
+            0.. if d <= theta => 2 * d,
+            ..0 if -d <= theta => -2 * d - 1,
+            _ => theta + d.abs(),
+        } as u32);
+        last = x;
+    }
+    mapped
+}
+
+#[cfg(feature = "ccsds")]
+fn write_bits(writer: &mut BitWriter<Vec<u8>, BigEndian>, bits: u32, value: u32) {
+    if bits > 0 {
+        writer
+            .write_var(bits, value)
+            .expect("writing to a Vec cannot fail");
+    }
+}
+
+/// Write a fundamental sequence: `zeros` zeros followed by a one
+#[cfg(feature = "ccsds")]
+fn write_fs(writer: &mut BitWriter<Vec<u8>, BigEndian>, zeros: u32) {
+    let mut zeros = zeros;
+    while zeros > 0 {
+        let n = zeros.min(32);
+        write_bits(writer, n, 0);
+        zeros -= n;
+    }
+    write_bits(writer, 1, 1);
+}
+
+/// β and β(β+1)/2 for a value `m` of the second extension option
+fn second_extension_pair(m: u32) -> (u32, u32) {
+    let mut beta = 0;
+    while (beta + 1) * (beta + 2) / 2 <= m {
+        beta += 1;
+    }
+    (beta, beta * (beta + 1) / 2)
+}
+
+/// Invert the unit delay predictor and the mapping of prediction errors of an interval
+fn postprocess(samples: &mut [u32], xmax: u32) {
+    let Some((first, rest)) = samples.split_first_mut() else {
+        return;
+    };
+    let mut last = *first;
+    let med = xmax / 2 + 1;
+    for d in rest {
+        let half = (*d >> 1) + (*d & 1);
+        let mask = if last & med != 0 { xmax } else { 0 };
+        // θ = min(last, xmax - last)
+        if half <= mask ^ last {
+            last = match *d & 1 {
+                0 => last.wrapping_add(*d >> 1),
+                _ => last.wrapping_sub((*d >> 1) + 1),
+            };
+        } else {
+            last = mask ^ *d;
+        }
+        *d = last;
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    /// Position in bits from the start of `data`
+    position: usize,
+}
+
+impl BitReader<'_> {
+    fn truncated() -> Error {
+        Error::InvalidData("CCSDS data ends before all values are decoded".to_string())
+    }
+
+    /// Read `n` (at most 32) bits, most significant bit first
+    fn read(&mut self, n: u32) -> Result<u32> {
+        if n == 0 {
+            return Ok(0);
+        }
+        let end = self.position + n as usize;
+        if end > self.data.len() * 8 {
+            return Err(Self::truncated());
+        }
+        let (first, last) = (self.position / 8, (end - 1) / 8);
+        let bits = self.data[first..=last]
+            .iter()
+            .fold(0u64, |bits, &b| bits << 8 | b as u64);
+        let value = bits >> ((last + 1) * 8 - end) & (u64::MAX >> (64 - n));
+        self.position = end;
+        Ok(value as u32)
+    }
+
+    /// Read a fundamental sequence: the number of zeros before the next one
+    fn read_fs(&mut self) -> Result<u32> {
+        let mut zeros = 0;
+        loop {
+            let byte = *self
+                .data
+                .get(self.position / 8)
+                .ok_or_else(Self::truncated)?;
+            let offset = self.position % 8;
+            let rest = byte << offset;
+            if rest != 0 {
+                let z = rest.leading_zeros();
+                self.position += z as usize + 1;
+                return Ok(zeros + z);
+            }
+            zeros += 8 - offset as u32;
+            self.position += 8 - offset;
+        }
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+}
@@ -1,14 +1,21 @@
-use std::io::Read;
+use std::io::{Read, Write};
 
-use bitstream_io::{BigEndian, BitRead};
+use bitstream_io::{BigEndian, BitRead, BitWrite};
 use byteorder::ReadBytesExt;
 use itertools::Itertools;
 
-use crate::templates::data_representation::DataRepresentationTemplate5_200;
-use crate::templates::read_octets;
+use crate::limits::check_allocation;
+use crate::templates::data_representation::{
+    DataRepresentationTemplate5_42, DataRepresentationTemplate5_200, bit_width,
+};
+use crate::templates::product_definition::ProductDefinitionTemplate4_254;
+use crate::templates::{read_octets, write_octets};
 use crate::{Error, Result};
 
-use super::{DataRepresentationTemplate5_0, DataRepresentationTemplate5_3};
+use super::{
+    DataRepresentationTemplate5_0, DataRepresentationTemplate5_2, DataRepresentationTemplate5_3,
+    DataRepresentationTemplate5_41,
+};
 
 /// Template 7.0: Grid point data - simple packing
 ///
@@ -19,7 +26,10 @@ pub fn read_data_7_0<R: Read>(
     tmpl: &DataRepresentationTemplate5_0,
 ) -> Result<Vec<i32>> {
     let mut reader = bitstream_io::BitReader::<_, BigEndian>::new(reader);
-    let mut values = Vec::with_capacity(number_of_values as usize);
+    let mut values = Vec::with_capacity(check_allocation::<i32>(
+        number_of_values as usize,
+        "number of values",
+    )?);
     for _ in 0..number_of_values as usize {
         let v: u32 = reader.read_var(tmpl.bits_per_value as u32)?;
         // TODO: handle NA value?
@@ -28,25 +38,105 @@ pub fn read_data_7_0<R: Read>(
     Ok(values)
 }
 
-/// Template 7.3: Grid point data - complex packing and spatial differencing
+/// Template 7.0: Encode packed values with simple packing
+///
+/// Missing values (i32::MIN) are not supported and must be left out with a bit-map.
+pub fn write_data_7_0<W: Write>(
+    writer: &mut W,
+    values: &[i32],
+    tmpl: &DataRepresentationTemplate5_0,
+) -> Result<()> {
+    let mut writer = bitstream_io::BitWriter::<_, BigEndian>::new(writer);
+    for &v in values {
+        writer.write_var(tmpl.bits_per_value as u32, not_missing(v)? as u32)?;
+    }
+    writer.byte_align()?;
+    Ok(())
+}
+
+/// Template 7.2: Grid point data - complex packing
 ///
 /// NAN is represented as i32::MIN
+pub fn read_data_7_2<R: Read>(
+    reader: &mut R,
+    tmpl: &DataRepresentationTemplate5_2,
+) -> Result<Vec<i32>> {
+    let mut reader = bitstream_io::BitReader::<_, BigEndian>::new(reader);
+    read_groups(&mut reader, tmpl, 0)
+}
+
+/// Template 7.3: Grid point data - complex packing and spatial differencing
+///
+/// NAN is represented as i32::MIN. Missing values are left out of the differences.
 pub fn read_data_7_3<R: Read>(
     mut reader: &mut R,
     tmpl: &DataRepresentationTemplate5_3,
 ) -> Result<Vec<i32>> {
-    let tmpl2 = &tmpl.template_2;
+    let order = tmpl.order_of_spatial_differencing;
+    let octets = tmpl.number_of_octets_extra_descriptors;
+    if !(1..=2).contains(&order) {
+        return Err(Error::UnsupportedData(format!(
+            "order of spatial differencing {}",
+            order
+        )));
+    }
+    if !(1..=4).contains(&octets) {
+        return Err(Error::InvalidData(format!(
+            "number of octets of extra descriptors must be 1 to 4, but got {}",
+            octets
+        )));
+    }
+    let initial = (0..order)
+        .map(|_| read_octets(&mut reader, octets))
+        .collect::<std::io::Result<Vec<i32>>>()?;
+    let z_min: i32 = read_octets(&mut reader, octets)?;
+    let mut reader = bitstream_io::BitReader::<_, BigEndian>::new(&mut reader);
+    let mut values = read_groups(&mut reader, &tmpl.template_2, z_min)?;
+    if values.len() < initial.len() {
+        return Err(Error::InvalidData(format!(
+            "{} values are fewer than the order of spatial differencing",
+            values.len()
+        )));
+    }
+    let overflow = || Error::InvalidData("spatial differencing overflows 32 bits".to_string());
+    let (mut previous, mut before_previous) = (0i64, 0i64);
+    for (n, value) in values.iter_mut().filter(|v| **v != i32::MIN).enumerate() {
+        let restored = match (n, order) {
+            (n, _) if n < initial.len() => initial[n] as i64,
+            (_, 1) => *value as i64 + previous,
+            _ => *value as i64 + 2 * previous - before_previous,
+        };
+        *value = i32::try_from(restored)
+            .ok()
+            .filter(|&v| v != i32::MIN)
+            .ok_or_else(overflow)?;
+        (previous, before_previous) = (restored, previous);
+    }
+    Ok(values)
+}
+
+/// Group references, widths and lengths followed by the packed values (7.2 and 7.3)
+///
+/// `z_min` is added to every value that is not missing.
+fn read_groups<R: BitRead>(
+    reader: &mut R,
+    tmpl2: &DataRepresentationTemplate5_2,
+    z_min: i32,
+) -> Result<Vec<i32>> {
     let tmpl0 = &tmpl2.template_0;
-    assert_eq!(
-        tmpl.order_of_spatial_differencing, 2,
-        "Only 2nd order is supported"
-    );
-    assert_eq!(tmpl.number_of_octets_extra_descriptors, 2);
-    let z1: i32 = read_octets(&mut reader, tmpl.number_of_octets_extra_descriptors)?;
-    let z2: i32 = read_octets(&mut reader, tmpl.number_of_octets_extra_descriptors)?;
-    let z_min: i32 = read_octets(&mut reader, tmpl.number_of_octets_extra_descriptors)?;
     let ng = tmpl2.number_of_groups_of_data_values;
-    let mut reader = bitstream_io::BitReader::<_, BigEndian>::new(&mut reader);
+    let missing_values = match tmpl2.missing_value_management_used {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        n => {
+            return Err(Error::UnsupportedData(format!(
+                "missing value management {}",
+                n
+            )));
+        }
+    };
+    check_allocation::<u32>(ng as usize, "number of groups")?;
     let group_refs = (0..ng)
         .map(|_| reader.read_var::<u32>(tmpl0.bits_per_value as u32))
         .collect::<std::io::Result<Vec<u32>>>()?;
@@ -59,6 +149,11 @@ pub fn read_data_7_3<R: Read>(
         .map(|_| reader.read_var::<u32>(tmpl2.number_of_bits_for_scaled_group_lengths as u32))
         .collect::<std::io::Result<Vec<u32>>>()?;
     reader.byte_align();
+    // the largest values of a field are the missing value substitutes: all ones, and one less
+    let is_missing = |v: u32, bits: u32| {
+        let all_ones = (1u64 << bits) - 1;
+        (v as u64) + (missing_values as u64) > all_ones
+    };
     let mut values: Vec<i32> = vec![];
     for (gi, ((gref, gw), gl)) in group_refs
         .into_iter()
@@ -66,27 +161,438 @@ pub fn read_data_7_3<R: Read>(
         .zip_eq(group_lengths)
         .enumerate()
     {
-        let group_width = tmpl2.reference_for_group_widths as u32 + gw;
+        let group_width = (tmpl2.reference_for_group_widths as u32)
+            .checked_add(gw)
+            .filter(|&w| w <= 32)
+            .ok_or_else(|| Error::InvalidData("group width exceeds 32 bits".to_string()))?;
         let group_length = if (gi as u32) < ng - 1 {
-            tmpl2.reference_for_group_lengths
-                + (tmpl2.length_increment_for_the_group_lengths as u32 * gl)
+            (tmpl2.length_increment_for_the_group_lengths as u32)
+                .checked_mul(gl)
+                .and_then(|l| l.checked_add(tmpl2.reference_for_group_lengths))
+                .ok_or_else(|| Error::InvalidData("group length exceeds 32 bits".to_string()))?
         } else {
             tmpl2.true_length_of_last_group
         };
+        check_allocation::<i32>(values.len() + group_length as usize, "number of values")?;
+        // a group of constant values is missing if its reference is a substitute
+        let group_missing = group_width == 0 && is_missing(gref, tmpl0.bits_per_value as u32);
         for _ in 0..group_length {
             let v = reader.read_var::<u32>(group_width)?;
-            let value = z_min + gref as i32 + v as i32;
+            if group_missing || (group_width > 0 && is_missing(v, group_width)) {
+                values.push(i32::MIN);
+                continue;
+            }
+            let value = i32::try_from(z_min as i64 + gref as i64 + v as i64)
+                .ok()
+                .filter(|&v| v != i32::MIN)
+                .ok_or_else(|| Error::InvalidData("packed value exceeds 32 bits".to_string()))?;
             values.push(value);
         }
     }
-    values[0] = z1;
-    values[1] = z2;
-    for i in 2..values.len() {
-        values[i] = values[i] + (2 * values[i - 1]) - values[i - 2];
-    }
     Ok(values)
 }
 
+/// Template 7.2: Encode packed values with complex packing
+///
+/// `template_0` is typically the result of [`DataRepresentationTemplate5_0::pack`]; its
+/// `bits_per_value` is replaced with the width of the group references. Missing values
+/// (i32::MIN) are written with primary missing value management.
+pub fn write_data_7_2<W: Write>(
+    writer: &mut W,
+    values: &[i32],
+    template_0: &DataRepresentationTemplate5_0,
+) -> Result<DataRepresentationTemplate5_2> {
+    let values = values
+        .iter()
+        .map(|&v| (v != i32::MIN).then_some(v as i64))
+        .collect::<Vec<_>>();
+    let mut writer = bitstream_io::BitWriter::<_, BigEndian>::new(writer);
+    write_groups(&mut writer, &values, template_0)
+}
+
+/// Template 7.3: Encode packed values with complex packing and spatial differencing
+///
+/// `order_of_spatial_differencing` is 1 or 2. See [`write_data_7_2`] for `template_0`.
+/// Missing values (i32::MIN) are left out of the differences.
+pub fn write_data_7_3<W: Write>(
+    writer: &mut W,
+    values: &[i32],
+    template_0: &DataRepresentationTemplate5_0,
+    order_of_spatial_differencing: u8,
+) -> Result<DataRepresentationTemplate5_3> {
+    let order = order_of_spatial_differencing as usize;
+    if !(1..=2).contains(&order) {
+        return Err(Error::UnsupportedData(format!(
+            "order of spatial differencing {}",
+            order
+        )));
+    }
+    let present = values
+        .iter()
+        .filter(|&&v| v != i32::MIN)
+        .map(|&v| v as i64)
+        .collect::<Vec<_>>();
+    if present.len() <= order {
+        return Err(Error::InvalidData(format!(
+            "{} values are too few for spatial differencing of order {}",
+            present.len(),
+            order
+        )));
+    }
+    let mut diffs = (0..present.len())
+        .map(|i| match i {
+            i if i < order => 0,
+            i if order == 1 => present[i] - present[i - 1],
+            i => present[i] - 2 * present[i - 1] + present[i - 2],
+        })
+        .collect::<Vec<_>>();
+    let z_min = diffs[order..].iter().copied().min().unwrap();
+    diffs[..order].fill(z_min);
+    for d in diffs.iter_mut() {
+        *d -= z_min;
+    }
+    let z_min = i32::try_from(z_min)
+        .map_err(|_| Error::InvalidData("differences exceed 32 bits".to_string()))?;
+    let descriptors = present[..order]
+        .iter()
+        .map(|&v| v as i32)
+        .chain([z_min])
+        .collect::<Vec<_>>();
+    let octets = descriptors
+        .iter()
+        .map(|v| match v.unsigned_abs() {
+            0..0x80 => 1,
+            0x80..0x8000 => 2,
+            0x8000..0x800000 => 3,
+            _ => 4,
+        })
+        .max()
+        .unwrap();
+    for &v in &descriptors {
+        write_octets(&mut *writer, octets, v)?;
+    }
+    let mut diffs = diffs.into_iter();
+    let values = values
+        .iter()
+        .map(|&v| match v {
+            i32::MIN => None,
+            _ => diffs.next(),
+        })
+        .collect::<Vec<_>>();
+    let mut writer = bitstream_io::BitWriter::<_, BigEndian>::new(writer);
+    Ok(DataRepresentationTemplate5_3 {
+        template_2: write_groups(&mut writer, &values, template_0)?,
+        order_of_spatial_differencing,
+        number_of_octets_extra_descriptors: octets,
+    })
+}
+
+fn not_missing(value: i32) -> Result<i32> {
+    match value {
+        i32::MIN => Err(Error::InvalidData(
+            "missing values must be left out with a bit-map".to_string(),
+        )),
+        v => Ok(v),
+    }
+}
+
+/// Split non-negative values into groups and write them with their references, widths
+/// and lengths
+///
+/// Missing values (`None`) take the largest value of their group, all ones, and a group
+/// of missing values only takes the largest reference (primary missing value management).
+fn write_groups<W: BitWrite>(
+    writer: &mut W,
+    values: &[Option<i64>],
+    template_0: &DataRepresentationTemplate5_0,
+) -> Result<DataRepresentationTemplate5_2> {
+    let groups = split_groups(values);
+    let any_missing = groups.iter().any(|g| g.missing);
+    let width = |range: i64| {
+        u32::try_from(range)
+            .map(bit_width)
+            .map_err(|_| Error::InvalidData("values exceed 32 bits".to_string()))
+    };
+    let widths = groups
+        .iter()
+        .map(|g| width(g.range()))
+        .collect::<Result<Vec<_>>>()?;
+    // with missing values, the largest reference is kept for groups of missing values only
+    let max_ref = groups.iter().filter_map(|g| g.min()).max().unwrap_or(0);
+    let bits_per_value = width(max_ref + any_missing as i64)?;
+    let missing_ref = ((1u64 << bits_per_value) - 1) as u32;
+    let refs = groups
+        .iter()
+        .map(|g| g.min().map_or(missing_ref, |min| min as u32))
+        .collect::<Vec<_>>();
+    let reference_for_group_widths = widths.iter().copied().min().unwrap_or(0);
+    let number_of_bits_used_for_the_group_widths =
+        bit_width((widths.iter().copied().max().unwrap_or(0) - reference_for_group_widths) as u32);
+    let (last, rest) = groups
+        .split_last()
+        .map_or((0, &[][..]), |(l, r)| (l.len, r));
+    let reference_for_group_lengths = rest.iter().map(|g| g.len).min().unwrap_or(last);
+    let number_of_bits_for_scaled_group_lengths = bit_width(
+        rest.iter()
+            .map(|g| g.len - reference_for_group_lengths)
+            .max()
+            .unwrap_or(0),
+    );
+
+    for &r in &refs {
+        writer.write_var(bits_per_value as u32, r)?;
+    }
+    writer.byte_align()?;
+    for &w in &widths {
+        writer.write_var(
+            number_of_bits_used_for_the_group_widths as u32,
+            (w - reference_for_group_widths) as u32,
+        )?;
+    }
+    writer.byte_align()?;
+    for g in rest {
+        writer.write_var(
+            number_of_bits_for_scaled_group_lengths as u32,
+            g.len - reference_for_group_lengths,
+        )?;
+    }
+    if !groups.is_empty() {
+        // the last group is given by true_length_of_last_group
+        writer.write_var(number_of_bits_for_scaled_group_lengths as u32, 0u32)?;
+    }
+    writer.byte_align()?;
+    let mut values = values.iter();
+    for ((g, &w), &r) in groups.iter().zip(&widths).zip(&refs) {
+        let all_ones = ((1u64 << w) - 1) as u32;
+        for &v in values.by_ref().take(g.len as usize) {
+            let v = v.map_or(all_ones, |v| (v - r as i64) as u32);
+            writer.write_var(w as u32, v)?;
+        }
+    }
+    writer.byte_align()?;
+
+    Ok(DataRepresentationTemplate5_2 {
+        template_0: DataRepresentationTemplate5_0 {
+            bits_per_value,
+            ..template_0.clone()
+        },
+        group_splitting_method_used: 1,
+        missing_value_management_used: any_missing as u8,
+        primary_missing_value_substitute: 0,
+        secondary_missing_value_substitute: 0,
+        number_of_groups_of_data_values: groups.len() as u32,
+        reference_for_group_widths,
+        number_of_bits_used_for_the_group_widths,
+        reference_for_group_lengths,
+        length_increment_for_the_group_lengths: 1,
+        true_length_of_last_group: last,
+        number_of_bits_for_scaled_group_lengths,
+    })
+}
+
+/// Run of values with their range; `min > max` if all are missing
+struct Group {
+    len: u32,
+    min: i64,
+    max: i64,
+    missing: bool,
+}
+
+impl Group {
+    fn new(values: &[Option<i64>]) -> Group {
+        let present = values.iter().flatten();
+        Group {
+            len: values.len() as u32,
+            min: present.clone().copied().min().unwrap_or(i64::MAX),
+            max: present.copied().max().unwrap_or(i64::MIN),
+            missing: values.iter().any(Option::is_none),
+        }
+    }
+
+    /// Smallest value, the group reference, or `None` if all values are missing
+    fn min(&self) -> Option<i64> {
+        (self.min <= self.max).then_some(self.min)
+    }
+
+    /// Range of the values to pack, with room for the missing value substitute
+    fn range(&self) -> i64 {
+        match self.min() {
+            Some(min) => self.max - min + self.missing as i64,
+            None => 0,
+        }
+    }
+
+    fn bits(&self, overhead: u64) -> u64 {
+        self.len as u64 * bit_width(self.range() as u32) as u64 + overhead
+    }
+
+    fn merge(&self, other: &Group) -> Group {
+        Group {
+            len: self.len + other.len,
+            min: self.min.min(other.min),
+            max: self.max.max(other.max),
+            missing: self.missing || other.missing,
+        }
+    }
+}
+
+/// Split values into groups of similar magnitude
+///
+/// Starts from short runs and merges each into the preceding group while that saves bits,
+/// counting the reference, width and length of a group as its overhead.
+fn split_groups(values: &[Option<i64>]) -> Vec<Group> {
+    const INITIAL_LENGTH: usize = 8;
+    const MAX_LENGTH: u32 = 1 << 16;
+    let max = values.iter().flatten().copied().max().unwrap_or(0);
+    let overhead = bit_width(max.clamp(0, u32::MAX as i64) as u32) as u64 + 4 + 8;
+    let mut groups: Vec<Group> = Vec::new();
+    for chunk in values.chunks(INITIAL_LENGTH) {
+        let group = Group::new(chunk);
+        match groups.last_mut() {
+            Some(last) if last.len + group.len <= MAX_LENGTH => {
+                let merged = last.merge(&group);
+                if merged.bits(overhead) <= last.bits(overhead) + group.bits(overhead) {
+                    *last = merged;
+                } else {
+                    groups.push(group);
+                }
+            }
+            _ => groups.push(group),
+        }
+    }
+    groups
+}
+
+/// Template 7.41 (PNG)
+///
+/// Returns the packed values, to be unpacked with the simple packing parameters of the
+/// template.
+pub fn read_data_7_41<R: Read>(
+    reader: &mut R,
+    number_of_values: u32,
+    tmpl: &DataRepresentationTemplate5_41,
+) -> Result<Vec<i32>> {
+    let n = check_allocation::<i32>(number_of_values as usize, "number of values")?;
+    if tmpl.template_0.bits_per_value == 0 {
+        return Ok(vec![0; n]);
+    }
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let samples = super::png::decode(&data)?;
+    Ok(samples.into_iter().map(|v| v as i32).collect())
+}
+
+/// Template 7.41: Encode packed values as a PNG image `width` values wide
+///
+/// The image depth is the smallest of 8, 16, 24 or 32 bits that holds
+/// `template_0.bits_per_value`, and becomes the template's `bits_per_value`. As with other
+/// encoders, a field of constant values (0 bits per value) is written without data.
+#[cfg(feature = "png")]
+pub fn write_data_7_41<W: Write>(
+    writer: &mut W,
+    values: &[i32],
+    template_0: &DataRepresentationTemplate5_0,
+    width: u32,
+) -> Result<DataRepresentationTemplate5_41> {
+    let bits_per_value = match template_0.bits_per_value {
+        0 => 0,
+        1..=8 => 8,
+        9..=16 => 16,
+        17..=24 => 24,
+        _ => 32,
+    };
+    let template = DataRepresentationTemplate5_41 {
+        template_0: DataRepresentationTemplate5_0 {
+            bits_per_value,
+            ..template_0.clone()
+        },
+    };
+    if bits_per_value == 0 {
+        return Ok(template);
+    }
+    if width == 0 || values.is_empty() || !values.len().is_multiple_of(width as usize) {
+        return Err(Error::InvalidData(format!(
+            "{} values do not form rows of width {}",
+            values.len(),
+            width
+        )));
+    }
+    let height = u32::try_from(values.len() / width as usize)
+        .map_err(|_| Error::InvalidData("too many values for a PNG image".to_string()))?;
+    let samples = values
+        .iter()
+        .map(|&v| not_missing(v).map(|v| v as u32))
+        .collect::<Result<Vec<_>>>()?;
+    writer.write_all(&super::png::encode(&samples, width, height, bits_per_value))?;
+    Ok(template)
+}
+
+/// Template 7.42 (CCSDS recommended lossless compression)
+///
+/// Returns the packed values, to be unpacked with the simple packing parameters of the
+/// template.
+pub fn read_data_7_42<R: Read>(
+    reader: &mut R,
+    number_of_values: u32,
+    tmpl: &DataRepresentationTemplate5_42,
+) -> Result<Vec<i32>> {
+    let n = check_allocation::<i32>(number_of_values as usize, "number of values")?;
+    if tmpl.template_0.bits_per_value == 0 {
+        return Ok(vec![0; n]);
+    }
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+    let samples = super::ccsds::decode(
+        &data,
+        n,
+        tmpl.template_0.bits_per_value,
+        tmpl.ccsds_flags,
+        tmpl.block_size as usize,
+        tmpl.reference_sample_interval as usize,
+    )?;
+    Ok(samples.into_iter().map(|v| v as i32).collect())
+}
+
+/// Template 7.42: Encode packed values with CCSDS lossless compression
+///
+/// Blocks of 32 preprocessed samples are written with the split sample option, or
+/// uncompressed, with a reference sample every 128 blocks as ecCodes does. As with other
+/// encoders, a field of constant values (0 bits per value) is written without data.
+#[cfg(feature = "ccsds")]
+pub fn write_data_7_42<W: Write>(
+    writer: &mut W,
+    values: &[i32],
+    template_0: &DataRepresentationTemplate5_0,
+) -> Result<DataRepresentationTemplate5_42> {
+    let bits_per_value = template_0.bits_per_value;
+    if bits_per_value > 32 {
+        return Err(Error::InvalidData(format!(
+            "{bits_per_value} bits per value do not fit in CCSDS samples"
+        )));
+    }
+    let template = DataRepresentationTemplate5_42 {
+        template_0: template_0.clone(),
+        ccsds_flags: super::ccsds::encode_flags(bits_per_value),
+        block_size: super::ccsds::ENCODE_BLOCK_SIZE as u8,
+        reference_sample_interval: super::ccsds::ENCODE_RSI as u16,
+    };
+    if bits_per_value == 0 {
+        return Ok(template);
+    }
+    let xmax = u32::MAX >> (32 - bits_per_value);
+    let samples = values
+        .iter()
+        .map(|&v| match not_missing(v)? as u32 {
+            v if v <= xmax => Ok(v),
+            v => Err(Error::InvalidData(format!(
+                "packed value {v} does not fit in {bits_per_value} bits"
+            ))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    writer.write_all(&super::ccsds::encode(&samples, bits_per_value))?;
+    Ok(template)
+}
+
 /// Template 7.200 (Run length packing with level values)
 ///
 /// NAN is represented as i32::MIN
@@ -102,19 +608,32 @@ pub fn read_data_7_200<R: Read>(
             drs_template.number_of_bits
         )));
     }
-    let mut values: Vec<i32> = Vec::with_capacity(number_of_values as usize);
+    // levels are 0..=mv, and larger octets are the base 255 - mv digits of run lengths
+    let base = 255u16.checked_sub(drs_template.mv).ok_or_else(|| {
+        Error::InvalidData(format!(
+            "maximum level value {} does not fit in 8 bits",
+            drs_template.mv
+        ))
+    })? as u64;
+    let mut values: Vec<i32> = Vec::with_capacity(check_allocation::<i32>(
+        number_of_values as usize,
+        "number of values",
+    )?);
     let mut lv = reader.read_u8()?;
     let mut p = 0;
     while p < size {
         p += 1;
-        let mut run_length: u32 = 1;
-        let mut m: u32 = 1;
+        let mut run_length: u64 = 1;
+        let mut m: u64 = 1;
         let mut next = 0;
         while p < size {
             next = reader.read_u8()?;
             if next as u16 > drs_template.mv {
-                run_length += (next as u16 - drs_template.mv - 1) as u32 * m;
-                m *= (255 - drs_template.mv) as u32;
+                run_length = m
+                    .checked_mul(next as u64 - drs_template.mv as u64 - 1)
+                    .and_then(|digit| run_length.checked_add(digit))
+                    .ok_or_else(|| Error::InvalidData("run length overflows".to_string()))?;
+                m = m.saturating_mul(base);
                 p += 1;
             } else {
                 break;
@@ -122,12 +641,471 @@ pub fn read_data_7_200<R: Read>(
         }
         let value = match lv {
             0 => i32::MIN,
-            _ => drs_template.mvl_scaled_representative_values[(lv - 1) as usize] as i32,
+            _ if lv as u16 > drs_template.mv => {
+                return Err(Error::InvalidData(format!(
+                    "run length without a preceding level value at octet {}",
+                    p
+                )));
+            }
+            _ => match drs_template
+                .mvl_scaled_representative_values
+                .get((lv - 1) as usize)
+            {
+                Some(&value) => value as i32,
+                None => {
+                    return Err(Error::InvalidData(format!(
+                        "level value {} exceeds the {} representative values",
+                        lv, drs_template.mvl
+                    )));
+                }
+            },
         };
-        for _ in 0..run_length {
-            values.push(value);
+        let len = values.len() as u64 + run_length;
+        if len > number_of_values as u64 {
+            return Err(Error::InvalidData(format!(
+                "run lengths exceed the {} values of the field",
+                number_of_values
+            )));
         }
+        values.resize(len as usize, value);
         lv = next;
     }
     Ok(values)
 }
+
+/// Template 7.200 with the decimal scale factor applied, e.g. precipitation in mm/h
+///
+/// NAN is represented as f32::NAN
+pub fn read_values_7_200<R: Read>(
+    reader: &mut R,
+    size: usize,
+    number_of_values: u32,
+    drs_template: &DataRepresentationTemplate5_200,
+) -> Result<Vec<f32>> {
+    Ok(
+        read_data_7_200(reader, size, number_of_values, drs_template)?
+            .into_iter()
+            .map(|v| drs_template.unpack(v))
+            .collect(),
+    )
+}
+
+/// Data section of a CCITT IA5 character string product (Template 4.254)
+pub fn read_data_character_string<R: Read>(
+    reader: &mut R,
+    pds_template: &ProductDefinitionTemplate4_254,
+) -> Result<String> {
+    let mut buf = Vec::with_capacity(check_allocation::<u8>(
+        pds_template.number_of_characters as usize,
+        "number of characters",
+    )?);
+    reader
+        .take(pds_template.number_of_characters as u64)
+        .read_to_end(&mut buf)?;
+    if buf.len() != pds_template.number_of_characters as usize {
+        return Err(Error::InvalidData(format!(
+            "expected {} characters, but got {}",
+            pds_template.number_of_characters,
+            buf.len()
+        )));
+    }
+    if !buf.is_ascii() {
+        return Err(Error::InvalidData(
+            "character string must be CCITT IA5 (ASCII)".to_string(),
+        ));
+    }
+    Ok(String::from_utf8(buf).expect("ASCII is valid UTF-8"))
+}
+
+/// Section 6: Bit-map (bit-map indicator 0)
+///
+/// Returns one flag per grid point; `true` means a data value is present.
+/// Bits are read from the most significant bit of each octet.
+pub fn read_bitmap<R: Read>(reader: &mut R, number_of_data_points: u32) -> Result<Vec<bool>> {
+    check_allocation::<bool>(number_of_data_points as usize, "number of data points")?;
+    let mut reader = bitstream_io::BitReader::<_, BigEndian>::new(reader);
+    (0..number_of_data_points)
+        .map(|_| Ok(reader.read_bit()?))
+        .collect()
+}
+
+/// Section 6: Encode a bit-map (bit-map indicator 0)
+///
+/// The inverse of [`read_bitmap`]; the last octet is padded with zeros.
+pub fn write_bitmap<W: Write>(writer: &mut W, bitmap: &[bool]) -> Result<()> {
+    let mut writer = bitstream_io::BitWriter::<_, BigEndian>::new(writer);
+    for &b in bitmap {
+        writer.write_bit(b)?;
+    }
+    writer.byte_align()?;
+    Ok(())
+}
+
+/// Expand packed values to all grid points using a bit-map
+///
+/// Points absent from the bit-map are filled with i32::MIN (NAN).
+pub fn apply_bitmap(values: &[i32], bitmap: &[bool]) -> Result<Vec<i32>> {
+    let present = bitmap.iter().filter(|&&b| b).count();
+    if present != values.len() {
+        return Err(Error::InvalidData(format!(
+            "bit-map has {} points present, but got {} values",
+            present,
+            values.len()
+        )));
+    }
+    let mut values = values.iter();
+    Ok(bitmap
+        .iter()
+        .map(|&b| match b {
+            true => *values.next().unwrap(),
+            false => i32::MIN,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Template 5.200 of 8-bit levels 0 to `mv` with `mvl` representative values 10, 20, ...
+    fn template_5_200(mv: u16, mvl: u16) -> DataRepresentationTemplate5_200 {
+        DataRepresentationTemplate5_200 {
+            number_of_bits: 8,
+            mv,
+            mvl,
+            decimal_scale_factor: 0,
+            mvl_scaled_representative_values: (1..=mvl as i16).map(|v| v * 10).collect(),
+        }
+    }
+
+    fn decode_7_200(octets: &[u8], number_of_values: u32, mv: u16, mvl: u16) -> Result<Vec<i32>> {
+        read_data_7_200(
+            &mut &octets[..],
+            octets.len(),
+            number_of_values,
+            &template_5_200(mv, mvl),
+        )
+    }
+
+    fn template_5_0(bits_per_value: u8) -> DataRepresentationTemplate5_0 {
+        DataRepresentationTemplate5_0 {
+            reference_value: 0.0,
+            binary_scale_factor: 0,
+            decimal_scale_factor: 0,
+            bits_per_value,
+            type_of_original_field_values: 0,
+        }
+    }
+
+    /// Smooth values with a jump, noise and a plateau, with every `missing`-th value missing
+    fn complex_values(missing: Option<usize>) -> Vec<i32> {
+        let mut state = 0x2545_f491_u32;
+        let mut values: Vec<i32> = (0..300).map(|i| 1000 + (i * i) % 97).collect();
+        values.extend((0..200).map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 16) as i32
+        }));
+        values.extend([40_000; 100]);
+        if let Some(missing) = missing {
+            for v in values.iter_mut().step_by(missing) {
+                *v = i32::MIN;
+            }
+            // a group of missing values only
+            values[450..480].fill(i32::MIN);
+        }
+        values
+    }
+
+    #[test]
+    fn complex_round_trip_7_2() {
+        for missing in [None, Some(7), Some(1)] {
+            let values = complex_values(missing);
+            let mut data = Vec::new();
+            let tmpl = write_data_7_2(&mut data, &values, &template_5_0(16)).unwrap();
+            assert_eq!(
+                tmpl.number_of_groups_of_data_values > 1,
+                missing != Some(1),
+                "missing {missing:?}"
+            );
+            assert_eq!(tmpl.missing_value_management_used, missing.is_some() as u8);
+            assert_eq!(read_data_7_2(&mut &data[..], &tmpl).unwrap(), values);
+        }
+    }
+
+    #[test]
+    fn complex_round_trip_7_3() {
+        for order in [1, 2] {
+            for missing in [None, Some(5)] {
+                let values = complex_values(missing);
+                let mut data = Vec::new();
+                let tmpl = write_data_7_3(&mut data, &values, &template_5_0(16), order).unwrap();
+                assert_eq!(tmpl.order_of_spatial_differencing, order);
+                let decoded = read_data_7_3(&mut &data[..], &tmpl).unwrap();
+                assert_eq!(decoded, values, "order {order}, missing {missing:?}");
+            }
+        }
+        // smooth values need fewer bits once differenced
+        let values = (0..1000).map(|i| 5000 + i * 3).collect::<Vec<_>>();
+        let (mut simple, mut differenced) = (Vec::new(), Vec::new());
+        write_data_7_2(&mut simple, &values, &template_5_0(13)).unwrap();
+        write_data_7_3(&mut differenced, &values, &template_5_0(13), 2).unwrap();
+        assert!(differenced.len() * 4 < simple.len());
+        let too_few = [1, i32::MIN, 2];
+        assert!(write_data_7_3(&mut Vec::new(), &too_few, &template_5_0(2), 2).is_err());
+    }
+
+    /// Template 5.2 of `groups` groups of constant values, with 0-bit references and widths
+    fn template_5_2(groups: u32) -> DataRepresentationTemplate5_2 {
+        DataRepresentationTemplate5_2 {
+            template_0: template_5_0(0),
+            group_splitting_method_used: 1,
+            missing_value_management_used: 0,
+            primary_missing_value_substitute: 0,
+            secondary_missing_value_substitute: 0,
+            number_of_groups_of_data_values: groups,
+            reference_for_group_widths: 0,
+            number_of_bits_used_for_the_group_widths: 0,
+            reference_for_group_lengths: 0,
+            length_increment_for_the_group_lengths: 1,
+            true_length_of_last_group: 1,
+            number_of_bits_for_scaled_group_lengths: 0,
+        }
+    }
+
+    #[test]
+    fn complex_overflow() {
+        // reference + increment * scaled length beyond u32::MAX
+        let mut long_groups = template_5_2(2);
+        long_groups.reference_for_group_lengths = u32::MAX;
+        long_groups.number_of_bits_for_scaled_group_lengths = 1;
+        assert!(matches!(
+            read_data_7_2(&mut &[0x80][..], &long_groups),
+            Err(Error::InvalidData(_))
+        ));
+        long_groups.reference_for_group_lengths = 0;
+        long_groups.length_increment_for_the_group_lengths = 255;
+        long_groups.number_of_bits_for_scaled_group_lengths = 32;
+        let data = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0];
+        assert!(matches!(
+            read_data_7_2(&mut &data[..], &long_groups),
+            Err(Error::InvalidData(_))
+        ));
+
+        // z_min + reference + value beyond i32::MAX
+        let mut large = template_5_2(1);
+        large.template_0.bits_per_value = 32;
+        let tmpl_3 = DataRepresentationTemplate5_3 {
+            template_2: large,
+            order_of_spatial_differencing: 1,
+            number_of_octets_extra_descriptors: 4,
+        };
+        let mut data = vec![0, 0, 0, 0, 0x7f, 0xff, 0xff, 0xff];
+        data.extend(0x7fff_ffffu32.to_be_bytes());
+        assert!(matches!(
+            read_data_7_3(&mut &data[..], &tmpl_3),
+            Err(Error::InvalidData(_))
+        ));
+
+        // differences that add up beyond i32::MAX
+        let mut sums = tmpl_3.clone();
+        sums.template_2.true_length_of_last_group = 3;
+        let mut data = vec![0x7f, 0xff, 0xff, 0xf0, 0, 0, 0, 0];
+        data.extend(0x10u32.to_be_bytes());
+        assert!(matches!(
+            read_data_7_3(&mut &data[..], &sums),
+            Err(Error::InvalidData(_))
+        ));
+    }
+
+    /// Template 5.42 with the CCSDS options of ecCodes, as written to GRIB2 files
+    fn template_5_42(bits_per_value: u8, ccsds_flags: u8) -> DataRepresentationTemplate5_42 {
+        DataRepresentationTemplate5_42 {
+            template_0: template_5_0(bits_per_value),
+            ccsds_flags,
+            block_size: 32,
+            reference_sample_interval: 128,
+        }
+    }
+
+    /// Reproducible samples of the top 12 bits of a linear congruential generator
+    fn lcg_12_bits() -> impl Iterator<Item = i32> {
+        let mut state = 0x2545_f491_u32;
+        std::iter::repeat_with(move || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 20) as i32
+        })
+    }
+
+    #[test]
+    fn ccsds_libaec_12_bits() {
+        // encoded by libaec 1.1.4 (graec -m -n 12 -j 32 -r 128): a ramp, a constant run that
+        // becomes zero blocks, noise left uncompressed, and squares
+        let data = [
+            55, 208, 85, 85, 85, 85, 85, 85, 85, 86, 170, 170, 170, 170, 170, 170, 170, 163, 85,
+            85, 85, 85, 85, 85, 85, 85, 170, 170, 170, 170, 170, 170, 170, 170, 53, 85, 85, 85, 85,
+            85, 85, 85, 90, 170, 170, 170, 170, 170, 170, 170, 163, 85, 85, 85, 85, 85, 85, 85, 85,
+            170, 170, 170, 170, 170, 170, 170, 170, 96, 0, 0, 0, 0, 0, 0, 31, 255, 255, 255, 225,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 63, 197, 46, 93, 198, 179,
+            26, 236, 224, 28, 66, 123, 175, 76, 15, 133, 45, 147, 56, 26, 242, 68, 33, 107, 232,
+            222, 21, 142, 32, 146, 54, 203, 69, 203, 72, 120, 67, 237, 18, 163, 92, 253, 222, 97,
+            199, 225, 41, 166, 15, 236, 127, 203, 5, 248, 50, 106, 119, 89, 190, 85, 211, 16, 92,
+            164, 226, 245, 39, 121, 214, 61, 190, 228, 133, 61, 108, 12, 16, 134, 136, 129, 82,
+            145, 87, 9, 215, 88, 202, 156, 79, 104, 75, 21, 44, 137, 162, 87, 161, 222, 6, 26, 55,
+            19, 186, 159, 20, 117, 134, 37, 215, 29, 91, 20, 177, 159, 196, 79, 8, 145, 173, 204,
+            75, 200, 204, 31, 44, 189, 88, 179, 97, 29, 215, 146, 253, 122, 29, 201, 143, 149, 73,
+            183, 213, 49, 74, 65, 94, 211, 16, 0, 44, 0, 0, 0, 127, 255, 255, 254, 97, 194, 76, 41,
+            2, 16, 72, 226, 69, 141, 30, 68, 153, 82, 230, 77, 157, 62, 133, 26, 84, 234, 85, 173,
+            94, 197, 155, 83, 170, 164, 146, 73, 36, 159, 255, 251, 178, 219, 175, 130, 24, 163,
+            146, 89, 167, 162, 154, 160, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut expected: Vec<i32> = (0..128).map(|i| 2000 + i * 3).collect();
+        expected.extend([1500; 128]);
+        expected.extend(lcg_12_bits().take(100));
+        expected.extend((0..44).map(|i| i * i));
+        let decoded = read_data_7_42(&mut &data[..], 400, &template_5_42(12, 12)).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[test]
+    fn ccsds_libaec_24_bits() {
+        // encoded by libaec 1.1.4 (graec -m -3 -n 24 -j 32 -r 128), samples in 3 octets
+        let data = [
+            92, 0, 0, 2, 170, 170, 170, 170, 170, 170, 170, 190, 135, 161, 232, 122, 30, 135, 161,
+            232, 122, 30, 135, 161, 232, 122, 30, 135, 161, 232, 122, 30, 135, 161, 232, 122, 30,
+            135, 161, 232, 122, 30, 135, 161, 232, 122, 30, 135, 161, 232, 124, 0, 31, 64, 0, 31,
+            64, 0, 31, 64, 0, 31, 64, 0, 31, 64, 0, 31, 64, 0, 31, 64, 0, 31, 67, 17, 17, 25, 203,
+            86, 51, 142, 42, 134, 99, 178, 157, 218, 88, 220, 3, 128, 168, 133, 24, 239, 118, 102,
+            22, 151, 252, 137, 240, 221, 26, 91, 113, 18, 102, 243, 88, 54, 10, 30, 72, 237, 152,
+            67, 12, 141, 125, 71, 113, 187, 211, 74, 178, 129, 92, 65, 12, 50, 71, 45, 69, 150,
+            204, 56, 185, 162, 198, 145, 123, 47, 8, 209, 142, 226, 72, 230, 142, 145, 31, 255,
+            253, 163, 142, 149, 43, 243, 186, 130, 222, 241, 204, 220, 50, 75, 63, 11, 208, 83, 47,
+            167, 8, 145, 121, 4, 175, 229, 171, 8, 191, 35, 209, 145, 75, 180, 231, 236, 236, 208,
+            105, 203, 53, 250, 152, 188, 160, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ];
+        let mut expected: Vec<i32> = (0..40).map(|i| (1 << 23) + i * 1000).collect();
+        expected.extend(lcg_12_bits().take(40).map(|v| (v * 4099) & 0xff_ffff));
+        let decoded = read_data_7_42(&mut &data[..], 80, &template_5_42(24, 14)).unwrap();
+        assert_eq!(decoded, expected);
+    }
+
+    #[cfg(feature = "ccsds")]
+    #[test]
+    fn ccsds_round_trip_7_42() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for bits_per_value in [1, 3, 8, 12, 16, 20, 24, 31, 32] {
+            let xmax = u32::MAX >> (32 - bits_per_value);
+            // smooth values, noise, constant runs, and the extremes
+            let mut values: Vec<i32> = (0..5000).map(|i| ((i / 7) as u32 & xmax) as i32).collect();
+            values.extend((0..4133).map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                ((state >> 32) as u32 & xmax) as i32
+            }));
+            values.extend([0; 100]);
+            values.extend([xmax as i32, 0, xmax as i32, xmax as i32]);
+            let template_0 = DataRepresentationTemplate5_0 {
+                reference_value: 0.0,
+                binary_scale_factor: 0,
+                decimal_scale_factor: 0,
+                bits_per_value,
+                type_of_original_field_values: 0,
+            };
+            let mut data = Vec::new();
+            let tmpl = write_data_7_42(&mut data, &values, &template_0).unwrap();
+            let decoded = read_data_7_42(&mut &data[..], values.len() as u32, &tmpl).unwrap();
+            assert_eq!(decoded, values, "{bits_per_value} bits per value");
+        }
+    }
+
+    #[cfg(feature = "ccsds")]
+    #[test]
+    fn ccsds_compresses_7_42() {
+        let values = vec![1000; 4096];
+        let template_0 = DataRepresentationTemplate5_0 {
+            reference_value: 0.0,
+            binary_scale_factor: 0,
+            decimal_scale_factor: 0,
+            bits_per_value: 16,
+            type_of_original_field_values: 0,
+        };
+        let mut data = Vec::new();
+        let tmpl = write_data_7_42(&mut data, &values, &template_0).unwrap();
+        assert!(data.len() < 4096 / 4, "{} octets", data.len());
+        assert_eq!(read_data_7_42(&mut &data[..], 4096, &tmpl).unwrap(), values);
+        assert!(write_data_7_42(&mut Vec::new(), &[1 << 16], &template_0).is_err());
+    }
+
+    #[test]
+    fn run_lengths_7_200() {
+        // with mv = 3 the run-length digits are 4..=255 in base 252, after the first value
+        assert_eq!(
+            decode_7_200(&[1, 2, 5, 3], 4, 3, 3).unwrap(),
+            [10, 20, 20, 30]
+        );
+        assert_eq!(
+            decode_7_200(&[2, 4 + 1, 4 + 1], 254, 3, 3).unwrap(),
+            vec![20; 254]
+        );
+    }
+
+    #[test]
+    fn missing_level_7_200() {
+        assert_eq!(
+            decode_7_200(&[0, 5, 1], 3, 3, 3).unwrap(),
+            [i32::MIN, i32::MIN, 10]
+        );
+        let values = read_values_7_200(&mut &[0u8, 1][..], 2, 2, &template_5_200(3, 3)).unwrap();
+        assert!(values[0].is_nan());
+        assert_eq!(values[1], 10.0);
+    }
+
+    #[test]
+    fn level_without_representative_value_7_200() {
+        assert!(decode_7_200(&[1, 3], 2, 3, 2).is_err());
+    }
+
+    #[test]
+    fn leading_run_length_7_200() {
+        assert!(decode_7_200(&[5, 1], 2, 3, 3).is_err());
+    }
+
+    #[test]
+    fn maximum_level_7_200() {
+        // without room for run lengths every octet is a level
+        assert_eq!(decode_7_200(&[1, 255], 2, 255, 255).unwrap(), [10, 2550]);
+        assert!(decode_7_200(&[1, 1], 2, 256, 3).is_err());
+    }
+
+    #[test]
+    fn run_length_overflow_7_200() {
+        let mut octets = vec![1];
+        octets.extend([255; 16]);
+        assert!(decode_7_200(&octets, u32::MAX, 3, 3).is_err());
+    }
+
+    #[test]
+    fn runs_beyond_number_of_values_7_200() {
+        assert!(decode_7_200(&[1, 10], 4, 3, 3).is_err());
+        assert!(decode_7_200(&[1, 2, 3], 2, 3, 3).is_err());
+    }
+
+    #[test]
+    fn random_octets_7_200() {
+        // linear congruential generator of Numerical Recipes, for reproducible inputs
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (state >> 24) as u8
+        };
+        for _ in 0..20_000 {
+            let mv = next() as u16 + (next() % 4 == 0) as u16 * 2;
+            let mvl = next() as u16 % (mv + 2);
+            let number_of_values = next() as u32 * 4;
+            let octets = (0..next() % 64).map(|_| next()).collect::<Vec<_>>();
+            // any result but a panic is fine
+            let _ = decode_7_200(&octets, number_of_values, mv, mvl);
+        }
+    }
+}
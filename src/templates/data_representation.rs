@@ -1,9 +1,16 @@
-use std::io::Read;
+use std::io::{Read, Write};
+use std::sync::Arc;
 
-use super::GribRead;
-use crate::Result;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug)]
+use super::registry::{CustomTemplate, is_registered, read_registered};
+use super::{GribRead, GribWrite};
+use crate::limits::check_allocation;
+use crate::{Error, Result};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataRepresentationTemplate5_0 {
     pub reference_value: f32,
     pub binary_scale_factor: i16,
@@ -13,6 +20,68 @@ pub struct DataRepresentationTemplate5_0 {
 }
 
 impl DataRepresentationTemplate5_0 {
+    /// Convert a packed value to its physical value: Y = (R + X * 2^E) / 10^D
+    ///
+    /// NAN (i32::MIN) is converted to f32::NAN.
+    pub fn unpack(&self, packed: i32) -> f32 {
+        if packed == i32::MIN {
+            return f32::NAN;
+        }
+        let e = 2f64.powi(self.binary_scale_factor as i32);
+        let d = 10f64.powi(-(self.decimal_scale_factor as i32));
+        ((self.reference_value as f64 + packed as f64 * e) * d) as f32
+    }
+
+    /// Pack physical values with the given scale factors: X = round((Y * 10^D - R) / 2^E)
+    ///
+    /// The reference value R is the minimum of the scaled values and `bits_per_value` is
+    /// what the largest packed value needs. NAN is packed as i32::MIN.
+    pub fn pack(
+        values: &[f32],
+        decimal_scale_factor: i16,
+        binary_scale_factor: i16,
+    ) -> Result<(Self, Vec<i32>)> {
+        let d = 10f64.powi(decimal_scale_factor as i32);
+        let e = 2f64.powi(-(binary_scale_factor as i32));
+        let min = values
+            .iter()
+            .filter(|v| !v.is_nan())
+            .map(|&v| v as f64 * d)
+            .fold(f64::INFINITY, f64::min);
+        let mut reference_value = if min.is_finite() { min as f32 } else { 0.0 };
+        // R is stored as f32 and must not exceed the minimum
+        if reference_value as f64 > min {
+            reference_value = reference_value.next_down();
+        }
+        let packed = values
+            .iter()
+            .map(|&v| {
+                if v.is_nan() {
+                    return Ok(i32::MIN);
+                }
+                let x = ((v as f64 * d - reference_value as f64) * e).round();
+                if !(0.0..=i32::MAX as f64).contains(&x) {
+                    return Err(Error::InvalidData(format!(
+                        "{} cannot be packed with decimal scale factor {} and binary scale factor {}",
+                        v, decimal_scale_factor, binary_scale_factor
+                    )));
+                }
+                Ok(x as i32)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let max = packed.iter().copied().filter(|&x| x != i32::MIN).max();
+        Ok((
+            Self {
+                reference_value,
+                binary_scale_factor,
+                decimal_scale_factor,
+                bits_per_value: bit_width(max.unwrap_or(0) as u32),
+                type_of_original_field_values: 0,
+            },
+            packed,
+        ))
+    }
+
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
             reference_value: reader.read_grib_value()?,
@@ -22,9 +91,19 @@ impl DataRepresentationTemplate5_0 {
             type_of_original_field_values: reader.read_grib_value()?,
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.reference_value)?;
+        writer.write_grib_value(self.binary_scale_factor)?;
+        writer.write_grib_value(self.decimal_scale_factor)?;
+        writer.write_grib_value(self.bits_per_value)?;
+        writer.write_grib_value(self.type_of_original_field_values)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataRepresentationTemplate5_2 {
     pub template_0: DataRepresentationTemplate5_0,
     pub group_splitting_method_used: u8,
@@ -57,9 +136,26 @@ impl DataRepresentationTemplate5_2 {
             number_of_bits_for_scaled_group_lengths: reader.read_grib_value()?,
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.group_splitting_method_used)?;
+        writer.write_grib_value(self.missing_value_management_used)?;
+        writer.write_grib_value(self.primary_missing_value_substitute)?;
+        writer.write_grib_value(self.secondary_missing_value_substitute)?;
+        writer.write_grib_value(self.number_of_groups_of_data_values)?;
+        writer.write_grib_value(self.reference_for_group_widths)?;
+        writer.write_grib_value(self.number_of_bits_used_for_the_group_widths)?;
+        writer.write_grib_value(self.reference_for_group_lengths)?;
+        writer.write_grib_value(self.length_increment_for_the_group_lengths)?;
+        writer.write_grib_value(self.true_length_of_last_group)?;
+        writer.write_grib_value(self.number_of_bits_for_scaled_group_lengths)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataRepresentationTemplate5_3 {
     pub template_2: DataRepresentationTemplate5_2,
     pub order_of_spatial_differencing: u8,
@@ -74,10 +170,100 @@ impl DataRepresentationTemplate5_3 {
             number_of_octets_extra_descriptors: reader.read_grib_value()?,
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_2.write(writer)?;
+        writer.write_grib_value(self.order_of_spatial_differencing)?;
+        writer.write_grib_value(self.number_of_octets_extra_descriptors)?;
+        Ok(())
+    }
+}
+
+/// Template 5.40 (Grid point data - JPEG 2000 code stream format)
+///
+/// Only the template is parsed: JPEG 2000 code streams are neither decoded nor encoded by
+/// this crate.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DataRepresentationTemplate5_40 {
+    pub template_0: DataRepresentationTemplate5_0,
+    /// Type of compression used (Code table 5.40), 0 for lossless and 1 for lossy
+    pub type_of_compression_used: u8,
+    /// Target compression ratio M in M:1, 255 for lossless compression
+    pub target_compression_ratio: u8,
+}
+
+impl DataRepresentationTemplate5_40 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: DataRepresentationTemplate5_0::read(reader)?,
+            type_of_compression_used: reader.read_grib_value()?,
+            target_compression_ratio: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.type_of_compression_used)?;
+        writer.write_grib_value(self.target_compression_ratio)?;
+        Ok(())
+    }
+}
+
+/// Template 5.41 (Grid point data - Portable Network Graphics (PNG))
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DataRepresentationTemplate5_41 {
+    pub template_0: DataRepresentationTemplate5_0,
+}
+
+impl DataRepresentationTemplate5_41 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: DataRepresentationTemplate5_0::read(reader)?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)
+    }
+}
+
+/// Template 5.42 (Grid point and spectral data - CCSDS recommended lossless compression)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DataRepresentationTemplate5_42 {
+    pub template_0: DataRepresentationTemplate5_0,
+    /// CCSDS compression options mask, with the flags of libaec
+    pub ccsds_flags: u8,
+    /// Number of samples in a block
+    pub block_size: u8,
+    /// Number of blocks between reference samples
+    pub reference_sample_interval: u16,
+}
+
+impl DataRepresentationTemplate5_42 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: DataRepresentationTemplate5_0::read(reader)?,
+            ccsds_flags: reader.read_grib_value()?,
+            block_size: reader.read_grib_value()?,
+            reference_sample_interval: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.ccsds_flags)?;
+        writer.write_grib_value(self.block_size)?;
+        writer.write_grib_value(self.reference_sample_interval)?;
+        Ok(())
+    }
 }
 
 /// Template 5.200 (Run length packing with level values)
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DataRepresentationTemplate5_200 {
     pub number_of_bits: u8,
     pub mv: u16,
@@ -96,11 +282,113 @@ impl DataRepresentationTemplate5_200 {
             mvl_scaled_representative_values: Vec::new(),
         };
         tmpl.mvl_scaled_representative_values
-            .reserve(tmpl.mvl.into());
+            .reserve(check_allocation::<i16>(tmpl.mvl.into(), "mvl")?);
         for _ in 0..tmpl.mvl {
             tmpl.mvl_scaled_representative_values
                 .push(reader.read_grib_value()?);
         }
         Ok(tmpl)
     }
+
+    /// Convert a representative value to its physical value: Y = V / 10^D
+    ///
+    /// NAN (i32::MIN, level 0) is converted to f32::NAN.
+    pub fn unpack(&self, scaled: i32) -> f32 {
+        if scaled == i32::MIN {
+            return f32::NAN;
+        }
+        (scaled as f64 * 10f64.powi(-(self.decimal_scale_factor as i32))) as f32
+    }
+}
+
+/// Any data representation template, dispatched by template number
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DataRepresentationTemplate {
+    Template5_0(DataRepresentationTemplate5_0),
+    Template5_2(DataRepresentationTemplate5_2),
+    Template5_3(DataRepresentationTemplate5_3),
+    Template5_40(DataRepresentationTemplate5_40),
+    Template5_41(DataRepresentationTemplate5_41),
+    Template5_42(DataRepresentationTemplate5_42),
+    Template5_200(DataRepresentationTemplate5_200),
+    /// Template parsed by a parser registered with [`register_template`]
+    ///
+    /// Skipped by serde: serializing it fails, as the parsed value is opaque.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn CustomTemplate>),
+    /// Template not implemented by this crate (raw template bytes)
+    Unknown(Vec<u8>),
+}
+
+impl DataRepresentationTemplate {
+    /// Read the template identified by `template_number` (Section 5, octets 10-11)
+    pub fn read_any<R: Read>(template_number: u16, reader: &mut R) -> Result<Self> {
+        Self::read_any_for_centre(template_number, None, reader)
+    }
+
+    /// Read the template, consulting the parsers registered for the originating `centre`
+    pub fn read_any_for_centre<R: Read>(
+        template_number: u16,
+        centre: Option<u16>,
+        reader: &mut R,
+    ) -> Result<Self> {
+        Ok(match template_number {
+            0 => Self::Template5_0(DataRepresentationTemplate5_0::read(reader)?),
+            2 => Self::Template5_2(DataRepresentationTemplate5_2::read(reader)?),
+            3 => Self::Template5_3(DataRepresentationTemplate5_3::read(reader)?),
+            40 => Self::Template5_40(DataRepresentationTemplate5_40::read(reader)?),
+            41 => Self::Template5_41(DataRepresentationTemplate5_41::read(reader)?),
+            42 => Self::Template5_42(DataRepresentationTemplate5_42::read(reader)?),
+            200 => Self::Template5_200(DataRepresentationTemplate5_200::read(reader)?),
+            _ => match read_registered(5, template_number, centre, reader) {
+                Some(template) => Self::Custom(template?),
+                None => {
+                    let mut buf = Vec::new();
+                    reader.read_to_end(&mut buf)?;
+                    Self::Unknown(buf)
+                }
+            },
+        })
+    }
+
+    /// Write the template (Section 5, octets 12-nn)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Template5_0(t) => t.write(writer),
+            Self::Template5_2(t) => t.write(writer),
+            Self::Template5_3(t) => t.write(writer),
+            Self::Template5_40(t) => t.write(writer),
+            Self::Template5_41(t) => t.write(writer),
+            Self::Template5_42(t) => t.write(writer),
+            Self::Unknown(buf) => Ok(writer.write_all(buf)?),
+            _ => Err(Error::UnsupportedData(
+                "writing this data representation template".to_string(),
+            )),
+        }
+    }
+
+    /// Simple packing parameters shared by the templates derived from Template 5.0
+    pub fn template_0(&self) -> Option<&DataRepresentationTemplate5_0> {
+        match self {
+            Self::Template5_0(t) => Some(t),
+            Self::Template5_2(t) => Some(&t.template_0),
+            Self::Template5_3(t) => Some(&t.template_2.template_0),
+            Self::Template5_40(t) => Some(&t.template_0),
+            Self::Template5_41(t) => Some(&t.template_0),
+            Self::Template5_42(t) => Some(&t.template_0),
+            _ => None,
+        }
+    }
+
+    /// Whether `read_any` parses the template (built in or registered for all centres)
+    pub fn is_supported(template_number: u16) -> bool {
+        matches!(template_number, 0 | 2 | 3 | 40 | 41 | 42 | 200)
+            || is_registered(5, template_number, None)
+    }
+}
+
+/// Number of bits needed to represent `value`
+pub(crate) fn bit_width(value: u32) -> u8 {
+    (u32::BITS - value.leading_zeros()) as u8
 }
@@ -1,10 +1,18 @@
-use std::io::Read;
+use std::io::{Read, Write};
+use std::sync::Arc;
 
-use super::GribRead;
-use crate::Result;
+use byteorder::{BigEndian, WriteBytesExt};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::registry::{CustomTemplate, is_registered, read_registered};
+use super::{GribRead, GribWrite};
+use crate::limits::check_allocation;
+use crate::{Error, Result};
 
 /// Template 3.0 (Latitude/longitude)
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct GridDefinitionTemplate3_0 {
     pub shape_of_earth: u8,
     pub scale_factor_of_radius: u8,
@@ -52,4 +60,332 @@ impl GridDefinitionTemplate3_0 {
         };
         Ok(tmpl)
     }
+
+    /// Write the template; coordinates are written in sign and magnitude
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.shape_of_earth)?;
+        writer.write_grib_value(self.scale_factor_of_radius)?;
+        writer.write_grib_value(self.scale_value_of_radius)?;
+        writer.write_grib_value(self.scale_factor_of_major_axis)?;
+        writer.write_grib_value(self.scale_value_of_major_axis)?;
+        writer.write_grib_value(self.scale_factor_of_minor_axis)?;
+        writer.write_grib_value(self.scale_value_of_minor_axis)?;
+        writer.write_grib_value(self.n_i)?;
+        writer.write_grib_value(self.n_j)?;
+        writer.write_grib_value(self.basic_angle)?;
+        writer.write_grib_value(self.subdivisions_of_basic_angle)?;
+        writer.write_grib_value(self.la1)?;
+        writer.write_grib_value(self.lo1)?;
+        writer.write_grib_value(self.resolution_and_component_flags)?;
+        writer.write_grib_value(self.la2)?;
+        writer.write_grib_value(self.lo2)?;
+        writer.write_grib_value(self.d_i)?;
+        writer.write_grib_value(self.d_j)?;
+        writer.write_grib_value(self.scanning_mode)?;
+        Ok(())
+    }
+
+    /// Radius of the spherical earth in metres (shape of the earth 1), or `None` if missing
+    pub fn radius(&self) -> Option<f64> {
+        scaled(self.scale_factor_of_radius, self.scale_value_of_radius)
+    }
+
+    /// Major axis of the oblate spheroid earth (shape of the earth 3 in km, 7 in metres), or
+    /// `None` if missing
+    pub fn major_axis(&self) -> Option<f64> {
+        scaled(
+            self.scale_factor_of_major_axis,
+            self.scale_value_of_major_axis,
+        )
+    }
+
+    /// Minor axis of the oblate spheroid earth (shape of the earth 3 in km, 7 in metres), or
+    /// `None` if missing
+    pub fn minor_axis(&self) -> Option<f64> {
+        scaled(
+            self.scale_factor_of_minor_axis,
+            self.scale_value_of_minor_axis,
+        )
+    }
+
+    /// Unit of the coordinates in degrees (10^-6 unless the basic angle is given)
+    pub fn angle_unit(&self) -> f64 {
+        match (self.basic_angle, self.subdivisions_of_basic_angle) {
+            (0 | u32::MAX, _) | (_, 0 | u32::MAX) => 1e-6,
+            (angle, subdivisions) => angle as f64 / subdivisions as f64,
+        }
+    }
+
+    /// Latitude and longitude in degrees at row `j` and column `i` (fractional indices allowed)
+    ///
+    /// Longitudes run from `lo1` in the scanning direction without being normalized, so
+    /// they may exceed 180 or 360.
+    pub fn lat_lon(&self, j: f64, i: f64) -> (f64, f64) {
+        let unit = self.angle_unit();
+        let (la1, la2) = (self.la1 as f64 * unit, self.la2 as f64 * unit);
+        let (lo1, mut lo2) = (self.lo1 as f64 * unit, self.lo2 as f64 * unit);
+        if self.scanning_mode & 0x80 == 0 && lo2 < lo1 {
+            lo2 += 360.0;
+        } else if self.scanning_mode & 0x80 != 0 && lo2 > lo1 {
+            lo2 -= 360.0;
+        }
+        let step = |first: f64, last: f64, n: u32| match n {
+            0 | 1 => 0.0,
+            n => (last - first) / (n - 1) as f64,
+        };
+        (
+            la1 + j * step(la1, la2, self.n_j),
+            lo1 + i * step(lo1, lo2, self.n_i),
+        )
+    }
+}
+
+/// `value * 10^-factor`, or `None` if the scale factor or the value is missing (all bits one)
+fn scaled(factor: u8, value: u32) -> Option<f64> {
+    if factor == u8::MAX || value == u32::MAX {
+        return None;
+    }
+    Some(value as f64 * 10f64.powi(-(factor as i32)))
+}
+
+/// Template 3.101 (General unstructured grid)
+///
+/// The coordinates of the points are not in the message but in a grid description
+/// identified by `uuid`, see [`crate::icon`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridDefinitionTemplate3_101 {
+    pub shape_of_earth: u8,
+    /// Number of the grid used (e.g. 26 for the ICON global R3B7 grid)
+    pub number_of_grid_used: u32,
+    /// Number of the grid in the reference (e.g. 1 for cell centres of an ICON grid)
+    pub number_of_grid_in_reference: u8,
+    /// UUID of the horizontal grid
+    pub uuid: [u8; 16],
+}
+
+impl GridDefinitionTemplate3_101 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut tmpl = Self {
+            shape_of_earth: reader.read_grib_value()?,
+            number_of_grid_used: reader.read_grib_u24()?,
+            number_of_grid_in_reference: reader.read_grib_value()?,
+            uuid: [0; 16],
+        };
+        reader.read_exact(&mut tmpl.uuid)?;
+        Ok(tmpl)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.shape_of_earth)?;
+        writer.write_u24::<BigEndian>(self.number_of_grid_used)?;
+        writer.write_grib_value(self.number_of_grid_in_reference)?;
+        writer.write_all(&self.uuid)?;
+        Ok(())
+    }
+
+    /// UUID in the usual hyphenated form (e.g. "a27b8de6-18c4-11e4-820a-b5b098c6a5c0")
+    pub fn uuid_string(&self) -> String {
+        format_uuid(&self.uuid)
+    }
+}
+
+/// Template 3.120 (Azimuth-range projection)
+///
+/// The values are ordered radial by radial, `n_b` bins from the radar outwards each.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GridDefinitionTemplate3_120 {
+    /// Number of bins along each radial
+    pub n_b: u32,
+    /// Number of radials
+    pub n_r: u32,
+    /// Latitude and longitude of the radar site in 10^-6 degrees
+    pub la1: i32,
+    pub lo1: i32,
+    /// Spacing of the bins along radials in 10^-3 m
+    pub d_x: u32,
+    /// Distance from the radar to the inner bound of the first bin in 10^-3 m
+    pub d_start: u32,
+    pub scanning_mode: u8,
+    /// Starting azimuth (10^-1 degrees clockwise from north) and azimuthal width
+    /// (10^-2 degrees, negative for counterclockwise) of each radial
+    pub radials: Vec<(u16, i16)>,
+}
+
+impl GridDefinitionTemplate3_120 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut tmpl = Self {
+            n_b: reader.read_grib_value()?,
+            n_r: reader.read_grib_value()?,
+            la1: reader.read_grib_value()?,
+            lo1: reader.read_grib_value()?,
+            d_x: reader.read_grib_value()?,
+            d_start: reader.read_grib_value()?,
+            scanning_mode: reader.read_grib_value()?,
+            radials: Vec::new(),
+        };
+        let n_r = check_allocation::<(u16, i16)>(tmpl.n_r as usize, "number of radials")?;
+        tmpl.radials.reserve(n_r);
+        for _ in 0..n_r {
+            tmpl.radials
+                .push((reader.read_grib_value()?, reader.read_grib_value()?));
+        }
+        Ok(tmpl)
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.n_b)?;
+        writer.write_grib_value(self.radials.len() as u32)?;
+        writer.write_grib_value(self.la1)?;
+        writer.write_grib_value(self.lo1)?;
+        writer.write_grib_value(self.d_x)?;
+        writer.write_grib_value(self.d_start)?;
+        writer.write_grib_value(self.scanning_mode)?;
+        for &(azimuth, width) in &self.radials {
+            writer.write_grib_value(azimuth)?;
+            writer.write_grib_value(width)?;
+        }
+        Ok(())
+    }
+
+    /// Latitude and longitude of the radar site in degrees
+    pub fn site(&self) -> (f64, f64) {
+        (self.la1 as f64 * 1e-6, self.lo1 as f64 * 1e-6)
+    }
+
+    /// Distance in metres from the radar to the outer bound of the last bin
+    pub fn max_range(&self) -> f64 {
+        (self.d_start as f64 + self.n_b as f64 * self.d_x as f64) * 1e-3
+    }
+
+    /// Radial and bin containing the point at `azimuth` degrees clockwise from north and
+    /// `range` metres from the radar, `None` outside all radials
+    pub fn locate(&self, azimuth: f64, range: f64) -> Option<(usize, usize)> {
+        let bin = (range * 1e3 - self.d_start as f64) / self.d_x as f64;
+        if !(0.0..self.n_b as f64).contains(&bin) {
+            return None;
+        }
+        let radial = self.radials.iter().position(|&(start, width)| {
+            let start = start as f64 * 1e-1;
+            let width = width as f64 * 1e-2;
+            // angle from the start of the radial in its direction of sweep
+            let offset = (azimuth - start) * width.signum();
+            offset.rem_euclid(360.0) < width.abs()
+        })?;
+        Some((radial, bin as usize))
+    }
+}
+
+/// Format a UUID in the hyphenated form
+pub fn format_uuid(uuid: &[u8; 16]) -> String {
+    let mut s = String::with_capacity(36);
+    for (i, b) in uuid.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            s.push('-');
+        }
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+/// Parse a UUID in the hyphenated (or plain hexadecimal) form
+pub fn parse_uuid(s: &str) -> Option<[u8; 16]> {
+    let digits = s
+        .trim()
+        .chars()
+        .filter(|&c| c != '-')
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<Vec<_>>>()?;
+    if digits.len() != 32 {
+        return None;
+    }
+    let mut uuid = [0; 16];
+    for (b, pair) in uuid.iter_mut().zip(digits.chunks(2)) {
+        *b = pair[0] << 4 | pair[1];
+    }
+    Some(uuid)
+}
+
+/// Any grid definition template, dispatched by template number
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GridDefinitionTemplate {
+    Template3_0(GridDefinitionTemplate3_0),
+    Template3_101(GridDefinitionTemplate3_101),
+    Template3_120(GridDefinitionTemplate3_120),
+    /// Template parsed by a parser registered with [`register_template`]
+    ///
+    /// Skipped by serde: serializing it fails, as the parsed value is opaque.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn CustomTemplate>),
+    /// Template not implemented by this crate (raw template bytes)
+    Unknown(Vec<u8>),
+}
+
+impl GridDefinitionTemplate {
+    /// Read the template identified by `template_number` (Section 3, octets 13-14)
+    pub fn read_any<R: Read>(template_number: u16, reader: &mut R) -> Result<Self> {
+        Self::read_any_for_centre(template_number, None, reader)
+    }
+
+    /// Read the template, consulting the parsers registered for the originating `centre`
+    pub fn read_any_for_centre<R: Read>(
+        template_number: u16,
+        centre: Option<u16>,
+        reader: &mut R,
+    ) -> Result<Self> {
+        Ok(match template_number {
+            0 => Self::Template3_0(GridDefinitionTemplate3_0::read(reader)?),
+            101 => Self::Template3_101(GridDefinitionTemplate3_101::read(reader)?),
+            120 => Self::Template3_120(GridDefinitionTemplate3_120::read(reader)?),
+            _ => match read_registered(3, template_number, centre, reader) {
+                Some(template) => Self::Custom(template?),
+                None => {
+                    let mut buf = Vec::new();
+                    reader.read_to_end(&mut buf)?;
+                    Self::Unknown(buf)
+                }
+            },
+        })
+    }
+
+    /// Write the template (Section 3, octets 15-nn)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Template3_0(t) => t.write(writer),
+            Self::Template3_101(t) => t.write(writer),
+            Self::Template3_120(t) => t.write(writer),
+            Self::Custom(_) => Err(Error::UnsupportedData(
+                "writing a custom grid definition template".to_string(),
+            )),
+            Self::Unknown(buf) => Ok(writer.write_all(buf)?),
+        }
+    }
+
+    /// Number of rows and columns (nj, ni) of a regular grid
+    pub fn shape(&self) -> Option<(u32, u32)> {
+        match self {
+            Self::Template3_0(t) => Some((t.n_j, t.n_i)),
+            Self::Template3_120(t) => Some((t.n_r, t.n_b)),
+            _ => None,
+        }
+    }
+
+    /// Latitude and longitude in degrees at row `j` and column `i`, for grids with known
+    /// coordinates
+    ///
+    /// Unstructured grids (Template 3.101) need their grid description, see [`crate::icon`],
+    /// and azimuth-range grids (Template 3.120) are resampled by [`crate::radar`].
+    pub fn lat_lon(&self, j: f64, i: f64) -> Option<(f64, f64)> {
+        match self {
+            Self::Template3_0(t) => Some(t.lat_lon(j, i)),
+            _ => None,
+        }
+    }
+
+    /// Whether `read_any` parses the template (built in or registered for all centres)
+    pub fn is_supported(template_number: u16) -> bool {
+        matches!(template_number, 0 | 101 | 120) || is_registered(3, template_number, None)
+    }
 }
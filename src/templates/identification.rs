@@ -0,0 +1,129 @@
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::registry::{CustomTemplate, read_registered};
+use super::{GribRead, GribWrite};
+use crate::{Error, Result};
+
+/// Template 1.0 (calendar definition)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdentificationTemplate1_0 {
+    pub type_of_calendar: u8,
+}
+
+impl IdentificationTemplate1_0 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            type_of_calendar: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.type_of_calendar)?;
+        Ok(())
+    }
+}
+
+/// Template 1.1 (paleontological offset)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdentificationTemplate1_1 {
+    pub number_of_tens_of_thousands_of_years_of_offset: u16,
+}
+
+impl IdentificationTemplate1_1 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            number_of_tens_of_thousands_of_years_of_offset: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.number_of_tens_of_thousands_of_years_of_offset)?;
+        Ok(())
+    }
+}
+
+/// Template 1.2 (calendar definition and paleontological offset)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct IdentificationTemplate1_2 {
+    pub type_of_calendar: u8,
+    pub number_of_tens_of_thousands_of_years_of_offset: u16,
+}
+
+impl IdentificationTemplate1_2 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            type_of_calendar: reader.read_grib_value()?,
+            number_of_tens_of_thousands_of_years_of_offset: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.type_of_calendar)?;
+        writer.write_grib_value(self.number_of_tens_of_thousands_of_years_of_offset)?;
+        Ok(())
+    }
+}
+
+/// Any identification template, dispatched by template number
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum IdentificationTemplate {
+    Template1_0(IdentificationTemplate1_0),
+    Template1_1(IdentificationTemplate1_1),
+    Template1_2(IdentificationTemplate1_2),
+    /// Template parsed by a parser registered with [`register_template`]
+    ///
+    /// Skipped by serde: serializing it fails, as the parsed value is opaque.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn CustomTemplate>),
+    /// Template not implemented by this crate (raw template bytes)
+    Unknown(Vec<u8>),
+}
+
+impl IdentificationTemplate {
+    /// Read the template identified by `template_number` (Section 1, octets 22-23)
+    pub fn read_any<R: Read>(template_number: u16, reader: &mut R) -> Result<Self> {
+        Self::read_any_for_centre(template_number, None, reader)
+    }
+
+    /// Read the template, consulting the parsers registered for the originating `centre`
+    pub fn read_any_for_centre<R: Read>(
+        template_number: u16,
+        centre: Option<u16>,
+        reader: &mut R,
+    ) -> Result<Self> {
+        Ok(match template_number {
+            0 => Self::Template1_0(IdentificationTemplate1_0::read(reader)?),
+            1 => Self::Template1_1(IdentificationTemplate1_1::read(reader)?),
+            2 => Self::Template1_2(IdentificationTemplate1_2::read(reader)?),
+            _ => match read_registered(1, template_number, centre, reader) {
+                Some(template) => Self::Custom(template?),
+                None => {
+                    let mut buf = Vec::new();
+                    reader.read_to_end(&mut buf)?;
+                    Self::Unknown(buf)
+                }
+            },
+        })
+    }
+
+    /// Write the template (Section 1, octets 24-nn)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Template1_0(t) => t.write(writer),
+            Self::Template1_1(t) => t.write(writer),
+            Self::Template1_2(t) => t.write(writer),
+            Self::Custom(_) => Err(Error::UnsupportedData(
+                "writing a custom identification template".to_string(),
+            )),
+            Self::Unknown(buf) => Ok(writer.write_all(buf)?),
+        }
+    }
+}
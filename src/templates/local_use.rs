@@ -0,0 +1,230 @@
+use std::io::Read;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "jma", feature = "ecmwf", feature = "ncep"))]
+use super::GribRead;
+#[cfg(feature = "jma")]
+use super::OperatingInfo;
+use crate::Result;
+
+/// Originating centre of NCEP (Washington) in Common Code Table C-11
+pub const CENTRE_NCEP: u16 = 7;
+/// Originating centre of JMA (Tokyo) in Common Code Table C-11
+pub const CENTRE_JMA: u16 = 34;
+/// Originating centre of NOAA OAR (Office of Oceanic and Atmospheric Research), used by MRMS
+pub const CENTRE_NOAA_OAR: u16 = 161;
+/// Originating centre of ECMWF in Common Code Table C-11
+pub const CENTRE_ECMWF: u16 = 98;
+
+/// JMA local use section (Section 2)
+#[cfg(feature = "jma")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JmaLocalUse {
+    pub version_of_local_use: u8,
+    pub run_identifier: u16,
+    /// Quality control information, whose layout depends on `version_of_local_use`
+    pub quality_info: JmaQualityInfo,
+}
+
+#[cfg(feature = "jma")]
+impl JmaLocalUse {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let version_of_local_use = reader.read_grib_value()?;
+        let run_identifier = reader.read_grib_value()?;
+        let quality_info = match version_of_local_use {
+            1 => JmaQualityInfo::Version1(OperatingInfo::read(reader)?),
+            _ => {
+                let mut buf = Vec::new();
+                reader.read_to_end(&mut buf)?;
+                JmaQualityInfo::Unknown(buf)
+            }
+        };
+        Ok(Self {
+            version_of_local_use,
+            run_identifier,
+            quality_info,
+        })
+    }
+
+    /// Radar and rain gauge operating information, if the version of the section has it
+    pub fn operating_info(&self) -> Option<&OperatingInfo> {
+        match &self.quality_info {
+            JmaQualityInfo::Version1(info) => Some(info),
+            JmaQualityInfo::Unknown(_) => None,
+        }
+    }
+}
+
+/// Quality control information of a JMA local use section
+#[cfg(feature = "jma")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum JmaQualityInfo {
+    /// Version 1: radar sites and rain gauge networks in operation, in the layout of the
+    /// operating information of templates 4.50008 and 4.50010
+    Version1(OperatingInfo),
+    /// Version not known to this crate (octets after the run identifier)
+    Unknown(Vec<u8>),
+}
+
+/// ECMWF local use section (Section 2) with MARS labelling
+#[cfg(feature = "ecmwf")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EcmwfLocalUse {
+    pub local_definition_number: u16,
+    /// MARS class (e.g. 1 = "od" operational archive)
+    pub class: u8,
+    /// MARS type (e.g. 2 = "an" analysis, 9 = "fc" forecast)
+    pub type_: u8,
+    /// MARS stream (e.g. 1025 = "oper", 1035 = "enfo")
+    pub stream: u16,
+    /// Experiment version (e.g. "0001")
+    pub expver: String,
+    /// Octets specific to the local definition number
+    pub local_definition: Vec<u8>,
+}
+
+#[cfg(feature = "ecmwf")]
+impl EcmwfLocalUse {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut tmpl = Self {
+            local_definition_number: reader.read_grib_value()?,
+            class: reader.read_grib_value()?,
+            type_: reader.read_grib_value()?,
+            stream: reader.read_grib_value()?,
+            expver: {
+                let mut buf = [0u8; 4];
+                reader.read_exact(&mut buf)?;
+                String::from_utf8_lossy(&buf).into_owned()
+            },
+            local_definition: Vec::new(),
+        };
+        reader.read_to_end(&mut tmpl.local_definition)?;
+        Ok(tmpl)
+    }
+
+    /// MARS class name (e.g. "od"), `None` if not known to this crate
+    pub fn class_name(&self) -> Option<&'static str> {
+        Some(match self.class {
+            1 => "od",
+            2 => "rd",
+            _ => return None,
+        })
+    }
+
+    /// MARS type name (e.g. "fc"), `None` if not known to this crate
+    pub fn type_name(&self) -> Option<&'static str> {
+        Some(match self.type_ {
+            2 => "an",
+            9 => "fc",
+            10 => "cf",
+            11 => "pf",
+            17 => "em",
+            18 => "es",
+            _ => return None,
+        })
+    }
+
+    /// MARS stream name (e.g. "oper"), `None` if not known to this crate
+    pub fn stream_name(&self) -> Option<&'static str> {
+        Some(match self.stream {
+            1025 => "oper",
+            1030 => "scda",
+            1035 => "enfo",
+            _ => return None,
+        })
+    }
+}
+
+/// NCEP local use section (Section 2)
+#[cfg(feature = "ncep")]
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NcepLocalUse {
+    /// Local use section identifier (e.g. 1 = ensemble information)
+    pub local_section_id: u8,
+    pub data: Vec<u8>,
+}
+
+#[cfg(feature = "ncep")]
+impl NcepLocalUse {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut tmpl = Self {
+            local_section_id: reader.read_grib_value()?,
+            data: Vec::new(),
+        };
+        reader.read_to_end(&mut tmpl.data)?;
+        Ok(tmpl)
+    }
+}
+
+/// Local use section contents, dispatched by the originating centre in Section 1
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LocalUse {
+    #[cfg(feature = "ncep")]
+    Ncep(NcepLocalUse),
+    #[cfg(feature = "jma")]
+    Jma(JmaLocalUse),
+    #[cfg(feature = "ecmwf")]
+    Ecmwf(EcmwfLocalUse),
+    /// Centre not supported (or not enabled) by this crate (raw section body)
+    Unknown(Vec<u8>),
+}
+
+impl LocalUse {
+    /// Read Section 2 contents of a message from `centre` (Section 1, octets 6-7)
+    pub fn read_any<R: Read>(centre: u16, reader: &mut R) -> Result<Self> {
+        Ok(match centre {
+            #[cfg(feature = "ncep")]
+            CENTRE_NCEP => Self::Ncep(NcepLocalUse::read(reader)?),
+            #[cfg(feature = "jma")]
+            CENTRE_JMA => Self::Jma(JmaLocalUse::read(reader)?),
+            #[cfg(feature = "ecmwf")]
+            CENTRE_ECMWF => Self::Ecmwf(EcmwfLocalUse::read(reader)?),
+            _ => {
+                let mut buf = Vec::new();
+                reader.read_to_end(&mut buf)?;
+                Self::Unknown(buf)
+            }
+        })
+    }
+}
+
+#[cfg(all(test, feature = "jma"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jma_version_1_operating_info() {
+        let mut body = vec![1, 0, 7];
+        body.extend(0x8000_0000_0000_0001u64.to_be_bytes());
+        body.extend(0u64.to_be_bytes());
+        body.extend(0x4000_0000_0000_0000u64.to_be_bytes());
+        let LocalUse::Jma(local_use) =
+            LocalUse::read_any(CENTRE_JMA, &mut body.as_slice()).unwrap()
+        else {
+            panic!("not a JMA local use section");
+        };
+        assert_eq!(local_use.run_identifier, 7);
+        let info = local_use.operating_info().unwrap();
+        assert!(info.is_radar_operating(0));
+        assert!(info.is_radar_operating(63));
+        assert!(!info.is_radar_operating(64));
+        assert!(info.is_rain_gauge_operating(1));
+    }
+
+    #[test]
+    fn jma_unknown_version_keeps_octets() {
+        let body = [9, 0, 1, 0xAB, 0xCD];
+        let local_use = JmaLocalUse::read(&mut body.as_slice()).unwrap();
+        assert!(local_use.operating_info().is_none());
+        assert!(
+            matches!(local_use.quality_info, JmaQualityInfo::Unknown(ref v) if v == &[0xAB, 0xCD])
+        );
+    }
+}
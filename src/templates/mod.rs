@@ -1,16 +1,25 @@
+pub(crate) mod ccsds;
 pub mod data;
 pub mod data_representation;
 pub mod grid_definition;
+pub mod identification;
+pub mod local_use;
+pub(crate) mod png;
 pub mod product_definition;
+pub mod registry;
 
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use std::io::Read;
 use std::io::Result;
+use std::io::Write;
 
 pub use data::*;
 pub use data_representation::*;
 pub use grid_definition::*;
+pub use identification::*;
+pub use local_use::*;
 pub use product_definition::*;
+pub use registry::{CustomTemplate, TemplateParser, register_template, unregister_template};
 
 pub trait FromGribValue: Sized {
     fn from_grib_reader(reader: impl ReadBytesExt) -> Result<Self>;
@@ -73,23 +82,182 @@ impl FromGribValue for u64 {
     }
 }
 
+/// Values of all bits set to one, used by GRIB for missing values
+///
+/// Signed values are in sign and magnitude, so all bits set to one reads as `-MAX`.
+pub trait AllBitsOne: Sized + PartialEq {
+    const ALL_BITS_ONE: Self;
+}
+
+impl AllBitsOne for u8 {
+    const ALL_BITS_ONE: Self = u8::MAX;
+}
+
+impl AllBitsOne for i8 {
+    const ALL_BITS_ONE: Self = -i8::MAX;
+}
+
+impl AllBitsOne for u16 {
+    const ALL_BITS_ONE: Self = u16::MAX;
+}
+
+impl AllBitsOne for i16 {
+    const ALL_BITS_ONE: Self = -i16::MAX;
+}
+
+impl AllBitsOne for u32 {
+    const ALL_BITS_ONE: Self = u32::MAX;
+}
+
+impl AllBitsOne for i32 {
+    const ALL_BITS_ONE: Self = -i32::MAX;
+}
+
+impl AllBitsOne for u64 {
+    const ALL_BITS_ONE: Self = u64::MAX;
+}
+
+/// `None` for a missing value (all bits set to one)
+impl<T: FromGribValue + AllBitsOne> FromGribValue for Option<T> {
+    fn from_grib_reader(reader: impl ReadBytesExt) -> Result<Self> {
+        let value = T::from_grib_reader(reader)?;
+        Ok((value != T::ALL_BITS_ONE).then_some(value))
+    }
+}
+
+/// Readers of the octets of GRIB templates
+///
+/// [`read_grib_value`](Self::read_grib_value) reads big-endian values, with signed integers in
+/// sign and magnitude (e.g. `0x8001` is -1 as `i16`), and `Option`s that are `None` when all
+/// bits are set to one. This is what template parsers registered with
+/// [`register_template`] need:
+///
+/// ```ignore
+/// fn parse(reader: &mut dyn Read) -> tinygrib2::Result<Arc<dyn CustomTemplate>> {
+///     let level: Option<u32> = reader.read_grib_value()?;
+///     let offset: i16 = reader.read_grib_value()?;
+///     let count = reader.read_grib_u24()?;
+///     // ...
+/// }
+/// ```
 pub trait GribRead: ReadBytesExt {
     fn read_grib_value<T: FromGribValue>(&mut self) -> Result<T> {
         T::from_grib_reader(self)
     }
+
+    /// Read a 3-octet unsigned value
+    fn read_grib_u24(&mut self) -> Result<u32> {
+        self.read_u24::<BigEndian>()
+    }
+
+    /// Read a 3-octet value in sign and magnitude
+    fn read_grib_i24(&mut self) -> Result<i32> {
+        Ok(match self.read_grib_u24()? {
+            u if u < 0x800000 => u as i32,
+            u => -((u & 0x7FFFFF) as i32),
+        })
+    }
 }
 
-impl<T: Read> GribRead for T {}
+impl<T: Read + ?Sized> GribRead for T {}
 
 pub fn read_octets<R: ReadBytesExt>(mut reader: R, n: u8) -> std::io::Result<i32> {
     Ok(match n {
         1 => i8::from_grib_reader(reader)? as i32,
         2 => i16::from_grib_reader(reader)? as i32,
-        3 => match reader.read_u24::<byteorder::BigEndian>()? {
-            u if u < 0x800000 => u as i32,
-            u => -((u & 0x7FFFFF) as i32),
-        },
+        3 => reader.read_grib_i24()?,
         4 => i32::from_grib_reader(reader)?,
         _ => unreachable!(),
     })
 }
+
+pub trait ToGribValue {
+    fn to_grib_writer(self, writer: impl WriteBytesExt) -> Result<()>;
+}
+
+impl ToGribValue for u8 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u8(self)
+    }
+}
+
+impl ToGribValue for i8 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        // sign and magnitude; -128 saturates to -127
+        writer.write_u8(match self {
+            v if v >= 0 => v as u8,
+            v => 0x80 | v.unsigned_abs().min(0x7F),
+        })
+    }
+}
+
+impl ToGribValue for u16 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u16::<BigEndian>(self)
+    }
+}
+
+impl ToGribValue for i16 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u16::<BigEndian>(match self {
+            v if v >= 0 => v as u16,
+            v => 0x8000 | v.unsigned_abs().min(0x7FFF),
+        })
+    }
+}
+
+impl ToGribValue for f32 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_f32::<BigEndian>(self)
+    }
+}
+
+impl ToGribValue for u32 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u32::<BigEndian>(self)
+    }
+}
+
+impl ToGribValue for i32 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u32::<BigEndian>(match self {
+            v if v >= 0 => v as u32,
+            v => 0x80000000 | v.unsigned_abs().min(0x7FFFFFFF),
+        })
+    }
+}
+
+impl ToGribValue for u64 {
+    fn to_grib_writer(self, mut writer: impl WriteBytesExt) -> Result<()> {
+        writer.write_u64::<BigEndian>(self)
+    }
+}
+
+/// All bits set to one for `None`
+impl<T: ToGribValue + AllBitsOne> ToGribValue for Option<T> {
+    fn to_grib_writer(self, writer: impl WriteBytesExt) -> Result<()> {
+        self.unwrap_or(T::ALL_BITS_ONE).to_grib_writer(writer)
+    }
+}
+
+pub trait GribWrite: WriteBytesExt {
+    fn write_grib_value<T: ToGribValue>(&mut self, value: T) -> Result<()> {
+        value.to_grib_writer(self)
+    }
+}
+
+impl<T: Write + ?Sized> GribWrite for T {}
+
+/// Write `value` in `n` octets (sign and magnitude), the inverse of [`read_octets`]
+pub fn write_octets<W: WriteBytesExt>(mut writer: W, n: u8, value: i32) -> std::io::Result<()> {
+    match n {
+        1 => (value.clamp(-0x7F, 0x7F) as i8).to_grib_writer(writer),
+        2 => (value.clamp(-0x7FFF, 0x7FFF) as i16).to_grib_writer(writer),
+        3 => writer.write_u24::<byteorder::BigEndian>(match value.clamp(-0x7FFFFF, 0x7FFFFF) {
+            v if v >= 0 => v as u32,
+            v => 0x800000 | v.unsigned_abs(),
+        }),
+        4 => value.to_grib_writer(writer),
+        _ => unreachable!(),
+    }
+}
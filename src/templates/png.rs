@@ -0,0 +1,676 @@
+//! Minimal PNG encoder for Template 7.41 and rendering (zlib stream with fixed Huffman codes),
+//! and decoder for Template 7.41 with a built-in inflate
+
+use bitstream_io::{BitWrite, BitWriter, LittleEndian};
+
+use crate::limits::check_allocation;
+use crate::{Error, Result};
+
+/// Encode samples of `bit_depth` bits (8, 16, 24 or 32) as a grayscale, RGB or RGBA image
+///
+/// Each row is filtered with the Sub filter before compression.
+pub(crate) fn encode(samples: &[u32], width: u32, height: u32, bit_depth: u8) -> Vec<u8> {
+    let (depth, color_type, bytes_per_pixel) = match bit_depth {
+        8 => (8, 0, 1),
+        16 => (16, 0, 2),
+        24 => (8, 2, 3),
+        32 => (8, 6, 4),
+        _ => unreachable!("unsupported bit depth"),
+    };
+    let stride = width as usize * bytes_per_pixel;
+    let mut raw = Vec::with_capacity((stride + 1) * height as usize);
+    for row in samples.chunks(width as usize) {
+        raw.push(1); // Sub
+        let start = raw.len();
+        for &s in row {
+            raw.extend_from_slice(&s.to_be_bytes()[4 - bytes_per_pixel..]);
+        }
+        for i in (start + bytes_per_pixel..raw.len()).rev() {
+            raw[i] = raw[i].wrapping_sub(raw[i - bytes_per_pixel]);
+        }
+    }
+
+    let mut ihdr = Vec::with_capacity(13);
+    ihdr.extend_from_slice(&width.to_be_bytes());
+    ihdr.extend_from_slice(&height.to_be_bytes());
+    ihdr.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    write_chunk(&mut png, b"IHDR", &ihdr);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, &b| {
+        (0..8).fold(crc ^ b as u32, |c, _| match c & 1 {
+            1 => 0xEDB88320 ^ (c >> 1),
+            _ => c >> 1,
+        })
+    })
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (a, b) = data.iter().fold((1u32, 0u32), |(a, b), &x| {
+        let a = (a + x as u32) % 65521;
+        (a, (b + a) % 65521)
+    });
+    (b << 16) | a
+}
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+
+type Bits<'a> = BitWriter<&'a mut Vec<u8>, LittleEndian>;
+
+/// Compress `data` into a zlib stream of a single block with fixed Huffman codes
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut writer = BitWriter::endian(&mut out, LittleEndian);
+    write_bits(&mut writer, 1, 1); // BFINAL
+    write_bits(&mut writer, 2, 1); // fixed Huffman codes
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; data.len()];
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if i + MIN_MATCH <= data.len() {
+            let max_len = MAX_MATCH.min(data.len() - i);
+            let mut candidate = head[hash(&data[i..])];
+            let mut chain = 0;
+            while candidate != usize::MAX && i - candidate <= WINDOW && chain < MAX_CHAIN {
+                let len = data[candidate..]
+                    .iter()
+                    .zip(&data[i..i + max_len])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, i - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                chain += 1;
+            }
+        }
+        let len = if best_len >= MIN_MATCH {
+            let code = LENGTH_BASES.partition_point(|&b| b as usize <= best_len) - 1;
+            write_literal_length(&mut writer, 257 + code as u32);
+            write_bits(
+                &mut writer,
+                LENGTH_EXTRA[code] as u32,
+                (best_len - LENGTH_BASES[code] as usize) as u32,
+            );
+            let code = DISTANCE_BASES.partition_point(|&b| b as usize <= best_dist) - 1;
+            write_code(&mut writer, code as u32, 5);
+            write_bits(
+                &mut writer,
+                DISTANCE_EXTRA[code] as u32,
+                (best_dist - DISTANCE_BASES[code] as usize) as u32,
+            );
+            best_len
+        } else {
+            write_literal_length(&mut writer, data[i] as u32);
+            1
+        };
+        for j in i..i + len {
+            if j + MIN_MATCH <= data.len() {
+                let h = hash(&data[j..]);
+                prev[j] = head[h];
+                head[h] = j;
+            }
+        }
+        i += len;
+    }
+    write_literal_length(&mut writer, 256); // end of block
+    writer.byte_align().expect("writing to a Vec cannot fail");
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+fn hash(data: &[u8]) -> usize {
+    let v = (data[0] as u32) << 16 | (data[1] as u32) << 8 | data[2] as u32;
+    (v.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+fn write_bits(writer: &mut Bits, bits: u32, value: u32) {
+    if bits > 0 {
+        writer
+            .write_var(bits, value)
+            .expect("writing to a Vec cannot fail");
+    }
+}
+
+fn write_literal_length(writer: &mut Bits, symbol: u32) {
+    match symbol {
+        0..=143 => write_code(writer, 0x30 + symbol, 8),
+        144..=255 => write_code(writer, 0x190 + symbol - 144, 9),
+        256..=279 => write_code(writer, symbol - 256, 7),
+        _ => write_code(writer, 0xC0 + symbol - 280, 8),
+    }
+}
+
+/// Huffman codes are packed starting from their most significant bit
+fn write_code(writer: &mut Bits, code: u32, bits: u32) {
+    write_bits(writer, bits, code.reverse_bits() >> (32 - bits));
+}
+
+/// Decode a grayscale, grayscale with alpha, RGB or RGBA image of at most 32 bits per pixel
+///
+/// Each pixel becomes a sample of its channels' bits, most significant first, as written by
+/// [`encode`] and other Template 7.41 encoders. Chunk CRCs are not checked; the Adler-32 of
+/// the image data is.
+pub(crate) fn decode(png: &[u8]) -> Result<Vec<u32>> {
+    let mut chunks = png
+        .strip_prefix(b"\x89PNG\r\n\x1a\n")
+        .ok_or_else(|| invalid("missing signature"))?;
+    let mut header = None;
+    let mut idat = Vec::new();
+    loop {
+        if chunks.len() < 12 {
+            return Err(invalid("chunks end before IEND"));
+        }
+        let length = u32::from_be_bytes(chunks[..4].try_into().unwrap()) as usize;
+        let kind = &chunks[4..8];
+        let data = chunks
+            .get(8..8 + length)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        chunks = chunks
+            .get(12 + length..)
+            .ok_or_else(|| invalid("truncated chunk"))?;
+        match kind {
+            b"IHDR" if data.len() == 13 => header = Some(data),
+            b"IDAT" => idat.extend_from_slice(data),
+            b"IEND" => break,
+            // ancillary chunks
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => {
+                return Err(Error::UnsupportedData(format!(
+                    "PNG chunk {}",
+                    String::from_utf8_lossy(kind)
+                )));
+            }
+        }
+    }
+    let header = header.ok_or_else(|| invalid("missing IHDR"))?;
+    let width = u32::from_be_bytes(header[..4].try_into().unwrap()) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().unwrap()) as usize;
+    let (depth, color_type) = (header[8], header[9]);
+    let channels = match (color_type, depth) {
+        (0, 1 | 2 | 4 | 8 | 16) => 1,
+        (4, 8 | 16) => 2,
+        (2, 8 | 16) => 3,
+        (6, 8 | 16) => 4,
+        _ => {
+            return Err(Error::UnsupportedData(format!(
+                "PNG color type {color_type} of bit depth {depth}"
+            )));
+        }
+    };
+    let bits_per_pixel = channels * depth as usize;
+    if bits_per_pixel > 32 {
+        return Err(Error::UnsupportedData(format!(
+            "PNG of {bits_per_pixel} bits per pixel"
+        )));
+    }
+    if header[12] != 0 {
+        return Err(Error::UnsupportedData("interlaced PNG".to_string()));
+    }
+    let n = check_allocation::<u32>(width.saturating_mul(height), "number of PNG pixels")?;
+    let stride = (width * bits_per_pixel).div_ceil(8);
+    let size = check_allocation::<u8>((stride + 1).saturating_mul(height), "PNG image data")?;
+    let raw = inflate(&idat, size)?;
+    if raw.len() != size {
+        return Err(invalid("image data is shorter than the image"));
+    }
+
+    let bytes_per_pixel = bits_per_pixel.div_ceil(8);
+    let mut samples = Vec::with_capacity(n);
+    let mut previous = vec![0u8; stride];
+    let mut row = vec![0u8; stride];
+    for line in raw.chunks(stride + 1) {
+        row.copy_from_slice(&line[1..]);
+        unfilter(line[0], &mut row, &previous, bytes_per_pixel)?;
+        if bits_per_pixel < 8 {
+            let mask = (1u8 << bits_per_pixel) - 1;
+            samples.extend((0..width).map(|i| {
+                let offset = i * bits_per_pixel;
+                (row[offset / 8] >> (8 - bits_per_pixel - offset % 8) & mask) as u32
+            }));
+        } else {
+            samples.extend(
+                row.chunks(bytes_per_pixel)
+                    .map(|p| p.iter().fold(0u32, |v, &b| v << 8 | b as u32)),
+            );
+        }
+        std::mem::swap(&mut row, &mut previous);
+    }
+    Ok(samples)
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidData(format!("invalid PNG: {reason}"))
+}
+
+/// Reverse the filter of type `filter` of `row`, given the unfiltered previous row
+fn unfilter(filter: u8, row: &mut [u8], previous: &[u8], bpp: usize) -> Result<()> {
+    match filter {
+        0 => {}
+        1 => {
+            for i in bpp..row.len() {
+                row[i] = row[i].wrapping_add(row[i - bpp]);
+            }
+        }
+        2 => {
+            for (x, &b) in row.iter_mut().zip(previous) {
+                *x = x.wrapping_add(b);
+            }
+        }
+        3 => {
+            for i in 0..row.len() {
+                let a = if i >= bpp { row[i - bpp] as u16 } else { 0 };
+                row[i] = row[i].wrapping_add(((a + previous[i] as u16) / 2) as u8);
+            }
+        }
+        4 => {
+            for i in 0..row.len() {
+                let (a, c) = match i >= bpp {
+                    true => (row[i - bpp] as i16, previous[i - bpp] as i16),
+                    false => (0, 0),
+                };
+                let b = previous[i] as i16;
+                let p = a + b - c;
+                let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+                let predictor = match () {
+                    _ if pa <= pb && pa <= pc => a,
+                    _ if pb <= pc => b,
+                    _ => c,
+                };
+                row[i] = row[i].wrapping_add(predictor as u8);
+            }
+        }
+        _ => return Err(invalid(&format!("filter type {filter}"))),
+    }
+    Ok(())
+}
+
+/// Decompress a zlib stream of at most `limit` bytes
+fn inflate(data: &[u8], limit: usize) -> Result<Vec<u8>> {
+    match data {
+        [cmf, flg, ..]
+            if cmf & 0x0F == 8 && (*cmf as u16 * 256 + *flg as u16).is_multiple_of(31) =>
+        {
+            if flg & 0x20 != 0 {
+                return Err(Error::UnsupportedData("zlib preset dictionary".to_string()));
+            }
+        }
+        _ => return Err(invalid("zlib header")),
+    }
+    let mut reader = InflateReader {
+        data: &data[2..],
+        position: 0,
+    };
+    let mut out = Vec::with_capacity(limit);
+    loop {
+        let last = reader.read(1)?;
+        match reader.read(2)? {
+            0 => {
+                reader.align();
+                let length = reader.read(16)?;
+                if reader.read(16)? != !length & 0xFFFF {
+                    return Err(invalid("stored block length"));
+                }
+                let start = reader.position / 8;
+                let block = reader
+                    .data
+                    .get(start..start + length as usize)
+                    .ok_or_else(|| invalid("truncated zlib stream"))?;
+                if out.len() + block.len() > limit {
+                    return Err(invalid("image data is longer than the image"));
+                }
+                out.extend_from_slice(block);
+                reader.position += length as usize * 8;
+            }
+            1 => {
+                let mut lengths = [8u8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                let literal_length = Huffman::new(&lengths)?;
+                let distance = Huffman::new(&[5; 30])?;
+                inflate_block(&mut reader, &mut out, limit, &literal_length, &distance)?;
+            }
+            2 => {
+                let (literal_length, distance) = read_dynamic_codes(&mut reader)?;
+                inflate_block(&mut reader, &mut out, limit, &literal_length, &distance)?;
+            }
+            _ => return Err(invalid("zlib block type 3")),
+        }
+        if last == 1 {
+            break;
+        }
+    }
+    reader.align();
+    let start = reader.position / 8;
+    let checksum = reader
+        .data
+        .get(start..start + 4)
+        .ok_or_else(|| invalid("truncated zlib stream"))?;
+    if u32::from_be_bytes(checksum.try_into().unwrap()) != adler32(&out) {
+        return Err(invalid("Adler-32 mismatch"));
+    }
+    Ok(out)
+}
+
+/// Order of the code length code lengths of a dynamic block
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+fn read_dynamic_codes(reader: &mut InflateReader) -> Result<(Huffman, Huffman)> {
+    let hlit = reader.read(5)? as usize + 257;
+    let hdist = reader.read(5)? as usize + 1;
+    let hclen = reader.read(4)? as usize + 4;
+    let mut code_lengths = [0u8; 19];
+    for &i in &CODE_LENGTH_ORDER[..hclen] {
+        code_lengths[i] = reader.read(3)? as u8;
+    }
+    let code_length = Huffman::new(&code_lengths)?;
+    let mut lengths = Vec::with_capacity(hlit + hdist);
+    while lengths.len() < hlit + hdist {
+        let (length, repeat) = match code_length.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (
+                *lengths
+                    .last()
+                    .ok_or_else(|| invalid("repeated code length without a previous one"))?,
+                3 + reader.read(2)?,
+            ),
+            17 => (0, 3 + reader.read(3)?),
+            _ => (0, 11 + reader.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths.len() > hlit + hdist {
+        return Err(invalid("code lengths overrun"));
+    }
+    Ok((
+        Huffman::new(&lengths[..hlit])?,
+        Huffman::new(&lengths[hlit..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut InflateReader,
+    out: &mut Vec<u8>,
+    limit: usize,
+    literal_length: &Huffman,
+    distance: &Huffman,
+) -> Result<()> {
+    loop {
+        let symbol = literal_length.decode(reader)? as usize;
+        let length = match symbol {
+            0..=255 => {
+                if out.len() == limit {
+                    return Err(invalid("image data is longer than the image"));
+                }
+                out.push(symbol as u8);
+                continue;
+            }
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                LENGTH_BASES[code] as usize + reader.read(LENGTH_EXTRA[code] as u32)? as usize
+            }
+            _ => return Err(invalid("length code")),
+        };
+        let code = distance.decode(reader)? as usize;
+        if code >= 30 {
+            return Err(invalid("distance code"));
+        }
+        let dist =
+            DISTANCE_BASES[code] as usize + reader.read(DISTANCE_EXTRA[code] as u32)? as usize;
+        if dist > out.len() {
+            return Err(invalid("distance before the start of the data"));
+        }
+        if out.len() + length > limit {
+            return Err(invalid("image data is longer than the image"));
+        }
+        let start = out.len() - dist;
+        for i in start..start + length {
+            out.push(out[i]);
+        }
+    }
+}
+
+/// Canonical Huffman code, decoded one bit at a time
+struct Huffman {
+    /// Number of codes of each length
+    counts: [u16; 16],
+    /// Symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self> {
+        let mut counts = [0u16; 16];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = left * 2 - count as i32;
+            if left < 0 {
+                return Err(invalid("over-subscribed Huffman code"));
+            }
+        }
+        let mut symbols: Vec<u16> = (0..lengths.len() as u16)
+            .filter(|&s| lengths[s as usize] != 0)
+            .collect();
+        symbols.sort_by_key(|&s| lengths[s as usize]);
+        Ok(Self { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut InflateReader) -> Result<u16> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= reader.read(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(invalid("incomplete Huffman code"))
+    }
+}
+
+/// Reader of the bits of a deflate stream, least significant bit first
+struct InflateReader<'a> {
+    data: &'a [u8],
+    /// Position in bits from the start of `data`
+    position: usize,
+}
+
+impl InflateReader<'_> {
+    /// Read `n` (at most 16) bits, the first read being the least significant
+    fn read(&mut self, n: u32) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..n {
+            let byte = self
+                .data
+                .get(self.position / 8)
+                .ok_or_else(|| invalid("truncated zlib stream"))?;
+            value |= (((byte >> (self.position % 8)) & 1) as u32) << i;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.position = self.position.next_multiple_of(8);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG of `width` x `height` pixels whose image data is the given zlib stream
+    fn png(width: u32, height: u32, depth: u8, color_type: u8, zlib: &[u8]) -> Vec<u8> {
+        let mut ihdr = Vec::new();
+        ihdr.extend_from_slice(&width.to_be_bytes());
+        ihdr.extend_from_slice(&height.to_be_bytes());
+        ihdr.extend_from_slice(&[depth, color_type, 0, 0, 0]);
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        write_chunk(&mut png, b"IHDR", &ihdr);
+        write_chunk(&mut png, b"IDAT", zlib);
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// zlib stream of a single stored block
+    fn stored(data: &[u8]) -> Vec<u8> {
+        let mut zlib = vec![0x78, 0x01, 1];
+        zlib.extend_from_slice(&(data.len() as u16).to_le_bytes());
+        zlib.extend_from_slice(&(!(data.len() as u16)).to_le_bytes());
+        zlib.extend_from_slice(data);
+        zlib.extend_from_slice(&adler32(data).to_be_bytes());
+        zlib
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut state = 0x853c_49e6_748f_ea9bu64;
+        for bit_depth in [8, 16, 24, 32] {
+            let max = u32::MAX >> (32 - bit_depth);
+            // runs of repeated rows exercise back-references, noise the literals
+            let mut samples: Vec<u32> = (0..300 * 20).map(|i| (i / 300 * 37) & max).collect();
+            samples.extend((0..300 * 21).map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 32) as u32 & max
+            }));
+            let encoded = encode(&samples, 300, 41, bit_depth);
+            assert_eq!(decode(&encoded).unwrap(), samples, "{bit_depth} bits");
+        }
+    }
+
+    #[test]
+    fn stored_block_with_filters() {
+        // 4-bit grayscale, 3 x 3 pixels: rows of 2 octets, filtered with Up, Average and Paeth
+        let raw = [
+            2, 0x12, 0x30, //
+            3, 0x40, 0x20, //
+            4, 0x0F, 0xF0,
+        ];
+        let decoded = decode(&png(3, 3, 4, 0, &stored(&raw))).unwrap();
+        // row 1: 1 2 3; row 2: average of left and up; row 3: Paeth predictor
+        let row_1 = [0x12u8, 0x30];
+        let row_2 = [
+            0x40u8 + 0x12 / 2,
+            0x20u8.wrapping_add(((0x40 + 0x12 / 2) + 0x30) / 2),
+        ];
+        let row_3 = [0x0Fu8 + row_2[0], 0xF0u8.wrapping_add(row_2[1])];
+        let nibbles = |row: [u8; 2]| [row[0] >> 4, row[0] & 0xF, row[1] >> 4].map(|v| v as u32);
+        assert_eq!(
+            decoded,
+            [nibbles(row_1), nibbles(row_2), nibbles(row_3)].concat()
+        );
+    }
+
+    #[test]
+    fn dynamic_huffman_codes() {
+        // 16 x 6 pixels, compressed by zlib with dynamic Huffman codes and split into two IDAT
+        // chunks after an ancillary chunk
+        let png: [u8; 174] = [
+            137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 16, 0, 0, 0, 6,
+            8, 0, 0, 0, 0, 239, 23, 65, 59, 0, 0, 0, 3, 116, 69, 88, 116, 97, 0, 98, 220, 73, 162,
+            59, 0, 0, 0, 10, 73, 68, 65, 84, 120, 1, 5, 193, 49, 1, 132, 64, 12, 69, 186, 74, 63,
+            1, 0, 0, 0, 80, 73, 68, 65, 84, 193, 133, 67, 194, 23, 129, 136, 39, 34, 18, 126, 113,
+            18, 182, 64, 68, 68, 32, 129, 34, 34, 78, 2, 245, 214, 17, 113, 51, 99, 112, 149, 34,
+            127, 231, 247, 30, 92, 165, 77, 128, 193, 128, 1, 239, 128, 193, 128, 1, 243, 201, 185,
+            34, 102, 196, 92, 17, 115, 69, 28, 79, 186, 59, 221, 137, 59, 221, 125, 188, 42, 75,
+            101, 233, 149, 202, 250, 3, 206, 246, 37, 129, 141, 113, 37, 238, 0, 0, 0, 0, 73, 69,
+            78, 68, 174, 66, 96, 130,
+        ];
+        let expected: Vec<u32> = (0..6)
+            .flat_map(|y| (0..16).map(move |x| (x * 3 + y * y) % 11 * 20))
+            .collect();
+        assert_eq!(decode(&png).unwrap(), expected);
+    }
+
+    #[test]
+    fn zlib_fixed_huffman_codes() {
+        // zlib 1.2.13, level 9 with Z_FIXED: one block of fixed Huffman codes, mostly matches
+        let zlib = [
+            120, 1, 51, 48, 52, 49, 50, 50, 49, 52, 160, 55, 5, 0, 138, 66, 23, 111,
+        ];
+        let expected: Vec<u8> = (0..120u32).map(|i| (i * i % 7) as u8 + b'0').collect();
+        assert_eq!(inflate(&zlib, expected.len()).unwrap(), expected);
+        assert!(inflate(&zlib, expected.len() - 1).is_err());
+    }
+
+    #[test]
+    fn zlib_dynamic_huffman_codes() {
+        // zlib 1.2.13, level 9: one block of dynamic Huffman codes with literals and matches
+        let zlib = [
+            120, 218, 237, 204, 71, 1, 128, 48, 16, 0, 48, 109, 199, 46, 179, 148, 229, 223, 9,
+            177, 192, 159, 8, 72, 68, 196, 192, 198, 77, 199, 194, 73, 195, 68, 161, 34, 145, 121,
+            232, 89, 185, 104, 153, 57, 168, 25, 217, 137, 255, 254, 124, 191, 231, 238, 118, 165,
+        ];
+        let expected: Vec<u8> = (0..400u32).map(|i| (i / 4 * 7 % 23) as u8 + b'A').collect();
+        assert_eq!(inflate(&zlib, expected.len()).unwrap(), expected);
+        let mut corrupted = zlib;
+        corrupted[20] ^= 0x10;
+        assert!(inflate(&corrupted, expected.len()).is_err());
+    }
+
+    #[test]
+    fn invalid_images() {
+        let raw = [0, 1, 2];
+        let mut zlib = stored(&raw);
+        *zlib.last_mut().unwrap() ^= 1;
+        assert!(decode(&png(2, 1, 8, 0, &zlib)).is_err());
+        // one row short, and one row too many
+        assert!(decode(&png(2, 2, 8, 0, &stored(&raw))).is_err());
+        assert!(decode(&png(2, 1, 8, 0, &stored(&[raw, raw].concat()))).is_err());
+        // palette images
+        assert!(decode(&png(2, 1, 8, 3, &stored(&raw))).is_err());
+        assert!(decode(&encode(&[1, 2], 2, 1, 8)[..40]).is_err());
+    }
+}
@@ -1,11 +1,716 @@
-use std::io::Read;
+use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::Duration;
 
-use super::GribRead;
-use crate::Result;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use super::registry::{CustomTemplate, is_registered, read_registered};
+use super::{GribRead, GribWrite};
+use crate::codes::{StatisticalProcess, TimeUnit, TypeOfGeneratingProcess};
+use crate::surface::FixedSurface;
+use crate::time::{self, DateTime, ValidTime};
+use crate::{Error, Result};
 
 /// Template 4.0 (analysis or forecast at a horizontal level or in a horizontal layer at a point in time)
 #[derive(Debug)]
-pub struct ProductDefinitionTemplate4_0 {
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_0 {
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    pub type_of_generating_process: u8,
+    pub background_process: u8,
+    pub generating_process_identifier: u8,
+    pub hours_after_data_cutoff: u16,
+    pub minutes_after_data_cutoff: u8,
+    pub indicator_of_unit_of_time_range: u8,
+    pub forecast_time: i32,
+    pub type_of_first_fixed_surface: u8,
+    pub scale_factor_of_first_fixed_surface: i8,
+    pub scaled_value_of_first_fixed_surface: u32,
+    pub type_of_second_fixed_surface: u8,
+    pub scale_factor_of_second_fixed_surface: i8,
+    pub scaled_value_of_second_fixed_surface: u32,
+}
+
+impl ProductDefinitionTemplate4_0 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            parameter_category: reader.read_grib_value()?,
+            parameter_number: reader.read_grib_value()?,
+            type_of_generating_process: reader.read_grib_value()?,
+            background_process: reader.read_grib_value()?,
+            generating_process_identifier: reader.read_grib_value()?,
+            hours_after_data_cutoff: reader.read_grib_value()?,
+            minutes_after_data_cutoff: reader.read_grib_value()?,
+            indicator_of_unit_of_time_range: reader.read_grib_value()?,
+            forecast_time: reader.read_grib_value()?,
+            type_of_first_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_first_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_first_fixed_surface: reader.read_grib_value()?,
+            type_of_second_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_second_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
+        })
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.hours_after_data_cutoff)?;
+        writer.write_grib_value(self.minutes_after_data_cutoff)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range)?;
+        writer.write_grib_value(self.forecast_time)?;
+        writer.write_grib_value(self.type_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_first_fixed_surface)?;
+        writer.write_grib_value(self.type_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_second_fixed_surface)?;
+        Ok(())
+    }
+
+    /// Type of generating process (Code table 4.3)
+    pub fn generating_process(&self) -> TypeOfGeneratingProcess {
+        self.type_of_generating_process.into()
+    }
+
+    /// Unit of `forecast_time` (Code table 4.4)
+    pub fn time_unit(&self) -> TimeUnit {
+        self.indicator_of_unit_of_time_range.into()
+    }
+
+    pub fn first_fixed_surface(&self) -> FixedSurface {
+        FixedSurface::new(
+            self.type_of_first_fixed_surface,
+            self.scale_factor_of_first_fixed_surface,
+            self.scaled_value_of_first_fixed_surface,
+        )
+    }
+
+    pub fn second_fixed_surface(&self) -> FixedSurface {
+        FixedSurface::new(
+            self.type_of_second_fixed_surface,
+            self.scale_factor_of_second_fixed_surface,
+            self.scaled_value_of_second_fixed_surface,
+        )
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_1 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub type_of_ensemble_forecast: u8,
+    pub perturbation_number: u8,
+    pub number_of_forecasts_in_ensemble: u8,
+}
+
+impl ProductDefinitionTemplate4_1 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            type_of_ensemble_forecast: reader.read_grib_value()?,
+            perturbation_number: reader.read_grib_value()?,
+            number_of_forecasts_in_ensemble: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.type_of_ensemble_forecast)?;
+        writer.write_grib_value(self.perturbation_number)?;
+        writer.write_grib_value(self.number_of_forecasts_in_ensemble)?;
+        Ok(())
+    }
+}
+
+/// Template 4.2 (derived forecasts based on all ensemble members at a horizontal level or in a horizontal layer at a point in time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_2 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub derived_forecast: u8,
+    pub number_of_forecasts_in_ensemble: u8,
+}
+
+impl ProductDefinitionTemplate4_2 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            derived_forecast: reader.read_grib_value()?,
+            number_of_forecasts_in_ensemble: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.derived_forecast)?;
+        writer.write_grib_value(self.number_of_forecasts_in_ensemble)?;
+        Ok(())
+    }
+}
+
+/// Template 4.8 (average, accumulation and/or extreme values or other statistically processed values at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_8 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_8 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        self.interval.write(writer)
+    }
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_11 {
+    pub template_1: ProductDefinitionTemplate4_1,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_11 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_1: ProductDefinitionTemplate4_1::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_1.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.12 (derived forecasts based on all ensemble members at a horizontal level or in a horizontal layer, in a continuous or non-continuous interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_12 {
+    pub template_2: ProductDefinitionTemplate4_2,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_12 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_2: ProductDefinitionTemplate4_2::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_2.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.57 (analysis or forecast at a horizontal level or in a horizontal layer at a point in time for atmospheric chemical constituents based on a distribution function)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_57 {
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    pub constituent_type: u16,
+    pub number_of_modes_of_distribution: u16,
+    pub mode_number: u16,
+    pub type_of_distribution_function: u16,
+    pub distribution_function_parameters: Vec<DistributionFunctionParameter>,
+    pub type_of_generating_process: u8,
+    pub background_process: u8,
+    pub generating_process_identifier: u8,
+    pub hours_after_data_cutoff: u16,
+    pub minutes_after_data_cutoff: u8,
+    pub indicator_of_unit_of_time_range: u8,
+    pub forecast_time: i32,
+    pub type_of_first_fixed_surface: u8,
+    pub scale_factor_of_first_fixed_surface: i8,
+    pub scaled_value_of_first_fixed_surface: u32,
+    pub type_of_second_fixed_surface: u8,
+    pub scale_factor_of_second_fixed_surface: i8,
+    pub scaled_value_of_second_fixed_surface: u32,
+}
+
+impl ProductDefinitionTemplate4_57 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            parameter_category: reader.read_grib_value()?,
+            parameter_number: reader.read_grib_value()?,
+            constituent_type: reader.read_grib_value()?,
+            number_of_modes_of_distribution: reader.read_grib_value()?,
+            mode_number: reader.read_grib_value()?,
+            type_of_distribution_function: reader.read_grib_value()?,
+            distribution_function_parameters: {
+                let n = reader.read_grib_value()?;
+                DistributionFunctionParameter::read_list(reader, n)?
+            },
+            type_of_generating_process: reader.read_grib_value()?,
+            background_process: reader.read_grib_value()?,
+            generating_process_identifier: reader.read_grib_value()?,
+            hours_after_data_cutoff: reader.read_grib_value()?,
+            minutes_after_data_cutoff: reader.read_grib_value()?,
+            indicator_of_unit_of_time_range: reader.read_grib_value()?,
+            forecast_time: reader.read_grib_value()?,
+            type_of_first_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_first_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_first_fixed_surface: reader.read_grib_value()?,
+            type_of_second_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_second_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let n = u8::try_from(self.distribution_function_parameters.len()).map_err(|_| {
+            Error::InvalidData(format!(
+                "too many distribution function parameters: {}",
+                self.distribution_function_parameters.len()
+            ))
+        })?;
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.constituent_type)?;
+        writer.write_grib_value(self.number_of_modes_of_distribution)?;
+        writer.write_grib_value(self.mode_number)?;
+        writer.write_grib_value(self.type_of_distribution_function)?;
+        writer.write_grib_value(n)?;
+        DistributionFunctionParameter::write_list(&self.distribution_function_parameters, writer)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.hours_after_data_cutoff)?;
+        writer.write_grib_value(self.minutes_after_data_cutoff)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range)?;
+        writer.write_grib_value(self.forecast_time)?;
+        writer.write_grib_value(self.type_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_first_fixed_surface)?;
+        writer.write_grib_value(self.type_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_second_fixed_surface)?;
+        Ok(())
+    }
+}
+
+/// Template 4.58 (individual ensemble forecast, control and perturbed, at a horizontal level or in a horizontal layer at a point in time for atmospheric chemical constituents based on a distribution function)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_58 {
+    pub template_57: ProductDefinitionTemplate4_57,
+    pub type_of_ensemble_forecast: u8,
+    pub perturbation_number: u8,
+    pub number_of_forecasts_in_ensemble: u8,
+}
+
+impl ProductDefinitionTemplate4_58 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_57: ProductDefinitionTemplate4_57::read(reader)?,
+            type_of_ensemble_forecast: reader.read_grib_value()?,
+            perturbation_number: reader.read_grib_value()?,
+            number_of_forecasts_in_ensemble: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_57.write(writer)?;
+        writer.write_grib_value(self.type_of_ensemble_forecast)?;
+        writer.write_grib_value(self.perturbation_number)?;
+        writer.write_grib_value(self.number_of_forecasts_in_ensemble)?;
+        Ok(())
+    }
+}
+
+/// Template 4.60 (individual ensemble reforecast, control and perturbed, at a horizontal level or in a horizontal layer at a point in time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_60 {
+    pub template_1: ProductDefinitionTemplate4_1,
+    pub year_of_model_version_date: u16,
+    pub month_of_model_version_date: u8,
+    pub day_of_model_version_date: u8,
+    pub hour_of_model_version_date: u8,
+    pub minute_of_model_version_date: u8,
+    pub second_of_model_version_date: u8,
+}
+
+impl ProductDefinitionTemplate4_60 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_1: ProductDefinitionTemplate4_1::read(reader)?,
+            year_of_model_version_date: reader.read_grib_value()?,
+            month_of_model_version_date: reader.read_grib_value()?,
+            day_of_model_version_date: reader.read_grib_value()?,
+            hour_of_model_version_date: reader.read_grib_value()?,
+            minute_of_model_version_date: reader.read_grib_value()?,
+            second_of_model_version_date: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_1.write(writer)?;
+        writer.write_grib_value(self.year_of_model_version_date)?;
+        writer.write_grib_value(self.month_of_model_version_date)?;
+        writer.write_grib_value(self.day_of_model_version_date)?;
+        writer.write_grib_value(self.hour_of_model_version_date)?;
+        writer.write_grib_value(self.minute_of_model_version_date)?;
+        writer.write_grib_value(self.second_of_model_version_date)?;
+        Ok(())
+    }
+}
+
+/// Template 4.61 (individual ensemble reforecast, control and perturbed, at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_61 {
+    pub template_60: ProductDefinitionTemplate4_60,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_61 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_60: ProductDefinitionTemplate4_60::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_60.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.70 (post-processing analysis or forecast at a horizontal level or in a horizontal layer at a point in time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_70 {
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    pub input_process_identifier: u16,
+    pub input_originating_centre: u16,
+    pub type_of_post_processing: u8,
+    pub type_of_generating_process: u8,
+    pub background_process: u8,
+    pub generating_process_identifier: u8,
+    pub hours_after_data_cutoff: u16,
+    pub minutes_after_data_cutoff: u8,
+    pub indicator_of_unit_of_time_range: u8,
+    pub forecast_time: i32,
+    pub type_of_first_fixed_surface: u8,
+    pub scale_factor_of_first_fixed_surface: i8,
+    pub scaled_value_of_first_fixed_surface: u32,
+    pub type_of_second_fixed_surface: u8,
+    pub scale_factor_of_second_fixed_surface: i8,
+    pub scaled_value_of_second_fixed_surface: u32,
+}
+
+impl ProductDefinitionTemplate4_70 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            parameter_category: reader.read_grib_value()?,
+            parameter_number: reader.read_grib_value()?,
+            input_process_identifier: reader.read_grib_value()?,
+            input_originating_centre: reader.read_grib_value()?,
+            type_of_post_processing: reader.read_grib_value()?,
+            type_of_generating_process: reader.read_grib_value()?,
+            background_process: reader.read_grib_value()?,
+            generating_process_identifier: reader.read_grib_value()?,
+            hours_after_data_cutoff: reader.read_grib_value()?,
+            minutes_after_data_cutoff: reader.read_grib_value()?,
+            indicator_of_unit_of_time_range: reader.read_grib_value()?,
+            forecast_time: reader.read_grib_value()?,
+            type_of_first_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_first_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_first_fixed_surface: reader.read_grib_value()?,
+            type_of_second_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_second_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.input_process_identifier)?;
+        writer.write_grib_value(self.input_originating_centre)?;
+        writer.write_grib_value(self.type_of_post_processing)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.hours_after_data_cutoff)?;
+        writer.write_grib_value(self.minutes_after_data_cutoff)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range)?;
+        writer.write_grib_value(self.forecast_time)?;
+        writer.write_grib_value(self.type_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_first_fixed_surface)?;
+        writer.write_grib_value(self.type_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_second_fixed_surface)?;
+        Ok(())
+    }
+}
+
+/// Template 4.71 (post-processing individual ensemble forecast, control and perturbed, at a horizontal level or in a horizontal layer at a point in time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_71 {
+    pub template_70: ProductDefinitionTemplate4_70,
+    pub type_of_ensemble_forecast: u8,
+    pub perturbation_number: u8,
+    pub number_of_forecasts_in_ensemble: u8,
+}
+
+impl ProductDefinitionTemplate4_71 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_70: ProductDefinitionTemplate4_70::read(reader)?,
+            type_of_ensemble_forecast: reader.read_grib_value()?,
+            perturbation_number: reader.read_grib_value()?,
+            number_of_forecasts_in_ensemble: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_70.write(writer)?;
+        writer.write_grib_value(self.type_of_ensemble_forecast)?;
+        writer.write_grib_value(self.perturbation_number)?;
+        writer.write_grib_value(self.number_of_forecasts_in_ensemble)?;
+        Ok(())
+    }
+}
+
+/// Template 4.72 (post-processing average, accumulation, extreme values or other statistically processed values at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_72 {
+    pub template_70: ProductDefinitionTemplate4_70,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_72 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_70: ProductDefinitionTemplate4_70::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_70.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.73 (post-processing individual ensemble forecast, control and perturbed, at a horizontal level or in a horizontal layer, in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_73 {
+    pub template_71: ProductDefinitionTemplate4_71,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_73 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_71: ProductDefinitionTemplate4_71::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_71.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.86 (quantile forecasts at a horizontal level or in a horizontal layer at a point in time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_86 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub total_number_of_quantiles: u32,
+    pub quantile_value: u32,
+}
+
+impl ProductDefinitionTemplate4_86 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            total_number_of_quantiles: reader.read_grib_value()?,
+            quantile_value: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.total_number_of_quantiles)?;
+        writer.write_grib_value(self.quantile_value)?;
+        Ok(())
+    }
+}
+
+/// Template 4.87 (quantile forecasts at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_87 {
+    pub template_86: ProductDefinitionTemplate4_86,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_87 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_86: ProductDefinitionTemplate4_86::read(reader)?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_86.write(writer)?;
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.88 (analysis or forecast at a horizontal level or in a horizontal layer at a specified local time)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_88 {
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    pub type_of_generating_process: u8,
+    pub background_process: u8,
+    pub generating_process_identifier: u8,
+    pub hours_after_data_cutoff: u16,
+    pub minutes_after_data_cutoff: u8,
+    pub type_of_first_fixed_surface: u8,
+    pub scale_factor_of_first_fixed_surface: i8,
+    pub scaled_value_of_first_fixed_surface: u32,
+    pub type_of_second_fixed_surface: u8,
+    pub scale_factor_of_second_fixed_surface: i8,
+    pub scaled_value_of_second_fixed_surface: u32,
+    pub local_time_forecasts: Vec<LocalTimeForecast>,
+}
+
+impl ProductDefinitionTemplate4_88 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            parameter_category: reader.read_grib_value()?,
+            parameter_number: reader.read_grib_value()?,
+            type_of_generating_process: reader.read_grib_value()?,
+            background_process: reader.read_grib_value()?,
+            generating_process_identifier: reader.read_grib_value()?,
+            hours_after_data_cutoff: reader.read_grib_value()?,
+            minutes_after_data_cutoff: reader.read_grib_value()?,
+            type_of_first_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_first_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_first_fixed_surface: reader.read_grib_value()?,
+            type_of_second_fixed_surface: reader.read_grib_value()?,
+            scale_factor_of_second_fixed_surface: reader.read_grib_value()?,
+            scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
+            local_time_forecasts: (0..reader.read_grib_value::<u8>()?)
+                .map(|_| LocalTimeForecast::read(reader))
+                .collect::<Result<Vec<_>>>()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let n = u8::try_from(self.local_time_forecasts.len()).map_err(|_| {
+            Error::InvalidData(format!(
+                "too many local time forecasts: {}",
+                self.local_time_forecasts.len()
+            ))
+        })?;
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.hours_after_data_cutoff)?;
+        writer.write_grib_value(self.minutes_after_data_cutoff)?;
+        writer.write_grib_value(self.type_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_first_fixed_surface)?;
+        writer.write_grib_value(self.type_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_second_fixed_surface)?;
+        writer.write_grib_value(n)?;
+        for item in &self.local_time_forecasts {
+            item.write(writer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Template 4.91 (categorical forecast at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_91 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub categories: Vec<Category>,
+    pub interval: TimeInterval,
+}
+
+impl ProductDefinitionTemplate4_91 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            categories: (0..reader.read_grib_value::<u8>()?)
+                .map(|_| Category::read(reader))
+                .collect::<Result<Vec<_>>>()?,
+            interval: TimeInterval::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let n = u8::try_from(self.categories.len()).map_err(|_| {
+            Error::InvalidData(format!("too many categories: {}", self.categories.len()))
+        })?;
+        self.template_0.write(writer)?;
+        writer.write_grib_value(n)?;
+        for item in &self.categories {
+            item.write(writer)?;
+        }
+        self.interval.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.254 (CCITT IA5 character string)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_254 {
+    pub parameter_category: u8,
+    pub parameter_number: u8,
+    pub number_of_characters: u32,
+}
+
+impl ProductDefinitionTemplate4_254 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            parameter_category: reader.read_grib_value()?,
+            parameter_number: reader.read_grib_value()?,
+            number_of_characters: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.number_of_characters)?;
+        Ok(())
+    }
+}
+
+/// Template 4.1000 (cross-section of analysis and forecast at a point in time)
+///
+/// Experimental template of the WMO (Code table 4.0), also used by NCEP.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_1000 {
     pub parameter_category: u8,
     pub parameter_number: u8,
     pub type_of_generating_process: u8,
@@ -15,15 +720,9 @@ pub struct ProductDefinitionTemplate4_0 {
     pub minutes_after_data_cutoff: u8,
     pub indicator_of_unit_of_time_range: u8,
     pub forecast_time: i32,
-    pub type_of_first_fixed_surface: u8,
-    pub scale_factor_of_first_fixed_surface: i8,
-    pub scaled_value_of_first_fixed_surface: u32,
-    pub type_of_second_fixed_surface: u8,
-    pub scale_factor_of_second_fixed_surface: i8,
-    pub scaled_value_of_second_fixed_surface: u32,
 }
 
-impl ProductDefinitionTemplate4_0 {
+impl ProductDefinitionTemplate4_1000 {
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
             parameter_category: reader.read_grib_value()?,
@@ -35,67 +734,93 @@ impl ProductDefinitionTemplate4_0 {
             minutes_after_data_cutoff: reader.read_grib_value()?,
             indicator_of_unit_of_time_range: reader.read_grib_value()?,
             forecast_time: reader.read_grib_value()?,
-            type_of_first_fixed_surface: reader.read_grib_value()?,
-            scale_factor_of_first_fixed_surface: reader.read_grib_value()?,
-            scaled_value_of_first_fixed_surface: reader.read_grib_value()?,
-            type_of_second_fixed_surface: reader.read_grib_value()?,
-            scale_factor_of_second_fixed_surface: reader.read_grib_value()?,
-            scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.hours_after_data_cutoff)?;
+        writer.write_grib_value(self.minutes_after_data_cutoff)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range)?;
+        writer.write_grib_value(self.forecast_time)?;
+        Ok(())
+    }
 }
 
+/// Template 4.1001 (cross-section of averaged or otherwise statistically processed analysis or forecast over a range of time)
+///
+/// Experimental template of the WMO (Code table 4.0), also used by NCEP.
 #[derive(Debug)]
-pub struct ProductDefinitionTemplate4_1 {
-    pub template_0: ProductDefinitionTemplate4_0,
-    pub type_of_ensemble_forecast: u8,
-    pub perturbation_number: u8,
-    pub number_of_forecasts_in_ensemble: u8,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_1001 {
+    pub template_1000: ProductDefinitionTemplate4_1000,
+    pub time_range: TimeRange,
 }
 
-impl ProductDefinitionTemplate4_1 {
+impl ProductDefinitionTemplate4_1001 {
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
-            template_0: ProductDefinitionTemplate4_0::read(reader)?,
-            type_of_ensemble_forecast: reader.read_grib_value()?,
-            perturbation_number: reader.read_grib_value()?,
-            number_of_forecasts_in_ensemble: reader.read_grib_value()?,
+            template_1000: ProductDefinitionTemplate4_1000::read(reader)?,
+            time_range: TimeRange::read_specification(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_1000.write(writer)?;
+        self.time_range.write_specification(writer)?;
+        Ok(())
+    }
 }
 
-/// Template 4.8 (average, accumulation and/or extreme values or other statistically processed values at a horizontal level or in a horizontal layer in a continuous or non-continuous time interval)
+/// Template 4.1100 (Hovmöller-type grid with no averaging or other statistical processing)
+///
+/// Experimental template of the WMO (Code table 4.0), also used by NCEP.
 #[derive(Debug)]
-pub struct ProductDefinitionTemplate4_8 {
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_1100 {
     pub template_0: ProductDefinitionTemplate4_0,
-    pub interval: TimeInterval,
 }
 
-impl ProductDefinitionTemplate4_8 {
+impl ProductDefinitionTemplate4_1100 {
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
             template_0: ProductDefinitionTemplate4_0::read(reader)?,
-            interval: TimeInterval::read(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        Ok(())
+    }
 }
 
+/// Template 4.1101 (Hovmöller-type grid with averaging or other statistical processing)
+///
+/// Experimental template of the WMO (Code table 4.0), also used by NCEP.
 #[derive(Debug)]
-pub struct ProductDefinitionTemplate4_11 {
-    pub template_1: ProductDefinitionTemplate4_1,
-    pub interval: TimeInterval,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_1101 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub time_range: TimeRange,
 }
 
-impl ProductDefinitionTemplate4_11 {
+impl ProductDefinitionTemplate4_1101 {
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
-            template_1: ProductDefinitionTemplate4_1::read(reader)?,
-            interval: TimeInterval::read(reader)?,
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            time_range: TimeRange::read_specification(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        self.time_range.write_specification(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProductDefinitionTemplate4_50000 {
     pub template_0: ProductDefinitionTemplate4_0,
     pub base_product1: u8,
@@ -118,28 +843,145 @@ impl ProductDefinitionTemplate4_50000 {
             minute_difference2: reader.read_grib_value()?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        writer.write_grib_value(self.base_product1)?;
+        writer.write_grib_value(self.hour_difference1)?;
+        writer.write_grib_value(self.minute_difference1)?;
+        writer.write_grib_value(self.base_product2)?;
+        writer.write_grib_value(self.hour_difference2)?;
+        writer.write_grib_value(self.minute_difference2)?;
+        Ok(())
+    }
+}
+
+/// Template 4.50008 (JMA local: Radar/Raingauge-Analyzed Precipitation)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_50008 {
+    pub template_8: ProductDefinitionTemplate4_8,
+    pub operating_info: OperatingInfo,
+}
+
+impl ProductDefinitionTemplate4_50008 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_8: ProductDefinitionTemplate4_8::read(reader)?,
+            operating_info: OperatingInfo::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_8.write(writer)?;
+        self.operating_info.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.50009 (JMA local: Precipitation Nowcast)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_50009 {
+    pub template_8: ProductDefinitionTemplate4_8,
+    /// Radar operating information (part 1): bit map of radar sites in operation
+    pub radar_operating_info1: u64,
+    /// Radar operating information (part 2): bit map of radar sites in operation
+    pub radar_operating_info2: u64,
+}
+
+impl ProductDefinitionTemplate4_50009 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_8: ProductDefinitionTemplate4_8::read(reader)?,
+            radar_operating_info1: reader.read_grib_value()?,
+            radar_operating_info2: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_8.write(writer)?;
+        writer.write_grib_value(self.radar_operating_info1)?;
+        writer.write_grib_value(self.radar_operating_info2)?;
+        Ok(())
+    }
+
+    /// Returns whether the radar site at the given bit position (0 = most significant bit of part 1) was in operation
+    pub fn is_radar_operating(&self, site: u32) -> bool {
+        is_operating(
+            &[self.radar_operating_info1, self.radar_operating_info2],
+            site,
+        )
+    }
+}
+
+/// Template 4.50010 (JMA local: analysis at a point in time with radar and rain gauge operating information)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_50010 {
+    pub template_0: ProductDefinitionTemplate4_0,
+    pub operating_info: OperatingInfo,
+}
+
+impl ProductDefinitionTemplate4_50010 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_0: ProductDefinitionTemplate4_0::read(reader)?,
+            operating_info: OperatingInfo::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_0.write(writer)?;
+        self.operating_info.write(writer)?;
+        Ok(())
+    }
 }
 
+/// Template 4.50011 (JMA local: High-resolution Precipitation Nowcast, analysis)
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProductDefinitionTemplate4_50011 {
     pub template_8: ProductDefinitionTemplate4_8,
-    pub rader_operating_info1: u64,
-    pub rader_operating_info2: u64,
-    pub rader_operating_info3: u64,
+    pub operating_info: OperatingInfo,
 }
 
 impl ProductDefinitionTemplate4_50011 {
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
             template_8: ProductDefinitionTemplate4_8::read(reader)?,
-            rader_operating_info1: reader.read_grib_value()?,
-            rader_operating_info2: reader.read_grib_value()?,
-            rader_operating_info3: reader.read_grib_value()?,
+            operating_info: OperatingInfo::read(reader)?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_8.write(writer)?;
+        self.operating_info.write(writer)?;
+        Ok(())
+    }
+}
+
+/// Template 4.50012 (JMA local: High-resolution Precipitation Nowcast, forecast)
+///
+/// Shares the layout of template 4.50011; the statistically processed interval lies after the reference time.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ProductDefinitionTemplate4_50012 {
+    pub template_8: ProductDefinitionTemplate4_8,
+    pub operating_info: OperatingInfo,
+}
+
+impl ProductDefinitionTemplate4_50012 {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            template_8: ProductDefinitionTemplate4_8::read(reader)?,
+            operating_info: OperatingInfo::read(reader)?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.template_8.write(writer)?;
+        self.operating_info.write(writer)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProductDefinitionTemplate4_50031 {
     pub parameter_category: u8,
     pub parameter_number: u8,
@@ -186,9 +1028,515 @@ impl ProductDefinitionTemplate4_50031 {
             scaled_value_of_second_fixed_surface: reader.read_grib_value()?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.parameter_category)?;
+        writer.write_grib_value(self.parameter_number)?;
+        writer.write_grib_value(self.type_of_generating_process)?;
+        writer.write_grib_value(self.background_process)?;
+        writer.write_grib_value(self.generating_process_identifier)?;
+        writer.write_grib_value(self.tc_number)?;
+        writer.write_grib_value(self.typhoon_number)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range_start)?;
+        writer.write_grib_value(self.start_time)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_range_forecast)?;
+        writer.write_grib_value(self.forecast_time)?;
+        writer.write_grib_value(self.type_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_first_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_first_fixed_surface)?;
+        writer.write_grib_value(self.type_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scale_factor_of_second_fixed_surface)?;
+        writer.write_grib_value(self.scaled_value_of_second_fixed_surface)?;
+        Ok(())
+    }
+}
+
+/// Any product definition template, dispatched by template number
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ProductDefinitionTemplate {
+    Template4_0(ProductDefinitionTemplate4_0),
+    Template4_1(ProductDefinitionTemplate4_1),
+    Template4_2(ProductDefinitionTemplate4_2),
+    Template4_8(ProductDefinitionTemplate4_8),
+    Template4_11(ProductDefinitionTemplate4_11),
+    Template4_12(ProductDefinitionTemplate4_12),
+    Template4_57(ProductDefinitionTemplate4_57),
+    Template4_58(ProductDefinitionTemplate4_58),
+    Template4_60(ProductDefinitionTemplate4_60),
+    Template4_61(ProductDefinitionTemplate4_61),
+    Template4_70(ProductDefinitionTemplate4_70),
+    Template4_71(ProductDefinitionTemplate4_71),
+    Template4_72(ProductDefinitionTemplate4_72),
+    Template4_73(ProductDefinitionTemplate4_73),
+    Template4_86(ProductDefinitionTemplate4_86),
+    Template4_87(ProductDefinitionTemplate4_87),
+    Template4_88(ProductDefinitionTemplate4_88),
+    Template4_91(ProductDefinitionTemplate4_91),
+    Template4_254(ProductDefinitionTemplate4_254),
+    Template4_1000(ProductDefinitionTemplate4_1000),
+    Template4_1001(ProductDefinitionTemplate4_1001),
+    Template4_1100(ProductDefinitionTemplate4_1100),
+    Template4_1101(ProductDefinitionTemplate4_1101),
+    Template4_50000(ProductDefinitionTemplate4_50000),
+    Template4_50008(ProductDefinitionTemplate4_50008),
+    Template4_50009(ProductDefinitionTemplate4_50009),
+    Template4_50010(ProductDefinitionTemplate4_50010),
+    Template4_50011(ProductDefinitionTemplate4_50011),
+    Template4_50012(ProductDefinitionTemplate4_50012),
+    Template4_50031(ProductDefinitionTemplate4_50031),
+    /// Template parsed by a parser registered with [`register_template`]
+    ///
+    /// Skipped by serde: serializing it fails, as the parsed value is opaque.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(Arc<dyn CustomTemplate>),
+    /// Template not implemented by this crate (raw template bytes)
+    Unknown(Vec<u8>),
+}
+
+impl ProductDefinitionTemplate {
+    /// Read the template identified by `template_number` (Section 4, octets 8-9)
+    pub fn read_any<R: Read>(template_number: u16, reader: &mut R) -> Result<Self> {
+        Self::read_any_for_centre(template_number, None, reader)
+    }
+
+    /// Read the template, consulting the parsers registered for the originating `centre`
+    pub fn read_any_for_centre<R: Read>(
+        template_number: u16,
+        centre: Option<u16>,
+        reader: &mut R,
+    ) -> Result<Self> {
+        Ok(match template_number {
+            0 => Self::Template4_0(ProductDefinitionTemplate4_0::read(reader)?),
+            1 => Self::Template4_1(ProductDefinitionTemplate4_1::read(reader)?),
+            2 => Self::Template4_2(ProductDefinitionTemplate4_2::read(reader)?),
+            8 => Self::Template4_8(ProductDefinitionTemplate4_8::read(reader)?),
+            11 => Self::Template4_11(ProductDefinitionTemplate4_11::read(reader)?),
+            12 => Self::Template4_12(ProductDefinitionTemplate4_12::read(reader)?),
+            57 => Self::Template4_57(ProductDefinitionTemplate4_57::read(reader)?),
+            58 => Self::Template4_58(ProductDefinitionTemplate4_58::read(reader)?),
+            60 => Self::Template4_60(ProductDefinitionTemplate4_60::read(reader)?),
+            61 => Self::Template4_61(ProductDefinitionTemplate4_61::read(reader)?),
+            70 => Self::Template4_70(ProductDefinitionTemplate4_70::read(reader)?),
+            71 => Self::Template4_71(ProductDefinitionTemplate4_71::read(reader)?),
+            72 => Self::Template4_72(ProductDefinitionTemplate4_72::read(reader)?),
+            73 => Self::Template4_73(ProductDefinitionTemplate4_73::read(reader)?),
+            86 => Self::Template4_86(ProductDefinitionTemplate4_86::read(reader)?),
+            87 => Self::Template4_87(ProductDefinitionTemplate4_87::read(reader)?),
+            88 => Self::Template4_88(ProductDefinitionTemplate4_88::read(reader)?),
+            91 => Self::Template4_91(ProductDefinitionTemplate4_91::read(reader)?),
+            254 => Self::Template4_254(ProductDefinitionTemplate4_254::read(reader)?),
+            1000 => Self::Template4_1000(ProductDefinitionTemplate4_1000::read(reader)?),
+            1001 => Self::Template4_1001(ProductDefinitionTemplate4_1001::read(reader)?),
+            1100 => Self::Template4_1100(ProductDefinitionTemplate4_1100::read(reader)?),
+            1101 => Self::Template4_1101(ProductDefinitionTemplate4_1101::read(reader)?),
+            50000 => Self::Template4_50000(ProductDefinitionTemplate4_50000::read(reader)?),
+            50008 => Self::Template4_50008(ProductDefinitionTemplate4_50008::read(reader)?),
+            50009 => Self::Template4_50009(ProductDefinitionTemplate4_50009::read(reader)?),
+            50010 => Self::Template4_50010(ProductDefinitionTemplate4_50010::read(reader)?),
+            50011 => Self::Template4_50011(ProductDefinitionTemplate4_50011::read(reader)?),
+            50012 => Self::Template4_50012(ProductDefinitionTemplate4_50012::read(reader)?),
+            50031 => Self::Template4_50031(ProductDefinitionTemplate4_50031::read(reader)?),
+            _ => match read_registered(4, template_number, centre, reader) {
+                Some(template) => Self::Custom(template?),
+                None => {
+                    let mut buf = Vec::new();
+                    reader.read_to_end(&mut buf)?;
+                    Self::Unknown(buf)
+                }
+            },
+        })
+    }
+
+    /// Write the template (Section 4, octets 10-nn)
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        match self {
+            Self::Template4_0(t) => t.write(writer),
+            Self::Template4_1(t) => t.write(writer),
+            Self::Template4_2(t) => t.write(writer),
+            Self::Template4_8(t) => t.write(writer),
+            Self::Template4_11(t) => t.write(writer),
+            Self::Template4_12(t) => t.write(writer),
+            Self::Template4_57(t) => t.write(writer),
+            Self::Template4_58(t) => t.write(writer),
+            Self::Template4_60(t) => t.write(writer),
+            Self::Template4_61(t) => t.write(writer),
+            Self::Template4_70(t) => t.write(writer),
+            Self::Template4_71(t) => t.write(writer),
+            Self::Template4_72(t) => t.write(writer),
+            Self::Template4_73(t) => t.write(writer),
+            Self::Template4_86(t) => t.write(writer),
+            Self::Template4_87(t) => t.write(writer),
+            Self::Template4_88(t) => t.write(writer),
+            Self::Template4_91(t) => t.write(writer),
+            Self::Template4_254(t) => t.write(writer),
+            Self::Template4_1000(t) => t.write(writer),
+            Self::Template4_1001(t) => t.write(writer),
+            Self::Template4_1100(t) => t.write(writer),
+            Self::Template4_1101(t) => t.write(writer),
+            Self::Template4_50000(t) => t.write(writer),
+            Self::Template4_50008(t) => t.write(writer),
+            Self::Template4_50009(t) => t.write(writer),
+            Self::Template4_50010(t) => t.write(writer),
+            Self::Template4_50011(t) => t.write(writer),
+            Self::Template4_50012(t) => t.write(writer),
+            Self::Template4_50031(t) => t.write(writer),
+            Self::Custom(_) => Err(Error::UnsupportedData(
+                "writing a custom product definition template".to_string(),
+            )),
+            Self::Unknown(buf) => Ok(writer.write_all(buf)?),
+        }
+    }
+
+    /// Whether `read_any` parses the template (built in or registered for all centres)
+    pub fn is_supported(template_number: u16) -> bool {
+        matches!(
+            template_number,
+            0 | 1
+                | 2
+                | 8
+                | 11
+                | 12
+                | 57
+                | 58
+                | 60
+                | 61
+                | 70
+                | 71
+                | 72
+                | 73
+                | 86
+                | 87
+                | 88
+                | 91
+                | 254
+                | 1000
+                | 1001
+                | 1100
+                | 1101
+                | 50000
+                | 50008
+                | 50009
+                | 50010
+                | 50011
+                | 50012
+                | 50031
+        ) || is_registered(4, template_number, None)
+    }
+
+    /// The embedded template 4.0 fields, for templates derived from 4.0
+    pub fn template_0(&self) -> Option<&ProductDefinitionTemplate4_0> {
+        match self {
+            Self::Template4_0(t) => Some(t),
+            Self::Template4_1(t) => Some(&t.template_0),
+            Self::Template4_2(t) => Some(&t.template_0),
+            Self::Template4_8(t) => Some(&t.template_0),
+            Self::Template4_11(t) => Some(&t.template_1.template_0),
+            Self::Template4_12(t) => Some(&t.template_2.template_0),
+            Self::Template4_60(t) => Some(&t.template_1.template_0),
+            Self::Template4_61(t) => Some(&t.template_60.template_1.template_0),
+            Self::Template4_86(t) => Some(&t.template_0),
+            Self::Template4_87(t) => Some(&t.template_86.template_0),
+            Self::Template4_91(t) => Some(&t.template_0),
+            Self::Template4_1100(t) => Some(&t.template_0),
+            Self::Template4_1101(t) => Some(&t.template_0),
+            Self::Template4_50000(t) => Some(&t.template_0),
+            Self::Template4_50008(t) => Some(&t.template_8.template_0),
+            Self::Template4_50009(t) => Some(&t.template_8.template_0),
+            Self::Template4_50010(t) => Some(&t.template_0),
+            Self::Template4_50011(t) => Some(&t.template_8.template_0),
+            Self::Template4_50012(t) => Some(&t.template_8.template_0),
+            _ => None,
+        }
+    }
+
+    /// Type of generating process (Code table 4.3)
+    pub fn generating_process(&self) -> Option<TypeOfGeneratingProcess> {
+        if let Some(t) = self.template_0() {
+            return Some(t.generating_process());
+        }
+        let process = match self {
+            Self::Template4_57(t) => t.type_of_generating_process,
+            Self::Template4_58(t) => t.template_57.type_of_generating_process,
+            Self::Template4_70(t)
+            | Self::Template4_71(ProductDefinitionTemplate4_71 { template_70: t, .. })
+            | Self::Template4_72(ProductDefinitionTemplate4_72 { template_70: t, .. }) => {
+                t.type_of_generating_process
+            }
+            Self::Template4_73(t) => t.template_71.template_70.type_of_generating_process,
+            Self::Template4_88(t) => t.type_of_generating_process,
+            Self::Template4_1000(t)
+            | Self::Template4_1001(ProductDefinitionTemplate4_1001 {
+                template_1000: t, ..
+            }) => t.type_of_generating_process,
+            Self::Template4_50031(t) => t.type_of_generating_process,
+            _ => return None,
+        };
+        Some(process.into())
+    }
+
+    /// Parameter category and parameter number (Code table 4.1 and 4.2)
+    pub fn parameter(&self) -> Option<(u8, u8)> {
+        if let Some(t) = self.template_0() {
+            return Some((t.parameter_category, t.parameter_number));
+        }
+        match self {
+            Self::Template4_57(t) => Some((t.parameter_category, t.parameter_number)),
+            Self::Template4_58(t) => Some((
+                t.template_57.parameter_category,
+                t.template_57.parameter_number,
+            )),
+            Self::Template4_70(t)
+            | Self::Template4_71(ProductDefinitionTemplate4_71 { template_70: t, .. })
+            | Self::Template4_72(ProductDefinitionTemplate4_72 { template_70: t, .. }) => {
+                Some((t.parameter_category, t.parameter_number))
+            }
+            Self::Template4_73(t) => Some((
+                t.template_71.template_70.parameter_category,
+                t.template_71.template_70.parameter_number,
+            )),
+            Self::Template4_88(t) => Some((t.parameter_category, t.parameter_number)),
+            Self::Template4_254(t) => Some((t.parameter_category, t.parameter_number)),
+            Self::Template4_1000(t)
+            | Self::Template4_1001(ProductDefinitionTemplate4_1001 {
+                template_1000: t, ..
+            }) => Some((t.parameter_category, t.parameter_number)),
+            Self::Template4_50031(t) => Some((t.parameter_category, t.parameter_number)),
+            _ => None,
+        }
+    }
+
+    /// Indicator of unit of time range (Code table 4.4) and forecast time
+    ///
+    /// The forecast time is coded in sign and magnitude, and is negative for products valid
+    /// before the reference time.
+    pub fn forecast_time(&self) -> Option<(u8, i32)> {
+        if let Some(t) = self.template_0() {
+            return Some((t.indicator_of_unit_of_time_range, t.forecast_time));
+        }
+        match self {
+            Self::Template4_57(t) => Some((t.indicator_of_unit_of_time_range, t.forecast_time)),
+            Self::Template4_58(ProductDefinitionTemplate4_58 { template_57: t, .. }) => {
+                Some((t.indicator_of_unit_of_time_range, t.forecast_time))
+            }
+            Self::Template4_70(t)
+            | Self::Template4_71(ProductDefinitionTemplate4_71 { template_70: t, .. })
+            | Self::Template4_72(ProductDefinitionTemplate4_72 { template_70: t, .. }) => {
+                Some((t.indicator_of_unit_of_time_range, t.forecast_time))
+            }
+            Self::Template4_73(t) => Some((
+                t.template_71.template_70.indicator_of_unit_of_time_range,
+                t.template_71.template_70.forecast_time,
+            )),
+            Self::Template4_1000(t)
+            | Self::Template4_1001(ProductDefinitionTemplate4_1001 {
+                template_1000: t, ..
+            }) => Some((t.indicator_of_unit_of_time_range, t.forecast_time)),
+            Self::Template4_50031(t) => {
+                Some((t.indicator_of_unit_of_time_range_forecast, t.forecast_time))
+            }
+            _ => None,
+        }
+    }
+
+    /// Background generating process identifier, or `None` if missing (255)
+    pub fn background_process(&self) -> Option<u8> {
+        self.process_and_cutoff()
+            .and_then(|(background, ..)| (background != u8::MAX).then_some(background))
+    }
+
+    /// Analysis or forecast generating process identifier, or `None` if missing (255)
+    pub fn generating_process_identifier(&self) -> Option<u8> {
+        self.process_and_cutoff()
+            .and_then(|(_, identifier, ..)| (identifier != u8::MAX).then_some(identifier))
+    }
+
+    /// Time after the data cutoff, or `None` if missing (hours 65535)
+    ///
+    /// Missing minutes (255) count as zero.
+    pub fn data_cutoff(&self) -> Option<Duration> {
+        let (.., hours, minutes) = self.process_and_cutoff()?;
+        if hours == u16::MAX {
+            return None;
+        }
+        let minutes = if minutes == u8::MAX { 0 } else { minutes };
+        Some(Duration::from_secs(
+            hours as u64 * 3600 + minutes as u64 * 60,
+        ))
+    }
+
+    /// Background process, generating process identifier, and hours and minutes after the
+    /// data cutoff, as coded
+    fn process_and_cutoff(&self) -> Option<(u8, u8, u16, u8)> {
+        macro_rules! process {
+            ($t:expr) => {
+                Some((
+                    $t.background_process,
+                    $t.generating_process_identifier,
+                    $t.hours_after_data_cutoff,
+                    $t.minutes_after_data_cutoff,
+                ))
+            };
+        }
+        if let Some(t) = self.template_0() {
+            return process!(t);
+        }
+        match self {
+            Self::Template4_57(t) => process!(t),
+            Self::Template4_58(t) => process!(t.template_57),
+            Self::Template4_70(t) => process!(t),
+            Self::Template4_71(t) => process!(t.template_70),
+            Self::Template4_72(t) => process!(t.template_70),
+            Self::Template4_73(t) => process!(t.template_71.template_70),
+            Self::Template4_88(t) => process!(t),
+            Self::Template4_1000(t) => process!(t),
+            Self::Template4_1001(t) => process!(t.template_1000),
+            _ => None,
+        }
+    }
+
+    /// First and second fixed surfaces
+    pub fn fixed_surfaces(&self) -> Option<(FixedSurface, FixedSurface)> {
+        macro_rules! surfaces {
+            ($t:expr) => {
+                Some((
+                    FixedSurface::new(
+                        $t.type_of_first_fixed_surface,
+                        $t.scale_factor_of_first_fixed_surface,
+                        $t.scaled_value_of_first_fixed_surface,
+                    ),
+                    FixedSurface::new(
+                        $t.type_of_second_fixed_surface,
+                        $t.scale_factor_of_second_fixed_surface,
+                        $t.scaled_value_of_second_fixed_surface,
+                    ),
+                ))
+            };
+        }
+        if let Some(t) = self.template_0() {
+            return surfaces!(t);
+        }
+        match self {
+            Self::Template4_57(t) => surfaces!(t),
+            Self::Template4_58(t) => surfaces!(t.template_57),
+            Self::Template4_70(t) => surfaces!(t),
+            Self::Template4_71(t) => surfaces!(t.template_70),
+            Self::Template4_72(t) => surfaces!(t.template_70),
+            Self::Template4_73(t) => surfaces!(t.template_71.template_70),
+            Self::Template4_88(t) => surfaces!(t),
+            Self::Template4_50031(t) => surfaces!(t),
+            _ => None,
+        }
+    }
+
+    /// Ensemble metadata of ensemble member and derived ensemble templates
+    pub fn ensemble_info(&self) -> Option<EnsembleInfo> {
+        let member = |type_of_ensemble_forecast, perturbation_number, number_of_forecasts| {
+            Some(EnsembleInfo {
+                type_of_ensemble_forecast: Some(type_of_ensemble_forecast),
+                derived_forecast: None,
+                perturbation_number: Some(perturbation_number),
+                number_of_forecasts_in_ensemble: number_of_forecasts,
+            })
+        };
+        let derived = |t: &ProductDefinitionTemplate4_2| {
+            Some(EnsembleInfo {
+                type_of_ensemble_forecast: None,
+                derived_forecast: Some(t.derived_forecast),
+                perturbation_number: None,
+                number_of_forecasts_in_ensemble: t.number_of_forecasts_in_ensemble,
+            })
+        };
+        match self {
+            Self::Template4_1(t)
+            | Self::Template4_11(ProductDefinitionTemplate4_11 { template_1: t, .. })
+            | Self::Template4_60(ProductDefinitionTemplate4_60 { template_1: t, .. }) => member(
+                t.type_of_ensemble_forecast,
+                t.perturbation_number,
+                t.number_of_forecasts_in_ensemble,
+            ),
+            Self::Template4_61(t) => member(
+                t.template_60.template_1.type_of_ensemble_forecast,
+                t.template_60.template_1.perturbation_number,
+                t.template_60.template_1.number_of_forecasts_in_ensemble,
+            ),
+            Self::Template4_58(t) => member(
+                t.type_of_ensemble_forecast,
+                t.perturbation_number,
+                t.number_of_forecasts_in_ensemble,
+            ),
+            Self::Template4_71(t)
+            | Self::Template4_73(ProductDefinitionTemplate4_73 { template_71: t, .. }) => member(
+                t.type_of_ensemble_forecast,
+                t.perturbation_number,
+                t.number_of_forecasts_in_ensemble,
+            ),
+            Self::Template4_2(t)
+            | Self::Template4_12(ProductDefinitionTemplate4_12 { template_2: t, .. }) => derived(t),
+            _ => None,
+        }
+    }
+
+    /// Statistically processed time interval of 4.8-style templates
+    pub fn time_interval(&self) -> Option<&TimeInterval> {
+        match self {
+            Self::Template4_8(t) => Some(&t.interval),
+            Self::Template4_11(t) => Some(&t.interval),
+            Self::Template4_12(t) => Some(&t.interval),
+            Self::Template4_61(t) => Some(&t.interval),
+            Self::Template4_72(t) => Some(&t.interval),
+            Self::Template4_73(t) => Some(&t.interval),
+            Self::Template4_87(t) => Some(&t.interval),
+            Self::Template4_91(t) => Some(&t.interval),
+            Self::Template4_50008(t) => Some(&t.template_8.interval),
+            Self::Template4_50009(t) => Some(&t.template_8.interval),
+            Self::Template4_50011(t) => Some(&t.template_8.interval),
+            Self::Template4_50012(t) => Some(&t.template_8.interval),
+            _ => None,
+        }
+    }
+
+    /// Compute the valid time span from the reference time in Section 1
+    ///
+    /// The start is the reference time plus the forecast time. For statistically processed
+    /// templates the end is the end of the overall time interval; otherwise it equals the start.
+    pub fn valid_time(&self, reference_time: DateTime) -> Result<ValidTime> {
+        let Some((unit, forecast_time)) = self.forecast_time() else {
+            return Err(Error::UnsupportedData(
+                "product definition template has no forecast time".to_string(),
+            ));
+        };
+        let start = reference_time.add(unit, forecast_time as i64)?;
+        let end = match self {
+            Self::Template4_1001(ProductDefinitionTemplate4_1001 { time_range, .. })
+            | Self::Template4_1101(ProductDefinitionTemplate4_1101 { time_range, .. }) => start
+                .add(
+                    time_range.indicator_of_unit_of_time,
+                    time_range.length_of_the_time_range as i64,
+                )?,
+            _ => match self.time_interval() {
+                Some(interval) => interval.end_time(),
+                None => start,
+            },
+        };
+        Ok(ValidTime { start, end })
+    }
+}
+
+/// Ensemble metadata common to ensemble templates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EnsembleInfo {
+    /// Type of ensemble forecast (Code table 4.6), for individual members
+    pub type_of_ensemble_forecast: Option<u8>,
+    /// Derived forecast (Code table 4.7), for products derived from all members
+    pub derived_forecast: Option<u8>,
+    /// Perturbation number of the member
+    pub perturbation_number: Option<u8>,
+    pub number_of_forecasts_in_ensemble: u8,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TimeInterval {
     pub year: u16,
     pub month: u8,
@@ -200,6 +1548,20 @@ pub struct TimeInterval {
 }
 
 impl TimeInterval {
+    /// End of the overall time interval
+    ///
+    /// Like the reference time, it converts to `chrono` and `time` types with `TryFrom`.
+    pub fn end_time(&self) -> DateTime {
+        DateTime::new(
+            self.year,
+            self.month,
+            self.day,
+            self.hour,
+            self.minute,
+            self.second,
+        )
+    }
+
     pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
         Ok(Self {
             year: reader.read_grib_value()?,
@@ -213,9 +1575,26 @@ impl TimeInterval {
                 .collect::<Result<Vec<_>>>()?,
         })
     }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let n = u8::try_from(self.time_ranges.len()).map_err(|_| {
+            Error::InvalidData(format!("too many time ranges: {}", self.time_ranges.len()))
+        })?;
+        writer.write_grib_value(self.year)?;
+        writer.write_grib_value(self.month)?;
+        writer.write_grib_value(self.day)?;
+        writer.write_grib_value(self.hour)?;
+        writer.write_grib_value(self.minute)?;
+        writer.write_grib_value(self.second)?;
+        writer.write_grib_value(n)?;
+        for range in &self.time_ranges {
+            range.write(writer)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TimeRange {
     pub total_number_of_data_values_missing: u32,
     pub statistical_process: u8,
@@ -238,4 +1617,245 @@ impl TimeRange {
             time_increment: reader.read_grib_value()?,
         })
     }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.total_number_of_data_values_missing)?;
+        writer.write_grib_value(self.statistical_process)?;
+        writer.write_grib_value(self.type_of_time_increment)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time)?;
+        writer.write_grib_value(self.length_of_the_time_range)?;
+        writer.write_grib_value(self.indicator_of_unit_of_length_of_time_range)?;
+        writer.write_grib_value(self.time_increment)?;
+        Ok(())
+    }
+
+    /// Read a time range specification without the number of missing values, as coded in
+    /// templates 4.1001 and 4.1101
+    ///
+    /// `total_number_of_data_values_missing` is set to zero.
+    pub fn read_specification<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            total_number_of_data_values_missing: 0,
+            statistical_process: reader.read_grib_value()?,
+            type_of_time_increment: reader.read_grib_value()?,
+            indicator_of_unit_of_time: reader.read_grib_value()?,
+            length_of_the_time_range: reader.read_grib_value()?,
+            indicator_of_unit_of_length_of_time_range: reader.read_grib_value()?,
+            time_increment: reader.read_grib_value()?,
+        })
+    }
+
+    /// Write the time range specification without the number of missing values, the inverse
+    /// of [`TimeRange::read_specification`]
+    pub fn write_specification<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.statistical_process)?;
+        writer.write_grib_value(self.type_of_time_increment)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time)?;
+        writer.write_grib_value(self.length_of_the_time_range)?;
+        writer.write_grib_value(self.indicator_of_unit_of_length_of_time_range)?;
+        writer.write_grib_value(self.time_increment)?;
+        Ok(())
+    }
+
+    /// Type of statistical processing (Code table 4.10)
+    pub fn statistical_process(&self) -> StatisticalProcess {
+        self.statistical_process.into()
+    }
+
+    /// Unit of `length_of_the_time_range` (Code table 4.4)
+    pub fn time_unit(&self) -> TimeUnit {
+        self.indicator_of_unit_of_time.into()
+    }
+
+    /// Unit of `time_increment` (Code table 4.4)
+    pub fn increment_unit(&self) -> TimeUnit {
+        self.indicator_of_unit_of_length_of_time_range.into()
+    }
+
+    /// Length of the time range
+    pub fn duration(&self) -> Result<Duration> {
+        time::duration(
+            self.indicator_of_unit_of_time,
+            self.length_of_the_time_range as u64,
+        )
+    }
+
+    /// Time between successive fields in the time range, zero if continuous
+    pub fn increment(&self) -> Result<Duration> {
+        time::duration(
+            self.indicator_of_unit_of_length_of_time_range,
+            self.time_increment as u64,
+        )
+    }
+}
+
+/// Scaled parameter of a distribution function (templates 4.57 and 4.58)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DistributionFunctionParameter {
+    pub scale_factor: i8,
+    pub scaled_value: i32,
+}
+
+impl DistributionFunctionParameter {
+    /// Read `n` parameters, coded as the list of their scale factors followed by the list of
+    /// their scaled values
+    pub fn read_list<R: Read>(reader: &mut R, n: u8) -> Result<Vec<Self>> {
+        let scale_factors = (0..n)
+            .map(|_| reader.read_grib_value())
+            .collect::<std::io::Result<Vec<i8>>>()?;
+        scale_factors
+            .into_iter()
+            .map(|scale_factor| {
+                Ok(Self {
+                    scale_factor,
+                    scaled_value: reader.read_grib_value()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Write the scale factors of `parameters` followed by their scaled values
+    pub fn write_list<W: Write>(parameters: &[Self], writer: &mut W) -> Result<()> {
+        for parameter in parameters {
+            writer.write_grib_value(parameter.scale_factor)?;
+        }
+        for parameter in parameters {
+            writer.write_grib_value(parameter.scaled_value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Forecast used in a local time product (template 4.88)
+///
+/// The date is the local date the forecast applies to, and `forecast_time` is counted in
+/// `indicator_of_unit_of_forecast_time` units from that local date.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LocalTimeForecast {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub indicator_of_unit_of_forecast_time: u8,
+    pub forecast_time: i32,
+    pub number_of_time_increments: u8,
+    pub indicator_of_unit_of_time_increment: u8,
+    pub time_increment: u32,
+}
+
+impl LocalTimeForecast {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            year: reader.read_grib_value()?,
+            month: reader.read_grib_value()?,
+            day: reader.read_grib_value()?,
+            hour: reader.read_grib_value()?,
+            minute: reader.read_grib_value()?,
+            indicator_of_unit_of_forecast_time: reader.read_grib_value()?,
+            forecast_time: reader.read_grib_value()?,
+            number_of_time_increments: reader.read_grib_value()?,
+            indicator_of_unit_of_time_increment: reader.read_grib_value()?,
+            time_increment: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.year)?;
+        writer.write_grib_value(self.month)?;
+        writer.write_grib_value(self.day)?;
+        writer.write_grib_value(self.hour)?;
+        writer.write_grib_value(self.minute)?;
+        writer.write_grib_value(self.indicator_of_unit_of_forecast_time)?;
+        writer.write_grib_value(self.forecast_time)?;
+        writer.write_grib_value(self.number_of_time_increments)?;
+        writer.write_grib_value(self.indicator_of_unit_of_time_increment)?;
+        writer.write_grib_value(self.time_increment)?;
+        Ok(())
+    }
+}
+
+/// Category of a categorical forecast (template 4.91)
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Category {
+    pub category_type: u8,
+    pub code_figure: u8,
+    pub scale_factor_of_lower_limit: i8,
+    pub scaled_value_of_lower_limit: i32,
+    pub scale_factor_of_upper_limit: i8,
+    pub scaled_value_of_upper_limit: i32,
+}
+
+impl Category {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            category_type: reader.read_grib_value()?,
+            code_figure: reader.read_grib_value()?,
+            scale_factor_of_lower_limit: reader.read_grib_value()?,
+            scaled_value_of_lower_limit: reader.read_grib_value()?,
+            scale_factor_of_upper_limit: reader.read_grib_value()?,
+            scaled_value_of_upper_limit: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.category_type)?;
+        writer.write_grib_value(self.code_figure)?;
+        writer.write_grib_value(self.scale_factor_of_lower_limit)?;
+        writer.write_grib_value(self.scaled_value_of_lower_limit)?;
+        writer.write_grib_value(self.scale_factor_of_upper_limit)?;
+        writer.write_grib_value(self.scaled_value_of_upper_limit)?;
+        Ok(())
+    }
+}
+
+/// JMA radar and rain gauge operating information (templates 4.50008, 4.50010, 4.50011 and 4.50012)
+///
+/// Each field is a 64-bit map in which a set bit means the corresponding site or network was in operation.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct OperatingInfo {
+    /// Radar operating information (part 1)
+    pub radar_operating_info1: u64,
+    /// Radar operating information (part 2)
+    pub radar_operating_info2: u64,
+    /// Rain gauge operating information
+    pub rain_gauge_operating_info: u64,
+}
+
+impl OperatingInfo {
+    pub fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        Ok(Self {
+            radar_operating_info1: reader.read_grib_value()?,
+            radar_operating_info2: reader.read_grib_value()?,
+            rain_gauge_operating_info: reader.read_grib_value()?,
+        })
+    }
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_grib_value(self.radar_operating_info1)?;
+        writer.write_grib_value(self.radar_operating_info2)?;
+        writer.write_grib_value(self.rain_gauge_operating_info)?;
+        Ok(())
+    }
+
+    /// Returns whether the radar site at the given bit position (0 = most significant bit of part 1) was in operation
+    pub fn is_radar_operating(&self, site: u32) -> bool {
+        is_operating(
+            &[self.radar_operating_info1, self.radar_operating_info2],
+            site,
+        )
+    }
+
+    /// Returns whether the rain gauge network at the given bit position (0 = most significant bit) was in operation
+    pub fn is_rain_gauge_operating(&self, network: u32) -> bool {
+        is_operating(&[self.rain_gauge_operating_info], network)
+    }
+}
+
+/// Tests a bit in JMA operating information bit maps, counting from the most significant bit of the first word
+fn is_operating(bitmaps: &[u64], bit: u32) -> bool {
+    bitmaps
+        .get((bit / 64) as usize)
+        .is_some_and(|word| word & (1 << (63 - bit % 64)) != 0)
 }
@@ -0,0 +1,85 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::Read;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use crate::Result;
+
+/// Template parsed by a parser registered with [`register_template`]
+///
+/// Use [`downcast_ref`](#method.downcast_ref) to get the concrete type back.
+pub trait CustomTemplate: Any + Debug + Send + Sync {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Any + Debug + Send + Sync> CustomTemplate for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl dyn CustomTemplate {
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+}
+
+/// Parser of a template body
+///
+/// The octets of the body can be read with [`GribRead`](super::GribRead).
+pub type TemplateParser = fn(&mut dyn Read) -> Result<Arc<dyn CustomTemplate>>;
+
+type Key = (u8, u16, Option<u16>);
+
+fn registry() -> &'static RwLock<HashMap<Key, TemplateParser>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<Key, TemplateParser>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Register a parser for a template of section 1, 3, 4 or 5 that this crate does not support
+///
+/// With `centre`, the parser only applies to messages from that originating centre and takes
+/// precedence over a parser registered for all centres. Built-in templates cannot be overridden.
+pub fn register_template(
+    section: u8,
+    template_number: u16,
+    centre: Option<u16>,
+    parser: TemplateParser,
+) {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert((section, template_number, centre), parser);
+}
+
+/// Remove a parser registered with [`register_template`]
+pub fn unregister_template(section: u8, template_number: u16, centre: Option<u16>) {
+    registry()
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&(section, template_number, centre));
+}
+
+fn find_parser(section: u8, template_number: u16, centre: Option<u16>) -> Option<TemplateParser> {
+    let registry = registry().read().unwrap_or_else(|e| e.into_inner());
+    centre
+        .and_then(|c| registry.get(&(section, template_number, Some(c))))
+        .or_else(|| registry.get(&(section, template_number, None)))
+        .copied()
+}
+
+/// Whether a parser is registered for the template
+pub(crate) fn is_registered(section: u8, template_number: u16, centre: Option<u16>) -> bool {
+    find_parser(section, template_number, centre).is_some()
+}
+
+/// Parse the template with its registered parser, or return `None` if there is none
+pub(crate) fn read_registered<R: Read>(
+    section: u8,
+    template_number: u16,
+    centre: Option<u16>,
+    reader: &mut R,
+) -> Option<Result<Arc<dyn CustomTemplate>>> {
+    find_parser(section, template_number, centre).map(|parser| parser(reader))
+}
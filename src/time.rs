@@ -0,0 +1,456 @@
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Calendar date and time (UTC) as encoded in GRIB2 sections
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z
+    pub fn unix_timestamp(&self) -> i64 {
+        self.days_from_epoch() * 86400
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    /// Add `value` in units of Code table 4.4 (indicator of unit of time range)
+    pub fn add(&self, unit: u8, value: i64) -> Result<Self> {
+        if let Some(seconds) = unit_seconds(unit) {
+            return self.add_seconds(value * seconds as i64);
+        }
+        match unit {
+            3 => self.add_months(value),
+            4 => self.add_months(value * 12),
+            5 => self.add_months(value * 120),
+            6 => self.add_months(value * 360),
+            7 => self.add_months(value * 1200),
+            _ => Err(unsupported_unit(unit)),
+        }
+    }
+
+    pub fn add_seconds(&self, seconds: i64) -> Result<Self> {
+        let total = self.unix_timestamp() + seconds;
+        let (days, secs) = (total.div_euclid(86400), total.rem_euclid(86400));
+        let (year, month, day) = civil_from_days(days);
+        Ok(Self {
+            year: u16::try_from(year)
+                .map_err(|_| Error::InvalidData(format!("year {} is out of range", year)))?,
+            month,
+            day,
+            hour: (secs / 3600) as u8,
+            minute: (secs % 3600 / 60) as u8,
+            second: (secs % 60) as u8,
+        })
+    }
+
+    /// Time elapsed since `earlier`, `None` if `earlier` is later than `self`
+    pub fn duration_since(&self, earlier: DateTime) -> Option<Duration> {
+        let seconds = self.unix_timestamp() - earlier.unix_timestamp();
+        u64::try_from(seconds).ok().map(Duration::from_secs)
+    }
+
+    fn add_months(&self, months: i64) -> Result<Self> {
+        let m = self.year as i64 * 12 + (self.month as i64 - 1) + months;
+        let year = u16::try_from(m.div_euclid(12))
+            .map_err(|_| Error::InvalidData(format!("year {} is out of range", m / 12)))?;
+        let month = (m.rem_euclid(12) + 1) as u8;
+        Ok(Self {
+            year,
+            month,
+            day: self.day.min(days_in_month(year, month)),
+            ..*self
+        })
+    }
+
+    /// Days since 1970-01-01
+    fn days_from_epoch(&self) -> i64 {
+        // https://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let (m, d) = (self.month as i64, self.day as i64);
+        let y = self.year as i64 - if m <= 2 { 1 } else { 0 };
+        let era = y.div_euclid(400);
+        let yoe = y - era * 400;
+        let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + d - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+}
+
+impl From<DateTime> for std::time::SystemTime {
+    fn from(dt: DateTime) -> Self {
+        let ts = dt.unix_timestamp();
+        let d = std::time::Duration::from_secs(ts.unsigned_abs());
+        if ts >= 0 {
+            std::time::UNIX_EPOCH + d
+        } else {
+            std::time::UNIX_EPOCH - d
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<DateTime> for chrono::DateTime<chrono::Utc> {
+    type Error = Error;
+
+    fn try_from(dt: DateTime) -> Result<Self> {
+        chrono::NaiveDate::from_ymd_opt(dt.year as i32, dt.month as u32, dt.day as u32)
+            .and_then(|date| date.and_hms_opt(dt.hour as u32, dt.minute as u32, dt.second as u32))
+            .map(|naive| naive.and_utc())
+            .ok_or_else(|| invalid_date_time(dt))
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl TryFrom<chrono::DateTime<chrono::Utc>> for DateTime {
+    type Error = Error;
+
+    fn try_from(dt: chrono::DateTime<chrono::Utc>) -> Result<Self> {
+        use chrono::{Datelike, Timelike};
+
+        Ok(Self {
+            year: u16::try_from(dt.year())
+                .map_err(|_| Error::InvalidData(format!("year {} is out of range", dt.year())))?,
+            month: dt.month() as u8,
+            day: dt.day() as u8,
+            hour: dt.hour() as u8,
+            minute: dt.minute() as u8,
+            second: dt.second().min(59) as u8,
+        })
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<DateTime> for time::OffsetDateTime {
+    type Error = Error;
+
+    fn try_from(dt: DateTime) -> Result<Self> {
+        let date = time::Month::try_from(dt.month)
+            .and_then(|month| time::Date::from_calendar_date(dt.year as i32, month, dt.day));
+        let time = time::Time::from_hms(dt.hour, dt.minute, dt.second);
+        match (date, time) {
+            (Ok(date), Ok(time)) => Ok(time::PrimitiveDateTime::new(date, time).assume_utc()),
+            _ => Err(invalid_date_time(dt)),
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+impl TryFrom<time::OffsetDateTime> for DateTime {
+    type Error = Error;
+
+    fn try_from(dt: time::OffsetDateTime) -> Result<Self> {
+        let dt = dt.to_offset(time::UtcOffset::UTC);
+        Ok(Self {
+            year: u16::try_from(dt.year())
+                .map_err(|_| Error::InvalidData(format!("year {} is out of range", dt.year())))?,
+            month: dt.month().into(),
+            day: dt.day(),
+            hour: dt.hour(),
+            minute: dt.minute(),
+            second: dt.second(),
+        })
+    }
+}
+
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn invalid_date_time(dt: DateTime) -> Error {
+    Error::InvalidData(format!(
+        "invalid date and time {:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+    ))
+}
+
+/// Length in seconds of a unit of Code table 4.4 (indicator of unit of time range)
+///
+/// `None` for months and longer units, whose length depends on the date they are counted
+/// from, and for units not listed in the table.
+pub fn unit_seconds(unit: u8) -> Option<u64> {
+    Some(match unit {
+        0 => 60,
+        1 => 3600,
+        2 => 86400,
+        10 => 3 * 3600,
+        11 => 6 * 3600,
+        12 => 12 * 3600,
+        13 => 1,
+        _ => return None,
+    })
+}
+
+/// Duration of `value` in units of Code table 4.4 (indicator of unit of time range)
+///
+/// Months and longer units have no fixed length and are rejected; add them to a date with
+/// [`DateTime::add`] instead.
+pub fn duration(unit: u8, value: u64) -> Result<Duration> {
+    let seconds = unit_seconds(unit).ok_or_else(|| match unit {
+        3..=7 => Error::UnsupportedData(format!(
+            "indicator of unit of time range {} has no fixed length",
+            unit
+        )),
+        _ => unsupported_unit(unit),
+    })?;
+    value
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| Error::InvalidData(format!("time range {} is out of range", value)))
+}
+
+fn unsupported_unit(unit: u8) -> Error {
+    Error::UnsupportedData(format!("indicator of unit of time range {}", unit))
+}
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    (yoe + era * 400 + if m <= 2 { 1 } else { 0 }, m, d)
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Time span a product is valid for
+///
+/// `start == end` for products at a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidTime {
+    pub start: DateTime,
+    pub end: DateTime,
+}
+
+impl ValidTime {
+    /// Length of the span, zero for products at a point in time
+    pub fn duration(&self) -> Duration {
+        self.end.duration_since(self.start).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_timestamp() {
+        assert_eq!(DateTime::new(1970, 1, 1, 0, 0, 0).unix_timestamp(), 0);
+        assert_eq!(DateTime::new(1969, 12, 31, 23, 59, 59).unix_timestamp(), -1);
+        assert_eq!(
+            DateTime::new(2000, 3, 1, 0, 0, 0).unix_timestamp(),
+            951_868_800
+        );
+        assert_eq!(
+            DateTime::new(2024, 1, 2, 3, 4, 5).unix_timestamp(),
+            1_704_164_645
+        );
+        let time = std::time::SystemTime::from(DateTime::new(1969, 12, 31, 0, 0, 0));
+        assert_eq!(
+            std::time::UNIX_EPOCH.duration_since(time).unwrap(),
+            Duration::from_secs(86400)
+        );
+    }
+
+    #[test]
+    fn add_months() {
+        let dt = DateTime::new(2023, 11, 15, 6, 0, 0);
+        assert_eq!(dt.add(3, 3).unwrap(), DateTime::new(2024, 2, 15, 6, 0, 0));
+        assert_eq!(
+            dt.add(3, -11).unwrap(),
+            DateTime::new(2022, 12, 15, 6, 0, 0)
+        );
+        assert_eq!(dt.add(4, 2).unwrap(), DateTime::new(2025, 11, 15, 6, 0, 0));
+        assert_eq!(dt.add(3, 0).unwrap(), dt);
+        assert!(dt.add(4, 70_000).is_err());
+        assert!(dt.add(4, -3000).is_err());
+    }
+
+    #[test]
+    fn end_of_month_and_leap_years() {
+        let jan_31 = DateTime::new(2024, 1, 31, 12, 0, 0);
+        assert_eq!(
+            jan_31.add(3, 1).unwrap(),
+            DateTime::new(2024, 2, 29, 12, 0, 0)
+        );
+        assert_eq!(
+            jan_31.add(3, 13).unwrap(),
+            DateTime::new(2025, 2, 28, 12, 0, 0)
+        );
+        assert_eq!(
+            jan_31.add(3, 3).unwrap(),
+            DateTime::new(2024, 4, 30, 12, 0, 0)
+        );
+        let feb_29 = DateTime::new(2000, 2, 29, 0, 0, 0);
+        assert_eq!(
+            feb_29.add(4, 1).unwrap(),
+            DateTime::new(2001, 2, 28, 0, 0, 0)
+        );
+        assert_eq!(
+            feb_29.add(4, 4).unwrap(),
+            DateTime::new(2004, 2, 29, 0, 0, 0)
+        );
+        assert_eq!(
+            feb_29.add(4, 100).unwrap(),
+            DateTime::new(2100, 2, 28, 0, 0, 0)
+        );
+        assert_eq!(
+            feb_29.add(2, 1).unwrap(),
+            DateTime::new(2000, 3, 1, 0, 0, 0)
+        );
+        let feb_28 = DateTime::new(1900, 2, 28, 23, 0, 0);
+        assert_eq!(
+            feb_28.add(1, 1).unwrap(),
+            DateTime::new(1900, 3, 1, 0, 0, 0)
+        );
+        let new_year = DateTime::new(2024, 12, 31, 23, 59, 59);
+        assert_eq!(
+            new_year.add(13, 1).unwrap(),
+            DateTime::new(2025, 1, 1, 0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn add_every_unit() {
+        let dt = DateTime::new(2024, 1, 31, 0, 0, 0);
+        let add = |unit, value| dt.add(unit, value).unwrap();
+        assert_eq!(add(0, 90), DateTime::new(2024, 1, 31, 1, 30, 0));
+        assert_eq!(add(1, 25), DateTime::new(2024, 2, 1, 1, 0, 0));
+        assert_eq!(add(2, 30), DateTime::new(2024, 3, 1, 0, 0, 0));
+        assert_eq!(add(3, 1), DateTime::new(2024, 2, 29, 0, 0, 0));
+        assert_eq!(add(4, 1), DateTime::new(2025, 1, 31, 0, 0, 0));
+        assert_eq!(add(5, 1), DateTime::new(2034, 1, 31, 0, 0, 0));
+        assert_eq!(add(6, 1), DateTime::new(2054, 1, 31, 0, 0, 0));
+        assert_eq!(add(7, 1), DateTime::new(2124, 1, 31, 0, 0, 0));
+        assert_eq!(add(10, 2), DateTime::new(2024, 1, 31, 6, 0, 0));
+        assert_eq!(add(11, 3), DateTime::new(2024, 1, 31, 18, 0, 0));
+        assert_eq!(add(12, -2), DateTime::new(2024, 1, 30, 0, 0, 0));
+        assert_eq!(add(13, 61), DateTime::new(2024, 1, 31, 0, 1, 1));
+        for unit in [8, 9, 14, 255] {
+            assert!(matches!(dt.add(unit, 1), Err(Error::UnsupportedData(_))));
+        }
+        assert_eq!(duration(11, 2).unwrap(), Duration::from_secs(12 * 3600));
+        assert!(matches!(duration(3, 1), Err(Error::UnsupportedData(_))));
+        assert!(matches!(duration(0, u64::MAX), Err(Error::InvalidData(_))));
+    }
+
+    /// Template 4.0 for 2 m above ground, `forecast_time` in `unit` after the reference time
+    fn template_0(unit: u8, forecast_time: u32) -> Vec<u8> {
+        let mut bytes = vec![0, 0, 2, 0, 96, 0, 0, 0, unit];
+        bytes.extend(forecast_time.to_be_bytes());
+        bytes.extend([103, 0, 0, 0, 0, 2, 255, 255, 255, 255, 255, 255]);
+        bytes
+    }
+
+    /// Overall time interval ending at `end` with one range of `length` hours
+    fn interval(end: DateTime, statistical_process: u8, length: u32) -> Vec<u8> {
+        let mut bytes = end.year.to_be_bytes().to_vec();
+        bytes.extend([end.month, end.day, end.hour, end.minute, end.second, 1]);
+        bytes.extend([0, 0, 0, 0, statistical_process, 2, 1]);
+        bytes.extend(length.to_be_bytes());
+        bytes.extend([255, 0, 0, 0, 0]);
+        bytes
+    }
+
+    fn valid_time(template_number: u16, bytes: &[u8]) -> Result<ValidTime> {
+        let template = crate::templates::ProductDefinitionTemplate::read_any(
+            template_number,
+            &mut &bytes[..],
+        )?;
+        template.valid_time(DateTime::new(2024, 1, 2, 0, 0, 0))
+    }
+
+    #[test]
+    fn valid_time_at_a_point() {
+        let valid = valid_time(0, &template_0(11, 2)).unwrap();
+        assert_eq!(valid.start, DateTime::new(2024, 1, 2, 12, 0, 0));
+        assert_eq!(valid.end, valid.start);
+        assert_eq!(valid.duration(), Duration::ZERO);
+        assert!(valid_time(0, &template_0(8, 2)).is_err());
+    }
+
+    #[test]
+    fn valid_time_4_8() {
+        // 6-hour accumulation from 6 to 12 hours
+        let mut bytes = template_0(1, 6);
+        bytes.extend(interval(DateTime::new(2024, 1, 2, 12, 0, 0), 1, 6));
+        let valid = valid_time(8, &bytes).unwrap();
+        assert_eq!(valid.start, DateTime::new(2024, 1, 2, 6, 0, 0));
+        assert_eq!(valid.end, DateTime::new(2024, 1, 2, 12, 0, 0));
+        assert_eq!(valid.duration(), Duration::from_secs(6 * 3600));
+    }
+
+    #[test]
+    fn valid_time_4_11() {
+        // daily maximum of ensemble member 5 of 21, over the first day of the forecast
+        let mut bytes = template_0(2, 0);
+        bytes.extend([3, 5, 21]);
+        bytes.extend(interval(DateTime::new(2024, 1, 3, 0, 0, 0), 2, 24));
+        let valid = valid_time(11, &bytes).unwrap();
+        assert_eq!(valid.start, DateTime::new(2024, 1, 2, 0, 0, 0));
+        assert_eq!(valid.end, DateTime::new(2024, 1, 3, 0, 0, 0));
+        assert_eq!(valid.duration(), Duration::from_secs(86400));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_conversions() {
+        use chrono::TimeZone;
+
+        let dt = DateTime::new(2024, 2, 29, 18, 30, 15);
+        let utc = chrono::DateTime::<chrono::Utc>::try_from(dt).unwrap();
+        assert_eq!(
+            utc,
+            chrono::Utc
+                .with_ymd_and_hms(2024, 2, 29, 18, 30, 15)
+                .unwrap()
+        );
+        assert_eq!(utc.timestamp(), dt.unix_timestamp());
+        assert_eq!(DateTime::try_from(utc).unwrap(), dt);
+        let invalid = DateTime::new(2023, 2, 29, 0, 0, 0);
+        assert!(chrono::DateTime::<chrono::Utc>::try_from(invalid).is_err());
+        let before = chrono::Utc.with_ymd_and_hms(-1, 1, 1, 0, 0, 0).unwrap();
+        assert!(DateTime::try_from(before).is_err());
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time_conversions() {
+        let dt = DateTime::new(2024, 2, 29, 18, 30, 15);
+        let utc = time::OffsetDateTime::try_from(dt).unwrap();
+        assert_eq!(utc.unix_timestamp(), dt.unix_timestamp());
+        assert_eq!(DateTime::try_from(utc).unwrap(), dt);
+        let offset = utc.to_offset(time::UtcOffset::from_hms(9, 0, 0).unwrap());
+        assert_eq!(DateTime::try_from(offset).unwrap(), dt);
+        assert!(time::OffsetDateTime::try_from(DateTime::new(2024, 13, 1, 0, 0, 0)).is_err());
+        assert!(time::OffsetDateTime::try_from(DateTime::new(2024, 1, 1, 24, 0, 0)).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::io::{Read, Write};
+
+use crate::field::{Field, Message};
+use crate::packing::{Packing, PackingMethod};
+use crate::reader::SectionReader;
+use crate::writer::MessageWriter;
+use crate::{Error, Result};
+
+/// Repack every field of a stream with `method`, copying the other sections unchanged
+///
+/// Messages are read and written one at a time. The decimal and binary scale factors of
+/// each field are kept, so the values are preserved exactly. Returns the number of
+/// messages written.
+pub fn transcode<R: Read, W: Write>(
+    reader: R,
+    writer: &mut W,
+    method: PackingMethod,
+) -> Result<u64> {
+    let mut sections = SectionReader::new(reader);
+    let mut count = 0;
+    while let Some(message) = Message::read_sections(&mut sections)? {
+        transcode_message(&message, method)?.finish(writer)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Repack every field of `message` with `method`
+pub fn transcode_message(message: &Message, method: PackingMethod) -> Result<MessageWriter> {
+    let mut writer = message.writer()?;
+    for field in &message.fields {
+        writer.write_field_metadata(field)?;
+        writer.write_values(&field_values(field)?, &field_packing(field, method)?)?;
+    }
+    Ok(writer)
+}
+
+fn field_values(field: &Field) -> Result<Vec<Option<f32>>> {
+    Ok(field
+        .values()?
+        .into_iter()
+        .map(|v| (!v.is_nan()).then_some(v))
+        .collect())
+}
+
+fn field_packing(field: &Field, method: PackingMethod) -> Result<Packing> {
+    let template = field.data_representation_template()?;
+    let template_0 = template.template_0().ok_or(Error::UnsupportedTemplate {
+        section: 5,
+        number: field.data_representation.template_number,
+    })?;
+    Ok(Packing {
+        method,
+        decimal_scale_factor: template_0.decimal_scale_factor,
+        binary_scale_factor: template_0.binary_scale_factor,
+    })
+}
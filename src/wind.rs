@@ -0,0 +1,126 @@
+use crate::field::DecodedField;
+use crate::templates::GridDefinitionTemplate;
+use crate::{Error, Result};
+
+/// Parameter (category, number) of the U and V components of wind (Code table 4.2, discipline 0)
+const U_COMPONENT: (u8, u8) = (2, 2);
+const V_COMPONENT: (u8, u8) = (2, 3);
+
+/// U and V component fields of the same wind
+#[derive(Debug, Clone, Copy)]
+pub struct WindPair<'a> {
+    pub u: &'a DecodedField,
+    pub v: &'a DecodedField,
+}
+
+impl<'a> WindPair<'a> {
+    /// Pair `u` and `v`, checking that they are components of the same wind on the same grid
+    ///
+    /// The fields must have the same parameters apart from the component, the same grid, and
+    /// the same resolution and component flags, so that both are either relative to the
+    /// grid or to easterly and northerly directions.
+    pub fn new(u: &'a DecodedField, v: &'a DecodedField) -> Result<Self> {
+        if component(u) != Some(U_COMPONENT) || component(v) != Some(V_COMPONENT) {
+            return Err(Error::InvalidData(
+                "fields are not U and V components of wind".to_string(),
+            ));
+        }
+        if !same_wind(u, v) {
+            return Err(Error::InvalidData(
+                "U and V components are for different levels or times".to_string(),
+            ));
+        }
+        if component_flags(u) != component_flags(v) {
+            return Err(Error::InvalidData(
+                "U and V components have different vector orientations".to_string(),
+            ));
+        }
+        if !u.same_grid(v)? {
+            return Err(Error::InvalidData(
+                "U and V components are on different grids".to_string(),
+            ));
+        }
+        Ok(Self { u, v })
+    }
+
+    /// Whether the components are relative to the grid's i and j directions rather than
+    /// easterly and northerly (flag bit 5 of Template 3.0)
+    ///
+    /// On a regular latitude/longitude grid both orientations coincide.
+    pub fn grid_relative(&self) -> bool {
+        component_flags(self.u).is_some_and(|flags| flags & 0x08 != 0)
+    }
+
+    /// Wind speed at each point, NAN where either component is missing
+    pub fn speed(&self) -> Vec<f32> {
+        self.u
+            .values
+            .iter()
+            .zip(&self.v.values)
+            .map(|(u, v)| u.hypot(*v))
+            .collect()
+    }
+
+    /// Direction the wind blows from, in degrees clockwise from north (0 for calm), NAN where
+    /// either component is missing
+    pub fn direction(&self) -> Vec<f32> {
+        self.u
+            .values
+            .iter()
+            .zip(&self.v.values)
+            .map(|(&u, &v)| match (u, v) {
+                (0.0, 0.0) => 0.0,
+                _ => (-u).atan2(-v).to_degrees().rem_euclid(360.0),
+            })
+            .collect()
+    }
+}
+
+/// Pair every U component in `fields` with the V component of the same wind
+///
+/// U components without a matching V component are left out, as are pairs that fail the
+/// checks of [`WindPair::new`].
+pub fn pair_winds(fields: &[DecodedField]) -> Vec<WindPair<'_>> {
+    let (us, vs) = fields
+        .iter()
+        .fold((Vec::new(), Vec::new()), |(mut us, mut vs), f| {
+            match component(f) {
+                Some(U_COMPONENT) => us.push(f),
+                Some(V_COMPONENT) => vs.push(f),
+                _ => {}
+            }
+            (us, vs)
+        });
+    us.into_iter()
+        .filter_map(|u| {
+            vs.iter()
+                .filter(|v| same_wind(u, v))
+                .find_map(|v| WindPair::new(u, v).ok())
+        })
+        .collect()
+}
+
+fn component(field: &DecodedField) -> Option<(u8, u8)> {
+    match field.discipline {
+        0 => field.product_definition.template.parameter(),
+        _ => None,
+    }
+}
+
+fn same_wind(u: &DecodedField, v: &DecodedField) -> bool {
+    let (pu, pv) = (
+        &u.product_definition.template,
+        &v.product_definition.template,
+    );
+    u.reference_time == v.reference_time
+        && u.valid_time == v.valid_time
+        && pu.fixed_surfaces() == pv.fixed_surfaces()
+        && pu.ensemble_info() == pv.ensemble_info()
+}
+
+fn component_flags(field: &DecodedField) -> Option<u8> {
+    match &*field.grid_definition {
+        GridDefinitionTemplate::Template3_0(t) => Some(t.resolution_and_component_flags),
+        _ => None,
+    }
+}
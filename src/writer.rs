@@ -0,0 +1,398 @@
+use std::io::Write;
+
+use crate::field::Field;
+use crate::message::{
+    DataRepresentationSectionHeader, GridDefinitionSectionHeader, IdentificationSectionHeader,
+    IndicatorSectionHeader, ProductDefinition, ProductDefinitionSectionHeader, SectionHeader,
+};
+use crate::packing::Packing;
+use crate::reader::can_follow;
+use crate::templates::{DataRepresentationTemplate, GridDefinitionTemplate, write_bitmap};
+use crate::time::DateTime;
+use crate::{Error, Result};
+
+/// Writer of a single GRIB2 message
+///
+/// Sections 0, 1 and 8 are written by the writer; sections 2 to 7 are added in order with
+/// [`write_section`](Self::write_section). The message is assembled in memory so that the
+/// total length in Section 0 can be filled in by [`finish`](Self::finish).
+#[derive(Debug, Clone)]
+pub struct MessageWriter {
+    buf: Vec<u8>,
+    last_section: u8,
+    /// Bodies of the latest Sections 2 and 3, to leave out repetitions
+    local_use: Option<Vec<u8>>,
+    grid_definition: Option<Vec<u8>>,
+}
+
+impl MessageWriter {
+    /// Start a message with Section 0 (for `discipline`) and Section 1 without a template
+    pub fn new(discipline: u8, identification: &IdentificationSectionHeader) -> Result<Self> {
+        Self::with_identification_template(discipline, identification, &[])
+    }
+
+    /// Start a message with Section 0 (for `discipline`) and Section 1 followed by the octets
+    /// of its template (octets 24-nn, see [`crate::templates::IdentificationTemplate::write`])
+    ///
+    /// The section length is recomputed.
+    pub fn with_identification_template(
+        discipline: u8,
+        identification: &IdentificationSectionHeader,
+        template: &[u8],
+    ) -> Result<Self> {
+        if identification.template_number.is_none() && !template.is_empty() {
+            return Err(Error::InvalidData(
+                "an identification template needs a template number".to_string(),
+            ));
+        }
+        let mut buf = Vec::new();
+        IndicatorSectionHeader {
+            identifier: 0x47524942, // "GRIB"
+            reserved: 0,
+            discipline,
+            edition_number: 2,
+            total_length: 0, // filled in by finish()
+        }
+        .write(&mut buf)?;
+        let mut body = Vec::new();
+        identification.write(&mut body)?;
+        body.extend_from_slice(template);
+        let section_length = u32::try_from(body.len() + 5)
+            .map_err(|_| Error::InvalidData("section 1 is too long".to_string()))?;
+        SectionHeader {
+            section_length,
+            number_of_section: 1,
+        }
+        .write(&mut buf)?;
+        buf.extend_from_slice(&body);
+        Ok(Self {
+            buf,
+            last_section: 1,
+            local_use: None,
+            grid_definition: None,
+        })
+    }
+
+    /// Append a section from its number and the octets following octet 5
+    pub fn write_section(&mut self, number_of_section: u8, body: &[u8]) -> Result<()> {
+        if !(2..=7).contains(&number_of_section)
+            || !can_follow(self.last_section, number_of_section)
+        {
+            return Err(Error::InvalidData(format!(
+                "section {} cannot follow section {}",
+                number_of_section, self.last_section
+            )));
+        }
+        let section_length = u32::try_from(body.len() + 5).map_err(|_| {
+            Error::InvalidData(format!("section {} is too long", number_of_section))
+        })?;
+        SectionHeader {
+            section_length,
+            number_of_section,
+        }
+        .write(&mut self.buf)?;
+        self.buf.extend_from_slice(body);
+        self.last_section = number_of_section;
+        match number_of_section {
+            2 => self.local_use = Some(body.to_vec()),
+            3 => self.grid_definition = Some(body.to_vec()),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Append Sections 2 to 4 of `field` unchanged
+    ///
+    /// Sections 2 and 3 are left out when they repeat the latest ones in this message.
+    pub fn write_field_metadata(&mut self, field: &Field) -> Result<()> {
+        if let Some((_, body)) = &field.local_use
+            && self.local_use.as_ref() != Some(body)
+        {
+            self.write_section(2, body)?;
+        }
+        let mut body = Vec::new();
+        field.grid_definition.write(&mut body)?;
+        body.extend_from_slice(&field.grid_definition_body);
+        if self.grid_definition.as_ref() != Some(&body) || self.last_section == 2 {
+            self.write_section(3, &body)?;
+        }
+        let mut body = Vec::new();
+        field.product_definition.write(&mut body)?;
+        body.extend_from_slice(&field.product_definition_body);
+        self.write_section(4, &body)
+    }
+
+    /// Append Sections 2 to 7 of `field` with the packed data unchanged
+    ///
+    /// A bit-map that refers to a previous one (indicator 254) is written out in full, so
+    /// fields can be copied from several messages in any order.
+    pub fn write_field(&mut self, field: &Field) -> Result<()> {
+        self.write_field_metadata(field)?;
+        let mut body = Vec::new();
+        field.data_representation.write(&mut body)?;
+        body.extend_from_slice(&field.data_representation_body);
+        self.write_section(5, &body)?;
+        let mut body = match field.bitmap.bit_map_indicator {
+            254 => vec![0],
+            indicator => vec![indicator],
+        };
+        body.extend_from_slice(&field.bitmap_body);
+        self.write_section(6, &body)?;
+        self.write_section(7, &field.data_body)
+    }
+
+    /// Append Section 3 (the section length in `header` is recomputed)
+    pub fn write_grid_definition(
+        &mut self,
+        header: &GridDefinitionSectionHeader,
+        template: &GridDefinitionTemplate,
+    ) -> Result<()> {
+        let mut body = Vec::new();
+        header.write(&mut body)?;
+        template.write(&mut body)?;
+        self.write_section(3, &body)
+    }
+
+    /// Append Section 4 (`nv` and the section length in `header` are recomputed)
+    pub fn write_product_definition(
+        &mut self,
+        header: &ProductDefinitionSectionHeader,
+        product_definition: &ProductDefinition,
+    ) -> Result<()> {
+        let nv = u16::try_from(product_definition.coordinate_values.len())
+            .map_err(|_| Error::InvalidData("too many coordinate values".to_string()))?;
+        let mut body = Vec::new();
+        ProductDefinitionSectionHeader {
+            nv,
+            ..header.clone()
+        }
+        .write(&mut body)?;
+        product_definition.write(&mut body)?;
+        self.write_section(4, &body)
+    }
+
+    /// Append Section 5 (the section length in `header` is recomputed)
+    pub fn write_data_representation(
+        &mut self,
+        header: &DataRepresentationSectionHeader,
+        template: &DataRepresentationTemplate,
+    ) -> Result<()> {
+        let mut body = Vec::new();
+        header.write(&mut body)?;
+        template.write(&mut body)?;
+        self.write_section(5, &body)
+    }
+
+    /// Append Sections 5, 6 and 7 for one value per grid point
+    ///
+    /// Missing points (`None`) are left out of the data section and recorded in a bit-map;
+    /// when no point is missing, Section 6 only has bit-map indicator 255.
+    pub fn write_values(&mut self, values: &[Option<f32>], packing: &Packing) -> Result<()> {
+        let present = values.iter().flatten().copied().collect::<Vec<_>>();
+        let (template, data) = packing.encode(&present)?;
+        let number_of_values = u32::try_from(present.len())
+            .map_err(|_| Error::InvalidData("too many values".to_string()))?;
+        let template_number = match template {
+            DataRepresentationTemplate::Template5_0(_) => 0,
+            DataRepresentationTemplate::Template5_2(_) => 2,
+            DataRepresentationTemplate::Template5_3(_) => 3,
+            DataRepresentationTemplate::Template5_41(_) => 41,
+            DataRepresentationTemplate::Template5_42(_) => 42,
+            _ => unreachable!("not produced by Packing"),
+        };
+        self.write_data_representation(
+            &DataRepresentationSectionHeader {
+                section_length: 0,
+                number_of_values,
+                template_number,
+            },
+            &template,
+        )?;
+        let mut bitmap = Vec::new();
+        if present.len() == values.len() {
+            bitmap.push(255);
+        } else {
+            bitmap.push(0);
+            let flags = values.iter().map(|v| v.is_some()).collect::<Vec<_>>();
+            write_bitmap(&mut bitmap, &flags)?;
+        }
+        self.write_section(6, &bitmap)?;
+        self.write_section(7, &data)
+    }
+
+    /// Append Section 8 and return the complete message
+    pub fn into_bytes(mut self) -> Result<Vec<u8>> {
+        if !can_follow(self.last_section, 8) {
+            return Err(Error::InvalidData(format!(
+                "a message cannot end after section {}",
+                self.last_section
+            )));
+        }
+        self.buf.extend_from_slice(b"7777");
+        let total_length = self.buf.len() as u64;
+        self.buf[8..16].copy_from_slice(&total_length.to_be_bytes());
+        Ok(self.buf)
+    }
+
+    /// Append Section 8, write the complete message and return its total length
+    pub fn finish<W: Write>(self, writer: &mut W) -> Result<u64> {
+        let buf = self.into_bytes()?;
+        writer.write_all(&buf)?;
+        Ok(buf.len() as u64)
+    }
+}
+
+/// Builder of a complete message with any number of fields
+///
+/// Fields use the grid and local use data set last before them, and Sections 2 and 3 are
+/// only repeated when they change. Values are checked against the grid size and packed by
+/// [`build`](Self::build).
+///
+/// ```ignore
+/// let message = MessageBuilder::new(0)
+///     .reference_time(DateTime::new(2024, 1, 1, 0, 0, 0))
+///     .grid(grid_header, grid_template)
+///     .field(pds_header, temperature, values, packing)
+///     .build()?;
+/// ```
+#[derive(Debug)]
+pub struct MessageBuilder {
+    discipline: u8,
+    identification: IdentificationSectionHeader,
+    local_uses: Vec<Vec<u8>>,
+    grids: Vec<(GridDefinitionSectionHeader, GridDefinitionTemplate)>,
+    fields: Vec<FieldSpec>,
+}
+
+#[derive(Debug)]
+struct FieldSpec {
+    local_use: Option<usize>,
+    grid: Option<usize>,
+    header: ProductDefinitionSectionHeader,
+    product_definition: ProductDefinition,
+    values: Vec<Option<f32>>,
+    packing: Packing,
+}
+
+impl MessageBuilder {
+    /// Start a message for `discipline` (Code Table 0.0)
+    ///
+    /// Section 1 defaults to missing centre and sub-centre, master tables version 2, no
+    /// local tables, and missing production status and type of data.
+    pub fn new(discipline: u8) -> Self {
+        Self {
+            discipline,
+            identification: IdentificationSectionHeader {
+                section_length: 21,
+                centre: 65535,
+                sub_centre: 65535,
+                tables_version: 2,
+                local_tables_version: 0,
+                significance_of_reference_time: 1,
+                year: 1970,
+                month: 1,
+                day: 1,
+                hour: 0,
+                minute: 0,
+                second: 0,
+                production_status_of_processed_data: 255,
+                type_of_processed_data: 255,
+                template_number: None,
+            },
+            local_uses: Vec::new(),
+            grids: Vec::new(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Replace Section 1
+    pub fn identification(mut self, identification: IdentificationSectionHeader) -> Self {
+        self.identification = identification;
+        self
+    }
+
+    pub fn centre(mut self, centre: u16, sub_centre: u16) -> Self {
+        self.identification.centre = centre;
+        self.identification.sub_centre = sub_centre;
+        self
+    }
+
+    /// Reference time (Section 1, octets 13-19)
+    pub fn reference_time(mut self, time: DateTime) -> Self {
+        self.identification.set_reference_time(time);
+        self
+    }
+
+    /// Section 2 body for the following fields
+    pub fn local_use(mut self, body: Vec<u8>) -> Self {
+        self.local_uses.push(body);
+        self
+    }
+
+    /// Grid of the following fields (the section length is recomputed)
+    pub fn grid(
+        mut self,
+        header: GridDefinitionSectionHeader,
+        template: GridDefinitionTemplate,
+    ) -> Self {
+        self.grids.push((header, template));
+        self
+    }
+
+    /// Add a field with one value per grid point, `None` for missing points
+    pub fn field(
+        mut self,
+        header: ProductDefinitionSectionHeader,
+        product_definition: ProductDefinition,
+        values: impl Into<Vec<Option<f32>>>,
+        packing: Packing,
+    ) -> Self {
+        self.fields.push(FieldSpec {
+            local_use: self.local_uses.len().checked_sub(1),
+            grid: self.grids.len().checked_sub(1),
+            header,
+            product_definition,
+            values: values.into(),
+            packing,
+        });
+        self
+    }
+
+    /// Assemble the message
+    pub fn build(&self) -> Result<Vec<u8>> {
+        if self.fields.is_empty() {
+            return Err(Error::InvalidData("a message needs a field".to_string()));
+        }
+        let mut writer = MessageWriter::new(self.discipline, &self.identification)?;
+        let mut local_use = None;
+        let mut grid = None;
+        for (i, field) in self.fields.iter().enumerate() {
+            let Some(grid_index) = field.grid else {
+                return Err(Error::InvalidData(format!("field {} has no grid", i)));
+            };
+            let (grid_header, grid_template) = &self.grids[grid_index];
+            if field.values.len() != grid_header.number_of_data_points as usize {
+                return Err(Error::InvalidData(format!(
+                    "field {} has {} values, but the grid has {} points",
+                    i,
+                    field.values.len(),
+                    grid_header.number_of_data_points
+                )));
+            }
+            if field.local_use != local_use
+                && let Some(index) = field.local_use
+            {
+                writer.write_section(2, &self.local_uses[index])?;
+                grid = None;
+            }
+            local_use = field.local_use;
+            if grid != Some(grid_index) {
+                writer.write_grid_definition(grid_header, grid_template)?;
+                grid = Some(grid_index);
+            }
+            writer.write_product_definition(&field.header, &field.product_definition)?;
+            writer.write_values(&field.values, &field.packing)?;
+        }
+        writer.into_bytes()
+    }
+}